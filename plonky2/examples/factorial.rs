@@ -39,5 +39,6 @@ fn main() -> Result<()> {
         proof.public_inputs[0], proof.public_inputs[1]
     );
 
-    data.verify(proof)
+    data.verify(proof)?;
+    Ok(())
 }
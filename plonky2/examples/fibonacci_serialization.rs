@@ -63,5 +63,6 @@ fn main() -> Result<()> {
         proof.public_inputs[0], proof.public_inputs[1], proof.public_inputs[2]
     );
 
-    data.verify(proof)
+    data.verify(proof)?;
+    Ok(())
 }
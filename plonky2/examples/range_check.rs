@@ -34,5 +34,6 @@ fn main() -> Result<()> {
         proof.public_inputs[0], log_max,
     );
 
-    data.verify(proof)
+    data.verify(proof)?;
+    Ok(())
 }
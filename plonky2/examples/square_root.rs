@@ -148,5 +148,6 @@ fn main() -> Result<()> {
         assert_eq!(data, data_from_bytes);
     }
 
-    data.verify(proof)
+    data.verify(proof)?;
+    Ok(())
 }
@@ -0,0 +1,384 @@
+//! Batched FRI openings of oracles of different degrees.
+//!
+//! Instead of padding all polynomials to the largest degree, the openings of each degree are
+//! described by their own [`FriInstanceInfo`](crate::fri::structure::FriInstanceInfo), and the
+//! reduced polynomial of a smaller instance is added to the FRI codeword once the latter has been
+//! folded down to the degree of that instance. A single FRI proof thus covers all the instances,
+//! with each of their oracles committed at its own height.
+//!
+//! Batched proofs can be verified natively with
+//! [`verify_batch_fri_proof`](verifier::verify_batch_fri_proof), or in a circuit with
+//! [`CircuitBuilder::verify_batch_fri_proof`](crate::plonk::circuit_builder::CircuitBuilder::verify_batch_fri_proof).
+
+#[cfg(not(feature = "std"))]
+use alloc::vec::Vec;
+
+use crate::fri::{FriConfig, FriParams};
+
+pub mod prover;
+pub mod recursive_verifier;
+pub mod verifier;
+
+/// Returns the FRI parameters for a batched opening of instances whose polynomials have
+/// `2^degree_bits[i]` coefficients, in strictly decreasing order of degrees.
+///
+/// The reduction arity bits are those of the largest degree, with steps split so that the folded
+/// codeword reaches the degree of each instance, and with additional steps if the smallest degree
+/// is below the degree of the final polynomial.
+pub fn batch_fri_params(config: &FriConfig, degree_bits: &[usize], hiding: bool) -> FriParams {
+    assert!(!degree_bits.is_empty(), "At least one instance is needed.");
+    assert!(
+        degree_bits.windows(2).all(|w| w[0] > w[1]),
+        "Instances must be sorted by strictly decreasing degree."
+    );
+
+    // Each folded codeword is committed to in a Merkle tree of height at least `cap_height`.
+    assert!(
+        degree_bits[degree_bits.len() - 1] + config.rate_bits >= config.cap_height,
+        "The smallest instance is too small for the Merkle caps."
+    );
+
+    let max_degree_bits = degree_bits[0];
+    let mut params = config.fri_params(max_degree_bits, hiding);
+    let max_arity_bits = params
+        .reduction_arity_bits
+        .iter()
+        .copied()
+        .max()
+        .unwrap_or(1);
+
+    // The degrees reached by the folded codeword, in decreasing order.
+    let mut targets = params
+        .reduction_arity_bits
+        .iter()
+        .scan(max_degree_bits, |bits, &arity_bits| {
+            *bits -= arity_bits;
+            Some(*bits)
+        })
+        .chain(degree_bits[1..].iter().copied())
+        .collect::<Vec<_>>();
+    targets.sort_unstable_by(|a, b| b.cmp(a));
+    targets.dedup();
+
+    let mut reduction_arity_bits = Vec::with_capacity(targets.len());
+    let mut current_bits = max_degree_bits;
+    for target in targets {
+        while current_bits - target > max_arity_bits {
+            reduction_arity_bits.push(max_arity_bits);
+            current_bits -= max_arity_bits;
+        }
+        reduction_arity_bits.push(current_bits - target);
+        current_bits = target;
+    }
+    params.reduction_arity_bits = reduction_arity_bits;
+
+    params
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(not(feature = "std"))]
+    use alloc::{vec, vec::Vec};
+
+    use anyhow::Result;
+    use itertools::Itertools;
+
+    use super::batch_fri_params;
+    use crate::batch_fri::prover::batch_prove_openings;
+    use crate::batch_fri::verifier::verify_batch_fri_proof;
+    use crate::field::extension::Extendable;
+    use crate::field::polynomial::PolynomialCoeffs;
+    use crate::field::types::{Field, Sample};
+    use crate::fri::oracle::PolynomialBatch;
+    use crate::fri::proof::FriProof;
+    use crate::fri::reduction_strategies::FriReductionStrategy;
+    use crate::fri::structure::{
+        FriBatchInfo, FriBatchInfoTarget, FriInstanceInfo, FriInstanceInfoTarget, FriOpeningBatch,
+        FriOpeningBatchTarget, FriOpenings, FriOpeningsTarget, FriOracleInfo, FriPolynomialInfo,
+    };
+    use crate::fri::witness_util::set_fri_proof_target;
+    use crate::fri::{FriConfig, FriParams};
+    use crate::hash::merkle_tree::MerkleCap;
+    use crate::iop::challenger::{Challenger, RecursiveChallenger};
+    use crate::iop::witness::{PartialWitness, WitnessWrite};
+    use crate::plonk::circuit_builder::CircuitBuilder;
+    use crate::plonk::circuit_data::CircuitConfig;
+    use crate::plonk::config::{GenericConfig, PoseidonGoldilocksConfig};
+    use crate::plonk::plonk_common::salt_size;
+    use crate::util::timing::TimingTree;
+
+    const D: usize = 2;
+    type C = PoseidonGoldilocksConfig;
+    type F = <C as GenericConfig<D>>::F;
+    type FE = <F as Extendable<D>>::Extension;
+    type H = <C as GenericConfig<D>>::Hasher;
+
+    fn fri_config() -> FriConfig {
+        FriConfig {
+            rate_bits: 1,
+            cap_height: 1,
+            proof_of_work_bits: 2,
+            reduction_strategy: FriReductionStrategy::ConstantArityBits(2, 2),
+            num_query_rounds: 10,
+        }
+    }
+
+    #[test]
+    fn test_batch_fri_params() {
+        let config = fri_config();
+        // The standard reduction of degree `2^9` has steps of 2 bits down to `2^1`.
+        let params = batch_fri_params(&config, &[9], false);
+        assert_eq!(params.reduction_arity_bits, vec![2, 2, 2, 2]);
+
+        // Each degree is reached by the folded codeword, and the folding goes on down to the
+        // smallest degree.
+        let params = batch_fri_params(&config, &[9, 6, 4, 0], false);
+        assert_eq!(params.reduction_arity_bits, vec![2, 1, 1, 1, 1, 2, 1]);
+        assert_eq!(params.degree_bits, 9);
+        assert_eq!(params.final_poly_bits(), 0);
+    }
+
+    /// Commits to random polynomials of the given degrees and opens them at a random point, and at
+    /// its shift for even-indexed instances. If `recursive` is set, the proof is also verified in a
+    /// circuit.
+    fn prove_and_verify(degree_bits: &[usize], hiding: bool, recursive: bool) -> Result<()> {
+        let config = fri_config();
+        let params = batch_fri_params(&config, degree_bits, hiding);
+        let mut timing = TimingTree::default();
+
+        let oracles = degree_bits
+            .iter()
+            .enumerate()
+            .map(|(i, &bits)| {
+                let polys = (0..i + 2)
+                    .map(|_| PolynomialCoeffs::new(F::rand_vec(1 << bits)))
+                    .collect();
+                PolynomialBatch::<F, C, D>::from_coeffs(
+                    polys,
+                    config.rate_bits,
+                    hiding,
+                    config.cap_height,
+                    &mut timing,
+                    None,
+                )
+            })
+            .collect::<Vec<_>>();
+
+        let zeta = FE::rand();
+        let instances = oracles
+            .iter()
+            .enumerate()
+            .map(|(i, oracle)| {
+                let polynomials = FriPolynomialInfo::from_range(0, 0..oracle.polynomials.len());
+                let mut batches = vec![FriBatchInfo {
+                    point: zeta,
+                    polynomials: polynomials.clone(),
+                }];
+                if i % 2 == 0 {
+                    batches.push(FriBatchInfo {
+                        point: zeta.double(),
+                        polynomials,
+                    });
+                }
+                FriInstanceInfo {
+                    oracles: vec![FriOracleInfo {
+                        num_polys: oracle.polynomials.len(),
+                        blinding: hiding,
+                    }],
+                    batches,
+                }
+            })
+            .collect::<Vec<_>>();
+        let openings = instances
+            .iter()
+            .zip(&oracles)
+            .map(|(instance, oracle)| FriOpenings {
+                batches: instance
+                    .batches
+                    .iter()
+                    .map(|batch| FriOpeningBatch {
+                        values: oracle
+                            .polynomials
+                            .iter()
+                            .map(|p| p.to_extension::<D>().eval(batch.point))
+                            .collect(),
+                    })
+                    .collect(),
+            })
+            .collect::<Vec<_>>();
+        let caps = oracles
+            .iter()
+            .map(|o| o.merkle_tree.cap.clone())
+            .collect_vec();
+
+        let mut challenger = Challenger::<F, H>::new();
+        for opening in &openings {
+            challenger.observe_openings(opening);
+        }
+        let oracle_refs = oracles.iter().map(|o| vec![o]).collect_vec();
+        let oracle_refs = oracle_refs.iter().map(Vec::as_slice).collect_vec();
+        let proof = batch_prove_openings::<F, C, D>(
+            &instances,
+            &oracle_refs,
+            &mut challenger,
+            &params,
+            &mut timing,
+        );
+
+        let mut challenger = Challenger::<F, H>::new();
+        for opening in &openings {
+            challenger.observe_openings(opening);
+        }
+        let challenges = challenger.fri_challenges::<C, D>(
+            &proof.commit_phase_merkle_caps,
+            &proof.final_poly,
+            proof.pow_witness,
+            params.degree_bits,
+            &config,
+        );
+        verify_batch_fri_proof::<F, C, D>(
+            &instances,
+            &openings,
+            &challenges,
+            &caps,
+            &proof,
+            &params,
+            degree_bits,
+        )?;
+
+        if recursive {
+            verify_in_circuit(&instances, &openings, &caps, &proof, &params, degree_bits)?;
+        }
+
+        // A wrong opening of the smallest instance is detected.
+        let mut wrong_openings = openings;
+        wrong_openings.last_mut().unwrap().batches[0].values[0] += FE::ONE;
+        assert!(verify_batch_fri_proof::<F, C, D>(
+            &instances,
+            &wrong_openings,
+            &challenges,
+            &caps,
+            &proof,
+            &params,
+            degree_bits,
+        )
+        .is_err());
+        if recursive {
+            assert!(verify_in_circuit(
+                &instances,
+                &wrong_openings,
+                &caps,
+                &proof,
+                &params,
+                degree_bits
+            )
+            .is_err());
+        }
+
+        Ok(())
+    }
+
+    fn verify_in_circuit(
+        instances: &[FriInstanceInfo<F, D>],
+        openings: &[FriOpenings<F, D>],
+        caps: &[MerkleCap<F, H>],
+        proof: &FriProof<F, H, D>,
+        params: &FriParams,
+        degree_bits: &[usize],
+    ) -> Result<()> {
+        let mut builder = CircuitBuilder::<F, D>::new(CircuitConfig::standard_recursion_config());
+        let mut pw = PartialWitness::new();
+
+        let instances_t = instances
+            .iter()
+            .map(|instance| FriInstanceInfoTarget {
+                oracles: instance.oracles.clone(),
+                batches: instance
+                    .batches
+                    .iter()
+                    .map(|batch| FriBatchInfoTarget {
+                        point: builder.constant_extension(batch.point),
+                        polynomials: batch.polynomials.clone(),
+                    })
+                    .collect(),
+            })
+            .collect_vec();
+        let openings_t = openings
+            .iter()
+            .map(|os| FriOpeningsTarget {
+                batches: os
+                    .batches
+                    .iter()
+                    .map(|batch| FriOpeningBatchTarget {
+                        values: builder.add_virtual_extension_targets(batch.values.len()),
+                    })
+                    .collect(),
+            })
+            .collect_vec();
+        for (os_t, os) in openings_t.iter().zip(openings) {
+            pw.set_fri_openings(os_t, os);
+        }
+        let caps_t = caps
+            .iter()
+            .map(|cap| {
+                let cap_t = builder.add_virtual_cap(params.config.cap_height);
+                pw.set_cap_target(&cap_t, cap);
+                cap_t
+            })
+            .collect_vec();
+        let num_leaves_per_oracle = instances
+            .iter()
+            .map(|instance| {
+                instance
+                    .oracles
+                    .iter()
+                    .map(|o| o.num_polys + salt_size(o.blinding && params.hiding))
+                    .collect()
+            })
+            .collect_vec();
+        let proof_t =
+            builder.add_virtual_batch_fri_proof(&num_leaves_per_oracle, params, degree_bits);
+        set_fri_proof_target(&mut pw, &proof_t, proof);
+
+        let mut challenger = RecursiveChallenger::<F, H, D>::new(&mut builder);
+        for os_t in &openings_t {
+            challenger.observe_openings(os_t);
+        }
+        let challenges = challenger.fri_challenges(
+            &mut builder,
+            &proof_t.commit_phase_merkle_caps,
+            &proof_t.final_poly,
+            proof_t.pow_witness,
+            &params.config,
+        );
+        builder.verify_batch_fri_proof::<C>(
+            &instances_t,
+            &openings_t,
+            &challenges,
+            &caps_t,
+            &proof_t,
+            params,
+            degree_bits,
+        );
+
+        let data = builder.build::<C>();
+        let proof = data.prove(pw)?;
+        data.verify(proof)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_batch_fri() -> Result<()> {
+        prove_and_verify(&[9, 6, 4, 0], false, false)
+    }
+
+    #[test]
+    fn test_batch_fri_hiding() -> Result<()> {
+        prove_and_verify(&[8, 5], true, false)
+    }
+
+    #[test]
+    fn test_batch_fri_recursive() -> Result<()> {
+        prove_and_verify(&[7, 4, 2], true, true)
+    }
+}
@@ -0,0 +1,243 @@
+#[cfg(not(feature = "std"))]
+use alloc::{format, vec::Vec};
+
+use plonky2_maybe_rayon::*;
+
+use crate::field::extension::{flatten, unflatten, Extendable, FieldExtension};
+use crate::field::polynomial::PolynomialCoeffs;
+use crate::field::types::Field;
+use crate::fri::oracle::PolynomialBatch;
+use crate::fri::proof::{FriInitialTreeProof, FriProof, FriQueryRound, FriQueryStep};
+use crate::fri::prover::fri_proof_of_work;
+use crate::fri::structure::FriInstanceInfo;
+use crate::fri::FriParams;
+use crate::hash::hash_types::RichField;
+use crate::hash::merkle_tree::MerkleTree;
+use crate::iop::challenger::Challenger;
+use crate::plonk::config::GenericConfig;
+use crate::plonk::plonk_common::reduce_with_powers;
+use crate::timed;
+use crate::util::reverse_index_bits_in_place;
+use crate::util::timing::TimingTree;
+
+/// Produces a single FRI proof for the openings of several instances, whose oracles have
+/// different degrees.
+///
+/// `oracles[i]` are the oracles of `instances[i]`, which must all have the same degree. Instances
+/// must be sorted by strictly decreasing degree, and `fri_params` must be given by
+/// [`batch_fri_params`](crate::batch_fri::batch_fri_params) for these degrees.
+///
+/// The initial trees proofs of each query round hold the openings of the oracles of all the
+/// instances in order.
+pub fn batch_prove_openings<F, C, const D: usize>(
+    instances: &[FriInstanceInfo<F, D>],
+    oracles: &[&[&PolynomialBatch<F, C, D>]],
+    challenger: &mut Challenger<F, C::Hasher>,
+    fri_params: &FriParams,
+    timing: &mut TimingTree,
+) -> FriProof<F, C::Hasher, D>
+where
+    F: RichField + Extendable<D>,
+    C: GenericConfig<D, F = F>,
+{
+    assert!(D > 1, "Not implemented for D=1.");
+    assert_eq!(instances.len(), oracles.len());
+    assert_eq!(oracles[0][0].degree_log, fri_params.degree_bits);
+    let rate_bits = fri_params.config.rate_bits;
+
+    let alpha = challenger.get_extension_challenge::<D>();
+
+    // The low-degree polynomial of each instance, all combined with the same `alpha`.
+    let lde_polys = instances
+        .iter()
+        .zip(oracles)
+        .map(|(instance, oracles)| {
+            let degree_log = oracles[0].degree_log;
+            assert!(oracles.iter().all(|o| o.degree_log == degree_log));
+            let poly = PolynomialBatch::combine_openings(instance, oracles, alpha, timing);
+            poly.lde(rate_bits)
+        })
+        .collect::<Vec<_>>();
+
+    // Commit phase
+    let (trees, final_coeffs) = timed!(
+        timing,
+        "fold codewords in the commitment phase",
+        batch_fri_committed_trees::<F, C, D>(lde_polys, challenger, fri_params, timing)
+    );
+
+    // PoW phase
+    let pow_witness = timed!(
+        timing,
+        "find proof-of-work witness",
+        fri_proof_of_work::<F, C, D>(challenger, &fri_params.config)
+    );
+
+    // Query phase
+    let initial_merkle_trees = oracles
+        .iter()
+        .flat_map(|oracles| oracles.iter().map(|o| (&o.merkle_tree, o.degree_log)))
+        .collect::<Vec<_>>();
+    let query_round_proofs = batch_fri_prover_query_rounds::<F, C, D>(
+        &initial_merkle_trees,
+        &trees,
+        challenger,
+        fri_params,
+    );
+
+    FriProof {
+        commit_phase_merkle_caps: trees.iter().map(|t| t.cap.clone()).collect(),
+        query_round_proofs,
+        final_poly: final_coeffs,
+        pow_witness,
+    }
+}
+
+type BatchFriCommitedTrees<F, C, const D: usize> = (
+    Vec<MerkleTree<F, <C as GenericConfig<D>>::Hasher>>,
+    PolynomialCoeffs<<F as Extendable<D>>::Extension>,
+);
+
+fn batch_fri_committed_trees<F, C, const D: usize>(
+    lde_polys: Vec<PolynomialCoeffs<F::Extension>>,
+    challenger: &mut Challenger<F, C::Hasher>,
+    fri_params: &FriParams,
+    timing: &mut TimingTree,
+) -> BatchFriCommitedTrees<F, C, D>
+where
+    F: RichField + Extendable<D>,
+    C: GenericConfig<D, F = F>,
+{
+    let mut trees = Vec::with_capacity(fri_params.reduction_arity_bits.len());
+    let mut lde_polys = lde_polys.into_iter();
+
+    let mut coeffs = lde_polys.next().unwrap();
+    let mut shift = F::coset_shift();
+    let mut values = timed!(
+        timing,
+        &format!("perform initial FFT {}", coeffs.len()),
+        coeffs.coset_fft(shift.into())
+    );
+    let mut next_poly = lde_polys.next();
+    for arity_bits in &fri_params.reduction_arity_bits {
+        let arity = 1 << arity_bits;
+
+        reverse_index_bits_in_place(&mut values.values);
+        let chunked_values = values
+            .values
+            .par_chunks(arity)
+            .map(|chunk: &[F::Extension]| flatten(chunk))
+            .collect();
+        let tree = MerkleTree::<F, C::Hasher>::new(chunked_values, fri_params.config.cap_height);
+
+        challenger.observe_cap(&tree.cap);
+        trees.push(tree);
+
+        let beta = challenger.get_extension_challenge::<D>();
+        // P(x) = sum_{i<r} x^i * P_i(x^r) becomes sum_{i<r} beta^i * P_i(x).
+        coeffs = PolynomialCoeffs::new(
+            coeffs
+                .coeffs
+                .par_chunks_exact(arity)
+                .map(|chunk| reduce_with_powers(chunk, beta))
+                .collect::<Vec<_>>(),
+        );
+        shift = shift.exp_u64(arity as u64);
+
+        // Once the codeword has been folded down to the degree of the next instance, we add the
+        // polynomial of that instance, with a fresh power of `beta`. Its oracles are committed on
+        // the coset of the original shift, while the folded codeword is on the coset of `shift`,
+        // so we add `Q(X * coset_shift / shift)` for the verifier to use the openings of `Q`.
+        if next_poly.as_ref().is_some_and(|p| p.len() == coeffs.len()) {
+            let poly = next_poly.take().unwrap();
+            let scale = F::coset_shift() * shift.inverse();
+            let beta_arity = beta.exp_u64(arity as u64);
+            coeffs
+                .coeffs
+                .iter_mut()
+                .zip(poly.coeffs)
+                .zip(scale.powers())
+                .for_each(|((c, q), s)| *c += beta_arity * q.scalar_mul(s));
+            next_poly = lde_polys.next();
+        }
+
+        values = coeffs.coset_fft(shift.into())
+    }
+    assert!(
+        next_poly.is_none(),
+        "The FRI parameters must reach the degree of each instance."
+    );
+
+    // The coefficients being removed here should always be zero.
+    coeffs
+        .coeffs
+        .truncate(coeffs.len() >> fri_params.config.rate_bits);
+
+    challenger.observe_extension_elements(&coeffs.coeffs);
+    (trees, coeffs)
+}
+
+fn batch_fri_prover_query_rounds<F, C, const D: usize>(
+    initial_merkle_trees: &[(&MerkleTree<F, C::Hasher>, usize)],
+    trees: &[MerkleTree<F, C::Hasher>],
+    challenger: &mut Challenger<F, C::Hasher>,
+    fri_params: &FriParams,
+) -> Vec<FriQueryRound<F, C::Hasher, D>>
+where
+    F: RichField + Extendable<D>,
+    C: GenericConfig<D, F = F>,
+{
+    let n = fri_params.lde_size();
+    challenger
+        .get_n_challenges(fri_params.config.num_query_rounds)
+        .into_par_iter()
+        .map(|rand| {
+            let x_index = rand.to_canonical_u64() as usize % n;
+            batch_fri_prover_query_round::<F, C, D>(
+                initial_merkle_trees,
+                trees,
+                x_index,
+                fri_params,
+            )
+        })
+        .collect()
+}
+
+fn batch_fri_prover_query_round<F, C, const D: usize>(
+    initial_merkle_trees: &[(&MerkleTree<F, C::Hasher>, usize)],
+    trees: &[MerkleTree<F, C::Hasher>],
+    mut x_index: usize,
+    fri_params: &FriParams,
+) -> FriQueryRound<F, C::Hasher, D>
+where
+    F: RichField + Extendable<D>,
+    C: GenericConfig<D, F = F>,
+{
+    let mut query_steps = Vec::with_capacity(trees.len());
+    // The oracles of smaller instances are opened at the index of the folded point.
+    let initial_proof = initial_merkle_trees
+        .iter()
+        .map(|&(t, degree_log)| {
+            let index = x_index >> (fri_params.degree_bits - degree_log);
+            (t.get(index).to_vec(), t.prove(index))
+        })
+        .collect::<Vec<_>>();
+    for (i, tree) in trees.iter().enumerate() {
+        let arity_bits = fri_params.reduction_arity_bits[i];
+        let evals = unflatten(tree.get(x_index >> arity_bits));
+        let merkle_proof = tree.prove(x_index >> arity_bits);
+
+        query_steps.push(FriQueryStep {
+            evals,
+            merkle_proof,
+        });
+
+        x_index >>= arity_bits;
+    }
+    FriQueryRound {
+        initial_trees_proof: FriInitialTreeProof {
+            evals_proofs: initial_proof,
+        },
+        steps: query_steps,
+    }
+}
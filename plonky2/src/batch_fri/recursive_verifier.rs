@@ -0,0 +1,362 @@
+#[cfg(not(feature = "std"))]
+use alloc::{format, vec::Vec};
+
+use crate::field::extension::Extendable;
+use crate::fri::proof::{
+    FriChallengesTarget, FriInitialTreeProofTarget, FriProofTarget, FriQueryRoundTarget,
+};
+use crate::fri::recursive_verifier::PrecomputedReducedOpeningsTarget;
+use crate::fri::structure::{FriInstanceInfoTarget, FriOpeningsTarget};
+use crate::fri::FriParams;
+use crate::hash::hash_types::{MerkleCapTarget, RichField};
+use crate::iop::ext_target::{flatten_target, ExtensionTarget};
+use crate::iop::target::{BoolTarget, Target};
+use crate::plonk::circuit_builder::CircuitBuilder;
+use crate::plonk::config::{AlgebraicHasher, GenericConfig};
+use crate::with_context;
+
+impl<F: RichField + Extendable<D>, const D: usize> CircuitBuilder<F, D> {
+    /// Circuit version of [`verify_batch_fri_proof`](crate::batch_fri::verifier::verify_batch_fri_proof),
+    /// for a proof added with [`add_virtual_batch_fri_proof`](Self::add_virtual_batch_fri_proof).
+    ///
+    /// `initial_merkle_caps` are the caps of the oracles of all the instances in order, and the
+    /// polynomials of `instances[i]` have `2^degree_bits[i]` coefficients.
+    pub fn verify_batch_fri_proof<C: GenericConfig<D, F = F>>(
+        &mut self,
+        instances: &[FriInstanceInfoTarget<D>],
+        openings: &[FriOpeningsTarget<D>],
+        challenges: &FriChallengesTarget<D>,
+        initial_merkle_caps: &[MerkleCapTarget],
+        proof: &FriProofTarget<D>,
+        params: &FriParams,
+        degree_bits: &[usize],
+    ) where
+        C::Hasher: AlgebraicHasher<F>,
+    {
+        assert_eq!(instances.len(), openings.len());
+        assert_eq!(instances.len(), degree_bits.len());
+        assert_eq!(degree_bits[0], params.degree_bits);
+        assert_eq!(
+            instances.iter().map(|i| i.oracles.len()).sum::<usize>(),
+            initial_merkle_caps.len()
+        );
+        if let Some(max_arity_bits) = params.max_arity_bits() {
+            self.check_recursion_config(max_arity_bits);
+        }
+
+        debug_assert_eq!(
+            params.final_poly_len(),
+            proof.final_poly.len(),
+            "Final polynomial has wrong degree."
+        );
+
+        with_context!(
+            self,
+            "check PoW",
+            self.fri_verify_proof_of_work(challenges.fri_pow_response, &params.config)
+        );
+
+        // Check that parameters are coherent.
+        debug_assert_eq!(
+            params.config.num_query_rounds,
+            proof.query_round_proofs.len(),
+            "Number of query rounds does not match config."
+        );
+
+        let precomputed_reduced_evals = with_context!(
+            self,
+            "precompute reduced evaluations",
+            openings
+                .iter()
+                .map(|os| PrecomputedReducedOpeningsTarget::from_os_and_alpha(
+                    os,
+                    challenges.fri_alpha,
+                    self
+                ))
+                .collect::<Vec<_>>()
+        );
+
+        for (i, round_proof) in proof.query_round_proofs.iter().enumerate() {
+            // As in `verify_fri_proof`, we only record a context for the second FRI query.
+            let level = if i == 1 {
+                log::Level::Debug
+            } else {
+                log::Level::Trace
+            };
+
+            let num_queries = proof.query_round_proofs.len();
+            with_context!(
+                self,
+                level,
+                &format!("verify one (of {num_queries}) query rounds"),
+                self.batch_fri_verifier_query_round::<C>(
+                    instances,
+                    challenges,
+                    &precomputed_reduced_evals,
+                    initial_merkle_caps,
+                    proof,
+                    challenges.fri_query_indices[i],
+                    round_proof,
+                    params,
+                    degree_bits,
+                )
+            );
+        }
+    }
+
+    /// Returns `subgroup[x_index]`, i.e., the actual field element at the index given by
+    /// `x_index_bits` in an LDE domain. All instances are committed on the same coset.
+    fn batch_fri_subgroup_x(&mut self, x_index_bits: &[BoolTarget]) -> Target {
+        with_context!(self, "compute x from its index", {
+            let g = self.constant(F::coset_shift());
+            let phi = F::primitive_root_of_unity(x_index_bits.len());
+            let phi = self.exp_from_bits_const_base(phi, x_index_bits.iter().rev());
+            // subgroup_x = g * phi
+            self.mul(g, phi)
+        })
+    }
+
+    /// Verifies the initial trees proof of `instance`, whose oracles are opened at the index given
+    /// by `x_index_bits` in its LDE domain, and returns the combination of its openings at
+    /// `subgroup_x`, the corresponding point of that domain.
+    #[allow(clippy::too_many_arguments)]
+    fn batch_fri_verify_instance<C: GenericConfig<D, F = F>>(
+        &mut self,
+        instance: &FriInstanceInfoTarget<D>,
+        proof: &FriInitialTreeProofTarget,
+        initial_merkle_caps: &[MerkleCapTarget],
+        alpha: ExtensionTarget<D>,
+        precomputed_reduced_evals: &PrecomputedReducedOpeningsTarget<D>,
+        x_index_bits: &[BoolTarget],
+        cap_index: Target,
+        subgroup_x: Target,
+        params: &FriParams,
+    ) -> ExtensionTarget<D>
+    where
+        C::Hasher: AlgebraicHasher<F>,
+    {
+        with_context!(
+            self,
+            "check FRI initial proof",
+            self.fri_verify_initial_proof::<C::Hasher>(
+                x_index_bits,
+                proof,
+                initial_merkle_caps,
+                cap_index
+            )
+        );
+
+        with_context!(
+            self,
+            "combine initial oracles",
+            self.fri_combine_initial(
+                instance,
+                proof,
+                alpha,
+                subgroup_x,
+                precomputed_reduced_evals,
+                params,
+            )
+        )
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn batch_fri_verifier_query_round<C: GenericConfig<D, F = F>>(
+        &mut self,
+        instances: &[FriInstanceInfoTarget<D>],
+        challenges: &FriChallengesTarget<D>,
+        precomputed_reduced_evals: &[PrecomputedReducedOpeningsTarget<D>],
+        initial_merkle_caps: &[MerkleCapTarget],
+        proof: &FriProofTarget<D>,
+        x_index: Target,
+        round_proof: &FriQueryRoundTarget<D>,
+        params: &FriParams,
+        degree_bits: &[usize],
+    ) where
+        C::Hasher: AlgebraicHasher<F>,
+    {
+        let n_log = params.lde_bits();
+
+        // Note that this `low_bits` decomposition permits non-canonical binary encodings, which
+        // has a negligible impact on soundness error, see `verify_fri_proof`.
+        Self::assert_noncanonical_indices_ok(&params.config);
+        let mut x_index_bits = self.low_bits(x_index, n_log, F::BITS);
+
+        // The oracles of all instances are committed to in trees with the same cap height, and the
+        // index in a smaller tree is obtained by dropping low bits, so the cap index is shared.
+        let cap_index =
+            self.le_sum(x_index_bits[x_index_bits.len() - params.config.cap_height..].iter());
+
+        // Splits the initial trees proof and caps into those of each instance.
+        let mut evals_proofs = round_proof.initial_trees_proof.evals_proofs.iter();
+        let mut caps = initial_merkle_caps;
+        let mut instance_data = instances.iter().map(|instance| {
+            let num_oracles = instance.oracles.len();
+            let proof = FriInitialTreeProofTarget {
+                evals_proofs: evals_proofs.by_ref().take(num_oracles).cloned().collect(),
+            };
+            let (instance_caps, rest) = caps.split_at(num_oracles);
+            caps = rest;
+            (instance, proof, instance_caps)
+        });
+
+        let mut subgroup_x = self.batch_fri_subgroup_x(&x_index_bits);
+
+        // old_eval is the last derived evaluation; it will be checked for consistency with its
+        // committed "parent" value in the next iteration.
+        let (instance, initial_proof, instance_caps) = instance_data.next().unwrap();
+        let mut old_eval = self.batch_fri_verify_instance::<C>(
+            instance,
+            &initial_proof,
+            instance_caps,
+            challenges.fri_alpha,
+            &precomputed_reduced_evals[0],
+            &x_index_bits,
+            cap_index,
+            subgroup_x,
+            params,
+        );
+        let mut next_instance = 1;
+
+        let mut codeword_bits = params.degree_bits;
+        for (i, &arity_bits) in params.reduction_arity_bits.iter().enumerate() {
+            let evals = &round_proof.steps[i].evals;
+
+            // Split x_index into the index of the coset x is in, and the index of x within that coset.
+            let coset_index_bits = x_index_bits[arity_bits..].to_vec();
+            let x_index_within_coset_bits = &x_index_bits[..arity_bits];
+            let x_index_within_coset = self.le_sum(x_index_within_coset_bits.iter());
+
+            // Check consistency with our old evaluation from the previous round.
+            let new_eval = self.random_access_extension(x_index_within_coset, evals.clone());
+            self.connect_extension(new_eval, old_eval);
+
+            // Infer P(y) from {P(x)}_{x^arity=y}.
+            let beta = challenges.fri_betas[i];
+            old_eval = with_context!(
+                self,
+                "infer evaluation using interpolation",
+                self.compute_evaluation(
+                    subgroup_x,
+                    x_index_within_coset_bits,
+                    arity_bits,
+                    evals,
+                    beta,
+                )
+            );
+
+            with_context!(
+                self,
+                "verify FRI round Merkle proof.",
+                self.verify_merkle_proof_to_cap_with_cap_index::<C::Hasher>(
+                    flatten_target(evals),
+                    &coset_index_bits,
+                    cap_index,
+                    &proof.commit_phase_merkle_caps[i],
+                    &round_proof.steps[i].merkle_proof,
+                )
+            );
+
+            // Update the point x to x^arity.
+            subgroup_x = self.exp_power_of_2(subgroup_x, arity_bits);
+            codeword_bits -= arity_bits;
+
+            x_index_bits = coset_index_bits;
+
+            // Add the openings of the next instance once its degree is reached, as the prover does.
+            if next_instance < instances.len() && degree_bits[next_instance] == codeword_bits {
+                let (instance, initial_proof, instance_caps) = instance_data.next().unwrap();
+                // The folded point is on the coset of a power of the shift, so the point of the
+                // instance domain is computed from its index.
+                let instance_x = self.batch_fri_subgroup_x(&x_index_bits);
+                let reduced_openings = self.batch_fri_verify_instance::<C>(
+                    instance,
+                    &initial_proof,
+                    instance_caps,
+                    challenges.fri_alpha,
+                    &precomputed_reduced_evals[next_instance],
+                    &x_index_bits,
+                    cap_index,
+                    instance_x,
+                    params,
+                );
+                let beta_arity = self.exp_power_of_2_extension(beta, arity_bits);
+                old_eval = self.mul_add_extension(beta_arity, reduced_openings, old_eval);
+                next_instance += 1;
+            }
+        }
+        assert_eq!(
+            next_instance,
+            instances.len(),
+            "The FRI parameters must reach the degree of each instance."
+        );
+
+        // Final check of FRI. After all the reductions, we check that the final polynomial is equal
+        // to the one sent by the prover.
+        let eval = with_context!(
+            self,
+            &format!(
+                "evaluate final polynomial of length {}",
+                proof.final_poly.len()
+            ),
+            proof.final_poly.eval_scalar(self, subgroup_x)
+        );
+        self.connect_extension(eval, old_eval);
+    }
+
+    /// Adds the targets of a batched FRI proof for instances whose polynomials have
+    /// `2^degree_bits[i]` coefficients, and whose oracles have `num_leaves_per_oracle[i]` leaves.
+    pub fn add_virtual_batch_fri_proof(
+        &mut self,
+        num_leaves_per_oracle: &[Vec<usize>],
+        params: &FriParams,
+        degree_bits: &[usize],
+    ) -> FriProofTarget<D> {
+        assert_eq!(num_leaves_per_oracle.len(), degree_bits.len());
+        let cap_height = params.config.cap_height;
+        let rate_bits = params.config.rate_bits;
+        let num_queries = params.config.num_query_rounds;
+        let commit_phase_merkle_caps = (0..params.reduction_arity_bits.len())
+            .map(|_| self.add_virtual_cap(cap_height))
+            .collect();
+        let query_round_proofs = (0..num_queries)
+            .map(|_| {
+                let evals_proofs = num_leaves_per_oracle
+                    .iter()
+                    .zip(degree_bits)
+                    .flat_map(|(num_leaves, &bits)| {
+                        assert!(bits + rate_bits >= cap_height);
+                        self.add_virtual_fri_initial_trees_proof(
+                            num_leaves,
+                            bits + rate_bits - cap_height,
+                        )
+                        .evals_proofs
+                    })
+                    .collect();
+
+                let mut merkle_proof_len = params.lde_bits() - cap_height;
+                let steps = params
+                    .reduction_arity_bits
+                    .iter()
+                    .map(|&arity_bits| {
+                        merkle_proof_len -= arity_bits;
+                        self.add_virtual_fri_query_step(arity_bits, merkle_proof_len)
+                    })
+                    .collect();
+
+                FriQueryRoundTarget {
+                    initial_trees_proof: FriInitialTreeProofTarget { evals_proofs },
+                    steps,
+                }
+            })
+            .collect();
+        let final_poly = self.add_virtual_poly_coeff_ext(params.final_poly_len());
+        let pow_witness = self.add_virtual_target();
+        FriProofTarget {
+            commit_phase_merkle_caps,
+            query_round_proofs,
+            final_poly,
+            pow_witness,
+        }
+    }
+}
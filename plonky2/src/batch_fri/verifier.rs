@@ -0,0 +1,359 @@
+#[cfg(not(feature = "std"))]
+use alloc::vec::Vec;
+
+use crate::field::extension::{flatten, Extendable};
+use crate::field::types::Field;
+use crate::fri::proof::{
+    FriChallenges, FriInitialTreeProof, FriProof, FriQueryRound, FriQueryStep,
+};
+use crate::fri::structure::{FriInstanceInfo, FriOpenings};
+use crate::fri::verifier::{
+    compute_evaluation, fri_combine_initial, fri_verify_initial_proof, fri_verify_proof_of_work,
+    PrecomputedReducedOpenings,
+};
+use crate::fri::FriParams;
+use crate::hash::hash_types::RichField;
+use crate::hash::merkle_proofs::verify_merkle_proof_to_cap;
+use crate::hash::merkle_tree::MerkleCap;
+use crate::plonk::config::{GenericConfig, Hasher};
+use crate::plonk::plonk_common::salt_size;
+use crate::plonk::verification_error::{ensure_shape, VerificationError};
+use crate::util::reverse_bits;
+
+/// Verifies a FRI proof produced by
+/// [`batch_prove_openings`](crate::batch_fri::prover::batch_prove_openings) for the openings of
+/// several instances, whose polynomials have `2^degree_bits[i]` coefficients.
+///
+/// `initial_merkle_caps` are the caps of the oracles of all the instances in order.
+pub fn verify_batch_fri_proof<F, C, const D: usize>(
+    instances: &[FriInstanceInfo<F, D>],
+    openings: &[FriOpenings<F, D>],
+    challenges: &FriChallenges<F, D>,
+    initial_merkle_caps: &[MerkleCap<F, C::Hasher>],
+    proof: &FriProof<F, C::Hasher, D>,
+    params: &FriParams,
+    degree_bits: &[usize],
+) -> Result<(), VerificationError>
+where
+    F: RichField + Extendable<D>,
+    C: GenericConfig<D, F = F>,
+{
+    ensure_shape("openings", instances.len(), openings.len())?;
+    ensure_shape("degree_bits", instances.len(), degree_bits.len())?;
+    ensure_shape(
+        "initial_merkle_caps",
+        instances.iter().map(|i| i.oracles.len()).sum(),
+        initial_merkle_caps.len(),
+    )?;
+    validate_batch_fri_proof_shape::<F, C, D>(proof, instances, degree_bits, params)?;
+
+    // Check PoW.
+    fri_verify_proof_of_work(challenges.fri_pow_response, &params.config)?;
+
+    // Check that parameters are coherent.
+    ensure_shape(
+        "query_round_proofs",
+        params.config.num_query_rounds,
+        proof.query_round_proofs.len(),
+    )?;
+
+    let precomputed_reduced_evals = openings
+        .iter()
+        .map(|os| PrecomputedReducedOpenings::from_os_and_alpha(os, challenges.fri_alpha))
+        .collect::<Vec<_>>();
+    for (query_round, (&x_index, round_proof)) in challenges
+        .fri_query_indices
+        .iter()
+        .zip(&proof.query_round_proofs)
+        .enumerate()
+    {
+        batch_fri_verifier_query_round::<F, C, D>(
+            query_round,
+            instances,
+            challenges,
+            &precomputed_reduced_evals,
+            initial_merkle_caps,
+            proof,
+            x_index,
+            round_proof,
+            params,
+            degree_bits,
+        )?;
+    }
+
+    Ok(())
+}
+
+/// Splits the initial trees proof of a query round into the proofs of each instance.
+fn split_initial_trees_proof<F: RichField + Extendable<D>, H: Hasher<F>, const D: usize>(
+    instances: &[FriInstanceInfo<F, D>],
+    proof: &FriInitialTreeProof<F, H>,
+) -> Vec<FriInitialTreeProof<F, H>> {
+    let mut evals_proofs = proof.evals_proofs.iter();
+    instances
+        .iter()
+        .map(|instance| FriInitialTreeProof {
+            evals_proofs: evals_proofs
+                .by_ref()
+                .take(instance.oracles.len())
+                .cloned()
+                .collect(),
+        })
+        .collect()
+}
+
+/// Verifies the initial trees proof of `instance`, whose oracles are opened at `x_index`, and
+/// returns the combination of its openings at the corresponding point of its LDE domain.
+#[allow(clippy::too_many_arguments)]
+fn batch_fri_verify_instance<F, C, const D: usize>(
+    query_round: usize,
+    instance: &FriInstanceInfo<F, D>,
+    proof: &FriInitialTreeProof<F, C::Hasher>,
+    first_oracle: usize,
+    initial_merkle_caps: &[MerkleCap<F, C::Hasher>],
+    alpha: F::Extension,
+    precomputed_reduced_evals: &PrecomputedReducedOpenings<F, D>,
+    x_index: usize,
+    lde_bits: usize,
+    params: &FriParams,
+) -> Result<F::Extension, VerificationError>
+where
+    F: RichField + Extendable<D>,
+    C: GenericConfig<D, F = F>,
+{
+    let caps = &initial_merkle_caps[first_oracle..first_oracle + instance.oracles.len()];
+    fri_verify_initial_proof::<F, C::Hasher>(query_round, x_index, proof, caps).map_err(
+        |e| match e {
+            VerificationError::InitialMerkleProofFailure {
+                query_round,
+                oracle,
+            } => VerificationError::InitialMerkleProofFailure {
+                query_round,
+                oracle: first_oracle + oracle,
+            },
+            e => e,
+        },
+    )?;
+
+    // `subgroup_x` is `subgroup[x_index]`, i.e., the actual field element in the domain of the
+    // instance, which is committed on the same coset as the largest one.
+    let subgroup_x = F::MULTIPLICATIVE_GROUP_GENERATOR
+        * F::primitive_root_of_unity(lde_bits).exp_u64(reverse_bits(x_index, lde_bits) as u64);
+
+    Ok(fri_combine_initial::<F, C, D>(
+        instance,
+        proof,
+        alpha,
+        subgroup_x,
+        precomputed_reduced_evals,
+        params,
+    ))
+}
+
+#[allow(clippy::too_many_arguments)]
+fn batch_fri_verifier_query_round<F, C, const D: usize>(
+    query_round: usize,
+    instances: &[FriInstanceInfo<F, D>],
+    challenges: &FriChallenges<F, D>,
+    precomputed_reduced_evals: &[PrecomputedReducedOpenings<F, D>],
+    initial_merkle_caps: &[MerkleCap<F, C::Hasher>],
+    proof: &FriProof<F, C::Hasher, D>,
+    mut x_index: usize,
+    round_proof: &FriQueryRound<F, C::Hasher, D>,
+    params: &FriParams,
+    degree_bits: &[usize],
+) -> Result<(), VerificationError>
+where
+    F: RichField + Extendable<D>,
+    C: GenericConfig<D, F = F>,
+{
+    let rate_bits = params.config.rate_bits;
+    let initial_trees_proofs =
+        split_initial_trees_proof(instances, &round_proof.initial_trees_proof);
+    let mut first_oracle = 0;
+
+    // `subgroup_x` is `subgroup[x_index]`, i.e., the actual field element in the domain.
+    let log_n = params.lde_bits();
+    let mut subgroup_x = F::MULTIPLICATIVE_GROUP_GENERATOR
+        * F::primitive_root_of_unity(log_n).exp_u64(reverse_bits(x_index, log_n) as u64);
+
+    // old_eval is the last derived evaluation; it will be checked for consistency with its
+    // committed "parent" value in the next iteration.
+    let mut old_eval = batch_fri_verify_instance::<F, C, D>(
+        query_round,
+        &instances[0],
+        &initial_trees_proofs[0],
+        first_oracle,
+        initial_merkle_caps,
+        challenges.fri_alpha,
+        &precomputed_reduced_evals[0],
+        x_index,
+        log_n,
+        params,
+    )?;
+    first_oracle += instances[0].oracles.len();
+    let mut next_instance = 1;
+
+    let mut codeword_bits = params.degree_bits;
+    for (i, &arity_bits) in params.reduction_arity_bits.iter().enumerate() {
+        let arity = 1 << arity_bits;
+        let evals = &round_proof.steps[i].evals;
+
+        // Split x_index into the index of the coset x is in, and the index of x within that coset.
+        let coset_index = x_index >> arity_bits;
+        let x_index_within_coset = x_index & (arity - 1);
+
+        // Check consistency with our old evaluation from the previous round.
+        if evals[x_index_within_coset] != old_eval {
+            return Err(VerificationError::FriLayerMismatch {
+                query_round,
+                layer: i,
+            });
+        }
+
+        // Infer P(y) from {P(x)}_{x^arity=y}.
+        let beta = challenges.fri_betas[i];
+        old_eval = compute_evaluation(subgroup_x, x_index_within_coset, arity_bits, evals, beta);
+
+        verify_merkle_proof_to_cap::<F, C::Hasher>(
+            flatten(evals),
+            coset_index,
+            &proof.commit_phase_merkle_caps[i],
+            &round_proof.steps[i].merkle_proof,
+        )
+        .map_err(|_| VerificationError::CommitPhaseMerkleProofFailure {
+            query_round,
+            layer: i,
+        })?;
+
+        // Update the point x to x^arity.
+        subgroup_x = subgroup_x.exp_power_of_2(arity_bits);
+        codeword_bits -= arity_bits;
+
+        x_index = coset_index;
+
+        // Add the openings of the next instance once its degree is reached, as the prover does.
+        if next_instance < instances.len() && degree_bits[next_instance] == codeword_bits {
+            let instance = &instances[next_instance];
+            let reduced_openings = batch_fri_verify_instance::<F, C, D>(
+                query_round,
+                instance,
+                &initial_trees_proofs[next_instance],
+                first_oracle,
+                initial_merkle_caps,
+                challenges.fri_alpha,
+                &precomputed_reduced_evals[next_instance],
+                x_index,
+                codeword_bits + rate_bits,
+                params,
+            )?;
+            old_eval += beta.exp_u64(arity as u64) * reduced_openings;
+            first_oracle += instance.oracles.len();
+            next_instance += 1;
+        }
+    }
+
+    // Final check of FRI. After all the reductions, we check that the final polynomial is equal
+    // to the one sent by the prover.
+    if proof.final_poly.eval(subgroup_x.into()) != old_eval {
+        return Err(VerificationError::FinalPolyMismatch { query_round });
+    }
+
+    Ok(())
+}
+
+fn validate_batch_fri_proof_shape<F, C, const D: usize>(
+    proof: &FriProof<F, C::Hasher, D>,
+    instances: &[FriInstanceInfo<F, D>],
+    degree_bits: &[usize],
+    params: &FriParams,
+) -> Result<(), VerificationError>
+where
+    F: RichField + Extendable<D>,
+    C: GenericConfig<D, F = F>,
+{
+    let FriProof {
+        commit_phase_merkle_caps,
+        query_round_proofs,
+        final_poly,
+        pow_witness: _pow_witness,
+    } = proof;
+
+    ensure_shape("degree_bits", params.degree_bits, degree_bits[0])?;
+    // The folded codeword must reach the degree of each instance, in order.
+    let mut instance_bits = degree_bits[1..].iter().peekable();
+    let mut codeword_bits = params.degree_bits;
+    for arity_bits in &params.reduction_arity_bits {
+        codeword_bits -= arity_bits;
+        instance_bits.next_if_eq(&&codeword_bits);
+    }
+    ensure_shape(
+        "instances reached by FRI",
+        degree_bits.len() - 1,
+        degree_bits.len() - 1 - instance_bits.count(),
+    )?;
+
+    ensure_shape(
+        "commit_phase_merkle_caps",
+        params.reduction_arity_bits.len(),
+        commit_phase_merkle_caps.len(),
+    )?;
+    let cap_height = params.config.cap_height;
+    for cap in commit_phase_merkle_caps {
+        ensure_shape("commit_phase_merkle_caps height", cap_height, cap.height())?;
+    }
+
+    let rate_bits = params.config.rate_bits;
+    for query_round in query_round_proofs {
+        let FriQueryRound {
+            initial_trees_proof,
+            steps,
+        } = query_round;
+
+        let oracles = instances
+            .iter()
+            .zip(degree_bits)
+            .flat_map(|(instance, &bits)| instance.oracles.iter().map(move |o| (o, bits)))
+            .collect::<Vec<_>>();
+        ensure_shape(
+            "initial_trees_proof",
+            oracles.len(),
+            initial_trees_proof.evals_proofs.len(),
+        )?;
+        for ((leaf, merkle_proof), (oracle, bits)) in
+            initial_trees_proof.evals_proofs.iter().zip(oracles)
+        {
+            ensure_shape(
+                "initial_trees_proof leaf",
+                oracle.num_polys + salt_size(oracle.blinding && params.hiding),
+                leaf.len(),
+            )?;
+            ensure_shape(
+                "initial_trees_proof merkle_proof",
+                bits + rate_bits,
+                merkle_proof.len() + cap_height,
+            )?;
+        }
+
+        ensure_shape("steps", params.reduction_arity_bits.len(), steps.len())?;
+        let mut codeword_len_bits = params.lde_bits();
+        for (step, arity_bits) in steps.iter().zip(&params.reduction_arity_bits) {
+            let FriQueryStep {
+                evals,
+                merkle_proof,
+            } = step;
+
+            let arity = 1 << arity_bits;
+            codeword_len_bits -= arity_bits;
+
+            ensure_shape("step evals", arity, evals.len())?;
+            ensure_shape(
+                "step merkle_proof",
+                codeword_len_bits,
+                merkle_proof.len() + cap_height,
+            )?;
+        }
+    }
+
+    ensure_shape("final_poly", params.final_poly_len(), final_poly.len())
+}
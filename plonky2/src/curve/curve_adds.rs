@@ -0,0 +1,159 @@
+use core::ops::Add;
+
+use crate::curve::curve_types::{AffinePoint, Curve, ProjectivePoint};
+use crate::field::ops::Square;
+use crate::field::types::Field;
+
+impl<C: Curve> Add<ProjectivePoint<C>> for ProjectivePoint<C> {
+    type Output = ProjectivePoint<C>;
+
+    fn add(self, rhs: ProjectivePoint<C>) -> Self::Output {
+        let ProjectivePoint {
+            x: x1,
+            y: y1,
+            z: z1,
+        } = self;
+        let ProjectivePoint {
+            x: x2,
+            y: y2,
+            z: z2,
+        } = rhs;
+
+        if z1.is_zero() {
+            return rhs;
+        }
+        if z2.is_zero() {
+            return self;
+        }
+
+        let x1z2 = x1 * z2;
+        let y1z2 = y1 * z2;
+        let x2z1 = x2 * z1;
+        let y2z1 = y2 * z1;
+
+        // Check if we're doubling or adding inverses.
+        if x1z2 == x2z1 {
+            if y1z2 == y2z1 {
+                return self.double();
+            }
+            if y1z2 == -y2z1 {
+                return ProjectivePoint::ZERO;
+            }
+        }
+
+        // From https://www.hyperelliptic.org/EFD/g1p/data/shortw/projective/addition/add-1998-cmo-2
+        let z1z2 = z1 * z2;
+        let u = y2z1 - y1z2;
+        let uu = u.square();
+        let v = x2z1 - x1z2;
+        let vv = v.square();
+        let vvv = v * vv;
+        let r = vv * x1z2;
+        let a = uu * z1z2 - vvv - r.double();
+        let x3 = v * a;
+        let y3 = u * (r - a) - vvv * y1z2;
+        let z3 = vvv * z1z2;
+        ProjectivePoint::nonzero(x3, y3, z3)
+    }
+}
+
+impl<C: Curve> Add<AffinePoint<C>> for ProjectivePoint<C> {
+    type Output = ProjectivePoint<C>;
+
+    fn add(self, rhs: AffinePoint<C>) -> Self::Output {
+        let ProjectivePoint {
+            x: x1,
+            y: y1,
+            z: z1,
+        } = self;
+        let AffinePoint {
+            x: x2,
+            y: y2,
+            zero: zero2,
+        } = rhs;
+
+        if z1.is_zero() {
+            return rhs.to_projective();
+        }
+        if zero2 {
+            return self;
+        }
+
+        let x2z1 = x2 * z1;
+        let y2z1 = y2 * z1;
+
+        // Check if we're doubling or adding inverses.
+        if x1 == x2z1 {
+            if y1 == y2z1 {
+                return self.double();
+            }
+            if y1 == -y2z1 {
+                return ProjectivePoint::ZERO;
+            }
+        }
+
+        // From https://www.hyperelliptic.org/EFD/g1p/data/shortw/projective/addition/madd-1998-cmo
+        let u = y2z1 - y1;
+        let uu = u.square();
+        let v = x2z1 - x1;
+        let vv = v.square();
+        let vvv = v * vv;
+        let r = vv * x1;
+        let a = uu * z1 - vvv - r.double();
+        let x3 = v * a;
+        let y3 = u * (r - a) - vvv * y1;
+        let z3 = vvv * z1;
+        ProjectivePoint::nonzero(x3, y3, z3)
+    }
+}
+
+impl<C: Curve> Add<AffinePoint<C>> for AffinePoint<C> {
+    type Output = ProjectivePoint<C>;
+
+    fn add(self, rhs: AffinePoint<C>) -> Self::Output {
+        self.to_projective() + rhs
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::curve::curve_types::{AffinePoint, Curve, CurveScalar, ProjectivePoint};
+    use crate::curve::secp256k1::Secp256K1;
+    use crate::field::secp256k1_scalar::Secp256K1Scalar;
+    use crate::field::types::{Field, Sample};
+
+    #[test]
+    fn test_add_matches_double() {
+        let g = Secp256K1::GENERATOR_PROJECTIVE;
+        assert_eq!(g + g, g.double());
+        assert_eq!(
+            g + Secp256K1::GENERATOR_AFFINE,
+            Secp256K1::GENERATOR_AFFINE.double().to_projective()
+        );
+    }
+
+    #[test]
+    fn test_add_inverses() {
+        let p = CurveScalar(Secp256K1Scalar::rand()) * Secp256K1::GENERATOR_PROJECTIVE;
+        assert_eq!(p + p.neg(), ProjectivePoint::ZERO);
+        assert_eq!(p + ProjectivePoint::ZERO, p);
+        assert_eq!(p.to_affine() + AffinePoint::ZERO, p);
+    }
+
+    #[test]
+    fn test_add_consistency() {
+        let a = Secp256K1Scalar::rand();
+        let b = Secp256K1Scalar::rand();
+        let g = Secp256K1::GENERATOR_PROJECTIVE;
+
+        let lhs = CurveScalar(a) * g + CurveScalar(b) * g;
+        let rhs = CurveScalar(a + b) * g;
+        assert_eq!(lhs, rhs);
+
+        let mixed = CurveScalar(a) * g + (CurveScalar(b) * g).to_affine();
+        assert_eq!(mixed, rhs);
+
+        let three = CurveScalar(Secp256K1Scalar::from_canonical_u64(3)) * g;
+        assert_eq!(g + g + g, three);
+    }
+}
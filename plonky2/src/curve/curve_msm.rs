@@ -0,0 +1,102 @@
+#[cfg(not(feature = "std"))]
+use alloc::{vec, vec::Vec};
+
+use plonky2_maybe_rayon::*;
+
+use crate::curve::curve_multiplication::to_digits;
+use crate::curve::curve_types::{Curve, ProjectivePoint};
+use crate::field::types::Field;
+
+/// Computes `sum_i scalars[i] * points[i]` using Pippenger's bucket method, with windows of
+/// `window_bits` bits. Windows are processed in parallel when the `parallel` feature is enabled.
+pub fn msm_parallel<C: Curve>(
+    scalars: &[C::ScalarField],
+    points: &[ProjectivePoint<C>],
+    window_bits: usize,
+) -> ProjectivePoint<C> {
+    assert_eq!(
+        scalars.len(),
+        points.len(),
+        "Number of scalars and points must match"
+    );
+    assert!(window_bits > 0, "Window size must be positive");
+
+    let digits: Vec<Vec<usize>> = scalars
+        .iter()
+        .map(|x| to_digits::<C>(x, window_bits))
+        .collect();
+    let num_windows = C::ScalarField::BITS.div_ceil(window_bits);
+
+    let window_sums: Vec<ProjectivePoint<C>> = (0..num_windows)
+        .into_par_iter()
+        .map(|w| {
+            // `buckets[d - 1]` holds the sum of the points whose `w`-th digit is `d`.
+            let mut buckets = vec![ProjectivePoint::ZERO; (1 << window_bits) - 1];
+            for (point_digits, &point) in digits.iter().zip(points) {
+                let digit = point_digits[w];
+                if digit != 0 {
+                    buckets[digit - 1] = buckets[digit - 1] + point;
+                }
+            }
+
+            // Compute `sum_d d * buckets[d - 1]` with a running sum.
+            let mut running_sum = ProjectivePoint::ZERO;
+            let mut sum = ProjectivePoint::ZERO;
+            for &bucket in buckets.iter().rev() {
+                running_sum = running_sum + bucket;
+                sum = sum + running_sum;
+            }
+            sum
+        })
+        .collect();
+
+    window_sums
+        .into_iter()
+        .rev()
+        .fold(ProjectivePoint::ZERO, |mut acc, window_sum| {
+            for _ in 0..window_bits {
+                acc = acc.double();
+            }
+            acc + window_sum
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(not(feature = "std"))]
+    use alloc::vec::Vec;
+
+    use crate::curve::curve_msm::msm_parallel;
+    use crate::curve::curve_types::{Curve, CurveScalar, ProjectivePoint};
+    use crate::curve::secp256k1::Secp256K1;
+    use crate::field::secp256k1_scalar::Secp256K1Scalar;
+    use crate::field::types::Sample;
+
+    #[test]
+    fn test_msm() {
+        let g = Secp256K1::GENERATOR_PROJECTIVE;
+        let points: Vec<_> = (0..10)
+            .map(|_| CurveScalar(Secp256K1Scalar::rand()) * g)
+            .collect();
+        let scalars = Secp256K1Scalar::rand_vec(points.len());
+
+        let expected = scalars
+            .iter()
+            .zip(&points)
+            .fold(ProjectivePoint::ZERO, |acc, (&x, &p)| {
+                acc + CurveScalar(x) * p
+            });
+
+        for window_bits in [1, 4, 5, 8] {
+            assert_eq!(msm_parallel(&scalars, &points, window_bits), expected);
+        }
+    }
+
+    #[test]
+    fn test_msm_empty() {
+        assert_eq!(
+            msm_parallel::<Secp256K1>(&[], &[], 5),
+            ProjectivePoint::ZERO
+        );
+    }
+}
@@ -0,0 +1,100 @@
+#[cfg(not(feature = "std"))]
+use alloc::vec::Vec;
+use core::ops::Mul;
+
+use crate::curve::curve_types::{Curve, CurveScalar, ProjectivePoint};
+use crate::field::types::{Field, PrimeField};
+
+const WINDOW_BITS: usize = 4;
+const BASE: usize = 1 << WINDOW_BITS;
+
+/// Returns the little-endian `window_bits`-bit digits of `x`.
+pub(crate) fn to_digits<C: Curve>(x: &C::ScalarField, window_bits: usize) -> Vec<usize> {
+    debug_assert!(window_bits < usize::BITS as usize);
+    let limbs = x.to_canonical_biguint().to_u64_digits();
+    let bit = |i: usize| limbs.get(i / 64).map_or(0, |&limb| (limb >> (i % 64)) & 1) as usize;
+
+    let num_digits = C::ScalarField::BITS.div_ceil(window_bits);
+    (0..num_digits)
+        .map(|i| {
+            (0..window_bits)
+                .map(|j| bit(i * window_bits + j) << j)
+                .sum()
+        })
+        .collect()
+}
+
+impl<C: Curve> Mul<ProjectivePoint<C>> for CurveScalar<C> {
+    type Output = ProjectivePoint<C>;
+
+    #[allow(clippy::suspicious_arithmetic_impl)]
+    fn mul(self, rhs: ProjectivePoint<C>) -> Self::Output {
+        // Yao's method; see https://koclab.cs.ucsb.edu/teaching/ecc/eccPapers/Doche-ch09.pdf
+        let precomputed_powers: Vec<_> = (0..BASE)
+            .scan(ProjectivePoint::ZERO, |acc, _| {
+                let tmp = *acc;
+                *acc = *acc + rhs;
+                Some(tmp)
+            })
+            .collect();
+
+        let digits = to_digits::<C>(&self.0, WINDOW_BITS);
+
+        let mut y = ProjectivePoint::ZERO;
+        for &digit in digits.iter().rev() {
+            for _ in 0..WINDOW_BITS {
+                y = y.double();
+            }
+            y = y + precomputed_powers[digit];
+        }
+
+        y
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use num::BigUint;
+
+    use crate::curve::curve_types::{Curve, CurveScalar, ProjectivePoint};
+    use crate::curve::secp256k1::Secp256K1;
+    use crate::field::secp256k1_scalar::Secp256K1Scalar;
+    use crate::field::types::{Field, PrimeField, Sample};
+
+    #[test]
+    fn test_mul_by_small_scalars() {
+        let g = Secp256K1::GENERATOR_PROJECTIVE;
+        assert_eq!(
+            CurveScalar(Secp256K1Scalar::ZERO) * g,
+            ProjectivePoint::ZERO
+        );
+        assert_eq!(CurveScalar(Secp256K1Scalar::ONE) * g, g);
+        assert_eq!(CurveScalar(Secp256K1Scalar::TWO) * g, g.double());
+    }
+
+    #[test]
+    fn test_mul_by_order_is_zero() {
+        let g = Secp256K1::GENERATOR_PROJECTIVE;
+        let neg_one = CurveScalar(Secp256K1Scalar::NEG_ONE) * g;
+        assert_eq!(neg_one, g.neg());
+        assert_eq!(neg_one + g, ProjectivePoint::ZERO);
+    }
+
+    #[test]
+    fn test_mul_matches_double_and_add() {
+        let g = Secp256K1::GENERATOR_PROJECTIVE;
+        let k = Secp256K1Scalar::rand();
+
+        let bits = k.to_canonical_biguint();
+        let mut expected = ProjectivePoint::ZERO;
+        for i in (0..bits.bits()).rev() {
+            expected = expected.double();
+            if bits.bit(i) {
+                expected = expected + g;
+            }
+        }
+
+        assert_eq!(CurveScalar(k) * g, expected);
+        assert!(bits < BigUint::from(1u8) << 256);
+    }
+}
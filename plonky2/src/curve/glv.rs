@@ -0,0 +1,147 @@
+use num::BigUint;
+
+use crate::curve::curve_msm::msm_parallel;
+use crate::curve::curve_types::{AffinePoint, ProjectivePoint};
+use crate::curve::secp256k1::Secp256K1;
+use crate::field::secp256k1_base::Secp256K1Base;
+use crate::field::secp256k1_scalar::Secp256K1Scalar;
+use crate::field::types::{Field, PrimeField};
+
+/// A nontrivial cube root of unity in the base field. The map `(x, y) -> (beta * x, y)` is an
+/// endomorphism of secp256k1 which acts on points as multiplication by [`GLV_S`].
+pub const GLV_BETA: Secp256K1Base = Secp256K1Base([
+    13923278643952681454,
+    11308619431505398165,
+    7954561588662645993,
+    8856726876819556112,
+]);
+
+/// The eigenvalue of the GLV endomorphism, a nontrivial cube root of unity in the scalar field.
+pub const GLV_S: Secp256K1Scalar = Secp256K1Scalar([
+    16069571880186789234,
+    1310022930574435960,
+    11900229862571533402,
+    6008836872998760672,
+]);
+
+// Short basis `(A1, B1), (A2, B2)` of the lattice `{(a, b) : a + b * GLV_S = 0 mod n}`.
+const A1: Secp256K1Scalar = Secp256K1Scalar([16747920425669159701, 3496713202691238861, 0, 0]);
+const MINUS_B1: Secp256K1Scalar =
+    Secp256K1Scalar([8022177200260244675, 16448129721693014056, 0, 0]);
+const A2: Secp256K1Scalar = Secp256K1Scalar([6323353552219852760, 1498098850674701302, 1, 0]);
+const B2: Secp256K1Scalar = Secp256K1Scalar([16747920425669159701, 3496713202691238861, 0, 0]);
+
+/// Decomposes `k` as `k = k1 + k2 * GLV_S`, with `k1` and `k2` roughly 128 bits each.
+///
+/// Returns `(|k1|, |k2|, k1 < 0, k2 < 0)`, where `k1` and `k2` are interpreted as signed integers
+/// in `(-n/2, n/2]`.
+pub fn decompose_secp256k1_scalar(
+    k: Secp256K1Scalar,
+) -> (Secp256K1Scalar, Secp256K1Scalar, bool, bool) {
+    let p = Secp256K1Scalar::order();
+    let k_biguint = k.to_canonical_biguint();
+
+    let half_p = &p / BigUint::from(2u32);
+
+    // Round `b2 * k / n` and `-b1 * k / n` to the nearest integer.
+    let round_div = |x: BigUint| (x + &half_p) / &p;
+    let c1 = Secp256K1Scalar::from_noncanonical_biguint(round_div(
+        B2.to_canonical_biguint() * &k_biguint,
+    ));
+    let c2 = Secp256K1Scalar::from_noncanonical_biguint(round_div(
+        MINUS_B1.to_canonical_biguint() * &k_biguint,
+    ));
+
+    let k1_raw = k - c1 * A1 - c2 * A2;
+    let k2_raw = c1 * MINUS_B1 - c2 * B2;
+    debug_assert!(k1_raw + GLV_S * k2_raw == k);
+
+    let abs = |x: Secp256K1Scalar| {
+        let is_neg = x.to_canonical_biguint() > half_p;
+        (if is_neg { -x } else { x }, is_neg)
+    };
+    let (k1, k1_neg) = abs(k1_raw);
+    let (k2, k2_neg) = abs(k2_raw);
+
+    (k1, k2, k1_neg, k2_neg)
+}
+
+/// Computes `k * p` using the GLV endomorphism to halve the number of doublings.
+pub fn glv_mul(p: ProjectivePoint<Secp256K1>, k: Secp256K1Scalar) -> ProjectivePoint<Secp256K1> {
+    let (k1, k2, k1_neg, k2_neg) = decompose_secp256k1_scalar(k);
+
+    let p_affine = p.to_affine();
+    let sp = AffinePoint::<Secp256K1> {
+        x: p_affine.x * GLV_BETA,
+        y: p_affine.y,
+        zero: p_affine.zero,
+    };
+
+    let first = if k1_neg { p.neg() } else { p };
+    let second = if k2_neg {
+        sp.to_projective().neg()
+    } else {
+        sp.to_projective()
+    };
+
+    msm_parallel(&[k1, k2], &[first, second], 5)
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+    use num::BigUint;
+
+    use crate::curve::curve_types::{Curve, CurveScalar};
+    use crate::curve::glv::{decompose_secp256k1_scalar, glv_mul, GLV_BETA, GLV_S};
+    use crate::curve::secp256k1::Secp256K1;
+    use crate::field::secp256k1_base::Secp256K1Base;
+    use crate::field::secp256k1_scalar::Secp256K1Scalar;
+    use crate::field::types::{Field, PrimeField, Sample};
+
+    #[test]
+    fn test_glv_constants() {
+        assert_eq!(GLV_BETA.cube(), Secp256K1Base::ONE);
+        assert_ne!(GLV_BETA, Secp256K1Base::ONE);
+        assert_eq!(GLV_S.cube(), Secp256K1Scalar::ONE);
+        assert_ne!(GLV_S, Secp256K1Scalar::ONE);
+
+        let g = Secp256K1::GENERATOR_AFFINE;
+        let sg = (CurveScalar(GLV_S) * g.to_projective()).to_affine();
+        assert_eq!(sg.x, GLV_BETA * g.x);
+        assert_eq!(sg.y, g.y);
+    }
+
+    #[test]
+    fn test_glv_decompose() -> Result<()> {
+        for _ in 0..1000 {
+            let k = Secp256K1Scalar::rand();
+            let (k1, k2, k1_neg, k2_neg) = decompose_secp256k1_scalar(k);
+            let one = Secp256K1Scalar::ONE;
+            let m1 = if k1_neg { -one } else { one };
+            let m2 = if k2_neg { -one } else { one };
+
+            assert!(k1.to_canonical_biguint() < BigUint::from(1u8) << 128);
+            assert!(k2.to_canonical_biguint() < BigUint::from(1u8) << 128);
+            assert!(k1 * m1 + GLV_S * k2 * m2 == k);
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_glv_mul() -> Result<()> {
+        for _ in 0..20 {
+            let k = Secp256K1Scalar::rand();
+
+            let p = CurveScalar(Secp256K1Scalar::rand()) * Secp256K1::GENERATOR_PROJECTIVE;
+
+            let kp = CurveScalar(k) * p;
+            let glv = glv_mul(p, k);
+
+            assert!(kp == glv);
+        }
+
+        Ok(())
+    }
+}
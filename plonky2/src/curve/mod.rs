@@ -0,0 +1,10 @@
+//! Native elliptic curve arithmetic, used to build and test the curve gadgets in
+//! [gadgets](crate::gadgets).
+
+pub mod curve_adds;
+pub mod curve_msm;
+pub mod curve_multiplication;
+pub mod curve_types;
+pub mod ecdsa;
+pub mod glv;
+pub mod secp256k1;
@@ -1,12 +1,20 @@
 #[cfg(not(feature = "std"))]
-use alloc::{format, vec::Vec};
+use alloc::{borrow::Cow, format, vec::Vec};
+use core::mem::size_of;
+#[cfg(feature = "std")]
+use std::borrow::Cow;
+#[cfg(feature = "mmap")]
+use std::path::PathBuf;
 
+#[cfg(feature = "mmap")]
+use anyhow::Context;
+use anyhow::Result;
 use itertools::Itertools;
 use plonky2_field::types::Field;
 use plonky2_maybe_rayon::*;
 
 use crate::field::extension::Extendable;
-use crate::field::fft::FftRootTable;
+use crate::field::fft::{fft_root_table, ifft_with_options, FftRootTable};
 use crate::field::packed::PackedField;
 use crate::field::polynomial::{PolynomialCoeffs, PolynomialValues};
 use crate::fri::proof::FriProof;
@@ -14,10 +22,12 @@ use crate::fri::prover::fri_proof;
 use crate::fri::structure::{FriBatchInfo, FriInstanceInfo};
 use crate::fri::FriParams;
 use crate::hash::hash_types::RichField;
-use crate::hash::merkle_tree::MerkleTree;
+use crate::hash::merkle_tree::{MerkleStorage, MerkleTree};
 use crate::iop::challenger::Challenger;
-use crate::plonk::config::GenericConfig;
+use crate::plonk::config::{GenericConfig, Hasher};
 use crate::timed;
+#[cfg(feature = "mmap")]
+use crate::util::mmap::MmapSlice;
 use crate::util::reducing::ReducingFactor;
 use crate::util::timing::TimingTree;
 use crate::util::{log2_strict, reverse_bits, reverse_index_bits_in_place, transpose};
@@ -29,6 +39,8 @@ pub const SALT_SIZE: usize = 4;
 #[derive(Eq, PartialEq, Debug)]
 pub struct PolynomialBatch<F: RichField + Extendable<D>, C: GenericConfig<D, F = F>, const D: usize>
 {
+    /// The coefficients of the committed polynomials. This is empty for batches committed in
+    /// low-memory mode, whose polynomials are recomputed from the Merkle leaves by `polynomial`.
     pub polynomials: Vec<PolynomialCoeffs<F>>,
     pub merkle_tree: MerkleTree<F, C::Hasher>,
     pub degree_log: usize,
@@ -91,7 +103,7 @@ impl<F: RichField + Extendable<D>, C: GenericConfig<D, F = F>, const D: usize>
         let lde_values = timed!(
             timing,
             "FFT + blinding",
-            Self::lde_values(&polynomials, rate_bits, blinding, fft_root_table)
+            Self::lde_values(&polynomials, rate_bits, blinding, fft_root_table, None)
         );
 
         let mut leaves = timed!(timing, "transpose LDEs", transpose(&lde_values));
@@ -111,11 +123,215 @@ impl<F: RichField + Extendable<D>, C: GenericConfig<D, F = F>, const D: usize>
         }
     }
 
+    /// Like `from_values`, but uses the FFT tables precomputed in `context`, and writes the Merkle
+    /// leaves into a buffer recycled by `context` if it has one.
+    pub fn from_values_with_context(
+        values: Vec<PolynomialValues<F>>,
+        rate_bits: usize,
+        blinding: bool,
+        cap_height: usize,
+        timing: &mut TimingTree,
+        fft_root_table: Option<&FftRootTable<F>>,
+        context: &mut CommitmentContext<F>,
+    ) -> Result<Self> {
+        let coeffs = timed!(
+            timing,
+            "IFFT",
+            values
+                .into_par_iter()
+                .map(|v| ifft_with_options(v, None, Some(&context.root_table)))
+                .collect::<Vec<_>>()
+        );
+
+        Self::from_coeffs_with_context(
+            coeffs,
+            rate_bits,
+            blinding,
+            cap_height,
+            timing,
+            fft_root_table,
+            context,
+        )
+    }
+
+    /// Like `from_coeffs`, but uses the powers of the coset shift precomputed in `context`, and
+    /// writes the Merkle leaves into a buffer recycled by `context` if it has one.
+    pub fn from_coeffs_with_context(
+        polynomials: Vec<PolynomialCoeffs<F>>,
+        rate_bits: usize,
+        blinding: bool,
+        cap_height: usize,
+        timing: &mut TimingTree,
+        fft_root_table: Option<&FftRootTable<F>>,
+        context: &mut CommitmentContext<F>,
+    ) -> Result<Self> {
+        let degree = polynomials[0].len();
+        assert_eq!(
+            degree,
+            context.degree(),
+            "The commitment context is for polynomials of another degree"
+        );
+        let lde_bits = log2_strict(degree) + rate_bits;
+        let salt_size = if blinding { SALT_SIZE } else { 0 };
+
+        let lde_values = timed!(
+            timing,
+            "FFT",
+            Self::lde_values(
+                &polynomials,
+                rate_bits,
+                false,
+                fft_root_table,
+                Some(&context.coset_shift_powers),
+            )
+        );
+
+        let mut leaves = LeafBuffer::recycled(
+            context.leaf_buffers.pop().unwrap_or_default(),
+            1 << lde_bits,
+            polynomials.len() + salt_size,
+        );
+        timed!(timing, "transpose LDEs", {
+            leaves.extend_with_ldes(&lde_values, lde_bits);
+            drop(lde_values);
+            leaves.extend_with_salt(salt_size);
+        });
+
+        let merkle_tree = timed!(
+            timing,
+            "build Merkle tree",
+            leaves.into_merkle_tree(cap_height)?
+        );
+
+        Ok(Self {
+            polynomials,
+            merkle_tree,
+            degree_log: log2_strict(degree),
+            rate_bits,
+            blinding,
+        })
+    }
+
+    /// Like `from_values`, but interpolates and extends the polynomials in chunks of columns whose
+    /// buffers fit in `memory_budget` bytes, and drops their coefficients once hashed. They are
+    /// recomputed from the Merkle leaves when needed, see `polynomial`. The Merkle tree is kept in
+    /// `storage`, and this fails if its files cannot be created.
+    pub fn from_values_low_memory(
+        values: Vec<PolynomialValues<F>>,
+        rate_bits: usize,
+        blinding: bool,
+        cap_height: usize,
+        timing: &mut TimingTree,
+        fft_root_table: Option<&FftRootTable<F>>,
+        memory_budget: usize,
+        storage: &MerkleStorage,
+    ) -> Result<Self> {
+        let num_polys = values.len();
+        let degree = values[0].len();
+        let chunk_size = Self::low_memory_chunk_size(degree, rate_bits, memory_budget);
+        let coeff_chunks = into_chunks(values, chunk_size)
+            .map(|chunk| chunk.into_par_iter().map(|v| v.ifft()).collect());
+
+        Self::from_coeff_chunks(
+            coeff_chunks,
+            num_polys,
+            degree,
+            rate_bits,
+            blinding,
+            cap_height,
+            timing,
+            fft_root_table,
+            storage,
+        )
+    }
+
+    /// Like `from_coeffs`, but extends the polynomials in chunks of columns whose buffers fit in
+    /// `memory_budget` bytes, and drops their coefficients once hashed. They are recomputed from
+    /// the Merkle leaves when needed, see `polynomial`. The Merkle tree is kept in `storage`, and
+    /// this fails if its files cannot be created.
+    pub fn from_coeffs_low_memory(
+        polynomials: Vec<PolynomialCoeffs<F>>,
+        rate_bits: usize,
+        blinding: bool,
+        cap_height: usize,
+        timing: &mut TimingTree,
+        fft_root_table: Option<&FftRootTable<F>>,
+        memory_budget: usize,
+        storage: &MerkleStorage,
+    ) -> Result<Self> {
+        let num_polys = polynomials.len();
+        let degree = polynomials[0].len();
+        let chunk_size = Self::low_memory_chunk_size(degree, rate_bits, memory_budget);
+
+        Self::from_coeff_chunks(
+            into_chunks(polynomials, chunk_size),
+            num_polys,
+            degree,
+            rate_bits,
+            blinding,
+            cap_height,
+            timing,
+            fft_root_table,
+            storage,
+        )
+    }
+
+    /// The number of columns to process at once so that, for each of them, the coefficients and
+    /// the LDE fit in `memory_budget` bytes. This is at least one.
+    fn low_memory_chunk_size(degree: usize, rate_bits: usize, memory_budget: usize) -> usize {
+        let column_size = (degree + (degree << rate_bits)) * size_of::<F>();
+        (memory_budget / column_size).max(1)
+    }
+
+    /// Commits to the polynomials given in chunks, writing the LDE of each chunk directly into the
+    /// Merkle leaves before moving on to the next one.
+    fn from_coeff_chunks(
+        coeff_chunks: impl Iterator<Item = Vec<PolynomialCoeffs<F>>>,
+        num_polys: usize,
+        degree: usize,
+        rate_bits: usize,
+        blinding: bool,
+        cap_height: usize,
+        timing: &mut TimingTree,
+        fft_root_table: Option<&FftRootTable<F>>,
+        storage: &MerkleStorage,
+    ) -> Result<Self> {
+        let lde_bits = log2_strict(degree) + rate_bits;
+        let salt_size = if blinding { SALT_SIZE } else { 0 };
+        let mut leaves = LeafBuffer::new(storage, 1 << lde_bits, num_polys + salt_size)?;
+
+        timed!(timing, "FFT + transpose LDEs in chunks", {
+            for coeffs in coeff_chunks {
+                let lde_values = Self::lde_values(&coeffs, rate_bits, false, fft_root_table, None);
+                drop(coeffs);
+                leaves.extend_with_ldes(&lde_values, lde_bits);
+            }
+            leaves.extend_with_salt(salt_size);
+        });
+
+        let merkle_tree = timed!(
+            timing,
+            "build Merkle tree",
+            leaves.into_merkle_tree(cap_height)?
+        );
+
+        Ok(Self {
+            polynomials: Vec::new(),
+            merkle_tree,
+            degree_log: log2_strict(degree),
+            rate_bits,
+            blinding,
+        })
+    }
+
+    /// Evaluates `polynomials` on the LDE coset. If given, `coset_shift_powers` must hold the first
+    /// `degree` powers of `F::coset_shift()`, which are otherwise recomputed for each polynomial.
     fn lde_values(
         polynomials: &[PolynomialCoeffs<F>],
         rate_bits: usize,
         blinding: bool,
         fft_root_table: Option<&FftRootTable<F>>,
+        coset_shift_powers: Option<&[F]>,
     ) -> Vec<Vec<F>> {
         let degree = polynomials[0].len();
 
@@ -126,9 +342,23 @@ impl<F: RichField + Extendable<D>, C: GenericConfig<D, F = F>, const D: usize>
             .par_iter()
             .map(|p| {
                 assert_eq!(p.len(), degree, "Polynomial degrees inconsistent");
-                p.lde(rate_bits)
-                    .coset_fft_with_options(F::coset_shift(), Some(rate_bits), fft_root_table)
-                    .values
+                match coset_shift_powers {
+                    Some(powers) => {
+                        let mut shifted = p.lde(rate_bits);
+                        shifted
+                            .coeffs
+                            .iter_mut()
+                            .zip(powers)
+                            .for_each(|(c, &r)| *c *= r);
+                        shifted.fft_with_options(Some(rate_bits), fft_root_table)
+                    }
+                    None => p.lde(rate_bits).coset_fft_with_options(
+                        F::coset_shift(),
+                        Some(rate_bits),
+                        fft_root_table,
+                    ),
+                }
+                .values
             })
             .chain(
                 (0..salt_size)
@@ -138,11 +368,56 @@ impl<F: RichField + Extendable<D>, C: GenericConfig<D, F = F>, const D: usize>
             .collect()
     }
 
+    /// The number of committed polynomials.
+    pub fn num_polys(&self) -> usize {
+        if !self.polynomials.is_empty() {
+            return self.polynomials.len();
+        }
+        let salt_size = if self.blinding { SALT_SIZE } else { 0 };
+        if self.merkle_tree.num_leaves() == 0 {
+            return 0;
+        }
+        self.merkle_tree.get(0).len() - salt_size
+    }
+
+    /// The coefficients of the `index`th committed polynomial. For batches committed in low-memory
+    /// mode, these are interpolated from the LDE values on the original coset, which are the first
+    /// `2^degree_log` Merkle leaves, unless `recompute_polynomials` was called.
+    pub fn polynomial(&self, index: usize) -> Cow<'_, PolynomialCoeffs<F>> {
+        if !self.polynomials.is_empty() {
+            return Cow::Borrowed(&self.polynomials[index]);
+        }
+        let values = (0..1 << self.degree_log)
+            .map(|i| self.get_lde_values(i, 1 << self.rate_bits)[index])
+            .collect();
+        Cow::Owned(PolynomialValues::new(values).coset_ifft(F::coset_shift()))
+    }
+
+    /// For batches committed in low-memory mode, interpolates all committed polynomials from the
+    /// Merkle leaves and keeps them in `polynomials`, so that opening the batch interpolates each
+    /// of them only once. This does nothing for other batches.
+    pub fn recompute_polynomials(&mut self) {
+        if !self.polynomials.is_empty() {
+            return;
+        }
+        let rows = (0..1 << self.degree_log)
+            .map(|i| self.get_lde_values(i, 1 << self.rate_bits))
+            .collect::<Vec<_>>();
+        let polynomials = (0..self.num_polys())
+            .into_par_iter()
+            .map(|index| {
+                let values = rows.iter().map(|row| row[index]).collect();
+                PolynomialValues::new(values).coset_ifft(F::coset_shift())
+            })
+            .collect();
+        self.polynomials = polynomials;
+    }
+
     /// Fetches LDE values at the `index * step`th point.
     pub fn get_lde_values(&self, index: usize, step: usize) -> &[F] {
         let index = index * step;
         let index = reverse_bits(index, self.degree_log + self.rate_bits);
-        let slice = &self.merkle_tree.leaves[index];
+        let slice = self.merkle_tree.get(index);
         &slice[..slice.len() - if self.blinding { SALT_SIZE } else { 0 }]
     }
 
@@ -182,9 +457,42 @@ impl<F: RichField + Extendable<D>, C: GenericConfig<D, F = F>, const D: usize>
     ) -> FriProof<F, C::Hasher, D> {
         assert!(D > 1, "Not implemented for D=1.");
         let alpha = challenger.get_extension_challenge::<D>();
-        let mut alpha = ReducingFactor::new(alpha);
 
         // Final low-degree polynomial that goes into FRI.
+        let final_poly = Self::combine_openings(instance, oracles, alpha, timing);
+
+        let lde_final_poly = final_poly.lde(fri_params.config.rate_bits);
+        let lde_final_values = timed!(
+            timing,
+            &format!("perform final FFT {}", lde_final_poly.len()),
+            lde_final_poly.coset_fft(F::coset_shift().into())
+        );
+
+        let fri_proof = fri_proof::<F, C, D>(
+            &oracles
+                .par_iter()
+                .map(|c| &c.merkle_tree)
+                .collect::<Vec<_>>(),
+            lde_final_poly,
+            lde_final_values,
+            challenger,
+            fri_params,
+            timing,
+        );
+
+        fri_proof
+    }
+
+    /// Computes the polynomial whose low degree proves the openings of `instance`, i.e. the
+    /// combination by powers of `alpha` of the quotients `(f(X) - f(z)) / (X - z)` of all its
+    /// opened polynomials `f` by their opening points `z`.
+    pub(crate) fn combine_openings(
+        instance: &FriInstanceInfo<F, D>,
+        oracles: &[&Self],
+        alpha: F::Extension,
+        timing: &mut TimingTree,
+    ) -> PolynomialCoeffs<F::Extension> {
+        let mut alpha = ReducingFactor::new(alpha);
         let mut final_poly = PolynomialCoeffs::empty();
 
         // Each batch `i` consists of an opening point `z_i` and polynomials `{f_ij}_j` to be opened at that point.
@@ -197,7 +505,7 @@ impl<F: RichField + Extendable<D>, C: GenericConfig<D, F = F>, const D: usize>
         for FriBatchInfo { point, polynomials } in &instance.batches {
             // Collect the coefficients of all the polynomials in `polynomials`.
             let polys_coeff = polynomials.iter().map(|fri_poly| {
-                &oracles[fri_poly.oracle_index].polynomials[fri_poly.polynomial_index]
+                oracles[fri_poly.oracle_index].polynomial(fri_poly.polynomial_index)
             });
             let composition_poly = timed!(
                 timing,
@@ -210,25 +518,161 @@ impl<F: RichField + Extendable<D>, C: GenericConfig<D, F = F>, const D: usize>
             final_poly += quotient;
         }
 
-        let lde_final_poly = final_poly.lde(fri_params.config.rate_bits);
-        let lde_final_values = timed!(
-            timing,
-            &format!("perform final FFT {}", lde_final_poly.len()),
-            lde_final_poly.coset_fft(F::coset_shift().into())
-        );
+        final_poly
+    }
+}
 
-        let fri_proof = fri_proof::<F, C, D>(
-            &oracles
-                .par_iter()
-                .map(|c| &c.merkle_tree)
-                .collect::<Vec<_>>(),
-            lde_final_poly,
-            lde_final_values,
-            challenger,
-            fri_params,
-            timing,
-        );
+/// Splits `v` into consecutive chunks of `chunk_size` elements, the last one possibly being smaller,
+/// so that each chunk can be freed before the next one is processed.
+fn into_chunks<T>(v: Vec<T>, chunk_size: usize) -> impl Iterator<Item = Vec<T>> {
+    let mut iter = v.into_iter();
+    core::iter::from_fn(move || {
+        let chunk = iter.by_ref().take(chunk_size).collect::<Vec<_>>();
+        (!chunk.is_empty()).then_some(chunk)
+    })
+}
 
-        fri_proof
+/// Data for committing to polynomials of a fixed degree which is reused across commitments: the
+/// FFT root table for that degree, the powers of the coset shift, and the Merkle leaf buffers of
+/// previous commitments.
+#[derive(Clone, Debug)]
+pub struct CommitmentContext<F: Field> {
+    /// The FFT root table for polynomials of the context's degree.
+    root_table: FftRootTable<F>,
+    /// The first `degree` powers of `F::coset_shift()`.
+    coset_shift_powers: Vec<F>,
+    /// Recycled Merkle leaves, used as a stack.
+    leaf_buffers: Vec<Vec<Vec<F>>>,
+}
+
+impl<F: Field> CommitmentContext<F> {
+    /// Creates a context for committing to polynomials of degree `< degree`.
+    pub fn new(degree: usize) -> Self {
+        Self {
+            root_table: fft_root_table(degree),
+            coset_shift_powers: F::coset_shift().powers().take(degree).collect(),
+            leaf_buffers: Vec::new(),
+        }
+    }
+
+    /// The number of coefficients of the polynomials this context is for.
+    pub fn degree(&self) -> usize {
+        self.coset_shift_powers.len()
+    }
+
+    /// Keeps the allocations of `leaves`, typically taken from the Merkle tree of a batch which is
+    /// no longer needed, for a later commitment. Buffers are reused in last-in, first-out order,
+    /// so that recycling the batches of a proof in reverse order gives each batch of the next
+    /// proof the buffer of the same size.
+    pub fn recycle_leaves(&mut self, leaves: Vec<Vec<F>>) {
+        if !leaves.is_empty() {
+            self.leaf_buffers.push(leaves);
+        }
+    }
+}
+
+/// The leaves of a Merkle tree under construction, which are filled in chunks of columns.
+enum LeafBuffer<F: RichField> {
+    Memory(Vec<Vec<F>>),
+    #[cfg(feature = "mmap")]
+    Mmap {
+        leaves: MmapSlice<F>,
+        leaf_len: usize,
+        /// The number of columns already filled.
+        filled: usize,
+        dir: Option<PathBuf>,
+    },
+}
+
+impl<F: RichField> LeafBuffer<F> {
+    fn new(storage: &MerkleStorage, num_leaves: usize, leaf_len: usize) -> Result<Self> {
+        Ok(match storage {
+            MerkleStorage::Memory => Self::Memory(
+                (0..num_leaves)
+                    .map(|_| Vec::with_capacity(leaf_len))
+                    .collect(),
+            ),
+            #[cfg(feature = "mmap")]
+            MerkleStorage::Mmap(dir) => Self::Mmap {
+                leaves: MmapSlice::new(num_leaves * leaf_len, F::ZERO, dir.as_deref())
+                    .context("Failed to create memory-mapped Merkle leaves")?,
+                leaf_len,
+                filled: 0,
+                dir: dir.clone(),
+            },
+        })
+    }
+
+    /// An in-memory buffer reusing the allocations of `leaves`.
+    fn recycled(mut leaves: Vec<Vec<F>>, num_leaves: usize, leaf_len: usize) -> Self {
+        leaves.resize_with(num_leaves, Vec::new);
+        leaves.par_iter_mut().for_each(|leaf| {
+            leaf.clear();
+            leaf.reserve(leaf_len);
+        });
+        Self::Memory(leaves)
+    }
+
+    /// Fills the next columns with `lde_values`, the values of a chunk of polynomials on the LDE
+    /// coset of size `2^lde_bits`, in bit-reversed order.
+    fn extend_with_ldes(&mut self, lde_values: &[Vec<F>], lde_bits: usize) {
+        self.extend(lde_values.len(), |i, leaf| {
+            let row = reverse_bits(i, lde_bits);
+            leaf.iter_mut()
+                .zip(lde_values)
+                .for_each(|(x, column)| *x = column[row]);
+        });
+    }
+
+    /// Fills the last `salt_size` columns with random values.
+    fn extend_with_salt(&mut self, salt_size: usize) {
+        if salt_size > 0 {
+            self.extend(salt_size, |_, salt| {
+                salt.copy_from_slice(&F::rand_vec(salt_size));
+            });
+        }
+    }
+
+    /// Fills the next `num_columns` columns, calling `fill` with the index of each leaf and the
+    /// part of the leaf to fill.
+    fn extend(&mut self, num_columns: usize, fill: impl Fn(usize, &mut [F]) + Sync) {
+        match self {
+            Self::Memory(leaves) => leaves.par_iter_mut().enumerate().for_each(|(i, leaf)| {
+                let start = leaf.len();
+                leaf.resize(start + num_columns, F::ZERO);
+                fill(i, &mut leaf[start..]);
+            }),
+            #[cfg(feature = "mmap")]
+            Self::Mmap {
+                leaves,
+                leaf_len,
+                filled,
+                ..
+            } => {
+                let start = *filled;
+                leaves
+                    .par_chunks_exact_mut(*leaf_len)
+                    .enumerate()
+                    .for_each(|(i, leaf)| fill(i, &mut leaf[start..start + num_columns]));
+                *filled += num_columns;
+            }
+        }
+    }
+
+    fn into_merkle_tree<H: Hasher<F>>(self, cap_height: usize) -> Result<MerkleTree<F, H>> {
+        Ok(match self {
+            Self::Memory(leaves) => MerkleTree::new(leaves, cap_height),
+            #[cfg(feature = "mmap")]
+            Self::Mmap {
+                leaves,
+                leaf_len,
+                filled,
+                dir,
+            } => {
+                debug_assert_eq!(filled, leaf_len);
+                MerkleTree::new_mapped(leaves, leaf_len, cap_height, dir.as_deref())
+                    .context("Failed to create memory-mapped Merkle digests")?
+            }
+        })
     }
 }
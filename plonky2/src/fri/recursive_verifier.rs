@@ -25,7 +25,7 @@ use crate::with_context;
 impl<F: RichField + Extendable<D>, const D: usize> CircuitBuilder<F, D> {
     /// Computes P'(x^arity) from {P(x*g^i)}_(i=0..arity), where g is a `arity`-th root of unity
     /// and P' is the FRI reduced polynomial.
-    fn compute_evaluation(
+    pub(crate) fn compute_evaluation(
         &mut self,
         x: Target,
         x_index_within_coset_bits: &[BoolTarget],
@@ -58,7 +58,7 @@ impl<F: RichField + Extendable<D>, const D: usize> CircuitBuilder<F, D> {
     /// Make sure we have enough wires and routed wires to do the FRI checks efficiently. This check
     /// isn't required -- without it we'd get errors elsewhere in the stack -- but just gives more
     /// helpful errors.
-    fn check_recursion_config(&self, max_fri_arity_bits: usize) {
+    pub(crate) fn check_recursion_config(&self, max_fri_arity_bits: usize) {
         let random_access = RandomAccessGate::<F, D>::new_from_config(
             &self.config,
             max_fri_arity_bits.max(self.config.fri_config.cap_height),
@@ -91,7 +91,11 @@ impl<F: RichField + Extendable<D>, const D: usize> CircuitBuilder<F, D> {
         );
     }
 
-    fn fri_verify_proof_of_work(&mut self, fri_pow_response: Target, config: &FriConfig) {
+    pub(crate) fn fri_verify_proof_of_work(
+        &mut self,
+        fri_pow_response: Target,
+        config: &FriConfig,
+    ) {
         self.assert_leading_zeros(
             fri_pow_response,
             config.proof_of_work_bits + (64 - F::order().bits()) as u32,
@@ -175,7 +179,7 @@ impl<F: RichField + Extendable<D>, const D: usize> CircuitBuilder<F, D> {
         }
     }
 
-    fn fri_verify_initial_proof<H: AlgebraicHasher<F>>(
+    pub(crate) fn fri_verify_initial_proof<H: AlgebraicHasher<F>>(
         &mut self,
         x_index_bits: &[BoolTarget],
         proof: &FriInitialTreeProofTarget,
@@ -202,7 +206,7 @@ impl<F: RichField + Extendable<D>, const D: usize> CircuitBuilder<F, D> {
         }
     }
 
-    fn fri_combine_initial(
+    pub(crate) fn fri_combine_initial(
         &mut self,
         instance: &FriInstanceInfoTarget<D>,
         proof: &FriInitialTreeProofTarget,
@@ -212,12 +216,6 @@ impl<F: RichField + Extendable<D>, const D: usize> CircuitBuilder<F, D> {
         params: &FriParams,
     ) -> ExtensionTarget<D> {
         assert!(D > 1, "Not implemented for D=1.");
-        let degree_log = params.degree_bits;
-        debug_assert_eq!(
-            degree_log,
-            params.config.cap_height + proof.evals_proofs[0].1.siblings.len()
-                - params.config.rate_bits
-        );
         let subgroup_x = self.convert_to_ext(subgroup_x);
         let mut alpha = ReducingFactorTarget::new(alpha);
         let mut sum = self.zero_extension();
@@ -261,6 +259,15 @@ impl<F: RichField + Extendable<D>, const D: usize> CircuitBuilder<F, D> {
         C::Hasher: AlgebraicHasher<F>,
     {
         let n_log = log2_strict(n);
+        debug_assert_eq!(
+            params.degree_bits,
+            params.config.cap_height
+                + round_proof.initial_trees_proof.evals_proofs[0]
+                    .1
+                    .siblings
+                    .len()
+                - params.config.rate_bits
+        );
 
         // Note that this `low_bits` decomposition permits non-canonical binary encodings. Here we
         // verify that this has a negligible impact on soundness error.
@@ -372,7 +379,7 @@ impl<F: RichField + Extendable<D>, const D: usize> CircuitBuilder<F, D> {
     /// Thus ambiguous elements contribute a negligible amount to soundness error.
     ///
     /// Here we compare the probabilities as a sanity check, to verify the claim above.
-    fn assert_noncanonical_indices_ok(config: &FriConfig) {
+    pub(crate) fn assert_noncanonical_indices_ok(config: &FriConfig) {
         let num_ambiguous_elems = u64::MAX - F::ORDER + 1;
         let query_error = config.rate();
         let p_ambiguous = (num_ambiguous_elems as f64) / (F::ORDER as f64);
@@ -428,7 +435,7 @@ impl<F: RichField + Extendable<D>, const D: usize> CircuitBuilder<F, D> {
         }
     }
 
-    fn add_virtual_fri_initial_trees_proof(
+    pub(crate) fn add_virtual_fri_initial_trees_proof(
         &mut self,
         num_leaves_per_oracle: &[usize],
         initial_merkle_proof_len: usize,
@@ -444,7 +451,7 @@ impl<F: RichField + Extendable<D>, const D: usize> CircuitBuilder<F, D> {
         FriInitialTreeProofTarget { evals_proofs }
     }
 
-    fn add_virtual_fri_query_step(
+    pub(crate) fn add_virtual_fri_query_step(
         &mut self,
         arity_bits: usize,
         merkle_proof_len: usize,
@@ -459,12 +466,12 @@ impl<F: RichField + Extendable<D>, const D: usize> CircuitBuilder<F, D> {
 /// For each opening point, holds the reduced (by `alpha`) evaluations of each polynomial that's
 /// opened at that point.
 #[derive(Clone)]
-struct PrecomputedReducedOpeningsTarget<const D: usize> {
+pub(crate) struct PrecomputedReducedOpeningsTarget<const D: usize> {
     reduced_openings_at_point: Vec<ExtensionTarget<D>>,
 }
 
 impl<const D: usize> PrecomputedReducedOpeningsTarget<D> {
-    fn from_os_and_alpha<F: RichField + Extendable<D>>(
+    pub(crate) fn from_os_and_alpha<F: RichField + Extendable<D>>(
         openings: &FriOpeningsTarget<D>,
         alpha: ExtensionTarget<D>,
         builder: &mut CircuitBuilder<F, D>,
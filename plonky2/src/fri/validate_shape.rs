@@ -1,5 +1,3 @@
-use anyhow::ensure;
-
 use crate::field::extension::Extendable;
 use crate::fri::proof::{FriProof, FriQueryRound, FriQueryStep};
 use crate::fri::structure::FriInstanceInfo;
@@ -7,12 +5,13 @@ use crate::fri::FriParams;
 use crate::hash::hash_types::RichField;
 use crate::plonk::config::GenericConfig;
 use crate::plonk::plonk_common::salt_size;
+use crate::plonk::verification_error::{ensure_shape, VerificationError};
 
 pub(crate) fn validate_fri_proof_shape<F, C, const D: usize>(
     proof: &FriProof<F, C::Hasher, D>,
     instance: &FriInstanceInfo<F, D>,
     params: &FriParams,
-) -> anyhow::Result<()>
+) -> Result<(), VerificationError>
 where
     F: RichField + Extendable<D>,
     C: GenericConfig<D, F = F>,
@@ -26,7 +25,7 @@ where
 
     let cap_height = params.config.cap_height;
     for cap in commit_phase_merkle_caps {
-        ensure!(cap.height() == cap_height);
+        ensure_shape("commit_phase_merkle_caps height", cap_height, cap.height())?;
     }
 
     for query_round in query_round_proofs {
@@ -35,17 +34,29 @@ where
             steps,
         } = query_round;
 
-        ensure!(initial_trees_proof.evals_proofs.len() == instance.oracles.len());
+        ensure_shape(
+            "initial_trees_proof",
+            instance.oracles.len(),
+            initial_trees_proof.evals_proofs.len(),
+        )?;
         for ((leaf, merkle_proof), oracle) in initial_trees_proof
             .evals_proofs
             .iter()
             .zip(&instance.oracles)
         {
-            ensure!(leaf.len() == oracle.num_polys + salt_size(oracle.blinding && params.hiding));
-            ensure!(merkle_proof.len() + cap_height == params.lde_bits());
+            ensure_shape(
+                "initial_trees_proof leaf",
+                oracle.num_polys + salt_size(oracle.blinding && params.hiding),
+                leaf.len(),
+            )?;
+            ensure_shape(
+                "initial_trees_proof merkle_proof",
+                params.lde_bits(),
+                merkle_proof.len() + cap_height,
+            )?;
         }
 
-        ensure!(steps.len() == params.reduction_arity_bits.len());
+        ensure_shape("steps", params.reduction_arity_bits.len(), steps.len())?;
         let mut codeword_len_bits = params.lde_bits();
         for (step, arity_bits) in steps.iter().zip(&params.reduction_arity_bits) {
             let FriQueryStep {
@@ -56,12 +67,14 @@ where
             let arity = 1 << arity_bits;
             codeword_len_bits -= arity_bits;
 
-            ensure!(evals.len() == arity);
-            ensure!(merkle_proof.len() + cap_height == codeword_len_bits);
+            ensure_shape("step evals", arity, evals.len())?;
+            ensure_shape(
+                "step merkle_proof",
+                codeword_len_bits,
+                merkle_proof.len() + cap_height,
+            )?;
         }
     }
 
-    ensure!(final_poly.len() == params.final_poly_len());
-
-    Ok(())
+    ensure_shape("final_poly", params.final_poly_len(), final_poly.len())
 }
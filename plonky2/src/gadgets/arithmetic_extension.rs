@@ -639,7 +639,8 @@ mod tests {
         let data = builder.build::<C>();
         let proof = data.prove(pw)?;
 
-        verify(proof, &data.verifier_only, &data.common)
+        verify(proof, &data.verifier_only, &data.common)?;
+        Ok(())
     }
 
     #[test]
@@ -666,7 +667,8 @@ mod tests {
         let data = builder.build::<C>();
         let proof = data.prove(pw)?;
 
-        verify(proof, &data.verifier_only, &data.common)
+        verify(proof, &data.verifier_only, &data.common)?;
+        Ok(())
     }
 
     #[test]
@@ -704,6 +706,7 @@ mod tests {
         let data = builder.build::<C>();
         let proof = data.prove(pw)?;
 
-        verify(proof, &data.verifier_only, &data.common)
+        verify(proof, &data.verifier_only, &data.common)?;
+        Ok(())
     }
 }
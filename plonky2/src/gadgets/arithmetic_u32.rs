@@ -0,0 +1,395 @@
+#[cfg(not(feature = "std"))]
+use alloc::{
+    string::{String, ToString},
+    vec,
+    vec::Vec,
+};
+use core::marker::PhantomData;
+
+use crate::field::extension::Extendable;
+use crate::gates::arithmetic_u32::U32ArithmeticGate;
+use crate::gates::subtraction_u32::U32SubtractionGate;
+use crate::hash::hash_types::RichField;
+use crate::iop::generator::{GeneratedValues, SimpleGenerator};
+use crate::iop::target::Target;
+use crate::iop::witness::{PartitionWitness, Witness, WitnessWrite};
+use crate::plonk::circuit_builder::CircuitBuilder;
+use crate::plonk::circuit_data::CommonCircuitData;
+use crate::util::serialization::{Buffer, IoResult, Read, Write};
+
+/// A `Target` whose value is assumed to fit in 32 bits.
+///
+/// Targets produced by the gadgets below are range-checked by the gates computing them. Targets
+/// obtained through [`CircuitBuilder::add_virtual_u32_target`] are not, and should be checked with
+/// [`CircuitBuilder::range_check_u32`] if their value is not otherwise constrained.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub struct U32Target(pub Target);
+
+impl<F: RichField + Extendable<D>, const D: usize> CircuitBuilder<F, D> {
+    pub fn add_virtual_u32_target(&mut self) -> U32Target {
+        U32Target(self.add_virtual_target())
+    }
+
+    pub fn add_virtual_u32_targets(&mut self, n: usize) -> Vec<U32Target> {
+        self.add_virtual_targets(n)
+            .into_iter()
+            .map(U32Target)
+            .collect()
+    }
+
+    /// Returns a routable [`U32Target`] with the given constant value.
+    pub fn constant_u32(&mut self, c: u32) -> U32Target {
+        U32Target(self.constant(F::from_canonical_u32(c)))
+    }
+
+    pub fn zero_u32(&mut self) -> U32Target {
+        U32Target(self.zero())
+    }
+
+    pub fn one_u32(&mut self) -> U32Target {
+        U32Target(self.one())
+    }
+
+    pub fn connect_u32(&mut self, x: U32Target, y: U32Target) {
+        self.connect(x.0, y.0)
+    }
+
+    pub fn assert_zero_u32(&mut self, x: U32Target) {
+        self.assert_zero(x.0)
+    }
+
+    /// Checks that `x < 2^32` using a `BaseSumGate`.
+    pub fn range_check_u32(&mut self, x: U32Target) {
+        self.range_check(x.0, 32);
+    }
+
+    /// Checks for special cases where the value of
+    /// `x * y + z`
+    /// can be determined without adding a `U32ArithmeticGate`.
+    fn arithmetic_u32_special_cases(
+        &mut self,
+        x: U32Target,
+        y: U32Target,
+        z: U32Target,
+    ) -> Option<(U32Target, U32Target)> {
+        let x_const = self.target_as_constant(x.0);
+        let y_const = self.target_as_constant(y.0);
+        let z_const = self.target_as_constant(z.0);
+
+        // If both terms are constant, return their (constant) sum.
+        let first_term_const = match (x_const, y_const) {
+            (Some(xx), Some(yy)) => Some(xx * yy),
+            _ => None,
+        };
+
+        if let (Some(a), Some(b)) = (first_term_const, z_const) {
+            let sum = (a + b).to_canonical_u64();
+            let (low, high) = (sum as u32, (sum >> 32) as u32);
+            return Some((self.constant_u32(low), self.constant_u32(high)));
+        }
+
+        None
+    }
+
+    /// Returns `(low, high)` such that `x * y + z = high * 2^32 + low`.
+    pub fn mul_add_u32(
+        &mut self,
+        x: U32Target,
+        y: U32Target,
+        z: U32Target,
+    ) -> (U32Target, U32Target) {
+        if let Some(result) = self.arithmetic_u32_special_cases(x, y, z) {
+            return result;
+        }
+
+        let gate = U32ArithmeticGate::<F, D>::new_from_config(&self.config);
+        let (row, copy) = self.find_slot(gate, &[], &[]);
+
+        self.connect(Target::wire(row, gate.wire_ith_multiplicand_0(copy)), x.0);
+        self.connect(Target::wire(row, gate.wire_ith_multiplicand_1(copy)), y.0);
+        self.connect(Target::wire(row, gate.wire_ith_addend(copy)), z.0);
+
+        let output_low = U32Target(Target::wire(row, gate.wire_ith_output_low_half(copy)));
+        let output_high = U32Target(Target::wire(row, gate.wire_ith_output_high_half(copy)));
+
+        (output_low, output_high)
+    }
+
+    /// Returns `(low, high)` such that `a + b = high * 2^32 + low`.
+    pub fn add_u32(&mut self, a: U32Target, b: U32Target) -> (U32Target, U32Target) {
+        let one = self.one_u32();
+        self.mul_add_u32(a, one, b)
+    }
+
+    /// Returns `(low, high)` such that the sum of `to_add` is `high * 2^32 + low`.
+    pub fn add_many_u32(&mut self, to_add: &[U32Target]) -> (U32Target, U32Target) {
+        match to_add.len() {
+            0 => (self.zero_u32(), self.zero_u32()),
+            1 => (to_add[0], self.zero_u32()),
+            2 => self.add_u32(to_add[0], to_add[1]),
+            _ => {
+                // A sum of fewer than 2^32 values of 32 bits each is less than the field order, so
+                // it can be computed natively and then split by a single `U32ArithmeticGate` op.
+                debug_assert!((to_add.len() as u64) < 1 << 32);
+                let sum = U32Target(self.add_many(to_add.iter().map(|x| x.0)));
+                let one = self.one_u32();
+                let zero = self.zero_u32();
+                self.mul_add_u32(sum, one, zero)
+            }
+        }
+    }
+
+    /// Adds the `to_add` values together with an incoming `carry`, returning `(low, high)` such
+    /// that the total is `high * 2^32 + low`.
+    pub fn add_u32s_with_carry(
+        &mut self,
+        to_add: &[U32Target],
+        carry: U32Target,
+    ) -> (U32Target, U32Target) {
+        if to_add.len() == 1 {
+            return self.add_u32(to_add[0], carry);
+        }
+
+        let to_add = to_add.iter().copied().chain([carry]).collect::<Vec<_>>();
+        self.add_many_u32(&to_add)
+    }
+
+    /// Returns `(low, high)` such that `a * b = high * 2^32 + low`.
+    pub fn mul_u32(&mut self, a: U32Target, b: U32Target) -> (U32Target, U32Target) {
+        let zero = self.zero_u32();
+        self.mul_add_u32(a, b, zero)
+    }
+
+    /// Returns `(result, borrow)` where `result = x - y - borrow mod 2^32` and the output `borrow`
+    /// is 1 if this subtraction underflowed, and 0 otherwise.
+    pub fn sub_u32(
+        &mut self,
+        x: U32Target,
+        y: U32Target,
+        borrow: U32Target,
+    ) -> (U32Target, U32Target) {
+        let gate = U32SubtractionGate::<F, D>::new_from_config(&self.config);
+        let (row, copy) = self.find_slot(gate, &[], &[]);
+
+        self.connect(Target::wire(row, gate.wire_ith_input_x(copy)), x.0);
+        self.connect(Target::wire(row, gate.wire_ith_input_y(copy)), y.0);
+        self.connect(
+            Target::wire(row, gate.wire_ith_input_borrow(copy)),
+            borrow.0,
+        );
+
+        let output_result = U32Target(Target::wire(row, gate.wire_ith_output_result(copy)));
+        let output_borrow = U32Target(Target::wire(row, gate.wire_ith_output_borrow(copy)));
+
+        (output_result, output_borrow)
+    }
+
+    /// Returns `(low, high)` such that `x = high * 2^32 + low`, with both halves range-checked.
+    ///
+    /// Note that the decomposition is not checked to be canonical, i.e. `high * 2^32 + low` is
+    /// only constrained to equal `x` modulo the field order.
+    pub fn split_to_u32(&mut self, x: Target) -> (U32Target, U32Target) {
+        let low = self.add_virtual_u32_target();
+        let high = self.add_virtual_u32_target();
+
+        self.range_check_u32(low);
+        self.range_check_u32(high);
+
+        let base = self.constant(F::from_canonical_u64(1u64 << 32u64));
+        let combined = self.mul_add(high.0, base, low.0);
+        self.connect(x, combined);
+
+        self.add_simple_generator(SplitToU32Generator::<F, D> {
+            x,
+            low,
+            high,
+            _phantom: PhantomData,
+        });
+
+        (low, high)
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct SplitToU32Generator<F: RichField + Extendable<D>, const D: usize> {
+    x: Target,
+    low: U32Target,
+    high: U32Target,
+    _phantom: PhantomData<F>,
+}
+
+impl<F: RichField + Extendable<D>, const D: usize> SimpleGenerator<F, D>
+    for SplitToU32Generator<F, D>
+{
+    fn id(&self) -> String {
+        "SplitToU32Generator".to_string()
+    }
+
+    fn dependencies(&self) -> Vec<Target> {
+        vec![self.x]
+    }
+
+    fn run_once(&self, witness: &PartitionWitness<F>, out_buffer: &mut GeneratedValues<F>) {
+        let x = witness.get_target(self.x);
+        let x_u64 = x.to_canonical_u64();
+        let low = x_u64 as u32;
+        let high = (x_u64 >> 32) as u32;
+
+        out_buffer.set_u32_target(self.low, low);
+        out_buffer.set_u32_target(self.high, high);
+    }
+
+    fn serialize(&self, dst: &mut Vec<u8>, _common_data: &CommonCircuitData<F, D>) -> IoResult<()> {
+        dst.write_target(self.x)?;
+        dst.write_target_u32(self.low)?;
+        dst.write_target_u32(self.high)
+    }
+
+    fn deserialize(src: &mut Buffer, _common_data: &CommonCircuitData<F, D>) -> IoResult<Self> {
+        let x = src.read_target()?;
+        let low = src.read_target_u32()?;
+        let high = src.read_target_u32()?;
+        Ok(Self {
+            x,
+            low,
+            high,
+            _phantom: PhantomData,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+    use rand::rngs::OsRng;
+    use rand::Rng;
+
+    use super::*;
+    use crate::field::types::{Field, PrimeField64, Sample};
+    use crate::iop::witness::PartialWitness;
+    use crate::plonk::circuit_data::CircuitConfig;
+    use crate::plonk::config::{GenericConfig, PoseidonGoldilocksConfig};
+    use crate::plonk::verifier::verify;
+
+    #[test]
+    fn test_mul_add_u32() -> Result<()> {
+        const D: usize = 2;
+        type C = PoseidonGoldilocksConfig;
+        type F = <C as GenericConfig<D>>::F;
+        let config = CircuitConfig::standard_recursion_config();
+        let mut pw = PartialWitness::new();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+
+        let mut rng = OsRng;
+        let x_value = rng.gen::<u32>();
+        let y_value = rng.gen::<u32>();
+        let z_value = rng.gen::<u32>();
+        let expected = x_value as u64 * y_value as u64 + z_value as u64;
+
+        let x = builder.add_virtual_u32_target();
+        let y = builder.add_virtual_u32_target();
+        let z = builder.add_virtual_u32_target();
+        let (low, high) = builder.mul_add_u32(x, y, z);
+        let expected_low = builder.constant_u32(expected as u32);
+        let expected_high = builder.constant_u32((expected >> 32) as u32);
+        builder.connect_u32(low, expected_low);
+        builder.connect_u32(high, expected_high);
+
+        pw.set_u32_target(x, x_value);
+        pw.set_u32_target(y, y_value);
+        pw.set_u32_target(z, z_value);
+
+        let data = builder.build::<C>();
+        let proof = data.prove(pw)?;
+        verify(proof, &data.verifier_only, &data.common)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_add_many_u32() -> Result<()> {
+        const D: usize = 2;
+        type C = PoseidonGoldilocksConfig;
+        type F = <C as GenericConfig<D>>::F;
+        let config = CircuitConfig::standard_recursion_config();
+        let mut pw = PartialWitness::new();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+
+        let mut rng = OsRng;
+        let values = (0..5).map(|_| rng.gen::<u32>()).collect::<Vec<_>>();
+        let expected = values.iter().map(|&v| v as u64).sum::<u64>();
+
+        let targets = builder.add_virtual_u32_targets(values.len());
+        let (low, high) = builder.add_many_u32(&targets);
+        let expected_low = builder.constant_u32(expected as u32);
+        let expected_high = builder.constant_u32((expected >> 32) as u32);
+        builder.connect_u32(low, expected_low);
+        builder.connect_u32(high, expected_high);
+
+        for (&t, &v) in targets.iter().zip(&values) {
+            pw.set_u32_target(t, v);
+        }
+
+        let data = builder.build::<C>();
+        let proof = data.prove(pw)?;
+        verify(proof, &data.verifier_only, &data.common)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_sub_u32() -> Result<()> {
+        const D: usize = 2;
+        type C = PoseidonGoldilocksConfig;
+        type F = <C as GenericConfig<D>>::F;
+        let config = CircuitConfig::standard_recursion_config();
+        let mut pw = PartialWitness::new();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+
+        let mut rng = OsRng;
+        let x_value = rng.gen::<u32>();
+        let y_value = rng.gen::<u32>();
+        let borrow_value = rng.gen::<bool>() as u32;
+        let (expected, underflow_1) = x_value.overflowing_sub(y_value);
+        let (expected, underflow_2) = expected.overflowing_sub(borrow_value);
+
+        let x = builder.add_virtual_u32_target();
+        let y = builder.add_virtual_u32_target();
+        let borrow = builder.add_virtual_u32_target();
+        let (result, new_borrow) = builder.sub_u32(x, y, borrow);
+        let expected_result = builder.constant_u32(expected);
+        let expected_borrow = builder.constant_u32((underflow_1 || underflow_2) as u32);
+        builder.connect_u32(result, expected_result);
+        builder.connect_u32(new_borrow, expected_borrow);
+
+        pw.set_u32_target(x, x_value);
+        pw.set_u32_target(y, y_value);
+        pw.set_u32_target(borrow, borrow_value);
+
+        let data = builder.build::<C>();
+        let proof = data.prove(pw)?;
+        verify(proof, &data.verifier_only, &data.common)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_split_to_u32() -> Result<()> {
+        const D: usize = 2;
+        type C = PoseidonGoldilocksConfig;
+        type F = <C as GenericConfig<D>>::F;
+        let config = CircuitConfig::standard_recursion_config();
+        let pw = PartialWitness::new();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+
+        let x_value = F::rand().to_canonical_u64();
+        let x = builder.constant(F::from_canonical_u64(x_value));
+        let (low, high) = builder.split_to_u32(x);
+        let expected_low = builder.constant_u32(x_value as u32);
+        let expected_high = builder.constant_u32((x_value >> 32) as u32);
+        builder.connect_u32(low, expected_low);
+        builder.connect_u32(high, expected_high);
+
+        let data = builder.build::<C>();
+        let proof = data.prove(pw)?;
+        verify(proof, &data.verifier_only, &data.common)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,534 @@
+#[cfg(not(feature = "std"))]
+use alloc::{vec, vec::Vec};
+
+use num::BigUint;
+
+use crate::curve::curve_types::{AffinePoint, Curve, CurveScalar, ProjectivePoint};
+use crate::field::extension::Extendable;
+use crate::field::types::Field;
+use crate::gadgets::arithmetic_u32::U32Target;
+use crate::gadgets::biguint::BigUintTarget;
+use crate::gadgets::nonnative::NonNativeTarget;
+use crate::hash::hash_types::RichField;
+use crate::hash::keccak::KeccakHash;
+use crate::iop::target::{BoolTarget, Target};
+use crate::plonk::circuit_builder::CircuitBuilder;
+use crate::plonk::config::Hasher;
+
+/// The number of scalar bits processed per step of windowed scalar multiplication.
+const WINDOW_BITS: usize = 4;
+
+/// A target representing an affine point on the curve `C`. We use incomplete arithmetic for
+/// efficiency, so we assume these points are not zero.
+#[derive(Clone, Debug)]
+pub struct AffinePointTarget<C: Curve> {
+    pub x: NonNativeTarget<C::BaseField>,
+    pub y: NonNativeTarget<C::BaseField>,
+}
+
+impl<C: Curve> AffinePointTarget<C> {
+    pub fn to_vec(&self) -> Vec<NonNativeTarget<C::BaseField>> {
+        vec![self.x.clone(), self.y.clone()]
+    }
+}
+
+/// A fixed point with no known discrete logarithm relation to the inputs, used as the starting
+/// value of windowed scalar multiplications so that the incomplete addition formulas never see
+/// the point at infinity.
+fn offset_point<F: RichField, C: Curve>() -> ProjectivePoint<C> {
+    let hash_0 = <KeccakHash<32> as Hasher<F>>::hash_no_pad(&[F::ZERO]);
+    let hash_0_scalar =
+        C::ScalarField::from_noncanonical_biguint(BigUint::from_bytes_le(&hash_0.0));
+    CurveScalar(hash_0_scalar) * C::GENERATOR_PROJECTIVE
+}
+
+impl<F: RichField + Extendable<D>, const D: usize> CircuitBuilder<F, D> {
+    pub fn constant_affine_point<C: Curve>(
+        &mut self,
+        point: AffinePoint<C>,
+    ) -> AffinePointTarget<C> {
+        debug_assert!(!point.zero);
+        AffinePointTarget {
+            x: self.constant_nonnative(point.x),
+            y: self.constant_nonnative(point.y),
+        }
+    }
+
+    pub fn connect_affine_point<C: Curve>(
+        &mut self,
+        lhs: &AffinePointTarget<C>,
+        rhs: &AffinePointTarget<C>,
+    ) {
+        self.connect_nonnative(&lhs.x, &rhs.x);
+        self.connect_nonnative(&lhs.y, &rhs.y);
+    }
+
+    pub fn add_virtual_affine_point_target<C: Curve>(&mut self) -> AffinePointTarget<C> {
+        let x = self.add_virtual_nonnative_target();
+        let y = self.add_virtual_nonnative_target();
+
+        AffinePointTarget { x, y }
+    }
+
+    /// Asserts that `p` lies on the curve `C`.
+    pub fn curve_assert_valid<C: Curve>(&mut self, p: &AffinePointTarget<C>) {
+        let b = self.constant_nonnative(C::B);
+
+        let y_squared = self.mul_nonnative(&p.y, &p.y);
+        let x_squared = self.mul_nonnative(&p.x, &p.x);
+        let x_cubed = self.mul_nonnative(&x_squared, &p.x);
+        let mut rhs = self.add_nonnative(&x_cubed, &b);
+        if C::A.is_nonzero() {
+            let a = self.constant_nonnative(C::A);
+            let a_x = self.mul_nonnative(&a, &p.x);
+            rhs = self.add_nonnative(&rhs, &a_x);
+        }
+
+        self.connect_nonnative(&y_squared, &rhs);
+    }
+
+    pub fn curve_neg<C: Curve>(&mut self, p: &AffinePointTarget<C>) -> AffinePointTarget<C> {
+        let neg_y = self.neg_nonnative(&p.y);
+        AffinePointTarget {
+            x: p.x.clone(),
+            y: neg_y,
+        }
+    }
+
+    /// Returns `-p` if `b` is true, and `p` otherwise.
+    pub fn curve_conditional_neg<C: Curve>(
+        &mut self,
+        p: &AffinePointTarget<C>,
+        b: BoolTarget,
+    ) -> AffinePointTarget<C> {
+        AffinePointTarget {
+            x: p.x.clone(),
+            y: self.nonnative_conditional_neg(&p.y, b),
+        }
+    }
+
+    pub fn curve_double<C: Curve>(&mut self, p: &AffinePointTarget<C>) -> AffinePointTarget<C> {
+        let AffinePointTarget { x, y } = p;
+        let double_y = self.add_nonnative(y, y);
+        let inv_double_y = self.inv_nonnative(&double_y);
+        let x_squared = self.mul_nonnative(x, x);
+        let double_x_squared = self.add_nonnative(&x_squared, &x_squared);
+        let mut numerator = self.add_nonnative(&double_x_squared, &x_squared);
+        if C::A.is_nonzero() {
+            let a = self.constant_nonnative(C::A);
+            numerator = self.add_nonnative(&numerator, &a);
+        }
+
+        let lambda = self.mul_nonnative(&numerator, &inv_double_y);
+        let lambda_squared = self.mul_nonnative(&lambda, &lambda);
+        let x_double = self.add_nonnative(x, x);
+        let x3 = self.sub_nonnative(&lambda_squared, &x_double);
+        let x_diff = self.sub_nonnative(x, &x3);
+        let lambda_x_diff = self.mul_nonnative(&lambda, &x_diff);
+        let y3 = self.sub_nonnative(&lambda_x_diff, y);
+
+        AffinePointTarget { x: x3, y: y3 }
+    }
+
+    pub fn curve_repeated_double<C: Curve>(
+        &mut self,
+        p: &AffinePointTarget<C>,
+        n: usize,
+    ) -> AffinePointTarget<C> {
+        let mut result = p.clone();
+        for _ in 0..n {
+            result = self.curve_double(&result);
+        }
+
+        result
+    }
+
+    /// Adds two points, which must be distinct and not inverses of each other; otherwise the
+    /// circuit is unsatisfiable.
+    pub fn curve_add<C: Curve>(
+        &mut self,
+        p1: &AffinePointTarget<C>,
+        p2: &AffinePointTarget<C>,
+    ) -> AffinePointTarget<C> {
+        let AffinePointTarget { x: x1, y: y1 } = p1;
+        let AffinePointTarget { x: x2, y: y2 } = p2;
+
+        let u = self.sub_nonnative(y2, y1);
+        let v = self.sub_nonnative(x2, x1);
+        let v_inv = self.inv_nonnative(&v);
+        let s = self.mul_nonnative(&u, &v_inv);
+        let s_squared = self.mul_nonnative(&s, &s);
+        let x_sum = self.add_nonnative(x2, x1);
+        let x3 = self.sub_nonnative(&s_squared, &x_sum);
+        let x_diff = self.sub_nonnative(x1, &x3);
+        let prod = self.mul_nonnative(&s, &x_diff);
+        let y3 = self.sub_nonnative(&prod, y1);
+
+        AffinePointTarget { x: x3, y: y3 }
+    }
+
+    /// Returns `p1 + p2` if `b` is true, and `p1` otherwise. The sum is constrained regardless of
+    /// `b`, so the same restrictions as in `curve_add` apply.
+    pub fn curve_conditional_add<C: Curve>(
+        &mut self,
+        p1: &AffinePointTarget<C>,
+        p2: &AffinePointTarget<C>,
+        b: BoolTarget,
+    ) -> AffinePointTarget<C> {
+        let sum = self.curve_add(p1, p2);
+        let x = self.if_nonnative(b, &sum.x, &p1.x);
+        let y = self.if_nonnative(b, &sum.y, &p1.y);
+
+        AffinePointTarget { x, y }
+    }
+
+    /// Returns `v[access_index]`. The length of `v` must be a power of two.
+    pub fn random_access_curve_points<C: Curve>(
+        &mut self,
+        access_index: Target,
+        v: Vec<AffinePointTarget<C>>,
+    ) -> AffinePointTarget<C> {
+        let num_limbs = Self::num_nonnative_limbs::<C::BaseField>();
+        let mut random_access_nonnative = |coords: Vec<&NonNativeTarget<C::BaseField>>| {
+            let limbs = (0..num_limbs)
+                .map(|i| {
+                    let limbs_i = coords.iter().map(|c| c.value.limbs[i].0).collect();
+                    U32Target(self.random_access(access_index, limbs_i))
+                })
+                .collect();
+            NonNativeTarget::from_biguint_unsafe(BigUintTarget { limbs })
+        };
+
+        let x = random_access_nonnative(v.iter().map(|p| &p.x).collect());
+        let y = random_access_nonnative(v.iter().map(|p| &p.y).collect());
+
+        AffinePointTarget { x, y }
+    }
+
+    /// Splits a non-native field element into little-endian 4-bit windows.
+    pub fn split_nonnative_to_4_bit_limbs<FF: Field>(
+        &mut self,
+        x: &NonNativeTarget<FF>,
+    ) -> Vec<Target> {
+        // A base-16 `BaseSumGate` would exceed the degree bound of the standard configs, so we
+        // split into base-4 digits and combine them pairwise.
+        let base_4_digits: Vec<_> = x
+            .value
+            .limbs
+            .iter()
+            .flat_map(|&limb| self.split_le_base::<4>(limb.0, 16))
+            .collect();
+        base_4_digits
+            .chunks(2)
+            .map(|pair| self.mul_const_add(F::from_canonical_usize(4), pair[1], pair[0]))
+            .collect()
+    }
+
+    /// Returns `[p, p, 2p, 3p, ..., 15p]`. The first entry stands in for `0 * p`, which can't be
+    /// represented; callers must not add it.
+    fn precompute_window<C: Curve>(
+        &mut self,
+        p: &AffinePointTarget<C>,
+    ) -> Vec<AffinePointTarget<C>> {
+        let mut multiples = vec![p.clone(), p.clone(), self.curve_double(p)];
+        for i in 3..1 << WINDOW_BITS {
+            let next = self.curve_add(&multiples[i - 1], p);
+            multiples.push(next);
+        }
+
+        multiples
+    }
+
+    /// Computes `sum_i n_i * p_i` using windowed multiplication with shared doublings, where the
+    /// terms are given as `(p_i, n_i)` pairs.
+    pub fn curve_msm_windowed<C: Curve>(
+        &mut self,
+        terms: &[(AffinePointTarget<C>, NonNativeTarget<C::ScalarField>)],
+    ) -> AffinePointTarget<C> {
+        assert!(!terms.is_empty(), "Cannot compute an empty MSM");
+
+        let tables: Vec<_> = terms
+            .iter()
+            .map(|(p, _)| self.precompute_window(p))
+            .collect();
+        let windows: Vec<_> = terms
+            .iter()
+            .map(|(_, n)| self.split_nonnative_to_4_bit_limbs(n))
+            .collect();
+        let num_windows = windows.iter().map(Vec::len).max().unwrap();
+
+        let starting_point = offset_point::<F, C>();
+        let mut result = self.constant_affine_point(starting_point.to_affine());
+        let zero = self.zero();
+        for i in (0..num_windows).rev() {
+            if i + 1 < num_windows {
+                result = self.curve_repeated_double(&result, WINDOW_BITS);
+            }
+
+            for (table, term_windows) in tables.iter().zip(&windows) {
+                let Some(&window) = term_windows.get(i) else {
+                    continue;
+                };
+                let to_add = self.random_access_curve_points(window, table.clone());
+                let is_zero = self.is_equal(window, zero);
+                let should_add = self.not(is_zero);
+                result = self.curve_conditional_add(&result, &to_add, should_add);
+            }
+        }
+
+        // The starting point was doubled `WINDOW_BITS` times for every window but the first.
+        let mut starting_point_multiplied = starting_point;
+        for _ in 0..WINDOW_BITS * (num_windows - 1) {
+            starting_point_multiplied = starting_point_multiplied.double();
+        }
+        let to_subtract = self.constant_affine_point((-starting_point_multiplied).to_affine());
+        self.curve_add(&result, &to_subtract)
+    }
+
+    /// Computes `n * p` using windowed multiplication.
+    pub fn curve_scalar_mul_windowed<C: Curve>(
+        &mut self,
+        p: &AffinePointTarget<C>,
+        n: &NonNativeTarget<C::ScalarField>,
+    ) -> AffinePointTarget<C> {
+        self.curve_msm_windowed(&[(p.clone(), n.clone())])
+    }
+
+    /// Computes `n * base` for a point `base` known at circuit-building time. No doublings are
+    /// needed, since the multiples of `base` are precomputed natively.
+    pub fn fixed_base_curve_mul<C: Curve>(
+        &mut self,
+        base: AffinePoint<C>,
+        n: &NonNativeTarget<C::ScalarField>,
+    ) -> AffinePointTarget<C> {
+        let windows = self.split_nonnative_to_4_bit_limbs(n);
+
+        // Holds `(16^i) * base` for each window `i`.
+        let scaled_bases = (0..windows.len()).scan(base.to_projective(), |acc, _| {
+            let tmp = *acc;
+            for _ in 0..WINDOW_BITS {
+                *acc = acc.double();
+            }
+            Some(tmp)
+        });
+
+        let starting_point = offset_point::<F, C>();
+        let mut result = self.constant_affine_point(starting_point.to_affine());
+        let zero = self.zero();
+        for (window, scaled_base) in windows.into_iter().zip(scaled_bases) {
+            // `multiples[t] = t * scaled_base` for `t = 1..16`.
+            let multiples = (1..1 << WINDOW_BITS)
+                .scan(ProjectivePoint::ZERO, |acc, _| {
+                    *acc = *acc + scaled_base;
+                    Some(*acc)
+                })
+                .collect::<Vec<_>>();
+            let mut multiples = ProjectivePoint::batch_to_affine(&multiples)
+                .into_iter()
+                .map(|p| self.constant_affine_point(p))
+                .collect::<Vec<_>>();
+            // Entry 0 is never added, so it can be anything.
+            multiples.insert(0, multiples[0].clone());
+
+            let to_add = self.random_access_curve_points(window, multiples);
+            let is_zero = self.is_equal(window, zero);
+            let should_add = self.not(is_zero);
+            result = self.curve_conditional_add(&result, &to_add, should_add);
+        }
+
+        let to_subtract = self.constant_affine_point((-starting_point).to_affine());
+        self.curve_add(&result, &to_subtract)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(not(feature = "std"))]
+    use alloc::vec;
+
+    use anyhow::Result;
+
+    use crate::curve::curve_types::{AffinePoint, Curve, CurveScalar};
+    use crate::curve::secp256k1::Secp256K1;
+    use crate::field::secp256k1_base::Secp256K1Base;
+    use crate::field::secp256k1_scalar::Secp256K1Scalar;
+    use crate::field::types::{Field, Sample};
+    use crate::gadgets::biguint::BigUintTarget;
+    use crate::gadgets::nonnative::NonNativeTarget;
+    use crate::iop::witness::{PartialWitness, WitnessWrite};
+    use crate::plonk::circuit_builder::CircuitBuilder;
+    use crate::plonk::circuit_data::CircuitConfig;
+    use crate::plonk::config::{GenericConfig, PoseidonGoldilocksConfig};
+
+    const D: usize = 2;
+    type C = PoseidonGoldilocksConfig;
+    type F = <C as GenericConfig<D>>::F;
+
+    fn prove_and_verify(builder: CircuitBuilder<F, D>, pw: PartialWitness<F>) -> Result<()> {
+        let data = builder.build::<C>();
+        let proof = data.prove(pw)?;
+        data.verify(proof)?;
+        Ok(())
+    }
+
+    /// Checks the witness against all the constraints of the circuit, which is much cheaper than
+    /// proving it.
+    fn check_constraints(builder: CircuitBuilder<F, D>, pw: PartialWitness<F>) -> Result<()> {
+        let data = builder.mock_build::<C>();
+        let witness = data.generate_witness(pw)?;
+        data.check_constraints(witness)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_curve_point_is_valid() -> Result<()> {
+        let config = CircuitConfig::standard_ecc_config();
+        let pw = PartialWitness::new();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+
+        let g = Secp256K1::GENERATOR_AFFINE;
+        let g_target = builder.constant_affine_point(g);
+        let neg_g_target = builder.curve_neg(&g_target);
+
+        builder.curve_assert_valid(&g_target);
+        builder.curve_assert_valid(&neg_g_target);
+
+        prove_and_verify(builder, pw)
+    }
+
+    #[test]
+    fn test_curve_point_is_not_valid() {
+        let config = CircuitConfig::standard_ecc_config();
+        let pw = PartialWitness::new();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+
+        let g = Secp256K1::GENERATOR_AFFINE;
+        let not_g = AffinePoint::<Secp256K1> {
+            x: g.x,
+            y: g.y + Secp256K1Base::ONE,
+            zero: g.zero,
+        };
+        let not_g_target = builder.constant_affine_point(not_g);
+
+        builder.curve_assert_valid(&not_g_target);
+
+        assert!(prove_and_verify(builder, pw).is_err());
+    }
+
+    #[test]
+    fn test_curve_double_and_add() -> Result<()> {
+        let config = CircuitConfig::standard_ecc_config();
+        let mut pw = PartialWitness::new();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+
+        let g =
+            (CurveScalar(Secp256K1Scalar::rand()) * Secp256K1::GENERATOR_PROJECTIVE).to_affine();
+        let double_g = g.double();
+        let triple_g = (g + double_g).to_affine();
+
+        let g_target = builder.add_virtual_affine_point_target::<Secp256K1>();
+        pw.set_nonnative_target(&g_target.x, g.x);
+        pw.set_nonnative_target(&g_target.y, g.y);
+        builder.curve_assert_valid(&g_target);
+
+        let double_g_actual = builder.curve_double(&g_target);
+        let double_g_expected = builder.constant_affine_point(double_g);
+        builder.connect_affine_point(&double_g_actual, &double_g_expected);
+
+        let triple_g_actual = builder.curve_add(&g_target, &double_g_actual);
+        let triple_g_expected = builder.constant_affine_point(triple_g);
+        builder.connect_affine_point(&triple_g_actual, &triple_g_expected);
+
+        prove_and_verify(builder, pw)
+    }
+
+    #[test]
+    fn test_curve_conditional_add() -> Result<()> {
+        let config = CircuitConfig::standard_ecc_config();
+        let pw = PartialWitness::new();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+
+        let g = Secp256K1::GENERATOR_AFFINE;
+        let double_g = g.double();
+        let triple_g = (g + double_g).to_affine();
+
+        let g_target = builder.constant_affine_point(g);
+        let double_g_target = builder.constant_affine_point(double_g);
+
+        let t = builder._true();
+        let f = builder._false();
+        let added = builder.curve_conditional_add(&g_target, &double_g_target, t);
+        let not_added = builder.curve_conditional_add(&g_target, &double_g_target, f);
+
+        let triple_g_expected = builder.constant_affine_point(triple_g);
+        builder.connect_affine_point(&added, &triple_g_expected);
+        builder.connect_affine_point(&not_added, &g_target);
+
+        prove_and_verify(builder, pw)
+    }
+
+    /// Multiplies by a scalar with a single limb, to keep the circuit small.
+    #[test]
+    fn test_curve_scalar_mul_windowed_small() -> Result<()> {
+        let config = CircuitConfig::standard_ecc_config();
+        let pw = PartialWitness::new();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+
+        let g =
+            (CurveScalar(Secp256K1Scalar::rand()) * Secp256K1::GENERATOR_PROJECTIVE).to_affine();
+        let n = 0xdeadbeef_u32;
+        let n_g =
+            (CurveScalar(Secp256K1Scalar::from_canonical_u32(n)) * g.to_projective()).to_affine();
+
+        let g_target = builder.constant_affine_point(g);
+        let n_target = NonNativeTarget::<Secp256K1Scalar>::from_biguint_unsafe(BigUintTarget {
+            limbs: vec![builder.constant_u32(n)],
+        });
+        let n_g_actual = builder.curve_scalar_mul_windowed(&g_target, &n_target);
+        let n_g_expected = builder.constant_affine_point(n_g);
+        builder.connect_affine_point(&n_g_actual, &n_g_expected);
+
+        prove_and_verify(builder, pw)
+    }
+
+    #[test]
+    #[ignore = "building this circuit needs several GB of memory"]
+    fn test_curve_scalar_mul_windowed() -> Result<()> {
+        let config = CircuitConfig::standard_ecc_config();
+        let mut pw = PartialWitness::new();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+
+        let g =
+            (CurveScalar(Secp256K1Scalar::rand()) * Secp256K1::GENERATOR_PROJECTIVE).to_affine();
+        let n = Secp256K1Scalar::rand();
+        let n_g = (CurveScalar(n) * g.to_projective()).to_affine();
+
+        let g_target = builder.constant_affine_point(g);
+        let n_target = builder.add_virtual_nonnative_target::<Secp256K1Scalar>();
+        pw.set_nonnative_target(&n_target, n);
+        let n_g_actual = builder.curve_scalar_mul_windowed(&g_target, &n_target);
+        let n_g_expected = builder.constant_affine_point(n_g);
+        builder.connect_affine_point(&n_g_actual, &n_g_expected);
+
+        check_constraints(builder, pw)
+    }
+
+    #[test]
+    fn test_fixed_base_curve_mul() -> Result<()> {
+        let config = CircuitConfig::standard_ecc_config();
+        let mut pw = PartialWitness::new();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+
+        let g = Secp256K1::GENERATOR_AFFINE;
+        let n = Secp256K1Scalar::rand();
+        let n_g = (CurveScalar(n) * g.to_projective()).to_affine();
+
+        let n_target = builder.add_virtual_nonnative_target::<Secp256K1Scalar>();
+        pw.set_nonnative_target(&n_target, n);
+        let n_g_actual = builder.fixed_base_curve_mul(g, &n_target);
+        let n_g_expected = builder.constant_affine_point(n_g);
+        builder.connect_affine_point(&n_g_actual, &n_g_expected);
+
+        check_constraints(builder, pw)
+    }
+}
@@ -0,0 +1,137 @@
+use crate::curve::curve_types::Curve;
+use crate::curve::secp256k1::Secp256K1;
+use crate::field::extension::Extendable;
+use crate::field::secp256k1_scalar::Secp256K1Scalar;
+use crate::gadgets::curve::AffinePointTarget;
+use crate::gadgets::nonnative::NonNativeTarget;
+use crate::hash::hash_types::RichField;
+use crate::plonk::circuit_builder::CircuitBuilder;
+
+#[derive(Clone, Debug)]
+pub struct ECDSASecretKeyTarget<C: Curve>(pub NonNativeTarget<C::ScalarField>);
+
+#[derive(Clone, Debug)]
+pub struct ECDSAPublicKeyTarget<C: Curve>(pub AffinePointTarget<C>);
+
+#[derive(Clone, Debug)]
+pub struct ECDSASignatureTarget<C: Curve> {
+    pub r: NonNativeTarget<C::ScalarField>,
+    pub s: NonNativeTarget<C::ScalarField>,
+}
+
+impl<F: RichField + Extendable<D>, const D: usize> CircuitBuilder<F, D> {
+    /// Asserts that `sig` is a valid signature of the (already hashed) message `msg` under `pk`.
+    /// The public key is checked to lie on the curve, and `r` and `s` are checked to be nonzero.
+    pub fn verify_secp256k1_ecdsa_signature(
+        &mut self,
+        msg: &NonNativeTarget<Secp256K1Scalar>,
+        sig: &ECDSASignatureTarget<Secp256K1>,
+        pk: &ECDSAPublicKeyTarget<Secp256K1>,
+    ) {
+        let ECDSASignatureTarget { r, s } = sig;
+
+        self.curve_assert_valid(&pk.0);
+
+        // Inverting `r` only serves to assert that it is nonzero.
+        self.inv_nonnative(r);
+        let c = self.inv_nonnative(s);
+        let u1 = self.mul_nonnative(msg, &c);
+        let u2 = self.mul_nonnative(r, &c);
+
+        let point1 = self.fixed_base_curve_mul(Secp256K1::GENERATOR_AFFINE, &u1);
+        let point2 = self.glv_mul(&pk.0, &u2);
+        let point = self.curve_add(&point1, &point2);
+
+        let x = self.reduce::<Secp256K1Scalar>(&point.x.value);
+        self.connect_nonnative(r, &x);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+
+    use crate::curve::curve_types::{Curve, CurveScalar};
+    use crate::curve::ecdsa::{sign_message, ECDSAPublicKey, ECDSASecretKey, ECDSASignature};
+    use crate::curve::secp256k1::Secp256K1;
+    use crate::field::secp256k1_scalar::Secp256K1Scalar;
+    use crate::field::types::{Field, Sample};
+    use crate::gadgets::ecdsa::{ECDSAPublicKeyTarget, ECDSASignatureTarget};
+    use crate::iop::witness::PartialWitness;
+    use crate::plonk::circuit_builder::CircuitBuilder;
+    use crate::plonk::circuit_data::CircuitConfig;
+    use crate::plonk::config::{GenericConfig, PoseidonGoldilocksConfig};
+
+    const D: usize = 2;
+    type C = PoseidonGoldilocksConfig;
+    type F = <C as GenericConfig<D>>::F;
+
+    /// Builds a circuit verifying an ECDSA signature, which is made invalid if `valid` is false.
+    fn ecdsa_circuit_with_config(
+        config: CircuitConfig,
+        valid: bool,
+    ) -> (CircuitBuilder<F, D>, PartialWitness<F>) {
+        type Curve = Secp256K1;
+
+        let pw = PartialWitness::new();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+
+        let msg = Secp256K1Scalar::rand();
+        let msg_target = builder.constant_nonnative(msg);
+
+        let sk = ECDSASecretKey::<Curve>(Secp256K1Scalar::rand());
+        let pk = ECDSAPublicKey((CurveScalar(sk.0) * Curve::GENERATOR_PROJECTIVE).to_affine());
+        let pk_target = ECDSAPublicKeyTarget(builder.constant_affine_point(pk.0));
+
+        let sig = sign_message(msg, sk);
+        let ECDSASignature { r, s } = sig;
+        let s = if valid { s } else { s + Secp256K1Scalar::ONE };
+        let r_target = builder.constant_nonnative(r);
+        let s_target = builder.constant_nonnative(s);
+        let sig_target = ECDSASignatureTarget {
+            r: r_target,
+            s: s_target,
+        };
+
+        builder.verify_secp256k1_ecdsa_signature(&msg_target, &sig_target, &pk_target);
+        (builder, pw)
+    }
+
+    fn test_ecdsa_circuit_with_config(config: CircuitConfig) -> Result<()> {
+        let (builder, pw) = ecdsa_circuit_with_config(config, true);
+        let data = builder.build::<C>();
+        let proof = data.prove(pw)?;
+        data.verify(proof)?;
+        Ok(())
+    }
+
+    /// Checks the witness of a valid signature against all the constraints of the circuit, which
+    /// is much cheaper than proving it.
+    #[test]
+    fn test_ecdsa_circuit_constraints() -> Result<()> {
+        let (builder, pw) = ecdsa_circuit_with_config(CircuitConfig::standard_ecc_config(), true);
+        let data = builder.mock_build::<C>();
+        let witness = data.generate_witness(pw)?;
+        data.check_constraints(witness)?;
+        Ok(())
+    }
+
+    #[test]
+    #[ignore = "proving a circuit of 2^18 rows needs several GB of memory"]
+    fn test_ecdsa_circuit_narrow() -> Result<()> {
+        test_ecdsa_circuit_with_config(CircuitConfig::standard_ecc_config())
+    }
+
+    #[test]
+    #[ignore = "proving this circuit needs several GB of memory"]
+    fn test_ecdsa_circuit_wide() -> Result<()> {
+        test_ecdsa_circuit_with_config(CircuitConfig::wide_ecc_config())
+    }
+
+    #[test]
+    fn test_ecdsa_circuit_invalid_signature() {
+        let (builder, pw) = ecdsa_circuit_with_config(CircuitConfig::standard_ecc_config(), false);
+        let data = builder.mock_build::<C>();
+        assert!(data.generate_witness(pw).is_err());
+    }
+}
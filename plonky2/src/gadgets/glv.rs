@@ -0,0 +1,208 @@
+#[cfg(not(feature = "std"))]
+use alloc::{
+    string::{String, ToString},
+    vec::Vec,
+};
+use core::marker::PhantomData;
+
+use crate::curve::glv::{decompose_secp256k1_scalar, GLV_BETA, GLV_S};
+use crate::curve::secp256k1::Secp256K1;
+use crate::field::extension::Extendable;
+use crate::field::secp256k1_base::Secp256K1Base;
+use crate::field::secp256k1_scalar::Secp256K1Scalar;
+use crate::field::types::{Field, PrimeField};
+use crate::gadgets::biguint::BigUintTarget;
+use crate::gadgets::curve::AffinePointTarget;
+use crate::gadgets::nonnative::NonNativeTarget;
+use crate::hash::hash_types::RichField;
+use crate::iop::generator::{GeneratedValues, SimpleGenerator};
+use crate::iop::target::{BoolTarget, Target};
+use crate::iop::witness::{PartitionWitness, Witness, WitnessWrite};
+use crate::plonk::circuit_builder::CircuitBuilder;
+use crate::plonk::circuit_data::CommonCircuitData;
+use crate::util::serialization::{Buffer, IoResult, Read, Write};
+
+/// The number of 32-bit limbs used for each half of a GLV decomposition.
+const GLV_HALF_LIMBS: usize = 4;
+
+impl<F: RichField + Extendable<D>, const D: usize> CircuitBuilder<F, D> {
+    pub fn secp256k1_glv_beta(&mut self) -> NonNativeTarget<Secp256K1Base> {
+        self.constant_nonnative(GLV_BETA)
+    }
+
+    /// Decomposes `k` as `k = k1 + k2 * GLV_S`, returning `(|k1|, |k2|, k1 < 0, k2 < 0)` as in
+    /// the native [`decompose_secp256k1_scalar`]. `|k1|` and `|k2|` are range-checked to 128 bits.
+    pub fn decompose_secp256k1_scalar(
+        &mut self,
+        k: &NonNativeTarget<Secp256K1Scalar>,
+    ) -> (
+        NonNativeTarget<Secp256K1Scalar>,
+        NonNativeTarget<Secp256K1Scalar>,
+        BoolTarget,
+        BoolTarget,
+    ) {
+        let k1 =
+            NonNativeTarget::from_biguint_unsafe(self.add_virtual_biguint_target(GLV_HALF_LIMBS));
+        let k2 =
+            NonNativeTarget::from_biguint_unsafe(self.add_virtual_biguint_target(GLV_HALF_LIMBS));
+        let k1_neg = self.add_virtual_bool_target_safe();
+        let k2_neg = self.add_virtual_bool_target_safe();
+
+        self.add_simple_generator(GLVDecompositionGenerator::<F, D> {
+            k: k.value.clone(),
+            k1: k1.value.clone(),
+            k2: k2.value.clone(),
+            k1_neg,
+            k2_neg,
+            _phantom: PhantomData,
+        });
+
+        // Check that `k1_raw + GLV_S * k2_raw == k`.
+        let k1_raw = self.nonnative_conditional_neg(&k1, k1_neg);
+        let k2_raw = self.nonnative_conditional_neg(&k2, k2_neg);
+        let s = self.constant_nonnative(GLV_S);
+        let k2_times_s = self.mul_nonnative(&k2_raw, &s);
+        let k_expected = self.add_nonnative(&k1_raw, &k2_times_s);
+        self.connect_nonnative(&k_expected, k);
+
+        (k1, k2, k1_neg, k2_neg)
+    }
+
+    /// Computes `k * p` using the GLV endomorphism to halve the number of doublings.
+    pub fn glv_mul(
+        &mut self,
+        p: &AffinePointTarget<Secp256K1>,
+        k: &NonNativeTarget<Secp256K1Scalar>,
+    ) -> AffinePointTarget<Secp256K1> {
+        let (k1, k2, k1_neg, k2_neg) = self.decompose_secp256k1_scalar(k);
+
+        let beta = self.secp256k1_glv_beta();
+        let beta_px = self.mul_nonnative(&p.x, &beta);
+        let sp = AffinePointTarget::<Secp256K1> {
+            x: beta_px,
+            y: p.y.clone(),
+        };
+
+        let p_neg = self.curve_conditional_neg(p, k1_neg);
+        let sp_neg = self.curve_conditional_neg(&sp, k2_neg);
+        self.curve_msm_windowed(&[(p_neg, k1), (sp_neg, k2)])
+    }
+}
+
+/// Computes the GLV decomposition of a secp256k1 scalar.
+#[derive(Debug, Default)]
+pub struct GLVDecompositionGenerator<F: RichField + Extendable<D>, const D: usize> {
+    k: BigUintTarget,
+    k1: BigUintTarget,
+    k2: BigUintTarget,
+    k1_neg: BoolTarget,
+    k2_neg: BoolTarget,
+    _phantom: PhantomData<F>,
+}
+
+impl<F: RichField + Extendable<D>, const D: usize> SimpleGenerator<F, D>
+    for GLVDecompositionGenerator<F, D>
+{
+    fn id(&self) -> String {
+        "GLVDecompositionGenerator".to_string()
+    }
+
+    fn dependencies(&self) -> Vec<Target> {
+        self.k.limbs.iter().map(|&l| l.0).collect()
+    }
+
+    fn run_once(&self, witness: &PartitionWitness<F>, out_buffer: &mut GeneratedValues<F>) {
+        let k = Secp256K1Scalar::from_noncanonical_biguint(witness.get_biguint_target(&self.k));
+        let (k1, k2, k1_neg, k2_neg) = decompose_secp256k1_scalar(k);
+
+        out_buffer.set_biguint_target(&self.k1, &k1.to_canonical_biguint());
+        out_buffer.set_biguint_target(&self.k2, &k2.to_canonical_biguint());
+        out_buffer.set_bool_target(self.k1_neg, k1_neg);
+        out_buffer.set_bool_target(self.k2_neg, k2_neg);
+    }
+
+    fn serialize(&self, dst: &mut Vec<u8>, _common_data: &CommonCircuitData<F, D>) -> IoResult<()> {
+        dst.write_target_biguint(&self.k)?;
+        dst.write_target_biguint(&self.k1)?;
+        dst.write_target_biguint(&self.k2)?;
+        dst.write_target_bool(self.k1_neg)?;
+        dst.write_target_bool(self.k2_neg)
+    }
+
+    fn deserialize(src: &mut Buffer, _common_data: &CommonCircuitData<F, D>) -> IoResult<Self> {
+        let k = src.read_target_biguint()?;
+        let k1 = src.read_target_biguint()?;
+        let k2 = src.read_target_biguint()?;
+        let k1_neg = src.read_target_bool()?;
+        let k2_neg = src.read_target_bool()?;
+        Ok(Self {
+            k,
+            k1,
+            k2,
+            k1_neg,
+            k2_neg,
+            _phantom: PhantomData,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+
+    use crate::curve::curve_types::{Curve, CurveScalar};
+    use crate::curve::glv::glv_mul;
+    use crate::curve::secp256k1::Secp256K1;
+    use crate::field::secp256k1_scalar::Secp256K1Scalar;
+    use crate::field::types::Sample;
+    use crate::iop::witness::{PartialWitness, WitnessWrite};
+    use crate::plonk::circuit_builder::CircuitBuilder;
+    use crate::plonk::circuit_data::CircuitConfig;
+    use crate::plonk::config::{GenericConfig, PoseidonGoldilocksConfig};
+
+    const D: usize = 2;
+    type C = PoseidonGoldilocksConfig;
+    type F = <C as GenericConfig<D>>::F;
+
+    #[test]
+    fn test_glv_decompose_gadget() -> Result<()> {
+        let config = CircuitConfig::standard_ecc_config();
+        let mut pw = PartialWitness::new();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+
+        let k = Secp256K1Scalar::rand();
+        let k_target = builder.add_virtual_nonnative_target::<Secp256K1Scalar>();
+        pw.set_nonnative_target(&k_target, k);
+        builder.decompose_secp256k1_scalar(&k_target);
+
+        let data = builder.build::<C>();
+        let proof = data.prove(pw)?;
+        data.verify(proof)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_glv_mul_gadget() -> Result<()> {
+        let config = CircuitConfig::standard_ecc_config();
+        let mut pw = PartialWitness::new();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+
+        let rando =
+            (CurveScalar(Secp256K1Scalar::rand()) * Secp256K1::GENERATOR_PROJECTIVE).to_affine();
+        let randot = builder.constant_affine_point(rando);
+
+        let scalar = Secp256K1Scalar::rand();
+        let scalar_target = builder.add_virtual_nonnative_target::<Secp256K1Scalar>();
+        pw.set_nonnative_target(&scalar_target, scalar);
+
+        let rando_glv_scalar = glv_mul(rando.to_projective(), scalar);
+        let expected = builder.constant_affine_point(rando_glv_scalar.to_affine());
+        let actual = builder.glv_mul(&randot, &scalar_target);
+        builder.connect_affine_point(&expected, &actual);
+
+        let data = builder.mock_build::<C>();
+        let witness = data.generate_witness(pw)?;
+        data.check_constraints(witness)?;
+        Ok(())
+    }
+}
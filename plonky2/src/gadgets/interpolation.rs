@@ -109,6 +109,7 @@ mod tests {
         let data = builder.build::<C>();
         let proof = data.prove(pw)?;
 
-        verify(proof, &data.verifier_only, &data.common)
+        verify(proof, &data.verifier_only, &data.common)?;
+        Ok(())
     }
 }
@@ -0,0 +1,277 @@
+#[cfg(not(feature = "std"))]
+use alloc::{vec, vec::Vec};
+
+use crate::field::extension::Extendable;
+use crate::hash::hash_types::RichField;
+use crate::iop::target::{BoolTarget, Target};
+use crate::plonk::circuit_builder::CircuitBuilder;
+
+/// The number of rounds of Keccak-f\[1600\].
+pub const KECCAK_ROUNDS: usize = 24;
+
+/// The number of bytes absorbed per permutation by Keccak-256.
+pub const KECCAK256_RATE_BYTES: usize = 136;
+
+const KECCAK_ROUND_CONSTANTS: [u64; KECCAK_ROUNDS] = [
+    0x0000000000000001,
+    0x0000000000008082,
+    0x800000000000808A,
+    0x8000000080008000,
+    0x000000000000808B,
+    0x0000000080000001,
+    0x8000000080008081,
+    0x8000000000008009,
+    0x000000000000008A,
+    0x0000000000000088,
+    0x0000000080008009,
+    0x000000008000000A,
+    0x000000008000808B,
+    0x800000000000008B,
+    0x8000000000008089,
+    0x8000000000008003,
+    0x8000000000008002,
+    0x8000000000000080,
+    0x000000000000800A,
+    0x800000008000000A,
+    0x8000000080008081,
+    0x8000000000008080,
+    0x0000000080000001,
+    0x8000000080008008,
+];
+
+/// The rho rotation offsets, indexed by lane `x + 5 * y`.
+const KECCAK_ROTATIONS: [usize; 25] = [
+    0, 1, 62, 28, 27, 36, 44, 6, 55, 20, 3, 10, 43, 25, 39, 41, 45, 15, 21, 8, 18, 2, 61, 56, 14,
+];
+
+/// Values entering chi are theta outputs in unreduced form, i.e. `a + c` where `a` is a state bit
+/// and `c` is the sum of the ten bits of the two adjacent columns, so each lies in `[0, 12)`. The
+/// chi lookup takes three of them packed in base 12, and reduces them mod 2 on the fly.
+const CHI_INPUT_BASE: u16 = 12;
+const CHI_TABLE_SIZE: u16 = CHI_INPUT_BASE * CHI_INPUT_BASE * CHI_INPUT_BASE;
+
+/// Computes `a ^ (!b & c)` for the parities of the packed theta outputs `a`, `b` and `c`.
+fn keccak_chi(input: u16) -> u16 {
+    let a = input % CHI_INPUT_BASE;
+    let b = (input / CHI_INPUT_BASE) % CHI_INPUT_BASE;
+    let c = input / (CHI_INPUT_BASE * CHI_INPUT_BASE);
+    (a ^ (!b & c)) & 1
+}
+
+/// Like `keccak_chi`, but additionally flips the output bit, to apply a set bit of an iota round
+/// constant.
+fn keccak_chi_iota(input: u16) -> u16 {
+    keccak_chi(input) ^ 1
+}
+
+/// The Keccak-f\[1600\] state, as 25 lanes of 64 little-endian bits. Lane `x + 5 * y` holds the
+/// lane at position `(x, y)` in the Keccak specification.
+#[derive(Copy, Clone, Debug)]
+pub struct KeccakStateTarget(pub [[BoolTarget; 64]; 25]);
+
+impl<F: RichField + Extendable<D>, const D: usize> CircuitBuilder<F, D> {
+    /// Applies the Keccak-f\[1600\] permutation to `state`.
+    ///
+    /// Theta is computed with unreduced additions, and its outputs are reduced mod 2 by the chi
+    /// lookups, which also apply iota. Rho and pi are permutations of targets, so they are free.
+    pub fn keccak_f1600(&mut self, state: &KeccakStateTarget) -> KeccakStateTarget {
+        let chi_inputs: Vec<u16> = (0..CHI_TABLE_SIZE).collect();
+        let chi_lut = self.add_lookup_table_from_fn(keccak_chi, &chi_inputs);
+        let chi_iota_lut = self.add_lookup_table_from_fn(keccak_chi_iota, &chi_inputs);
+
+        let mut lanes: Vec<Vec<Target>> = state
+            .0
+            .iter()
+            .map(|lane| lane.iter().map(|b| b.target).collect())
+            .collect();
+
+        for round_constant in KECCAK_ROUND_CONSTANTS {
+            // Theta, leaving outputs unreduced.
+            let column_sums: Vec<Vec<Target>> = (0..5)
+                .map(|x| {
+                    (0..64)
+                        .map(|z| self.add_many((0..5).map(|y| lanes[x + 5 * y][z])))
+                        .collect()
+                })
+                .collect();
+            let adjacent_column_sums: Vec<Vec<Target>> = (0..5)
+                .map(|x| {
+                    (0..64)
+                        .map(|z| {
+                            self.add(
+                                column_sums[(x + 4) % 5][z],
+                                column_sums[(x + 1) % 5][(z + 63) % 64],
+                            )
+                        })
+                        .collect()
+                })
+                .collect();
+            let theta: Vec<Vec<Target>> = (0..25)
+                .map(|i| {
+                    (0..64)
+                        .map(|z| self.add(lanes[i][z], adjacent_column_sums[i % 5][z]))
+                        .collect()
+                })
+                .collect();
+
+            // Rho and pi.
+            let mut rotated = vec![Vec::new(); 25];
+            for (i, lane) in theta.into_iter().enumerate() {
+                let (x, y) = (i % 5, i / 5);
+                let rotation = KECCAK_ROTATIONS[i];
+                rotated[y + 5 * ((2 * x + 3 * y) % 5)] =
+                    (0..64).map(|z| lane[(z + 64 - rotation) % 64]).collect();
+            }
+
+            // Chi and iota.
+            let base = F::from_canonical_u16(CHI_INPUT_BASE);
+            lanes = (0..25)
+                .map(|i| {
+                    let (x, y) = (i % 5, i / 5);
+                    let b = &rotated[(x + 1) % 5 + 5 * y];
+                    let c = &rotated[(x + 2) % 5 + 5 * y];
+                    (0..64)
+                        .map(|z| {
+                            let packed = self.mul_const_add(base, c[z], b[z]);
+                            let packed = self.mul_const_add(base, packed, rotated[i][z]);
+                            let lut = if i == 0 && (round_constant >> z) & 1 == 1 {
+                                chi_iota_lut
+                            } else {
+                                chi_lut
+                            };
+                            self.add_lookup_from_index(packed, lut)
+                        })
+                        .collect()
+                })
+                .collect();
+        }
+
+        // `new_unsafe` is safe here because the chi lookups only output bits.
+        KeccakStateTarget(core::array::from_fn(|i| {
+            core::array::from_fn(|z| BoolTarget::new_unsafe(lanes[i][z]))
+        }))
+    }
+
+    /// Computes the Keccak-256 hash of `bytes`, as used by Ethereum, returning the 32 bytes of the
+    /// digest. Each input target is range-checked to 8 bits.
+    pub fn keccak256(&mut self, bytes: &[Target]) -> [Target; 32] {
+        let mut bits: Vec<BoolTarget> = bytes.iter().flat_map(|&b| self.split_le(b, 8)).collect();
+
+        // Apply the pad10*1 padding with Keccak's domain separation bit. The message length is
+        // known when building the circuit, so the padding is constant.
+        let padded_len = (bytes.len() / KECCAK256_RATE_BYTES + 1) * KECCAK256_RATE_BYTES;
+        let mut padding = vec![0u8; padded_len - bytes.len()];
+        padding[0] |= 0x01;
+        *padding.last_mut().unwrap() |= 0x80;
+        for byte in padding {
+            for i in 0..8 {
+                bits.push(self.constant_bool((byte >> i) & 1 == 1));
+            }
+        }
+
+        let zero = self._false();
+        let mut state = KeccakStateTarget([[zero; 64]; 25]);
+        for (i, block) in bits.chunks(KECCAK256_RATE_BYTES * 8).enumerate() {
+            for (j, &bit) in block.iter().enumerate() {
+                let lane_bit = &mut state.0[j / 64][j % 64];
+                // The first block is absorbed into the all-zero state, so no XOR is needed.
+                *lane_bit = if i == 0 {
+                    bit
+                } else {
+                    self.xor_bits(*lane_bit, bit)
+                };
+            }
+            state = self.keccak_f1600(&state);
+        }
+
+        core::array::from_fn(|i| {
+            let lane = &state.0[i / 8];
+            self.le_sum(lane[8 * (i % 8)..8 * (i % 8 + 1)].iter())
+        })
+    }
+
+    fn xor_bits(&mut self, a: BoolTarget, b: BoolTarget) -> BoolTarget {
+        // a ^ b = a + b - 2ab
+        let a_minus_2ab = self.arithmetic(-F::TWO, F::ONE, a.target, b.target, a.target);
+        BoolTarget::new_unsafe(self.add(a_minus_2ab, b.target))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(not(feature = "std"))]
+    use alloc::vec::Vec;
+
+    use anyhow::Result;
+    use keccak_hash::keccak;
+    use rand::rngs::OsRng;
+    use rand::Rng;
+
+    use crate::field::types::{Field, PrimeField64, Sample};
+    use crate::hash::keccak::KeccakHash;
+    use crate::iop::witness::{PartialWitness, WitnessWrite};
+    use crate::plonk::circuit_builder::CircuitBuilder;
+    use crate::plonk::circuit_data::CircuitConfig;
+    use crate::plonk::config::{GenericConfig, Hasher, PoseidonGoldilocksConfig};
+
+    const D: usize = 2;
+    type C = PoseidonGoldilocksConfig;
+    type F = <C as GenericConfig<D>>::F;
+
+    fn check_keccak256(
+        builder: &mut CircuitBuilder<F, D>,
+        pw: &mut PartialWitness<F>,
+        input: &[u8],
+        expected: &[u8],
+    ) {
+        let input_targets = builder.add_virtual_targets(input.len());
+        for (&t, &byte) in input_targets.iter().zip(input) {
+            pw.set_target(t, F::from_canonical_u8(byte));
+        }
+        let hash = builder.keccak256(&input_targets);
+        for (&t, &byte) in hash.iter().zip(expected) {
+            let expected_byte = builder.constant(F::from_canonical_u8(byte));
+            builder.connect(t, expected_byte);
+        }
+    }
+
+    #[test]
+    fn test_keccak256() -> Result<()> {
+        let config = CircuitConfig::standard_recursion_config();
+        let mut pw = PartialWitness::new();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+
+        // The second input spans two blocks.
+        let mut rng = OsRng;
+        for len in [0, 137] {
+            let input: Vec<u8> = (0..len).map(|_| rng.gen()).collect();
+            let expected = keccak(&input).0;
+            check_keccak256(&mut builder, &mut pw, &input, &expected);
+        }
+
+        let data = builder.build::<C>();
+        let proof = data.prove(pw)?;
+        data.verify(proof)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_keccak256_matches_keccak_hash() -> Result<()> {
+        let config = CircuitConfig::standard_recursion_config();
+        let mut pw = PartialWitness::new();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+
+        let elements = F::rand_vec(4);
+        let input: Vec<u8> = elements
+            .iter()
+            .flat_map(|x| x.to_canonical_u64().to_le_bytes())
+            .collect();
+        let expected = <KeccakHash<32> as Hasher<F>>::hash_no_pad(&elements).0;
+        check_keccak256(&mut builder, &mut pw, &input, &expected);
+
+        let data = builder.build::<C>();
+        let proof = data.prove(pw)?;
+        data.verify(proof)?;
+        Ok(())
+    }
+}
@@ -4,12 +4,21 @@
 
 pub mod arithmetic;
 pub mod arithmetic_extension;
+pub mod arithmetic_u32;
+pub mod biguint;
+pub mod curve;
+pub mod ecdsa;
+pub mod glv;
 pub mod hash;
 pub mod interpolation;
+pub mod keccak;
 pub mod lookup;
+pub mod multiple_comparison;
+pub mod nonnative;
 pub mod polynomial;
 pub mod random_access;
 pub mod range_check;
 pub mod select;
+pub mod sha256;
 pub mod split_base;
 pub mod split_join;
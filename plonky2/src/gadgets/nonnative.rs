@@ -0,0 +1,658 @@
+#[cfg(not(feature = "std"))]
+use alloc::{
+    string::{String, ToString},
+    vec,
+    vec::Vec,
+};
+use core::marker::PhantomData;
+
+use num::{BigUint, Integer, One, Zero};
+
+use crate::field::extension::Extendable;
+use crate::field::types::{Field, PrimeField};
+use crate::gadgets::arithmetic_u32::U32Target;
+use crate::gadgets::biguint::BigUintTarget;
+use crate::hash::hash_types::RichField;
+use crate::iop::generator::{GeneratedValues, SimpleGenerator};
+use crate::iop::target::{BoolTarget, Target};
+use crate::iop::witness::{PartitionWitness, Witness, WitnessWrite};
+use crate::plonk::circuit_builder::CircuitBuilder;
+use crate::plonk::circuit_data::CommonCircuitData;
+use crate::util::serialization::{Buffer, IoResult, Read, Write};
+
+/// An element of the non-native field `FF`, represented as a `BigUintTarget`.
+///
+/// The gadgets below expect their inputs to be in reduced form, i.e. less than `|FF|`, and
+/// always return values in reduced form.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct NonNativeTarget<FF: Field> {
+    pub value: BigUintTarget,
+    _phantom: PhantomData<FF>,
+}
+
+impl<FF: Field> NonNativeTarget<FF> {
+    /// Wraps `value` without reducing it. The caller is responsible for `value` being less than
+    /// `|FF|`.
+    pub const fn from_biguint_unsafe(value: BigUintTarget) -> Self {
+        Self {
+            value,
+            _phantom: PhantomData,
+        }
+    }
+}
+
+impl<F: RichField + Extendable<D>, const D: usize> CircuitBuilder<F, D> {
+    /// The number of 32-bit limbs used to represent an element of `FF`.
+    pub fn num_nonnative_limbs<FF: Field>() -> usize {
+        FF::BITS.div_ceil(32)
+    }
+
+    pub fn constant_nonnative<FF: PrimeField>(&mut self, x: FF) -> NonNativeTarget<FF> {
+        let mut value = self.constant_biguint(&x.to_canonical_biguint());
+        value
+            .limbs
+            .resize_with(Self::num_nonnative_limbs::<FF>(), || self.zero_u32());
+
+        NonNativeTarget::from_biguint_unsafe(value)
+    }
+
+    pub fn zero_nonnative<FF: PrimeField>(&mut self) -> NonNativeTarget<FF> {
+        self.constant_nonnative(FF::ZERO)
+    }
+
+    /// Returns a new `NonNativeTarget` whose limbs are range-checked to 32 bits. It is not
+    /// checked to be less than `|FF|`; see `reduce_nonnative`.
+    pub fn add_virtual_nonnative_target<FF: Field>(&mut self) -> NonNativeTarget<FF> {
+        let value = self.add_virtual_biguint_target(Self::num_nonnative_limbs::<FF>());
+
+        NonNativeTarget::from_biguint_unsafe(value)
+    }
+
+    /// Asserts that two `NonNativeTarget`s, both assumed to be in reduced form, are equal.
+    pub fn connect_nonnative<FF: Field>(
+        &mut self,
+        lhs: &NonNativeTarget<FF>,
+        rhs: &NonNativeTarget<FF>,
+    ) {
+        self.connect_biguint(&lhs.value, &rhs.value);
+    }
+
+    /// Returns `x % |FF|` as a `NonNativeTarget`.
+    pub fn reduce<FF: Field>(&mut self, x: &BigUintTarget) -> NonNativeTarget<FF> {
+        let modulus = self.constant_biguint(&FF::order());
+        let value = self.rem_biguint(x, &modulus);
+
+        NonNativeTarget::from_biguint_unsafe(value)
+    }
+
+    pub fn reduce_nonnative<FF: Field>(&mut self, x: &NonNativeTarget<FF>) -> NonNativeTarget<FF> {
+        self.reduce(&x.value)
+    }
+
+    pub fn nonnative_to_canonical_biguint<FF: Field>(
+        &mut self,
+        x: &NonNativeTarget<FF>,
+    ) -> BigUintTarget {
+        x.value.clone()
+    }
+
+    /// Asserts that `x`, assumed to be range-checked to 32-bit limbs, is less than `|FF|`.
+    fn assert_reduced_nonnative<FF: Field>(&mut self, x: &NonNativeTarget<FF>) {
+        let modulus = self.constant_biguint(&FF::order());
+        let is_reduced = self.lt_biguint(&x.value, &modulus);
+        self.assert_one(is_reduced.target);
+    }
+
+    pub fn add_nonnative<FF: PrimeField>(
+        &mut self,
+        a: &NonNativeTarget<FF>,
+        b: &NonNativeTarget<FF>,
+    ) -> NonNativeTarget<FF> {
+        let sum = self.add_virtual_nonnative_target::<FF>();
+        let overflow = self.add_virtual_bool_target_safe();
+
+        self.add_simple_generator(NonNativeAdditionGenerator::<F, D> {
+            a: a.value.clone(),
+            b: b.value.clone(),
+            sum: sum.value.clone(),
+            overflow,
+            modulus: FF::order(),
+            _phantom: PhantomData,
+        });
+
+        let sum_expected = self.add_biguint(&a.value, &b.value);
+
+        let modulus = self.constant_biguint(&FF::order());
+        let mod_times_overflow = self.mul_biguint_by_bool(&modulus, overflow);
+        let sum_actual = self.add_biguint(&sum.value, &mod_times_overflow);
+        self.connect_biguint(&sum_expected, &sum_actual);
+
+        self.assert_reduced_nonnative(&sum);
+
+        sum
+    }
+
+    pub fn add_many_nonnative<FF: PrimeField>(
+        &mut self,
+        to_add: &[NonNativeTarget<FF>],
+    ) -> NonNativeTarget<FF> {
+        match to_add.split_first() {
+            None => self.zero_nonnative(),
+            Some((first, rest)) => rest
+                .iter()
+                .fold(first.clone(), |acc, x| self.add_nonnative(&acc, x)),
+        }
+    }
+
+    pub fn sub_nonnative<FF: PrimeField>(
+        &mut self,
+        a: &NonNativeTarget<FF>,
+        b: &NonNativeTarget<FF>,
+    ) -> NonNativeTarget<FF> {
+        let diff = self.add_virtual_nonnative_target::<FF>();
+        let overflow = self.add_virtual_bool_target_safe();
+
+        self.add_simple_generator(NonNativeSubtractionGenerator::<F, D> {
+            a: a.value.clone(),
+            b: b.value.clone(),
+            diff: diff.value.clone(),
+            overflow,
+            modulus: FF::order(),
+            _phantom: PhantomData,
+        });
+
+        // We check that `a + overflow * |FF| = b + diff`.
+        let modulus = self.constant_biguint(&FF::order());
+        let mod_times_overflow = self.mul_biguint_by_bool(&modulus, overflow);
+        let lhs = self.add_biguint(&a.value, &mod_times_overflow);
+        let rhs = self.add_biguint(&b.value, &diff.value);
+        self.connect_biguint(&lhs, &rhs);
+
+        self.assert_reduced_nonnative(&diff);
+
+        diff
+    }
+
+    pub fn mul_nonnative<FF: PrimeField>(
+        &mut self,
+        a: &NonNativeTarget<FF>,
+        b: &NonNativeTarget<FF>,
+    ) -> NonNativeTarget<FF> {
+        let prod = self.mul_biguint(&a.value, &b.value);
+        self.reduce(&prod)
+    }
+
+    pub fn mul_many_nonnative<FF: PrimeField>(
+        &mut self,
+        to_mul: &[NonNativeTarget<FF>],
+    ) -> NonNativeTarget<FF> {
+        match to_mul.split_first() {
+            None => self.constant_nonnative(FF::ONE),
+            Some((first, rest)) => rest
+                .iter()
+                .fold(first.clone(), |acc, x| self.mul_nonnative(&acc, x)),
+        }
+    }
+
+    pub fn neg_nonnative<FF: PrimeField>(
+        &mut self,
+        x: &NonNativeTarget<FF>,
+    ) -> NonNativeTarget<FF> {
+        let zero = self.zero_nonnative();
+        self.sub_nonnative(&zero, x)
+    }
+
+    /// Returns the inverse of `x`. The circuit is unsatisfiable if `x` is zero.
+    pub fn inv_nonnative<FF: PrimeField>(
+        &mut self,
+        x: &NonNativeTarget<FF>,
+    ) -> NonNativeTarget<FF> {
+        let inv = self.add_virtual_nonnative_target::<FF>();
+
+        self.add_simple_generator(NonNativeInverseGenerator::<F, D> {
+            x: x.value.clone(),
+            inv: inv.value.clone(),
+            modulus: FF::order(),
+            _phantom: PhantomData,
+        });
+
+        self.assert_reduced_nonnative(&inv);
+
+        let one = self.constant_nonnative(FF::ONE);
+        let x_times_inv = self.mul_nonnative(x, &inv);
+        self.connect_nonnative(&x_times_inv, &one);
+
+        inv
+    }
+
+    pub fn mul_nonnative_by_bool<FF: Field>(
+        &mut self,
+        a: &NonNativeTarget<FF>,
+        b: BoolTarget,
+    ) -> NonNativeTarget<FF> {
+        let value = self.mul_biguint_by_bool(&a.value, b);
+
+        NonNativeTarget::from_biguint_unsafe(value)
+    }
+
+    /// Returns `x` if `b` is true, and `y` otherwise.
+    pub fn if_nonnative<FF: PrimeField>(
+        &mut self,
+        b: BoolTarget,
+        x: &NonNativeTarget<FF>,
+        y: &NonNativeTarget<FF>,
+    ) -> NonNativeTarget<FF> {
+        let (x, y) = self.pad_biguints(&x.value, &y.value);
+        let limbs = x
+            .limbs
+            .iter()
+            .zip(&y.limbs)
+            .map(|(&x_limb, &y_limb)| U32Target(self.select(b, x_limb.0, y_limb.0)))
+            .collect();
+
+        NonNativeTarget::from_biguint_unsafe(BigUintTarget { limbs })
+    }
+
+    /// Returns `-x` if `b` is true, and `x` otherwise.
+    pub fn nonnative_conditional_neg<FF: PrimeField>(
+        &mut self,
+        x: &NonNativeTarget<FF>,
+        b: BoolTarget,
+    ) -> NonNativeTarget<FF> {
+        let neg = self.neg_nonnative(x);
+        self.if_nonnative(b, &neg, x)
+    }
+
+    pub fn bool_to_nonnative<FF: Field>(&mut self, b: BoolTarget) -> NonNativeTarget<FF> {
+        let mut limbs = vec![U32Target(b.target)];
+        limbs.resize_with(Self::num_nonnative_limbs::<FF>(), || self.zero_u32());
+
+        NonNativeTarget::from_biguint_unsafe(BigUintTarget { limbs })
+    }
+
+    /// Splits a non-native field element into its little-endian bits.
+    pub fn split_nonnative_to_bits<FF: Field>(
+        &mut self,
+        x: &NonNativeTarget<FF>,
+    ) -> Vec<BoolTarget> {
+        x.value
+            .limbs
+            .iter()
+            .flat_map(|&limb| self.split_le(limb.0, 32))
+            .collect()
+    }
+}
+
+/// Computes the reduced sum of two non-native field elements, along with an overflow bit.
+#[derive(Debug, Default)]
+pub struct NonNativeAdditionGenerator<F: RichField + Extendable<D>, const D: usize> {
+    a: BigUintTarget,
+    b: BigUintTarget,
+    sum: BigUintTarget,
+    overflow: BoolTarget,
+    modulus: BigUint,
+    _phantom: PhantomData<F>,
+}
+
+impl<F: RichField + Extendable<D>, const D: usize> SimpleGenerator<F, D>
+    for NonNativeAdditionGenerator<F, D>
+{
+    fn id(&self) -> String {
+        "NonNativeAdditionGenerator".to_string()
+    }
+
+    fn dependencies(&self) -> Vec<Target> {
+        self.a
+            .limbs
+            .iter()
+            .chain(&self.b.limbs)
+            .map(|&l| l.0)
+            .collect()
+    }
+
+    fn run_once(&self, witness: &PartitionWitness<F>, out_buffer: &mut GeneratedValues<F>) {
+        let a = witness.get_biguint_target(&self.a);
+        let b = witness.get_biguint_target(&self.b);
+        let a_plus_b = a + b;
+
+        let (overflow, sum) = if a_plus_b >= self.modulus {
+            (true, a_plus_b - &self.modulus)
+        } else {
+            (false, a_plus_b)
+        };
+
+        out_buffer.set_biguint_target(&self.sum, &sum);
+        out_buffer.set_bool_target(self.overflow, overflow);
+    }
+
+    fn serialize(&self, dst: &mut Vec<u8>, _common_data: &CommonCircuitData<F, D>) -> IoResult<()> {
+        dst.write_target_biguint(&self.a)?;
+        dst.write_target_biguint(&self.b)?;
+        dst.write_target_biguint(&self.sum)?;
+        dst.write_target_bool(self.overflow)?;
+        dst.write_biguint(&self.modulus)
+    }
+
+    fn deserialize(src: &mut Buffer, _common_data: &CommonCircuitData<F, D>) -> IoResult<Self> {
+        let a = src.read_target_biguint()?;
+        let b = src.read_target_biguint()?;
+        let sum = src.read_target_biguint()?;
+        let overflow = src.read_target_bool()?;
+        let modulus = src.read_biguint()?;
+        Ok(Self {
+            a,
+            b,
+            sum,
+            overflow,
+            modulus,
+            _phantom: PhantomData,
+        })
+    }
+}
+
+/// Computes the reduced difference of two non-native field elements, along with a bit indicating
+/// whether the subtraction wrapped around the modulus.
+#[derive(Debug, Default)]
+pub struct NonNativeSubtractionGenerator<F: RichField + Extendable<D>, const D: usize> {
+    a: BigUintTarget,
+    b: BigUintTarget,
+    diff: BigUintTarget,
+    overflow: BoolTarget,
+    modulus: BigUint,
+    _phantom: PhantomData<F>,
+}
+
+impl<F: RichField + Extendable<D>, const D: usize> SimpleGenerator<F, D>
+    for NonNativeSubtractionGenerator<F, D>
+{
+    fn id(&self) -> String {
+        "NonNativeSubtractionGenerator".to_string()
+    }
+
+    fn dependencies(&self) -> Vec<Target> {
+        self.a
+            .limbs
+            .iter()
+            .chain(&self.b.limbs)
+            .map(|&l| l.0)
+            .collect()
+    }
+
+    fn run_once(&self, witness: &PartitionWitness<F>, out_buffer: &mut GeneratedValues<F>) {
+        let a = witness.get_biguint_target(&self.a);
+        let b = witness.get_biguint_target(&self.b);
+
+        let (overflow, diff) = if a >= b {
+            (false, a - b)
+        } else {
+            (true, a + &self.modulus - b)
+        };
+
+        out_buffer.set_biguint_target(&self.diff, &diff);
+        out_buffer.set_bool_target(self.overflow, overflow);
+    }
+
+    fn serialize(&self, dst: &mut Vec<u8>, _common_data: &CommonCircuitData<F, D>) -> IoResult<()> {
+        dst.write_target_biguint(&self.a)?;
+        dst.write_target_biguint(&self.b)?;
+        dst.write_target_biguint(&self.diff)?;
+        dst.write_target_bool(self.overflow)?;
+        dst.write_biguint(&self.modulus)
+    }
+
+    fn deserialize(src: &mut Buffer, _common_data: &CommonCircuitData<F, D>) -> IoResult<Self> {
+        let a = src.read_target_biguint()?;
+        let b = src.read_target_biguint()?;
+        let diff = src.read_target_biguint()?;
+        let overflow = src.read_target_bool()?;
+        let modulus = src.read_biguint()?;
+        Ok(Self {
+            a,
+            b,
+            diff,
+            overflow,
+            modulus,
+            _phantom: PhantomData,
+        })
+    }
+}
+
+/// Computes the inverse of a non-native field element modulo a prime.
+#[derive(Debug, Default)]
+pub struct NonNativeInverseGenerator<F: RichField + Extendable<D>, const D: usize> {
+    x: BigUintTarget,
+    inv: BigUintTarget,
+    modulus: BigUint,
+    _phantom: PhantomData<F>,
+}
+
+impl<F: RichField + Extendable<D>, const D: usize> SimpleGenerator<F, D>
+    for NonNativeInverseGenerator<F, D>
+{
+    fn id(&self) -> String {
+        "NonNativeInverseGenerator".to_string()
+    }
+
+    fn dependencies(&self) -> Vec<Target> {
+        self.x.limbs.iter().map(|&l| l.0).collect()
+    }
+
+    fn run_once(&self, witness: &PartitionWitness<F>, out_buffer: &mut GeneratedValues<F>) {
+        let x = witness.get_biguint_target(&self.x).mod_floor(&self.modulus);
+        assert!(!x.is_zero(), "Cannot invert zero");
+
+        // The modulus is prime, so by Fermat's little theorem x^(p - 2) is the inverse of x.
+        let exponent = &self.modulus - BigUint::from(2u32);
+        let inv = x.modpow(&exponent, &self.modulus);
+        debug_assert!((&x * &inv).mod_floor(&self.modulus).is_one());
+
+        out_buffer.set_biguint_target(&self.inv, &inv);
+    }
+
+    fn serialize(&self, dst: &mut Vec<u8>, _common_data: &CommonCircuitData<F, D>) -> IoResult<()> {
+        dst.write_target_biguint(&self.x)?;
+        dst.write_target_biguint(&self.inv)?;
+        dst.write_biguint(&self.modulus)
+    }
+
+    fn deserialize(src: &mut Buffer, _common_data: &CommonCircuitData<F, D>) -> IoResult<Self> {
+        let x = src.read_target_biguint()?;
+        let inv = src.read_target_biguint()?;
+        let modulus = src.read_biguint()?;
+        Ok(Self {
+            x,
+            inv,
+            modulus,
+            _phantom: PhantomData,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::marker::PhantomData;
+
+    use anyhow::Result;
+
+    use crate::field::secp256k1_base::Secp256K1Base;
+    use crate::field::types::{Field, PrimeField, Sample};
+    use crate::iop::witness::{PartialWitness, Witness, WitnessWrite};
+    use crate::plonk::circuit_builder::CircuitBuilder;
+    use crate::plonk::circuit_data::{CircuitConfig, CircuitData};
+    use crate::plonk::config::{GenericConfig, PoseidonGoldilocksConfig};
+    use crate::util::serialization::{DefaultGateSerializer, DefaultGeneratorSerializer};
+
+    const D: usize = 2;
+    type C = PoseidonGoldilocksConfig;
+    type F = <C as GenericConfig<D>>::F;
+    type FF = Secp256K1Base;
+
+    fn prove_and_verify(builder: CircuitBuilder<F, D>, pw: PartialWitness<F>) -> Result<()> {
+        let data = builder.build::<C>();
+        let proof = data.prove(pw)?;
+        data.verify(proof)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_nonnative_add() -> Result<()> {
+        let x_ff = FF::rand();
+        let y_ff = FF::rand();
+        let sum_ff = x_ff + y_ff;
+
+        let config = CircuitConfig::standard_ecc_config();
+        let mut pw = PartialWitness::new();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+
+        let x = builder.add_virtual_nonnative_target::<FF>();
+        let y = builder.add_virtual_nonnative_target::<FF>();
+        let sum = builder.add_nonnative(&x, &y);
+
+        let sum_expected = builder.constant_nonnative(sum_ff);
+        builder.connect_nonnative(&sum, &sum_expected);
+
+        pw.set_nonnative_target(&x, x_ff);
+        pw.set_nonnative_target(&y, y_ff);
+
+        prove_and_verify(builder, pw)
+    }
+
+    #[test]
+    fn test_nonnative_many_adds() -> Result<()> {
+        let a_ff = FF::rand();
+        let b_ff = FF::rand();
+        let c_ff = FF::rand();
+        let d_ff = FF::rand();
+        let sum_ff = a_ff + b_ff + c_ff + d_ff;
+
+        let config = CircuitConfig::standard_ecc_config();
+        let pw = PartialWitness::new();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+
+        let all = [a_ff, b_ff, c_ff, d_ff].map(|x| builder.constant_nonnative(x));
+        let sum = builder.add_many_nonnative(&all);
+
+        let sum_expected = builder.constant_nonnative(sum_ff);
+        builder.connect_nonnative(&sum, &sum_expected);
+
+        prove_and_verify(builder, pw)
+    }
+
+    #[test]
+    fn test_nonnative_sub() -> Result<()> {
+        let x_ff = FF::rand();
+        let y_ff = FF::rand();
+        let diff_ff = x_ff - y_ff;
+        let neg_ff = -x_ff;
+
+        let config = CircuitConfig::standard_ecc_config();
+        let pw = PartialWitness::new();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+
+        let x = builder.constant_nonnative(x_ff);
+        let y = builder.constant_nonnative(y_ff);
+        let diff = builder.sub_nonnative(&x, &y);
+        let neg = builder.neg_nonnative(&x);
+
+        let diff_expected = builder.constant_nonnative(diff_ff);
+        let neg_expected = builder.constant_nonnative(neg_ff);
+        builder.connect_nonnative(&diff, &diff_expected);
+        builder.connect_nonnative(&neg, &neg_expected);
+
+        prove_and_verify(builder, pw)
+    }
+
+    #[test]
+    fn test_nonnative_mul() -> Result<()> {
+        let x_ff = FF::rand();
+        let y_ff = FF::rand();
+        let product_ff = x_ff * y_ff;
+
+        let config = CircuitConfig::standard_ecc_config();
+        let mut pw = PartialWitness::new();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+
+        let x = builder.add_virtual_nonnative_target::<FF>();
+        let y = builder.add_virtual_nonnative_target::<FF>();
+        let product = builder.mul_nonnative(&x, &y);
+
+        let product_expected = builder.constant_nonnative(product_ff);
+        builder.connect_nonnative(&product, &product_expected);
+
+        pw.set_nonnative_target(&x, x_ff);
+        pw.set_nonnative_target(&y, y_ff);
+
+        prove_and_verify(builder, pw)
+    }
+
+    #[test]
+    fn test_nonnative_inv() -> Result<()> {
+        let x_ff = FF::rand();
+        let inv_x_ff = x_ff.inverse();
+
+        let config = CircuitConfig::standard_ecc_config();
+        let pw = PartialWitness::new();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+
+        let x = builder.constant_nonnative(x_ff);
+        let inv_x = builder.inv_nonnative(&x);
+
+        let inv_x_expected = builder.constant_nonnative(inv_x_ff);
+        builder.connect_nonnative(&inv_x, &inv_x_expected);
+
+        prove_and_verify(builder, pw)
+    }
+
+    #[test]
+    fn test_nonnative_reduce() -> Result<()> {
+        let x_ff = FF::rand();
+        let y_ff = FF::rand();
+        let x_times_y = x_ff.to_canonical_biguint() * y_ff.to_canonical_biguint();
+
+        let config = CircuitConfig::standard_ecc_config();
+        let pw = PartialWitness::new();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+
+        let x_times_y = builder.constant_biguint(&x_times_y);
+        let reduced = builder.reduce::<FF>(&x_times_y);
+
+        let expected = builder.constant_nonnative(x_ff * y_ff);
+        builder.connect_nonnative(&reduced, &expected);
+
+        prove_and_verify(builder, pw)
+    }
+
+    #[test]
+    fn test_nonnative_witness_roundtrip() -> Result<()> {
+        let x_ff = FF::rand();
+
+        let config = CircuitConfig::standard_ecc_config();
+        let mut pw = PartialWitness::new();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+
+        let x = builder.add_virtual_nonnative_target::<FF>();
+        pw.set_nonnative_target(&x, x_ff);
+        assert_eq!(pw.get_nonnative_target(&x), x_ff);
+
+        let y = builder.add_nonnative(&x, &x);
+        let y_expected = builder.constant_nonnative(x_ff.double());
+        builder.connect_nonnative(&y, &y_expected);
+
+        let data = builder.build::<C>();
+
+        let gate_serializer = DefaultGateSerializer;
+        let generator_serializer = DefaultGeneratorSerializer::<C, D> {
+            _phantom: PhantomData,
+        };
+        let bytes = data
+            .to_bytes(&gate_serializer, &generator_serializer)
+            .unwrap();
+        let data =
+            CircuitData::<F, C, D>::from_bytes(&bytes, &gate_serializer, &generator_serializer)
+                .unwrap();
+
+        let proof = data.prove(pw)?;
+        data.verify(proof)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,463 @@
+#[cfg(not(feature = "std"))]
+use alloc::{vec, vec::Vec};
+
+use crate::field::extension::Extendable;
+use crate::gadgets::arithmetic_u32::U32Target;
+use crate::hash::hash_types::RichField;
+use crate::iop::target::{BoolTarget, Target};
+use crate::plonk::circuit_builder::CircuitBuilder;
+
+/// The number of bytes in a SHA-256 message block.
+pub const SHA256_BLOCK_BYTES: usize = 64;
+
+const SHA256_INITIAL_STATE: [u32; 8] = [
+    0x6a09e667, 0xbb67ae85, 0x3c6ef372, 0xa54ff53a, 0x510e527f, 0x9b05688c, 0x1f83d9ab, 0x5be0cd19,
+];
+
+const SHA256_ROUND_CONSTANTS: [u32; 64] = [
+    0x428a2f98, 0x71374491, 0xb5c0fbcf, 0xe9b5dba5, 0x3956c25b, 0x59f111f1, 0x923f82a4, 0xab1c5ed5,
+    0xd807aa98, 0x12835b01, 0x243185be, 0x550c7dc3, 0x72be5d74, 0x80deb1fe, 0x9bdc06a7, 0xc19bf174,
+    0xe49b69c1, 0xefbe4786, 0x0fc19dc6, 0x240ca1cc, 0x2de92c6f, 0x4a7484aa, 0x5cb0a9dc, 0x76f988da,
+    0x983e5152, 0xa831c66d, 0xb00327c8, 0xbf597fc7, 0xc6e00bf3, 0xd5a79147, 0x06ca6351, 0x14292967,
+    0x27b70a85, 0x2e1b2138, 0x4d2c6dfc, 0x53380d13, 0x650a7354, 0x766a0abb, 0x81c2c92e, 0x92722c85,
+    0xa2bfe8a1, 0xa81a664b, 0xc24b8b70, 0xc76c51a3, 0xd192e819, 0xd6990624, 0xf40e3585, 0x106aa070,
+    0x19a4c116, 0x1e376c08, 0x2748774c, 0x34b0bcb5, 0x391c0cb3, 0x4ed8aa4a, 0x5b9cca4f, 0x682e6ff3,
+    0x748f82ee, 0x78a5636f, 0x84c87814, 0x8cc70208, 0x90befffa, 0xa4506ceb, 0xbef9a3f7, 0xc67178f2,
+];
+
+// The lookup tables below take three bits packed as `x + 2y + 4z`.
+const SHA256_LUT_INPUTS: [u16; 8] = [0, 1, 2, 3, 4, 5, 6, 7];
+
+fn sha256_xor3(input: u16) -> u16 {
+    (input ^ (input >> 1) ^ (input >> 2)) & 1
+}
+
+fn sha256_ch(input: u16) -> u16 {
+    let (e, f, g) = (input & 1, (input >> 1) & 1, (input >> 2) & 1);
+    (e & f) ^ ((e ^ 1) & g)
+}
+
+fn sha256_maj(input: u16) -> u16 {
+    let (a, b, c) = (input & 1, (input >> 1) & 1, (input >> 2) & 1);
+    (a & b) ^ (a & c) ^ (b & c)
+}
+
+/// A `Target` whose value is assumed to fit in 8 bits.
+///
+/// Targets obtained through [`CircuitBuilder::add_virtual_byte_target`] are not range-checked
+/// themselves; gadgets consuming them, such as [`CircuitBuilder::sha256`], decompose them into bits,
+/// which enforces the range.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub struct ByteTarget(pub Target);
+
+/// A 32-bit word, held both as its little-endian bits and as a single target.
+#[derive(Copy, Clone, Debug)]
+struct Sha256Word {
+    bits: [BoolTarget; 32],
+    value: Target,
+}
+
+impl<F: RichField + Extendable<D>, const D: usize> CircuitBuilder<F, D> {
+    pub fn add_virtual_byte_target(&mut self) -> ByteTarget {
+        ByteTarget(self.add_virtual_target())
+    }
+
+    pub fn add_virtual_byte_targets(&mut self, n: usize) -> Vec<ByteTarget> {
+        self.add_virtual_targets(n)
+            .into_iter()
+            .map(ByteTarget)
+            .collect()
+    }
+
+    pub fn constant_byte(&mut self, c: u8) -> ByteTarget {
+        ByteTarget(self.constant(F::from_canonical_u8(c)))
+    }
+
+    pub fn connect_byte(&mut self, x: ByteTarget, y: ByteTarget) {
+        self.connect(x.0, y.0)
+    }
+
+    /// Computes the SHA-256 hash of `bytes`, returning the 32 bytes of the digest. The message
+    /// length is fixed when building the circuit.
+    pub fn sha256(&mut self, bytes: &[ByteTarget]) -> [ByteTarget; 32] {
+        // Big-endian bits, as in the SHA-256 specification.
+        let mut bits: Vec<BoolTarget> = bytes
+            .iter()
+            .flat_map(|&b| {
+                let mut byte_bits = self.split_le(b.0, 8);
+                byte_bits.reverse();
+                byte_bits
+            })
+            .collect();
+
+        let mut padding = vec![0x80u8];
+        let padded_len = (bytes.len() + 8).div_ceil(SHA256_BLOCK_BYTES) * SHA256_BLOCK_BYTES;
+        padding.resize(padded_len - bytes.len() - 8, 0);
+        padding.extend_from_slice(&(bytes.len() as u64 * 8).to_be_bytes());
+        for byte in padding {
+            for i in (0..8).rev() {
+                bits.push(self.constant_bool((byte >> i) & 1 == 1));
+            }
+        }
+
+        let mut state = SHA256_INITIAL_STATE.map(|h| self.constant_sha256_word(h));
+        for block in bits.chunks(SHA256_BLOCK_BYTES * 8) {
+            let words = core::array::from_fn(|i| {
+                let mut word_bits: [BoolTarget; 32] =
+                    block[32 * i..32 * (i + 1)].try_into().unwrap();
+                word_bits.reverse();
+                self.sha256_word_from_bits(word_bits)
+            });
+            state = self.sha256_compress_words(&state, &words);
+        }
+
+        core::array::from_fn(|i| {
+            let word = &state[i / 4];
+            let start = 8 * (3 - i % 4);
+            ByteTarget(self.le_sum(word.bits[start..start + 8].iter()))
+        })
+    }
+
+    /// Applies the SHA-256 compression function to `state` and the 16 big-endian words of
+    /// `block`. Every input word is range-checked to 32 bits.
+    pub fn sha256_compress(
+        &mut self,
+        state: &[U32Target; 8],
+        block: &[U32Target; 16],
+    ) -> [U32Target; 8] {
+        let state = state.map(|w| self.split_sha256_word(w.0, 32));
+        let block = block.map(|w| self.split_sha256_word(w.0, 32));
+        self.sha256_compress_words(&state, &block)
+            .map(|w| U32Target(w.value))
+    }
+
+    fn sha256_compress_words(
+        &mut self,
+        state: &[Sha256Word; 8],
+        block: &[Sha256Word; 16],
+    ) -> [Sha256Word; 8] {
+        let xor3_lut = self.add_lookup_table_from_fn(sha256_xor3, &SHA256_LUT_INPUTS);
+        let ch_lut = self.add_lookup_table_from_fn(sha256_ch, &SHA256_LUT_INPUTS);
+        let maj_lut = self.add_lookup_table_from_fn(sha256_maj, &SHA256_LUT_INPUTS);
+
+        // Message schedule.
+        let mut w = block.to_vec();
+        for t in 16..64 {
+            let s0 = self.sha256_small_sigma(&w[t - 15], 7, 18, 3, xor3_lut);
+            let s1 = self.sha256_small_sigma(&w[t - 2], 17, 19, 10, xor3_lut);
+            let sum = self.add_many([s1, w[t - 7].value, s0, w[t - 16].value]);
+            w.push(self.split_sha256_word(sum, 34));
+        }
+
+        let [mut a, mut b, mut c, mut d, mut e, mut f, mut g, mut h] = *state;
+        for (t, &k) in SHA256_ROUND_CONSTANTS.iter().enumerate() {
+            let big_s1 = self.sha256_big_sigma(&e, 6, 11, 25, xor3_lut);
+            let ch = self.sha256_bitwise3(&e, &f, &g, ch_lut);
+            let k = self.constant(F::from_canonical_u32(k));
+            let t1 = self.add_many([h.value, big_s1, ch, k, w[t].value]);
+
+            let big_s0 = self.sha256_big_sigma(&a, 2, 13, 22, xor3_lut);
+            let maj = self.sha256_bitwise3(&a, &b, &c, maj_lut);
+            let t2 = self.add(big_s0, maj);
+
+            h = g;
+            g = f;
+            f = e;
+            let new_e = self.add(d.value, t1);
+            e = self.split_sha256_word(new_e, 35);
+            d = c;
+            c = b;
+            b = a;
+            let new_a = self.add(t1, t2);
+            a = self.split_sha256_word(new_a, 35);
+        }
+
+        let working = [a, b, c, d, e, f, g, h];
+        core::array::from_fn(|i| {
+            let sum = self.add(state[i].value, working[i].value);
+            self.split_sha256_word(sum, 33)
+        })
+    }
+
+    fn constant_sha256_word(&mut self, c: u32) -> Sha256Word {
+        Sha256Word {
+            bits: core::array::from_fn(|i| self.constant_bool((c >> i) & 1 == 1)),
+            value: self.constant(F::from_canonical_u32(c)),
+        }
+    }
+
+    fn sha256_word_from_bits(&mut self, bits: [BoolTarget; 32]) -> Sha256Word {
+        let value = self.le_sum(bits.iter());
+        Sha256Word { bits, value }
+    }
+
+    /// Splits `x`, which must fit in `num_bits` bits, and returns `x mod 2^32` as a word.
+    fn split_sha256_word(&mut self, x: Target, num_bits: usize) -> Sha256Word {
+        let bits = self.split_le(x, num_bits);
+        let value = if num_bits > 32 {
+            let carry = self.le_sum(bits[32..].iter());
+            self.mul_const_add(-F::from_canonical_u64(1 << 32), carry, x)
+        } else {
+            x
+        };
+
+        Sha256Word {
+            bits: bits[..32].try_into().unwrap(),
+            value,
+        }
+    }
+
+    /// Applies the lookup table `lut` bitwise to the three words, returning the packed result.
+    fn sha256_bitwise3(
+        &mut self,
+        x: &Sha256Word,
+        y: &Sha256Word,
+        z: &Sha256Word,
+        lut: usize,
+    ) -> Target {
+        let bits: Vec<_> = (0..32)
+            .map(|i| self.sha256_lookup3(x.bits[i], y.bits[i], z.bits[i], lut))
+            .collect();
+        self.le_sum(bits.iter())
+    }
+
+    /// Computes `ROTR^r0(x) ^ ROTR^r1(x) ^ ROTR^r2(x)`.
+    fn sha256_big_sigma(
+        &mut self,
+        x: &Sha256Word,
+        r0: usize,
+        r1: usize,
+        r2: usize,
+        xor3_lut: usize,
+    ) -> Target {
+        let bits: Vec<_> = (0..32)
+            .map(|i| {
+                let (b0, b1, b2) = (
+                    x.bits[(i + r0) % 32],
+                    x.bits[(i + r1) % 32],
+                    x.bits[(i + r2) % 32],
+                );
+                self.sha256_lookup3(b0, b1, b2, xor3_lut)
+            })
+            .collect();
+        self.le_sum(bits.iter())
+    }
+
+    /// Computes `ROTR^r0(x) ^ ROTR^r1(x) ^ SHR^s(x)`.
+    fn sha256_small_sigma(
+        &mut self,
+        x: &Sha256Word,
+        r0: usize,
+        r1: usize,
+        s: usize,
+        xor3_lut: usize,
+    ) -> Target {
+        let zero = self._false();
+        let bits: Vec<_> = (0..32)
+            .map(|i| {
+                let (b0, b1) = (x.bits[(i + r0) % 32], x.bits[(i + r1) % 32]);
+                let b2 = x.bits.get(i + s).copied().unwrap_or(zero);
+                self.sha256_lookup3(b0, b1, b2, xor3_lut)
+            })
+            .collect();
+        self.le_sum(bits.iter())
+    }
+
+    fn sha256_lookup3(
+        &mut self,
+        x: BoolTarget,
+        y: BoolTarget,
+        z: BoolTarget,
+        lut: usize,
+    ) -> BoolTarget {
+        let packed = self.mul_const_add(F::TWO, z.target, y.target);
+        let packed = self.mul_const_add(F::TWO, packed, x.target);
+        // `new_unsafe` is safe here because all SHA-256 lookup tables output bits.
+        BoolTarget::new_unsafe(self.add_lookup_from_index(packed, lut))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(not(feature = "std"))]
+    use alloc::vec::Vec;
+
+    use anyhow::Result;
+    use rand::rngs::OsRng;
+    use rand::Rng;
+
+    use super::{SHA256_INITIAL_STATE, SHA256_ROUND_CONSTANTS};
+    use crate::field::types::Field;
+    use crate::iop::witness::{PartialWitness, WitnessWrite};
+    use crate::plonk::circuit_builder::CircuitBuilder;
+    use crate::plonk::circuit_data::CircuitConfig;
+    use crate::plonk::config::{GenericConfig, PoseidonGoldilocksConfig};
+
+    const D: usize = 2;
+    type C = PoseidonGoldilocksConfig;
+    type F = <C as GenericConfig<D>>::F;
+
+    /// A straightforward native implementation of SHA-256, used as a reference.
+    fn sha256_compress_native(state: &mut [u32; 8], block: &[u32; 16]) {
+        let mut w = block.to_vec();
+        for t in 16..64 {
+            let s0 = w[t - 15].rotate_right(7) ^ w[t - 15].rotate_right(18) ^ (w[t - 15] >> 3);
+            let s1 = w[t - 2].rotate_right(17) ^ w[t - 2].rotate_right(19) ^ (w[t - 2] >> 10);
+            w.push(
+                w[t - 16]
+                    .wrapping_add(s0)
+                    .wrapping_add(w[t - 7])
+                    .wrapping_add(s1),
+            );
+        }
+
+        let [mut a, mut b, mut c, mut d, mut e, mut f, mut g, mut h] = *state;
+        for t in 0..64 {
+            let s1 = e.rotate_right(6) ^ e.rotate_right(11) ^ e.rotate_right(25);
+            let ch = (e & f) ^ (!e & g);
+            let t1 = h
+                .wrapping_add(s1)
+                .wrapping_add(ch)
+                .wrapping_add(SHA256_ROUND_CONSTANTS[t])
+                .wrapping_add(w[t]);
+            let s0 = a.rotate_right(2) ^ a.rotate_right(13) ^ a.rotate_right(22);
+            let maj = (a & b) ^ (a & c) ^ (b & c);
+            let t2 = s0.wrapping_add(maj);
+
+            h = g;
+            g = f;
+            f = e;
+            e = d.wrapping_add(t1);
+            d = c;
+            c = b;
+            b = a;
+            a = t1.wrapping_add(t2);
+        }
+
+        for (s, x) in state.iter_mut().zip([a, b, c, d, e, f, g, h]) {
+            *s = s.wrapping_add(x);
+        }
+    }
+
+    fn sha256_native(msg: &[u8]) -> [u8; 32] {
+        let mut padded = msg.to_vec();
+        padded.push(0x80);
+        while padded.len() % 64 != 56 {
+            padded.push(0);
+        }
+        padded.extend_from_slice(&(msg.len() as u64 * 8).to_be_bytes());
+
+        let mut state = SHA256_INITIAL_STATE;
+        for block in padded.chunks(64) {
+            let words = core::array::from_fn(|i| {
+                u32::from_be_bytes(block[4 * i..4 * i + 4].try_into().unwrap())
+            });
+            sha256_compress_native(&mut state, &words);
+        }
+
+        let mut digest = [0; 32];
+        for (chunk, word) in digest.chunks_mut(4).zip(state) {
+            chunk.copy_from_slice(&word.to_be_bytes());
+        }
+        digest
+    }
+
+    #[test]
+    fn test_sha256_native() {
+        let digest = sha256_native(b"abc");
+        assert_eq!(
+            digest[..8],
+            [0xba, 0x78, 0x16, 0xbf, 0x8f, 0x01, 0xcf, 0xea]
+        );
+        assert_eq!(
+            digest[24..],
+            [0xb4, 0x10, 0xff, 0x61, 0xf2, 0x00, 0x15, 0xad]
+        );
+    }
+
+    #[test]
+    fn test_sha256() -> Result<()> {
+        let config = CircuitConfig::standard_recursion_config();
+        let mut pw = PartialWitness::new();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+
+        // The second message needs two blocks once padded.
+        let mut rng = OsRng;
+        for len in [3, 60] {
+            let msg: Vec<u8> = (0..len).map(|_| rng.gen()).collect();
+            let msg_targets = builder.add_virtual_byte_targets(len);
+            for (&t, &byte) in msg_targets.iter().zip(&msg) {
+                pw.set_target(t.0, F::from_canonical_u8(byte));
+            }
+
+            let digest = builder.sha256(&msg_targets);
+            for (t, byte) in digest.into_iter().zip(sha256_native(&msg)) {
+                let expected = builder.constant_byte(byte);
+                builder.connect_byte(t, expected);
+            }
+        }
+
+        let data = builder.build::<C>();
+        let proof = data.prove(pw)?;
+        data.verify(proof)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_sha256_compress() -> Result<()> {
+        let config = CircuitConfig::standard_recursion_config();
+        let mut pw = PartialWitness::new();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+
+        let mut rng = OsRng;
+        let mut state: [u32; 8] = core::array::from_fn(|_| rng.gen());
+        let block: [u32; 16] = core::array::from_fn(|_| rng.gen());
+
+        let state_targets = core::array::from_fn(|_| builder.add_virtual_u32_target());
+        let block_targets = core::array::from_fn(|_| builder.add_virtual_u32_target());
+        for (&t, &x) in state_targets.iter().zip(&state) {
+            pw.set_u32_target(t, x);
+        }
+        for (&t, &x) in block_targets.iter().zip(&block) {
+            pw.set_u32_target(t, x);
+        }
+
+        let output = builder.sha256_compress(&state_targets, &block_targets);
+        sha256_compress_native(&mut state, &block);
+        for (t, x) in output.into_iter().zip(state) {
+            let expected = builder.constant_u32(x);
+            builder.connect_u32(t, expected);
+        }
+
+        let data = builder.build::<C>();
+        let proof = data.prove(pw)?;
+        data.verify(proof)?;
+        Ok(())
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_sha256_wrong_digest() {
+        let config = CircuitConfig::standard_recursion_config();
+        let mut pw = PartialWitness::new();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+
+        let msg = b"abc";
+        let msg_targets = builder.add_virtual_byte_targets(msg.len());
+        for (&t, &byte) in msg_targets.iter().zip(msg) {
+            pw.set_target(t.0, F::from_canonical_u8(byte));
+        }
+
+        let digest = builder.sha256(&msg_targets);
+        let mut expected = sha256_native(msg);
+        expected[0] ^= 1;
+        for (t, byte) in digest.into_iter().zip(expected) {
+            let expected = builder.constant_byte(byte);
+            builder.connect_byte(t, expected);
+        }
+
+        let data = builder.build::<C>();
+        let proof = data.prove(pw).unwrap();
+        data.verify(proof).unwrap()
+    }
+}
@@ -160,7 +160,8 @@ mod tests {
 
         let proof = data.prove(pw)?;
 
-        verify(proof, &data.verifier_only, &data.common)
+        verify(proof, &data.verifier_only, &data.common)?;
+        Ok(())
     }
 
     #[test]
@@ -195,6 +196,7 @@ mod tests {
 
         let proof = data.prove(pw)?;
 
-        verify(proof, &data.verifier_only, &data.common)
+        verify(proof, &data.verifier_only, &data.common)?;
+        Ok(())
     }
 }
@@ -80,10 +80,10 @@ impl<F: RichField + Extendable<D>, const D: usize, const B: usize> Gate<F, D> fo
 
     fn eval_unfiltered_base_one(
         &self,
-        _vars: EvaluationVarsBase<F>,
-        _yield_constr: StridedConstraintConsumer<F>,
+        vars_base: EvaluationVarsBase<F>,
+        yield_constr: StridedConstraintConsumer<F>,
     ) {
-        panic!("use eval_unfiltered_base_packed instead");
+        self.eval_unfiltered_base_packed(vars_base.into(), yield_constr)
     }
 
     fn eval_unfiltered_base_batch(&self, vars_base: EvaluationVarsBaseBatch<F>) -> Vec<F> {
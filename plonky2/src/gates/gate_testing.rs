@@ -7,6 +7,7 @@ use crate::field::extension::{Extendable, FieldExtension};
 use crate::field::polynomial::{PolynomialCoeffs, PolynomialValues};
 use crate::field::types::{Field, Sample};
 use crate::gates::gate::Gate;
+use crate::gates::util::StridedConstraintConsumer;
 use crate::hash::hash_types::{HashOut, RichField};
 use crate::iop::witness::{PartialWitness, WitnessWrite};
 use crate::plonk::circuit_builder::CircuitBuilder;
@@ -117,6 +118,12 @@ pub fn test_eval_fns<
     };
 
     let evals_base = gate.eval_unfiltered_base_batch(vars_base_batch);
+    let mut evals_base_one = vec![F::ZERO; gate.num_constraints()];
+    gate.eval_unfiltered_base_one(
+        vars_base_batch.view(0),
+        StridedConstraintConsumer::new(&mut evals_base_one, 1, 0),
+    );
+    ensure!(evals_base_one == evals_base);
     let evals = gate.eval_unfiltered(vars);
     // This works because we have a batch of 1.
     ensure!(
@@ -159,5 +166,6 @@ pub fn test_eval_fns<
 
     let data = builder.build::<C>();
     let proof = data.prove(pw)?;
-    verify::<F, C, D>(proof, &data.verifier_only, &data.common)
+    verify::<F, C, D>(proof, &data.verifier_only, &data.common)?;
+    Ok(())
 }
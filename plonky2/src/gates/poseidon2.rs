@@ -0,0 +1,606 @@
+#[cfg(not(feature = "std"))]
+use alloc::{format, string::String, vec, vec::Vec};
+use core::marker::PhantomData;
+
+use crate::field::extension::Extendable;
+use crate::field::types::Field;
+use crate::gates::gate::Gate;
+use crate::gates::util::StridedConstraintConsumer;
+use crate::hash::hash_types::RichField;
+use crate::hash::poseidon2;
+use crate::hash::poseidon2::Poseidon2;
+use crate::iop::ext_target::ExtensionTarget;
+use crate::iop::generator::{GeneratedValues, SimpleGenerator, WitnessGeneratorRef};
+use crate::iop::target::Target;
+use crate::iop::wire::Wire;
+use crate::iop::witness::{PartitionWitness, Witness, WitnessWrite};
+use crate::plonk::circuit_builder::CircuitBuilder;
+use crate::plonk::circuit_data::CommonCircuitData;
+use crate::plonk::vars::{EvaluationTargets, EvaluationVars, EvaluationVarsBase};
+use crate::util::serialization::{Buffer, IoResult, Read, Write};
+
+/// Evaluates a full Poseidon2 permutation with `WIDTH` state elements.
+///
+/// Like `PoseidonGate`, this has a flag which can be used to swap the first four inputs with the
+/// next four, for ordering sibling digests in Merkle proofs.
+#[derive(Debug, Default)]
+pub struct Poseidon2Gate<F: RichField + Extendable<D>, const D: usize, const WIDTH: usize>(
+    PhantomData<F>,
+);
+
+impl<F: RichField + Extendable<D>, const D: usize, const WIDTH: usize> Poseidon2Gate<F, D, WIDTH> {
+    pub const fn new() -> Self {
+        Self(PhantomData)
+    }
+
+    /// The wire index for the `i`th input to the permutation.
+    pub(crate) const fn wire_input(i: usize) -> usize {
+        i
+    }
+
+    /// The wire index for the `i`th output to the permutation.
+    pub(crate) const fn wire_output(i: usize) -> usize {
+        WIDTH + i
+    }
+
+    /// If this is set to 1, the first four inputs will be swapped with the next four inputs. This
+    /// is useful for ordering hashes in Merkle proofs. Otherwise, this should be set to 0.
+    pub(crate) const WIRE_SWAP: usize = 2 * WIDTH;
+
+    const START_DELTA: usize = 2 * WIDTH + 1;
+
+    /// A wire which stores `swap * (input[i + 4] - input[i])`; used to compute the swapped inputs.
+    const fn wire_delta(i: usize) -> usize {
+        assert!(i < 4);
+        Self::START_DELTA + i
+    }
+
+    const START_FULL_0: usize = Self::START_DELTA + 4;
+
+    /// A wire which stores the input of the `i`-th S-box of the `round`-th round of the first set
+    /// of full rounds.
+    const fn wire_full_sbox_0(round: usize, i: usize) -> usize {
+        debug_assert!(
+            round != 0,
+            "First round S-box inputs are not stored as wires"
+        );
+        debug_assert!(round < poseidon2::HALF_N_FULL_ROUNDS);
+        debug_assert!(i < WIDTH);
+        Self::START_FULL_0 + WIDTH * (round - 1) + i
+    }
+
+    const START_PARTIAL: usize = Self::START_FULL_0 + WIDTH * (poseidon2::HALF_N_FULL_ROUNDS - 1);
+
+    /// A wire which stores the input of the S-box of the `round`-th round of the partial rounds.
+    const fn wire_partial_sbox(round: usize) -> usize {
+        debug_assert!(round < poseidon2::N_PARTIAL_ROUNDS);
+        Self::START_PARTIAL + round
+    }
+
+    const START_FULL_1: usize = Self::START_PARTIAL + poseidon2::N_PARTIAL_ROUNDS;
+
+    /// A wire which stores the input of the `i`-th S-box of the `round`-th round of the second set
+    /// of full rounds.
+    const fn wire_full_sbox_1(round: usize, i: usize) -> usize {
+        debug_assert!(round < poseidon2::HALF_N_FULL_ROUNDS);
+        debug_assert!(i < WIDTH);
+        Self::START_FULL_1 + WIDTH * round + i
+    }
+
+    /// End of wire indices, exclusive.
+    const fn end() -> usize {
+        Self::START_FULL_1 + WIDTH * poseidon2::HALF_N_FULL_ROUNDS
+    }
+}
+
+impl<F: RichField + Extendable<D> + Poseidon2<WIDTH>, const D: usize, const WIDTH: usize> Gate<F, D>
+    for Poseidon2Gate<F, D, WIDTH>
+{
+    fn id(&self) -> String {
+        format!("{self:?}<WIDTH={WIDTH}>")
+    }
+
+    fn serialize(
+        &self,
+        _dst: &mut Vec<u8>,
+        _common_data: &CommonCircuitData<F, D>,
+    ) -> IoResult<()> {
+        Ok(())
+    }
+
+    fn deserialize(_src: &mut Buffer, _common_data: &CommonCircuitData<F, D>) -> IoResult<Self> {
+        Ok(Poseidon2Gate::new())
+    }
+
+    fn eval_unfiltered(&self, vars: EvaluationVars<F, D>) -> Vec<F::Extension> {
+        let mut constraints = Vec::with_capacity(self.num_constraints());
+
+        // Assert that `swap` is binary.
+        let swap = vars.local_wires[Self::WIRE_SWAP];
+        constraints.push(swap * (swap - F::Extension::ONE));
+
+        // Assert that each delta wire is set properly: `delta_i = swap * (rhs - lhs)`.
+        for i in 0..4 {
+            let input_lhs = vars.local_wires[Self::wire_input(i)];
+            let input_rhs = vars.local_wires[Self::wire_input(i + 4)];
+            let delta_i = vars.local_wires[Self::wire_delta(i)];
+            constraints.push(swap * (input_rhs - input_lhs) - delta_i);
+        }
+
+        // Compute the possibly-swapped input layer.
+        let mut state = [F::Extension::ZERO; WIDTH];
+        for i in 0..4 {
+            let delta_i = vars.local_wires[Self::wire_delta(i)];
+            let input_lhs = Self::wire_input(i);
+            let input_rhs = Self::wire_input(i + 4);
+            state[i] = vars.local_wires[input_lhs] + delta_i;
+            state[i + 4] = vars.local_wires[input_rhs] - delta_i;
+        }
+        for i in 8..WIDTH {
+            state[i] = vars.local_wires[Self::wire_input(i)];
+        }
+
+        <F as Poseidon2<WIDTH>>::external_linear_layer_field(&mut state);
+
+        // First set of full rounds.
+        for r in 0..poseidon2::HALF_N_FULL_ROUNDS {
+            <F as Poseidon2<WIDTH>>::external_constant_layer_field(&mut state, r);
+            if r != 0 {
+                for i in 0..WIDTH {
+                    let sbox_in = vars.local_wires[Self::wire_full_sbox_0(r, i)];
+                    constraints.push(state[i] - sbox_in);
+                    state[i] = sbox_in;
+                }
+            }
+            <F as Poseidon2<WIDTH>>::sbox_layer_field(&mut state);
+            <F as Poseidon2<WIDTH>>::external_linear_layer_field(&mut state);
+        }
+
+        // Partial rounds.
+        for r in 0..poseidon2::N_PARTIAL_ROUNDS {
+            state[0] += F::Extension::from_canonical_u64(
+                <F as Poseidon2<WIDTH>>::INTERNAL_ROUND_CONSTANTS[r],
+            );
+            let sbox_in = vars.local_wires[Self::wire_partial_sbox(r)];
+            constraints.push(state[0] - sbox_in);
+            state[0] = <F as Poseidon2<WIDTH>>::sbox_monomial(sbox_in);
+            <F as Poseidon2<WIDTH>>::internal_linear_layer_field(&mut state);
+        }
+
+        // Second set of full rounds.
+        for r in 0..poseidon2::HALF_N_FULL_ROUNDS {
+            <F as Poseidon2<WIDTH>>::external_constant_layer_field(
+                &mut state,
+                poseidon2::HALF_N_FULL_ROUNDS + r,
+            );
+            for i in 0..WIDTH {
+                let sbox_in = vars.local_wires[Self::wire_full_sbox_1(r, i)];
+                constraints.push(state[i] - sbox_in);
+                state[i] = sbox_in;
+            }
+            <F as Poseidon2<WIDTH>>::sbox_layer_field(&mut state);
+            <F as Poseidon2<WIDTH>>::external_linear_layer_field(&mut state);
+        }
+
+        for i in 0..WIDTH {
+            constraints.push(state[i] - vars.local_wires[Self::wire_output(i)]);
+        }
+
+        constraints
+    }
+
+    fn eval_unfiltered_base_one(
+        &self,
+        vars: EvaluationVarsBase<F>,
+        mut yield_constr: StridedConstraintConsumer<F>,
+    ) {
+        // Assert that `swap` is binary.
+        let swap = vars.local_wires[Self::WIRE_SWAP];
+        yield_constr.one(swap * swap.sub_one());
+
+        // Assert that each delta wire is set properly: `delta_i = swap * (rhs - lhs)`.
+        for i in 0..4 {
+            let input_lhs = vars.local_wires[Self::wire_input(i)];
+            let input_rhs = vars.local_wires[Self::wire_input(i + 4)];
+            let delta_i = vars.local_wires[Self::wire_delta(i)];
+            yield_constr.one(swap * (input_rhs - input_lhs) - delta_i);
+        }
+
+        // Compute the possibly-swapped input layer.
+        let mut state = [F::ZERO; WIDTH];
+        for i in 0..4 {
+            let delta_i = vars.local_wires[Self::wire_delta(i)];
+            let input_lhs = Self::wire_input(i);
+            let input_rhs = Self::wire_input(i + 4);
+            state[i] = vars.local_wires[input_lhs] + delta_i;
+            state[i + 4] = vars.local_wires[input_rhs] - delta_i;
+        }
+        for i in 8..WIDTH {
+            state[i] = vars.local_wires[Self::wire_input(i)];
+        }
+
+        <F as Poseidon2<WIDTH>>::external_linear_layer(&mut state);
+
+        // First set of full rounds.
+        for r in 0..poseidon2::HALF_N_FULL_ROUNDS {
+            <F as Poseidon2<WIDTH>>::external_constant_layer(&mut state, r);
+            if r != 0 {
+                for i in 0..WIDTH {
+                    let sbox_in = vars.local_wires[Self::wire_full_sbox_0(r, i)];
+                    yield_constr.one(state[i] - sbox_in);
+                    state[i] = sbox_in;
+                }
+            }
+            <F as Poseidon2<WIDTH>>::sbox_layer_field(&mut state);
+            <F as Poseidon2<WIDTH>>::external_linear_layer(&mut state);
+        }
+
+        // Partial rounds.
+        for r in 0..poseidon2::N_PARTIAL_ROUNDS {
+            state[0] += F::from_canonical_u64(<F as Poseidon2<WIDTH>>::INTERNAL_ROUND_CONSTANTS[r]);
+            let sbox_in = vars.local_wires[Self::wire_partial_sbox(r)];
+            yield_constr.one(state[0] - sbox_in);
+            state[0] = <F as Poseidon2<WIDTH>>::sbox_monomial(sbox_in);
+            <F as Poseidon2<WIDTH>>::internal_linear_layer(&mut state);
+        }
+
+        // Second set of full rounds.
+        for r in 0..poseidon2::HALF_N_FULL_ROUNDS {
+            <F as Poseidon2<WIDTH>>::external_constant_layer(
+                &mut state,
+                poseidon2::HALF_N_FULL_ROUNDS + r,
+            );
+            for i in 0..WIDTH {
+                let sbox_in = vars.local_wires[Self::wire_full_sbox_1(r, i)];
+                yield_constr.one(state[i] - sbox_in);
+                state[i] = sbox_in;
+            }
+            <F as Poseidon2<WIDTH>>::sbox_layer_field(&mut state);
+            <F as Poseidon2<WIDTH>>::external_linear_layer(&mut state);
+        }
+
+        for i in 0..WIDTH {
+            yield_constr.one(state[i] - vars.local_wires[Self::wire_output(i)]);
+        }
+    }
+
+    fn eval_unfiltered_circuit(
+        &self,
+        builder: &mut CircuitBuilder<F, D>,
+        vars: EvaluationTargets<D>,
+    ) -> Vec<ExtensionTarget<D>> {
+        let mut constraints = Vec::with_capacity(self.num_constraints());
+
+        // Assert that `swap` is binary.
+        let swap = vars.local_wires[Self::WIRE_SWAP];
+        constraints.push(builder.mul_sub_extension(swap, swap, swap));
+
+        // Assert that each delta wire is set properly: `delta_i = swap * (rhs - lhs)`.
+        for i in 0..4 {
+            let input_lhs = vars.local_wires[Self::wire_input(i)];
+            let input_rhs = vars.local_wires[Self::wire_input(i + 4)];
+            let delta_i = vars.local_wires[Self::wire_delta(i)];
+            let diff = builder.sub_extension(input_rhs, input_lhs);
+            constraints.push(builder.mul_sub_extension(swap, diff, delta_i));
+        }
+
+        // Compute the possibly-swapped input layer.
+        let mut state = [builder.zero_extension(); WIDTH];
+        for i in 0..4 {
+            let delta_i = vars.local_wires[Self::wire_delta(i)];
+            let input_lhs = vars.local_wires[Self::wire_input(i)];
+            let input_rhs = vars.local_wires[Self::wire_input(i + 4)];
+            state[i] = builder.add_extension(input_lhs, delta_i);
+            state[i + 4] = builder.sub_extension(input_rhs, delta_i);
+        }
+        for i in 8..WIDTH {
+            state[i] = vars.local_wires[Self::wire_input(i)];
+        }
+
+        <F as Poseidon2<WIDTH>>::external_linear_layer_circuit(builder, &mut state);
+
+        // First set of full rounds.
+        for r in 0..poseidon2::HALF_N_FULL_ROUNDS {
+            <F as Poseidon2<WIDTH>>::external_constant_layer_circuit(builder, &mut state, r);
+            if r != 0 {
+                for i in 0..WIDTH {
+                    let sbox_in = vars.local_wires[Self::wire_full_sbox_0(r, i)];
+                    constraints.push(builder.sub_extension(state[i], sbox_in));
+                    state[i] = sbox_in;
+                }
+            }
+            <F as Poseidon2<WIDTH>>::sbox_layer_circuit(builder, &mut state);
+            <F as Poseidon2<WIDTH>>::external_linear_layer_circuit(builder, &mut state);
+        }
+
+        // Partial rounds.
+        for r in 0..poseidon2::N_PARTIAL_ROUNDS {
+            let c = F::from_canonical_u64(<F as Poseidon2<WIDTH>>::INTERNAL_ROUND_CONSTANTS[r]);
+            state[0] = builder.add_const_extension(state[0], c);
+            let sbox_in = vars.local_wires[Self::wire_partial_sbox(r)];
+            constraints.push(builder.sub_extension(state[0], sbox_in));
+            state[0] = <F as Poseidon2<WIDTH>>::sbox_monomial_circuit(builder, sbox_in);
+            <F as Poseidon2<WIDTH>>::internal_linear_layer_circuit(builder, &mut state);
+        }
+
+        // Second set of full rounds.
+        for r in 0..poseidon2::HALF_N_FULL_ROUNDS {
+            <F as Poseidon2<WIDTH>>::external_constant_layer_circuit(
+                builder,
+                &mut state,
+                poseidon2::HALF_N_FULL_ROUNDS + r,
+            );
+            for i in 0..WIDTH {
+                let sbox_in = vars.local_wires[Self::wire_full_sbox_1(r, i)];
+                constraints.push(builder.sub_extension(state[i], sbox_in));
+                state[i] = sbox_in;
+            }
+            <F as Poseidon2<WIDTH>>::sbox_layer_circuit(builder, &mut state);
+            <F as Poseidon2<WIDTH>>::external_linear_layer_circuit(builder, &mut state);
+        }
+
+        for i in 0..WIDTH {
+            constraints
+                .push(builder.sub_extension(state[i], vars.local_wires[Self::wire_output(i)]));
+        }
+
+        constraints
+    }
+
+    fn generators(&self, row: usize, _local_constants: &[F]) -> Vec<WitnessGeneratorRef<F, D>> {
+        let gen = Poseidon2Generator::<F, D, WIDTH> {
+            row,
+            _phantom: PhantomData,
+        };
+        vec![WitnessGeneratorRef::new(gen.adapter())]
+    }
+
+    fn num_wires(&self) -> usize {
+        Self::end()
+    }
+
+    fn num_constants(&self) -> usize {
+        0
+    }
+
+    fn degree(&self) -> usize {
+        7
+    }
+
+    fn num_constraints(&self) -> usize {
+        WIDTH * (poseidon2::N_FULL_ROUNDS_TOTAL - 1) + poseidon2::N_PARTIAL_ROUNDS + WIDTH + 1 + 4
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct Poseidon2Generator<
+    F: RichField + Extendable<D> + Poseidon2<WIDTH>,
+    const D: usize,
+    const WIDTH: usize,
+> {
+    row: usize,
+    _phantom: PhantomData<F>,
+}
+
+impl<F: RichField + Extendable<D> + Poseidon2<WIDTH>, const D: usize, const WIDTH: usize>
+    SimpleGenerator<F, D> for Poseidon2Generator<F, D, WIDTH>
+{
+    fn id(&self) -> String {
+        format!("Poseidon2Generator<WIDTH={WIDTH}>")
+    }
+
+    fn dependencies(&self) -> Vec<Target> {
+        (0..WIDTH)
+            .map(|i| Poseidon2Gate::<F, D, WIDTH>::wire_input(i))
+            .chain(Some(Poseidon2Gate::<F, D, WIDTH>::WIRE_SWAP))
+            .map(|column| Target::wire(self.row, column))
+            .collect()
+    }
+
+    fn run_once(&self, witness: &PartitionWitness<F>, out_buffer: &mut GeneratedValues<F>) {
+        let local_wire = |column| Wire {
+            row: self.row,
+            column,
+        };
+
+        let mut state: [F; WIDTH] = core::array::from_fn(|i| {
+            witness.get_wire(local_wire(Poseidon2Gate::<F, D, WIDTH>::wire_input(i)))
+        });
+
+        let swap_value = witness.get_wire(local_wire(Poseidon2Gate::<F, D, WIDTH>::WIRE_SWAP));
+        debug_assert!(swap_value == F::ZERO || swap_value == F::ONE);
+
+        for i in 0..4 {
+            let delta_i = swap_value * (state[i + 4] - state[i]);
+            out_buffer.set_wire(
+                local_wire(Poseidon2Gate::<F, D, WIDTH>::wire_delta(i)),
+                delta_i,
+            );
+        }
+
+        if swap_value == F::ONE {
+            for i in 0..4 {
+                state.swap(i, 4 + i);
+            }
+        }
+
+        <F as Poseidon2<WIDTH>>::external_linear_layer(&mut state);
+
+        for r in 0..poseidon2::HALF_N_FULL_ROUNDS {
+            <F as Poseidon2<WIDTH>>::external_constant_layer(&mut state, r);
+            if r != 0 {
+                for i in 0..WIDTH {
+                    out_buffer.set_wire(
+                        local_wire(Poseidon2Gate::<F, D, WIDTH>::wire_full_sbox_0(r, i)),
+                        state[i],
+                    );
+                }
+            }
+            <F as Poseidon2<WIDTH>>::sbox_layer_field(&mut state);
+            <F as Poseidon2<WIDTH>>::external_linear_layer(&mut state);
+        }
+
+        for r in 0..poseidon2::N_PARTIAL_ROUNDS {
+            state[0] += F::from_canonical_u64(<F as Poseidon2<WIDTH>>::INTERNAL_ROUND_CONSTANTS[r]);
+            out_buffer.set_wire(
+                local_wire(Poseidon2Gate::<F, D, WIDTH>::wire_partial_sbox(r)),
+                state[0],
+            );
+            state[0] = <F as Poseidon2<WIDTH>>::sbox_monomial(state[0]);
+            <F as Poseidon2<WIDTH>>::internal_linear_layer(&mut state);
+        }
+
+        for r in 0..poseidon2::HALF_N_FULL_ROUNDS {
+            <F as Poseidon2<WIDTH>>::external_constant_layer(
+                &mut state,
+                poseidon2::HALF_N_FULL_ROUNDS + r,
+            );
+            for i in 0..WIDTH {
+                out_buffer.set_wire(
+                    local_wire(Poseidon2Gate::<F, D, WIDTH>::wire_full_sbox_1(r, i)),
+                    state[i],
+                );
+            }
+            <F as Poseidon2<WIDTH>>::sbox_layer_field(&mut state);
+            <F as Poseidon2<WIDTH>>::external_linear_layer(&mut state);
+        }
+
+        for i in 0..WIDTH {
+            out_buffer.set_wire(
+                local_wire(Poseidon2Gate::<F, D, WIDTH>::wire_output(i)),
+                state[i],
+            );
+        }
+    }
+
+    fn serialize(&self, dst: &mut Vec<u8>, _common_data: &CommonCircuitData<F, D>) -> IoResult<()> {
+        dst.write_usize(self.row)
+    }
+
+    fn deserialize(src: &mut Buffer, _common_data: &CommonCircuitData<F, D>) -> IoResult<Self> {
+        let row = src.read_usize()?;
+        Ok(Self {
+            row,
+            _phantom: PhantomData,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+    use plonky2_field::goldilocks_field::GoldilocksField;
+
+    use super::*;
+    use crate::gates::gate_testing::{test_eval_fns, test_low_degree};
+    use crate::iop::generator::generate_partial_witness;
+    use crate::iop::witness::PartialWitness;
+    use crate::plonk::circuit_data::CircuitConfig;
+    use crate::plonk::config::{GenericConfig, Poseidon2GoldilocksConfig};
+
+    #[test]
+    fn wire_indices() {
+        type F = GoldilocksField;
+        type Gate = Poseidon2Gate<F, 4, 12>;
+
+        assert_eq!(Gate::wire_input(0), 0);
+        assert_eq!(Gate::wire_input(11), 11);
+        assert_eq!(Gate::wire_output(0), 12);
+        assert_eq!(Gate::wire_output(11), 23);
+        assert_eq!(Gate::WIRE_SWAP, 24);
+        assert_eq!(Gate::wire_delta(0), 25);
+        assert_eq!(Gate::wire_delta(3), 28);
+        assert_eq!(Gate::wire_full_sbox_0(1, 0), 29);
+        assert_eq!(Gate::wire_full_sbox_0(3, 0), 53);
+        assert_eq!(Gate::wire_full_sbox_0(3, 11), 64);
+        assert_eq!(Gate::wire_partial_sbox(0), 65);
+        assert_eq!(Gate::wire_partial_sbox(21), 86);
+        assert_eq!(Gate::wire_full_sbox_1(0, 0), 87);
+        assert_eq!(Gate::wire_full_sbox_1(3, 0), 123);
+        assert_eq!(Gate::wire_full_sbox_1(3, 11), 134);
+        assert_eq!(Gate::end(), 135);
+    }
+
+    fn check_generated_output<const WIDTH: usize>(swap: bool)
+    where
+        GoldilocksField: Poseidon2<WIDTH>,
+    {
+        const D: usize = 2;
+        type C = Poseidon2GoldilocksConfig;
+        type F = <C as GenericConfig<D>>::F;
+        type Gate<const WIDTH: usize> = Poseidon2Gate<F, D, WIDTH>;
+
+        let config = CircuitConfig {
+            num_wires: Gate::<WIDTH>::end(),
+            ..CircuitConfig::standard_recursion_config()
+        };
+        let mut builder = CircuitBuilder::new(config);
+        let gate = Gate::<WIDTH>::new();
+        let row = builder.add_gate(gate, vec![]);
+        let circuit = builder.build_prover::<C>();
+
+        let permutation_inputs: [F; WIDTH] = core::array::from_fn(F::from_canonical_usize);
+
+        let mut inputs = PartialWitness::new();
+        inputs.set_wire(
+            Wire {
+                row,
+                column: Gate::<WIDTH>::WIRE_SWAP,
+            },
+            F::from_bool(swap),
+        );
+        for i in 0..WIDTH {
+            inputs.set_wire(
+                Wire {
+                    row,
+                    column: Gate::<WIDTH>::wire_input(i),
+                },
+                permutation_inputs[i],
+            );
+        }
+
+        let witness =
+            generate_partial_witness(inputs, &circuit.prover_only, &circuit.common).unwrap();
+
+        let mut swapped_inputs = permutation_inputs;
+        if swap {
+            for i in 0..4 {
+                swapped_inputs.swap(i, i + 4);
+            }
+        }
+        let expected_outputs = <F as Poseidon2<WIDTH>>::poseidon2(swapped_inputs);
+        for i in 0..WIDTH {
+            let out = witness.get_wire(Wire {
+                row: 0,
+                column: Gate::<WIDTH>::wire_output(i),
+            });
+            assert_eq!(out, expected_outputs[i]);
+        }
+    }
+
+    #[test]
+    fn generated_output() {
+        check_generated_output::<8>(false);
+        check_generated_output::<12>(false);
+        check_generated_output::<12>(true);
+        check_generated_output::<16>(false);
+    }
+
+    #[test]
+    fn low_degree() {
+        type F = GoldilocksField;
+        test_low_degree(Poseidon2Gate::<F, 4, 8>::new());
+        test_low_degree(Poseidon2Gate::<F, 4, 12>::new());
+        test_low_degree(Poseidon2Gate::<F, 4, 16>::new());
+    }
+
+    #[test]
+    fn eval_fns() -> Result<()> {
+        const D: usize = 2;
+        type C = Poseidon2GoldilocksConfig;
+        type F = <C as GenericConfig<D>>::F;
+        test_eval_fns::<F, C, _, D>(Poseidon2Gate::<F, D, 8>::new())?;
+        test_eval_fns::<F, C, _, D>(Poseidon2Gate::<F, D, 12>::new())?;
+        test_eval_fns::<F, C, _, D>(Poseidon2Gate::<F, D, 16>::new())
+    }
+}
@@ -0,0 +1,255 @@
+//! AVX2 implementation of the full rounds of Poseidon2 over Goldilocks.
+//!
+//! The state is split into `WIDTH / 4` chunks of four elements, which are the blocks of the
+//! external matrix `circ(2 * M4, M4, ..., M4)`. We keep it transposed: vector `j` holds element `j`
+//! of every chunk, one chunk per lane, and unused lanes are kept at zero. This way the S-boxes and
+//! the multiplication of every chunk by `M4` are purely vertical, and only the sum of the chunks
+//! needs a horizontal reduction. The partial rounds have a single S-box each, so they are left to
+//! the scalar implementation.
+
+use core::arch::x86_64::*;
+use core::mem::transmute;
+
+use crate::field::goldilocks_field::GoldilocksField;
+use crate::field::types::{Field, Field64, PrimeField64};
+use crate::hash::poseidon2::{Poseidon2, HALF_N_FULL_ROUNDS};
+
+// The arithmetic below follows `Avx2GoldilocksField` in the field crate; see the comments there
+// for the tricks used to emulate unsigned comparisons and carries. All values are in `[0, 2^64)`,
+// but are not necessarily canonical.
+
+const SIGN_BIT: __m256i = unsafe { transmute([i64::MIN; 4]) };
+const SHIFTED_FIELD_ORDER: __m256i =
+    unsafe { transmute([GoldilocksField::ORDER ^ (i64::MIN as u64); 4]) };
+const EPSILON: __m256i = unsafe { transmute([GoldilocksField::ORDER.wrapping_neg(); 4]) };
+
+/// Add 2^63 with overflow, to emulate unsigned comparisons with signed ones.
+#[inline(always)]
+unsafe fn shift(x: __m256i) -> __m256i {
+    _mm256_xor_si256(x, SIGN_BIT)
+}
+
+/// Convert a value shifted by 2^63 to canonical form, keeping it shifted.
+#[inline(always)]
+unsafe fn canonicalize_s(x_s: __m256i) -> __m256i {
+    let mask = _mm256_cmpgt_epi64(SHIFTED_FIELD_ORDER, x_s);
+    let wrapback_amt = _mm256_andnot_si256(mask, EPSILON);
+    _mm256_add_epi64(x_s, wrapback_amt)
+}
+
+#[inline(always)]
+unsafe fn add(x: __m256i, y: __m256i) -> __m256i {
+    // Once `y` is canonical, `x + y < 2^64 + ORDER`, so the sum overflows at most once.
+    let y_s = canonicalize_s(shift(y));
+    let res_wrapped_s = _mm256_add_epi64(x, y_s);
+    let mask = _mm256_cmpgt_epi64(y_s, res_wrapped_s);
+    let wrapback_amt = _mm256_srli_epi64::<32>(mask);
+    shift(_mm256_add_epi64(res_wrapped_s, wrapback_amt))
+}
+
+#[inline(always)]
+unsafe fn double(x: __m256i) -> __m256i {
+    add(x, x)
+}
+
+/// Full 64-bit by 64-bit multiplication, returning the high and low words.
+#[inline(always)]
+unsafe fn mul64_64(x: __m256i, y: __m256i) -> (__m256i, __m256i) {
+    let x_hi = _mm256_castps_si256(_mm256_movehdup_ps(_mm256_castsi256_ps(x)));
+    let y_hi = _mm256_castps_si256(_mm256_movehdup_ps(_mm256_castsi256_ps(y)));
+
+    let mul_ll = _mm256_mul_epu32(x, y);
+    let mul_lh = _mm256_mul_epu32(x, y_hi);
+    let mul_hl = _mm256_mul_epu32(x_hi, y);
+    let mul_hh = _mm256_mul_epu32(x_hi, y_hi);
+
+    let mul_ll_hi = _mm256_srli_epi64::<32>(mul_ll);
+    let t0 = _mm256_add_epi64(mul_hl, mul_ll_hi);
+    let t0_lo = _mm256_and_si256(t0, EPSILON);
+    let t0_hi = _mm256_srli_epi64::<32>(t0);
+    let t1 = _mm256_add_epi64(mul_lh, t0_lo);
+    let t2 = _mm256_add_epi64(mul_hh, t0_hi);
+    let t1_hi = _mm256_srli_epi64::<32>(t1);
+    let res_hi = _mm256_add_epi64(t2, t1_hi);
+
+    let t1_lo = _mm256_castps_si256(_mm256_moveldup_ps(_mm256_castsi256_ps(t1)));
+    let res_lo = _mm256_blend_epi32::<0xaa>(mul_ll, t1_lo);
+
+    (res_hi, res_lo)
+}
+
+/// Full 64-bit squaring, returning the high and low words.
+#[inline(always)]
+unsafe fn square64(x: __m256i) -> (__m256i, __m256i) {
+    let x_hi = _mm256_castps_si256(_mm256_movehdup_ps(_mm256_castsi256_ps(x)));
+
+    let mul_ll = _mm256_mul_epu32(x, x);
+    let mul_lh = _mm256_mul_epu32(x, x_hi);
+    let mul_hh = _mm256_mul_epu32(x_hi, x_hi);
+
+    let mul_ll_hi = _mm256_srli_epi64::<33>(mul_ll);
+    let t0 = _mm256_add_epi64(mul_lh, mul_ll_hi);
+    let t0_hi = _mm256_srli_epi64::<31>(t0);
+    let res_hi = _mm256_add_epi64(mul_hh, t0_hi);
+
+    let mul_lh_lo = _mm256_slli_epi64::<33>(mul_lh);
+    let res_lo = _mm256_add_epi64(mul_ll, mul_lh_lo);
+
+    (res_hi, res_lo)
+}
+
+/// Reduce a 128-bit value, given as its high and low words, modulo the field order.
+#[inline(always)]
+unsafe fn reduce128((hi, lo): (__m256i, __m256i)) -> __m256i {
+    let lo_s = shift(lo);
+    let hi_hi = _mm256_srli_epi64::<32>(hi);
+
+    // lo - hi_hi. `hi_hi < 2^32`, so 32-bit comparisons are enough to detect the borrow.
+    let lo1_wrapped_s = _mm256_sub_epi64(lo_s, hi_hi);
+    let borrow_mask = _mm256_cmpgt_epi32(lo1_wrapped_s, lo_s);
+    let lo1_s = _mm256_sub_epi64(lo1_wrapped_s, _mm256_srli_epi64::<32>(borrow_mask));
+
+    // lo1 + hi_lo * EPSILON. The product is at most `0xfffffffe00000001`, so again 32-bit
+    // comparisons are enough to detect the carry.
+    let t = _mm256_mul_epu32(hi, EPSILON);
+    let lo2_wrapped_s = _mm256_add_epi64(lo1_s, t);
+    let carry_mask = _mm256_cmpgt_epi32(lo1_s, lo2_wrapped_s);
+    let lo2_s = _mm256_add_epi64(lo2_wrapped_s, _mm256_srli_epi64::<32>(carry_mask));
+
+    shift(lo2_s)
+}
+
+#[inline(always)]
+unsafe fn mul(x: __m256i, y: __m256i) -> __m256i {
+    reduce128(mul64_64(x, y))
+}
+
+#[inline(always)]
+unsafe fn square(x: __m256i) -> __m256i {
+    reduce128(square64(x))
+}
+
+#[inline(always)]
+unsafe fn sbox(x: __m256i) -> __m256i {
+    // x |--> x^7
+    let x2 = square(x);
+    let x4 = square(x2);
+    let x3 = mul(x, x2);
+    mul(x3, x4)
+}
+
+/// Returns a vector whose lanes all hold the sum of the lanes of `x`.
+#[inline(always)]
+unsafe fn horizontal_sum(x: __m256i) -> __m256i {
+    let x = add(x, _mm256_permute4x64_epi64::<0b10_11_00_01>(x));
+    add(x, _mm256_permute4x64_epi64::<0b01_00_11_10>(x))
+}
+
+/// Loads element `j` of each chunk of `values` into the lanes of a vector.
+#[inline(always)]
+unsafe fn load_transposed<const WIDTH: usize>(values: &[u64; WIDTH], j: usize) -> __m256i {
+    let mut lanes = [0u64; 4];
+    for (c, lane) in lanes.iter_mut().take(WIDTH / 4).enumerate() {
+        *lane = values[4 * c + j];
+    }
+    _mm256_loadu_si256(lanes.as_ptr().cast::<__m256i>())
+}
+
+/// Applies `HALF_N_FULL_ROUNDS` full rounds of the Poseidon2 permutation of width `WIDTH`,
+/// starting with the full round `*round_ctr`.
+#[inline]
+pub(crate) unsafe fn full_rounds<const WIDTH: usize>(
+    state: &mut [GoldilocksField; WIDTH],
+    round_ctr: &mut usize,
+) where
+    GoldilocksField: Poseidon2<WIDTH>,
+{
+    debug_assert!(matches!(WIDTH, 8 | 12 | 16));
+
+    let values = state.map(|x| x.to_noncanonical_u64());
+    let mut v: [__m256i; 4] = core::array::from_fn(|j| load_transposed(&values, j));
+
+    // Mask of the lanes which hold a chunk of the state.
+    let num_chunks = (WIDTH / 4) as i64;
+    let lane_mask = _mm256_cmpgt_epi64(
+        _mm256_set1_epi64x(num_chunks),
+        _mm256_setr_epi64x(0, 1, 2, 3),
+    );
+
+    for _ in 0..HALF_N_FULL_ROUNDS {
+        let round_constants =
+            &<GoldilocksField as Poseidon2<WIDTH>>::EXTERNAL_ROUND_CONSTANTS[*round_ctr];
+        for (j, v_j) in v.iter_mut().enumerate() {
+            *v_j = sbox(add(*v_j, load_transposed(round_constants, j)));
+        }
+
+        // Multiply every chunk by `M4`, with the addition chain of `apply_m4`.
+        let t0 = add(v[0], v[1]);
+        let t1 = add(v[2], v[3]);
+        let t2 = add(double(v[1]), t1);
+        let t3 = add(double(v[3]), t0);
+        let t4 = add(double(double(t1)), t3);
+        let t5 = add(double(double(t0)), t2);
+        let t6 = add(t3, t5);
+        let t7 = add(t2, t4);
+        v = [t6, t5, t7, t4];
+
+        // Add the sum of all chunks to each chunk. The unused lanes are zero, so they do not
+        // contribute to the sums, but they have to be cleared again afterwards.
+        for v_j in v.iter_mut() {
+            *v_j = _mm256_and_si256(add(*v_j, horizontal_sum(*v_j)), lane_mask);
+        }
+
+        *round_ctr += 1;
+    }
+
+    let mut lanes = [[0u64; 4]; 4];
+    for (j, v_j) in v.iter().enumerate() {
+        _mm256_storeu_si256(lanes[j].as_mut_ptr().cast::<__m256i>(), *v_j);
+    }
+    for (i, x) in state.iter_mut().enumerate() {
+        *x = GoldilocksField::from_noncanonical_u64(lanes[i % 4][i / 4]);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::field::types::Sample;
+
+    fn check_full_rounds<const WIDTH: usize>()
+    where
+        GoldilocksField: Poseidon2<WIDTH>,
+    {
+        type F = GoldilocksField;
+
+        // Include non-canonical inputs, which the vectorized arithmetic has to handle.
+        let mut input: [F; WIDTH] = F::rand_array();
+        input[0] = F::from_noncanonical_u64(u64::MAX);
+        input[1] = F::from_noncanonical_u64(F::ORDER);
+
+        for start in [0, HALF_N_FULL_ROUNDS] {
+            let mut expected = input;
+            for r in start..start + HALF_N_FULL_ROUNDS {
+                <F as Poseidon2<WIDTH>>::external_constant_layer(&mut expected, r);
+                <F as Poseidon2<WIDTH>>::sbox_layer_field(&mut expected);
+                <F as Poseidon2<WIDTH>>::external_linear_layer(&mut expected);
+            }
+
+            let mut actual = input;
+            let mut round_ctr = start;
+            unsafe {
+                full_rounds(&mut actual, &mut round_ctr);
+            }
+            assert_eq!(round_ctr, start + HALF_N_FULL_ROUNDS);
+            assert_eq!(actual, expected);
+        }
+    }
+
+    #[test]
+    fn test_full_rounds() {
+        check_full_rounds::<8>();
+        check_full_rounds::<12>();
+        check_full_rounds::<16>();
+    }
+}
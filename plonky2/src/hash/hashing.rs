@@ -6,7 +6,7 @@ use core::fmt::Debug;
 use crate::field::extension::Extendable;
 use crate::field::types::Field;
 use crate::hash::hash_types::{HashOut, HashOutTarget, RichField, NUM_HASH_OUT_ELTS};
-use crate::iop::target::Target;
+use crate::iop::target::{BoolTarget, Target};
 use crate::plonk::circuit_builder::CircuitBuilder;
 use crate::plonk::config::AlgebraicHasher;
 
@@ -27,6 +27,91 @@ impl<F: RichField + Extendable<D>, const D: usize> CircuitBuilder<F, D> {
         HashOutTarget::from_vec(self.hash_n_to_m_no_pad::<H>(inputs, NUM_HASH_OUT_ELTS))
     }
 
+    /// Hashes the first `length` elements of `inputs` with the `pad10*1` rule, matching
+    /// `Hasher::hash_pad`, where `length` is only known from the witness. The circuit constrains
+    /// `length` to lie in `[0, inputs.len()]`; the elements of `inputs` past `length` are ignored.
+    ///
+    /// The sponge absorbs enough blocks for the longest possible message, and the digest is then
+    /// selected with `random_access` from the states reached after each block.
+    pub fn hash_n_to_hash_pad_var_len<H: AlgebraicHasher<F>>(
+        &mut self,
+        inputs: &[Target],
+        length: Target,
+    ) -> HashOutTarget {
+        let rate = H::AlgebraicPermutation::RATE;
+        let max_len = inputs.len();
+        // The padded message of length `n` has `(n + 1) / rate + 1` blocks.
+        let max_num_blocks = (max_len + 1) / rate + 1;
+
+        // `length_is[i]` is set iff `length == i`. Exactly one of these flags is set, which also
+        // constrains `length` to be at most `max_len`.
+        let length_is: Vec<BoolTarget> = (0..=max_len)
+            .map(|i| {
+                let i = self.constant(F::from_canonical_usize(i));
+                self.is_equal(length, i)
+            })
+            .collect();
+        let one = self.one();
+        let num_flags_set = self.add_many(length_is.iter().map(|b| b.target));
+        self.connect(num_flags_set, one);
+
+        // `is_last_block[k]` is set iff block `k` is the last block of the padded message, i.e.
+        // iff `rate * k <= length + 1 < rate * (k + 1)`.
+        let is_last_block: Vec<Target> = (0..max_num_blocks)
+            .map(|k| {
+                let start = (rate * k).saturating_sub(1);
+                let end = (rate * (k + 1) - 1).min(max_len + 1);
+                self.add_many(length_is[start..end].iter().map(|b| b.target))
+            })
+            .collect();
+
+        // Build the padded buffer. Position `i` holds `inputs[i]` if `i < length`, the first
+        // padding one if `i == length`, and the final padding one if it ends the last block.
+        let mut is_before_end = one;
+        let padded: Vec<Target> = (0..max_num_blocks * rate)
+            .map(|i| {
+                let mut elt = if i <= max_len {
+                    is_before_end = self.sub(is_before_end, length_is[i].target);
+                    if i < max_len {
+                        self.mul_add(is_before_end, inputs[i], length_is[i].target)
+                    } else {
+                        length_is[i].target
+                    }
+                } else {
+                    self.zero()
+                };
+                if i % rate == rate - 1 {
+                    elt = self.add(elt, is_last_block[i / rate]);
+                }
+                elt
+            })
+            .collect();
+
+        let zero = self.zero();
+        let mut state = H::AlgebraicPermutation::new(core::iter::repeat(zero));
+        let mut digests = Vec::with_capacity(max_num_blocks.next_power_of_two());
+        for block in padded.chunks(rate) {
+            state.set_from_slice(block, 0);
+            state = self.permute::<H>(state);
+            digests.push(HashOutTarget::from_partial(
+                &state.squeeze()[..NUM_HASH_OUT_ELTS],
+                zero,
+            ));
+        }
+        // `random_access` needs a power-of-two number of choices.
+        let last_digest = *digests.last().unwrap();
+        digests.resize(max_num_blocks.next_power_of_two(), last_digest);
+
+        let weighted_flags: Vec<Target> = is_last_block
+            .iter()
+            .enumerate()
+            .skip(1)
+            .map(|(k, &b)| self.mul_const(F::from_canonical_usize(k), b))
+            .collect();
+        let last_block_index = self.add_many(weighted_flags);
+        self.random_access_hash(last_block_index, digests)
+    }
+
     pub fn hash_n_to_m_no_pad<H: AlgebraicHasher<F>>(
         &mut self,
         inputs: Vec<Target>,
@@ -143,3 +228,68 @@ pub fn hash_n_to_m_no_pad<F: RichField, P: PlonkyPermutation<F>>(
 pub fn hash_n_to_hash_no_pad<F: RichField, P: PlonkyPermutation<F>>(inputs: &[F]) -> HashOut<F> {
     HashOut::from_vec(hash_n_to_m_no_pad::<F, P>(inputs, NUM_HASH_OUT_ELTS))
 }
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+
+    use crate::field::types::{Field, Sample};
+    use crate::hash::poseidon::PoseidonHash;
+    use crate::iop::witness::{PartialWitness, WitnessWrite};
+    use crate::plonk::circuit_builder::CircuitBuilder;
+    use crate::plonk::circuit_data::CircuitConfig;
+    use crate::plonk::config::{GenericConfig, Hasher, PoseidonGoldilocksConfig};
+
+    #[test]
+    fn test_hash_n_to_hash_pad_var_len() -> Result<()> {
+        const D: usize = 2;
+        type C = PoseidonGoldilocksConfig;
+        type F = <C as GenericConfig<D>>::F;
+        type H = PoseidonHash;
+
+        let config = CircuitConfig::standard_recursion_config();
+        let mut pw = PartialWitness::new();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+
+        // With a rate of 8, these lengths cover padded messages of one, two and three blocks,
+        // including the boundaries where the two padding ones fall in different blocks.
+        let max_len = 20;
+        let inputs = F::rand_vec(max_len);
+        let input_targets = builder.add_virtual_targets(max_len);
+        pw.set_target_arr(&input_targets, &inputs);
+        for len in [0, 5, 6, 7, 13, 14, 20] {
+            let length = builder.add_virtual_target();
+            pw.set_target(length, F::from_canonical_usize(len));
+            let hash = builder.hash_n_to_hash_pad_var_len::<H>(&input_targets, length);
+            let expected = builder.constant_hash(H::hash_pad(&inputs[..len]));
+            builder.connect_hashes(hash, expected);
+        }
+
+        let data = builder.build::<C>();
+        let proof = data.prove(pw)?;
+        data.verify(proof)?;
+        Ok(())
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_hash_n_to_hash_pad_var_len_length_too_large() {
+        const D: usize = 2;
+        type C = PoseidonGoldilocksConfig;
+        type F = <C as GenericConfig<D>>::F;
+
+        let config = CircuitConfig::standard_recursion_config();
+        let mut pw = PartialWitness::new();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+
+        let input_targets = builder.add_virtual_targets(4);
+        pw.set_target_arr(&input_targets, &F::rand_vec(4));
+        let length = builder.add_virtual_target();
+        pw.set_target(length, F::from_canonical_usize(5));
+        builder.hash_n_to_hash_pad_var_len::<PoseidonHash>(&input_targets, length);
+
+        let data = builder.build::<C>();
+        let proof = data.prove(pw).unwrap();
+        data.verify(proof).unwrap();
+    }
+}
@@ -1,7 +1,7 @@
 #[cfg(not(feature = "std"))]
 use alloc::{vec, vec::Vec};
 
-use anyhow::{ensure, Result};
+use anyhow::{anyhow, ensure, Result};
 use itertools::Itertools;
 use serde::{Deserialize, Serialize};
 
@@ -13,6 +13,7 @@ use crate::iop::target::{BoolTarget, Target};
 use crate::plonk::circuit_builder::CircuitBuilder;
 use crate::plonk::circuit_data::VerifierCircuitTarget;
 use crate::plonk::config::{AlgebraicHasher, Hasher};
+use crate::util::log2_strict;
 
 #[derive(Clone, Debug, Serialize, Deserialize, Eq, PartialEq)]
 #[serde(bound = "")]
@@ -37,6 +38,81 @@ pub struct MerkleProofTarget {
     pub siblings: Vec<HashOutTarget>,
 }
 
+/// A proof that several leaves are present in a Merkle tree. The paths of the leaves share the
+/// digests they have in common, and the digests which can be computed from the leaves are omitted.
+#[derive(Clone, Debug, Serialize, Deserialize, Eq, PartialEq)]
+#[serde(bound = "")]
+pub struct BatchMerkleProof<F: RichField, H: Hasher<F>> {
+    /// The Merkle digests needed to compute the paths, layer by layer from the bottommost one, and
+    /// by increasing index within each layer.
+    pub siblings: Vec<H::Hash>,
+}
+
+impl<F: RichField, H: Hasher<F>> BatchMerkleProof<F, H> {
+    pub fn len(&self) -> usize {
+        self.siblings.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct BatchMerkleProofTarget {
+    /// The Merkle digests needed to compute the paths, as in `BatchMerkleProof`.
+    pub siblings: Vec<HashOutTarget>,
+}
+
+/// Returns the number of sibling digests in a batch proof for the given leaves, in a tree with
+/// `num_layers` layers below its cap. This only depends on the set of leaf indices.
+pub fn batch_merkle_proof_len(leaf_indices: &[usize], num_layers: usize) -> usize {
+    let mut nodes = leaf_indices.to_vec();
+    nodes.sort_unstable();
+    nodes.dedup();
+    let mut len = 0;
+    for _ in 0..num_layers {
+        let mut parents: Vec<usize> = nodes.iter().map(|&i| i >> 1).collect();
+        parents.dedup();
+        // Each parent has two children, and those on a path need no sibling digest.
+        len += 2 * parents.len() - nodes.len();
+        nodes = parents;
+    }
+    len
+}
+
+/// Computes the digests of the nodes on the paths of `nodes` up to the cap, given the digests of
+/// `nodes` sorted by index and without duplicates. Siblings which are not on any path are taken
+/// from `siblings`, in the order of `BatchMerkleProof`. Returns the digests of the cap entries on
+/// the paths, or `None` if `siblings` runs out.
+pub(crate) fn fold_batch_merkle_paths<T: Copy>(
+    mut nodes: Vec<(usize, T)>,
+    num_layers: usize,
+    siblings: &mut impl Iterator<Item = T>,
+    mut two_to_one: impl FnMut(T, T) -> T,
+) -> Option<Vec<(usize, T)>> {
+    for _ in 0..num_layers {
+        let mut parents = Vec::with_capacity(nodes.len());
+        let mut i = 0;
+        while i < nodes.len() {
+            let (index, digest) = nodes[i];
+            let parent_digest =
+                if index & 1 == 0 && i + 1 < nodes.len() && nodes[i + 1].0 == index + 1 {
+                    i += 1;
+                    two_to_one(digest, nodes[i].1)
+                } else if index & 1 == 0 {
+                    two_to_one(digest, siblings.next()?)
+                } else {
+                    two_to_one(siblings.next()?, digest)
+                };
+            parents.push((index >> 1, parent_digest));
+            i += 1;
+        }
+        nodes = parents;
+    }
+    Some(nodes)
+}
+
 /// Verifies that the given leaf data is present at the given index in the Merkle tree with the
 /// given root.
 pub fn verify_merkle_proof<F: RichField, H: Hasher<F>>(
@@ -76,6 +152,53 @@ pub fn verify_merkle_proof_to_cap<F: RichField, H: Hasher<F>>(
     Ok(())
 }
 
+/// Verifies that the given leaves are present at the given indices in the Merkle tree with the
+/// given cap, which has `height` layers below its root. Indices may be given in any order, and may
+/// repeat as long as the leaf data is the same.
+pub fn verify_batch_merkle_proof_to_cap<F: RichField, H: Hasher<F>>(
+    leaf_data: Vec<Vec<F>>,
+    leaf_indices: &[usize],
+    height: usize,
+    merkle_cap: &MerkleCap<F, H>,
+    proof: &BatchMerkleProof<F, H>,
+) -> Result<()> {
+    ensure!(
+        leaf_data.len() == leaf_indices.len(),
+        "Mismatched number of leaves and indices."
+    );
+    let cap_height = merkle_cap.height();
+    ensure!(cap_height <= height, "Invalid Merkle cap height.");
+    ensure!(
+        leaf_indices.iter().all(|&i| i < 1 << height),
+        "Invalid leaf index."
+    );
+
+    let mut nodes: Vec<(usize, H::Hash)> = leaf_indices
+        .iter()
+        .zip(&leaf_data)
+        .map(|(&i, leaf)| (i, H::hash_or_noop(leaf)))
+        .collect();
+    nodes.sort_by_key(|&(i, _)| i);
+    for pair in nodes.windows(2) {
+        ensure!(
+            pair[0].0 != pair[1].0 || pair[0].1 == pair[1].1,
+            "Conflicting leaves at the same index."
+        );
+    }
+    nodes.dedup_by_key(|&mut (i, _)| i);
+
+    let mut siblings = proof.siblings.iter().copied();
+    let cap_nodes =
+        fold_batch_merkle_paths(nodes, height - cap_height, &mut siblings, H::two_to_one)
+            .ok_or_else(|| anyhow!("Batch Merkle proof is too short."))?;
+    ensure!(siblings.next().is_none(), "Batch Merkle proof is too long.");
+    for (i, digest) in cap_nodes {
+        ensure!(digest == merkle_cap.0[i], "Invalid batch Merkle proof.");
+    }
+
+    Ok(())
+}
+
 impl<F: RichField + Extendable<D>, const D: usize> CircuitBuilder<F, D> {
     /// Verifies that the given leaf data is present at the given index in the Merkle tree with the
     /// given root. The index is given by its little-endian bits.
@@ -151,6 +274,62 @@ impl<F: RichField + Extendable<D>, const D: usize> CircuitBuilder<F, D> {
         }
     }
 
+    /// Verifies that the given leaves are present at the given indices in the Merkle tree with the
+    /// given cap, which has `height` layers below its root. The shape of the proof only depends on
+    /// the indices, so they must be known when building the circuit. The proof should have
+    /// `batch_merkle_proof_len(leaf_indices, height - cap_height)` siblings.
+    pub fn verify_batch_merkle_proof_to_cap<H: AlgebraicHasher<F>>(
+        &mut self,
+        leaf_data: Vec<Vec<Target>>,
+        leaf_indices: &[usize],
+        height: usize,
+        merkle_cap: &MerkleCapTarget,
+        proof: &BatchMerkleProofTarget,
+    ) {
+        assert_eq!(leaf_data.len(), leaf_indices.len());
+        let cap_height = log2_strict(merkle_cap.0.len());
+        let num_layers = height - cap_height;
+        assert_eq!(
+            proof.siblings.len(),
+            batch_merkle_proof_len(leaf_indices, num_layers)
+        );
+
+        let mut nodes: Vec<(usize, HashOutTarget)> = leaf_indices
+            .iter()
+            .zip(leaf_data)
+            .map(|(&i, leaf)| {
+                assert!(i < 1 << height, "Invalid leaf index.");
+                (i, self.hash_or_noop::<H>(leaf))
+            })
+            .collect();
+        nodes.sort_by_key(|&(i, _)| i);
+        for pair in nodes.windows(2) {
+            if pair[0].0 == pair[1].0 {
+                self.connect_hashes(pair[0].1, pair[1].1);
+            }
+        }
+        nodes.dedup_by_key(|&mut (i, _)| i);
+
+        let zero = self.zero();
+        let cap_nodes = fold_batch_merkle_paths(
+            nodes,
+            num_layers,
+            &mut proof.siblings.iter().copied(),
+            |left, right| {
+                let mut perm_inputs = H::AlgebraicPermutation::default();
+                perm_inputs.set_from_slice(&left.elements, 0);
+                perm_inputs.set_from_slice(&right.elements, NUM_HASH_OUT_ELTS);
+                perm_inputs.set_from_iter(core::iter::repeat(zero), 2 * NUM_HASH_OUT_ELTS);
+                let perm_outs = self.permute::<H>(perm_inputs);
+                HashOutTarget::from_vec(perm_outs.squeeze()[..NUM_HASH_OUT_ELTS].to_vec())
+            },
+        )
+        .expect("the number of siblings was checked above");
+        for (i, digest) in cap_nodes {
+            self.connect_hashes(digest, merkle_cap.0[i]);
+        }
+    }
+
     pub fn connect_hashes(&mut self, x: HashOutTarget, y: HashOutTarget) {
         for i in 0..NUM_HASH_OUT_ELTS {
             self.connect(x.elements[i], y.elements[i]);
@@ -216,9 +395,9 @@ mod tests {
         let i_c = builder.constant(F::from_canonical_usize(i));
         let i_bits = builder.split_le(i_c, log_n);
 
-        let data = builder.add_virtual_targets(tree.leaves[i].len());
+        let data = builder.add_virtual_targets(tree.get(i).len());
         for j in 0..data.len() {
-            pw.set_target(data[j], tree.leaves[i][j]);
+            pw.set_target(data[j], tree.get(i)[j]);
         }
 
         builder.verify_merkle_proof_to_cap::<<C as GenericConfig<D>>::InnerHasher>(
@@ -228,6 +407,55 @@ mod tests {
         let data = builder.build::<C>();
         let proof = data.prove(pw)?;
 
-        verify(proof, &data.verifier_only, &data.common)
+        verify(proof, &data.verifier_only, &data.common)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_recursive_batch_merkle_proof() -> Result<()> {
+        const D: usize = 2;
+        type C = PoseidonGoldilocksConfig;
+        type F = <C as GenericConfig<D>>::F;
+        let config = CircuitConfig::standard_recursion_config();
+        let mut pw = PartialWitness::new();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+
+        let log_n = 8;
+        let n = 1 << log_n;
+        let cap_height = 2;
+        let leaves = random_data::<F>(n, 7);
+        let tree = MerkleTree::<F, <C as GenericConfig<D>>::Hasher>::new(leaves, cap_height);
+        let mut indices: Vec<usize> = (0..8).map(|_| OsRng.gen_range(0..n)).collect();
+        indices.push(indices[0]);
+        let proof = tree.prove_batch(&indices);
+
+        let proof_t = BatchMerkleProofTarget {
+            siblings: builder.add_virtual_hashes(proof.len()),
+        };
+        for (&t, &sibling) in proof_t.siblings.iter().zip(&proof.siblings) {
+            pw.set_hash_target(t, sibling);
+        }
+
+        let cap_t = builder.add_virtual_cap(cap_height);
+        pw.set_cap_target(&cap_t, &tree.cap);
+
+        let leaf_data = indices
+            .iter()
+            .map(|&i| {
+                let data = builder.add_virtual_targets(tree.get(i).len());
+                pw.set_target_arr(&data, tree.get(i));
+                data
+            })
+            .collect();
+
+        builder.verify_batch_merkle_proof_to_cap::<<C as GenericConfig<D>>::InnerHasher>(
+            leaf_data, &indices, log_n, &cap_t, &proof_t,
+        );
+
+        let data = builder.build::<C>();
+        let proof = data.prove(pw)?;
+
+        verify(proof, &data.verifier_only, &data.common)?;
+        Ok(())
     }
 }
@@ -1,15 +1,23 @@
 #[cfg(not(feature = "std"))]
-use alloc::vec::Vec;
+use alloc::{vec, vec::Vec};
 use core::mem::MaybeUninit;
 use core::slice;
+#[cfg(feature = "mmap")]
+use std::{
+    io,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
 
 use plonky2_maybe_rayon::*;
 use serde::{Deserialize, Serialize};
 
 use crate::hash::hash_types::RichField;
-use crate::hash::merkle_proofs::MerkleProof;
+use crate::hash::merkle_proofs::{BatchMerkleProof, MerkleProof};
 use crate::plonk::config::{GenericHashOut, Hasher};
 use crate::util::log2_strict;
+#[cfg(feature = "mmap")]
+use crate::util::mmap::MmapSlice;
 
 /// The Merkle cap of height `h` of a Merkle tree is the `h`-th layer (from the root) of the tree.
 /// It can be used in place of the root to verify Merkle paths, which are `h` elements shorter.
@@ -42,23 +50,46 @@ impl<F: RichField, H: Hasher<F>> MerkleCap<F, H> {
     }
 }
 
-#[derive(Clone, Debug, Eq, PartialEq)]
+#[derive(Clone, Debug)]
 pub struct MerkleTree<F: RichField, H: Hasher<F>> {
-    /// The data in the leaves of the Merkle tree.
-    pub leaves: Vec<Vec<F>>,
+    /// The data in the leaves of the Merkle tree. This is empty if the tree is stored in
+    /// memory-mapped files, so it is only exposed through `get`, which handles both storages.
+    pub(crate) leaves: Vec<Vec<F>>,
 
-    /// The digests in the tree. Consists of `cap.len()` sub-trees, each corresponding to one
-    /// element in `cap`. Each subtree is contiguous and located at
-    /// `digests[digests.len() / cap.len() * i..digests.len() / cap.len() * (i + 1)]`.
-    /// Within each subtree, siblings are stored next to each other. The layout is,
-    /// left_child_subtree || left_child_digest || right_child_digest || right_child_subtree, where
-    /// left_child_digest and right_child_digest are H::Hash and left_child_subtree and
-    /// right_child_subtree recurse. Observe that the digest of a node is stored by its _parent_.
-    /// Consequently, the digests of the roots are not stored here (they can be found in `cap`).
-    pub digests: Vec<H::Hash>,
+    /// The digests in the tree, laid out as described in `all_digests`. This is empty if the tree
+    /// is stored in memory-mapped files, so it is only exposed through `all_digests`.
+    pub(crate) digests: Vec<H::Hash>,
 
     /// The Merkle cap.
     pub cap: MerkleCap<F, H>,
+
+    /// The leaves and digests, if they are stored in memory-mapped files rather than in `leaves`
+    /// and `digests`.
+    #[cfg(feature = "mmap")]
+    pub(crate) mapped: Option<Arc<MappedTree<F, H>>>,
+}
+
+/// The leaves and digests of a Merkle tree stored in memory-mapped files.
+#[cfg(feature = "mmap")]
+#[derive(Debug)]
+pub(crate) struct MappedTree<F: RichField, H: Hasher<F>> {
+    /// The leaves, concatenated.
+    leaves: MmapSlice<F>,
+    leaf_len: usize,
+    digests: MmapSlice<H::Hash>,
+}
+
+/// Where the leaves and digests of a Merkle tree are stored.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub enum MerkleStorage {
+    /// In memory, in the `MerkleTree` itself.
+    #[default]
+    Memory,
+    /// In memory-mapped temporary files, created in the given directory, or in the default
+    /// temporary directory if `None`. The OS pages the data in when it is accessed, and may evict
+    /// it under memory pressure.
+    #[cfg(feature = "mmap")]
+    Mmap(Option<PathBuf>),
 }
 
 impl<F: RichField, H: Hasher<F>> Default for MerkleTree<F, H> {
@@ -67,10 +98,83 @@ impl<F: RichField, H: Hasher<F>> Default for MerkleTree<F, H> {
             leaves: Vec::new(),
             digests: Vec::new(),
             cap: MerkleCap::default(),
+            #[cfg(feature = "mmap")]
+            mapped: None,
         }
     }
 }
 
+impl<F: RichField, H: Hasher<F>> PartialEq for MerkleTree<F, H> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cap == other.cap
+            && self.num_leaves() == other.num_leaves()
+            && (0..self.num_leaves()).all(|i| self.get(i) == other.get(i))
+            && self.all_digests() == other.all_digests()
+    }
+}
+
+impl<F: RichField, H: Hasher<F>> Eq for MerkleTree<F, H> {}
+
+/// A range of leaves, stored either as separate vectors or concatenated in a single buffer.
+#[derive(Clone, Copy)]
+enum Leaves<'a, F> {
+    Separate(&'a [Vec<F>]),
+    Concatenated {
+        data: &'a [F],
+        leaf_len: usize,
+        num_leaves: usize,
+    },
+}
+
+impl<'a, F> Leaves<'a, F> {
+    fn len(&self) -> usize {
+        match self {
+            Self::Separate(leaves) => leaves.len(),
+            Self::Concatenated { num_leaves, .. } => *num_leaves,
+        }
+    }
+
+    fn get(&self, i: usize) -> &'a [F] {
+        match self {
+            Self::Separate(leaves) => &leaves[i],
+            Self::Concatenated { data, leaf_len, .. } => &data[i * leaf_len..(i + 1) * leaf_len],
+        }
+    }
+
+    fn split_at(&self, mid: usize) -> (Self, Self) {
+        match *self {
+            Self::Separate(leaves) => {
+                let (left, right) = leaves.split_at(mid);
+                (Self::Separate(left), Self::Separate(right))
+            }
+            Self::Concatenated {
+                data,
+                leaf_len,
+                num_leaves,
+            } => {
+                let (left, right) = data.split_at(mid * leaf_len);
+                (
+                    Self::Concatenated {
+                        data: left,
+                        leaf_len,
+                        num_leaves: mid,
+                    },
+                    Self::Concatenated {
+                        data: right,
+                        leaf_len,
+                        num_leaves: num_leaves - mid,
+                    },
+                )
+            }
+        }
+    }
+
+    /// The `len` leaves starting from `start`.
+    fn range(&self, start: usize, len: usize) -> Self {
+        self.split_at(start).1.split_at(len).0
+    }
+}
+
 fn capacity_up_to_mut<T>(v: &mut Vec<T>, len: usize) -> &mut [MaybeUninit<T>] {
     assert!(v.capacity() >= len);
     let v_ptr = v.as_mut_ptr().cast::<MaybeUninit<T>>();
@@ -85,11 +189,11 @@ fn capacity_up_to_mut<T>(v: &mut Vec<T>, len: usize) -> &mut [MaybeUninit<T>] {
 
 fn fill_subtree<F: RichField, H: Hasher<F>>(
     digests_buf: &mut [MaybeUninit<H::Hash>],
-    leaves: &[Vec<F>],
+    leaves: Leaves<F>,
 ) -> H::Hash {
     assert_eq!(leaves.len(), digests_buf.len() / 2 + 1);
     if digests_buf.is_empty() {
-        H::hash_or_noop(&leaves[0])
+        H::hash_or_noop(leaves.get(0))
     } else {
         // Layout is: left recursive output || left child digest
         //             || right child digest || right recursive output.
@@ -115,7 +219,7 @@ fn fill_subtree<F: RichField, H: Hasher<F>>(
 fn fill_digests_buf<F: RichField, H: Hasher<F>>(
     digests_buf: &mut [MaybeUninit<H::Hash>],
     cap_buf: &mut [MaybeUninit<H::Hash>],
-    leaves: &[Vec<F>],
+    leaves: Leaves<F>,
     cap_height: usize,
 ) {
     // Special case of a tree that's all cap. The usual case will panic because we'll try to split
@@ -123,29 +227,27 @@ fn fill_digests_buf<F: RichField, H: Hasher<F>>(
     // `blah` chunks as opposed to chunks _of_ `blah`.)
     if digests_buf.is_empty() {
         debug_assert_eq!(cap_buf.len(), leaves.len());
-        cap_buf
-            .par_iter_mut()
-            .zip(leaves)
-            .for_each(|(cap_buf, leaf)| {
-                cap_buf.write(H::hash_or_noop(leaf));
-            });
+        cap_buf.par_iter_mut().enumerate().for_each(|(i, cap_buf)| {
+            cap_buf.write(H::hash_or_noop(leaves.get(i)));
+        });
         return;
     }
 
     let subtree_digests_len = digests_buf.len() >> cap_height;
     let subtree_leaves_len = leaves.len() >> cap_height;
     let digests_chunks = digests_buf.par_chunks_exact_mut(subtree_digests_len);
-    let leaves_chunks = leaves.par_chunks_exact(subtree_leaves_len);
     assert_eq!(digests_chunks.len(), cap_buf.len());
-    assert_eq!(digests_chunks.len(), leaves_chunks.len());
-    digests_chunks.zip(cap_buf).zip(leaves_chunks).for_each(
-        |((subtree_digests, subtree_cap), subtree_leaves)| {
+    assert_eq!(digests_chunks.len() * subtree_leaves_len, leaves.len());
+    digests_chunks
+        .zip(cap_buf)
+        .enumerate()
+        .for_each(|(i, (subtree_digests, subtree_cap))| {
             // We have `1 << cap_height` sub-trees, one for each entry in `cap`. They are totally
             // independent, so we schedule one task for each. `digests_buf` and `leaves` are split
             // into `1 << cap_height` slices, one for each sub-tree.
+            let subtree_leaves = leaves.range(i * subtree_leaves_len, subtree_leaves_len);
             subtree_cap.write(fill_subtree::<F, H>(subtree_digests, subtree_leaves));
-        },
-    );
+        });
 }
 
 impl<F: RichField, H: Hasher<F>> MerkleTree<F, H> {
@@ -166,7 +268,7 @@ impl<F: RichField, H: Hasher<F>> MerkleTree<F, H> {
 
         let digests_buf = capacity_up_to_mut(&mut digests, num_digests);
         let cap_buf = capacity_up_to_mut(&mut cap, len_cap);
-        fill_digests_buf::<F, H>(digests_buf, cap_buf, &leaves[..], cap_height);
+        fill_digests_buf::<F, H>(digests_buf, cap_buf, Leaves::Separate(&leaves), cap_height);
 
         unsafe {
             // SAFETY: `fill_digests_buf` and `cap` initialized the spare capacity up to
@@ -179,23 +281,135 @@ impl<F: RichField, H: Hasher<F>> MerkleTree<F, H> {
             leaves,
             digests,
             cap: MerkleCap(cap),
+            #[cfg(feature = "mmap")]
+            mapped: None,
         }
     }
 
+    /// Builds a tree whose leaves and digests are stored in memory-mapped files. The leaves, all of
+    /// the same non-zero length `leaf_len`, are concatenated in `leaves`. The file holding the
+    /// digests is created in `dir`, or in the default temporary directory if `dir` is `None`.
+    #[cfg(feature = "mmap")]
+    pub fn new_mapped(
+        leaves: MmapSlice<F>,
+        leaf_len: usize,
+        cap_height: usize,
+        dir: Option<&Path>,
+    ) -> io::Result<Self> {
+        assert!(leaf_len > 0, "Leaves should not be empty");
+        let num_leaves = leaves.len() / leaf_len;
+        assert_eq!(
+            num_leaves * leaf_len,
+            leaves.len(),
+            "Leaves should have the same length"
+        );
+        let log2_leaves_len = log2_strict(num_leaves);
+        assert!(
+            cap_height <= log2_leaves_len,
+            "cap_height={} should be at most log2(leaves.len())={}",
+            cap_height,
+            log2_leaves_len
+        );
+
+        let num_digests = 2 * (num_leaves - (1 << cap_height));
+        // Any valid digest will do as placeholder, since they are all overwritten.
+        let mut digests = MmapSlice::new(num_digests, H::hash_no_pad(&[]), dir)?;
+
+        let len_cap = 1 << cap_height;
+        let mut cap = Vec::with_capacity(len_cap);
+
+        let digests_buf = {
+            let digests: &mut [H::Hash] = &mut digests;
+            // SAFETY: `MaybeUninit<T>` has the same layout as `T`, and only valid digests are
+            // written to the buffer.
+            unsafe {
+                slice::from_raw_parts_mut(
+                    digests.as_mut_ptr().cast::<MaybeUninit<H::Hash>>(),
+                    digests.len(),
+                )
+            }
+        };
+        let cap_buf = capacity_up_to_mut(&mut cap, len_cap);
+        let leaves_view = Leaves::Concatenated {
+            data: &leaves,
+            leaf_len,
+            num_leaves,
+        };
+        fill_digests_buf::<F, H>(digests_buf, cap_buf, leaves_view, cap_height);
+
+        unsafe {
+            // SAFETY: `fill_digests_buf` initialized the spare capacity of `cap` up to `len_cap`.
+            cap.set_len(len_cap);
+        }
+
+        Ok(Self {
+            leaves: Vec::new(),
+            digests: Vec::new(),
+            cap: MerkleCap(cap),
+            mapped: Some(Arc::new(MappedTree {
+                leaves,
+                leaf_len,
+                digests,
+            })),
+        })
+    }
+
+    /// Whether the leaves and digests are stored in memory-mapped files.
+    pub fn is_mapped(&self) -> bool {
+        #[cfg(feature = "mmap")]
+        if self.mapped.is_some() {
+            return true;
+        }
+        false
+    }
+
+    /// The number of leaves, regardless of where they are stored.
+    pub fn num_leaves(&self) -> usize {
+        #[cfg(feature = "mmap")]
+        if let Some(mapped) = &self.mapped {
+            return mapped.leaves.len() / mapped.leaf_len;
+        }
+        self.leaves.len()
+    }
+
+    /// The leaf at index `i`, regardless of where it is stored.
     pub fn get(&self, i: usize) -> &[F] {
+        #[cfg(feature = "mmap")]
+        if let Some(mapped) = &self.mapped {
+            return &mapped.leaves[i * mapped.leaf_len..(i + 1) * mapped.leaf_len];
+        }
         &self.leaves[i]
     }
 
+    /// The digests of the tree, regardless of where they are stored.
+    ///
+    /// They consist of `cap.len()` sub-trees, each corresponding to one element in `cap`. Each
+    /// subtree is contiguous and located at
+    /// `digests[digests.len() / cap.len() * i..digests.len() / cap.len() * (i + 1)]`.
+    /// Within each subtree, siblings are stored next to each other. The layout is,
+    /// left_child_subtree || left_child_digest || right_child_digest || right_child_subtree, where
+    /// left_child_digest and right_child_digest are H::Hash and left_child_subtree and
+    /// right_child_subtree recurse. Observe that the digest of a node is stored by its _parent_.
+    /// Consequently, the digests of the roots are not stored here (they can be found in `cap`).
+    pub fn all_digests(&self) -> &[H::Hash] {
+        #[cfg(feature = "mmap")]
+        if let Some(mapped) = &self.mapped {
+            return &mapped.digests;
+        }
+        &self.digests
+    }
+
     /// Create a Merkle proof from a leaf index.
     pub fn prove(&self, leaf_index: usize) -> MerkleProof<F, H> {
         let cap_height = log2_strict(self.cap.len());
-        let num_layers = log2_strict(self.leaves.len()) - cap_height;
+        let num_layers = log2_strict(self.num_leaves()) - cap_height;
         debug_assert_eq!(leaf_index >> (cap_height + num_layers), 0);
 
         let digest_tree = {
+            let digests = self.all_digests();
             let tree_index = leaf_index >> num_layers;
-            let tree_len = self.digests.len() >> cap_height;
-            &self.digests[tree_len * tree_index..tree_len * (tree_index + 1)]
+            let tree_len = digests.len() >> cap_height;
+            &digests[tree_len * tree_index..tree_len * (tree_index + 1)]
         };
 
         // Mask out high bits to get the index within the sub-tree.
@@ -222,6 +436,173 @@ impl<F: RichField, H: Hasher<F>> MerkleTree<F, H> {
 
         MerkleProof { siblings }
     }
+
+    /// Create a batch Merkle proof for several leaf indices, which may be given in any order and
+    /// may repeat. Each digest is included at most once, and only if it cannot be computed from
+    /// the leaves themselves.
+    pub fn prove_batch(&self, leaf_indices: &[usize]) -> BatchMerkleProof<F, H> {
+        let cap_height = log2_strict(self.cap.len());
+        let digests = self.all_digests();
+        let num_layers = log2_strict(self.num_leaves()) - cap_height;
+        let subtree_digests_len = digests.len() >> cap_height;
+
+        let mut nodes = leaf_indices.to_vec();
+        nodes.sort_unstable();
+        nodes.dedup();
+
+        // Follow the same order as `fold_batch_merkle_paths`.
+        let mut siblings = Vec::new();
+        for layer in 0..num_layers {
+            let subtree_layer_len = 1 << (num_layers - layer);
+            for (j, &i) in nodes.iter().enumerate() {
+                let sibling = i ^ 1;
+                let sibling_known = if i & 1 == 0 {
+                    nodes.get(j + 1) == Some(&sibling)
+                } else {
+                    j > 0 && nodes[j - 1] == sibling
+                };
+                if !sibling_known {
+                    let subtree_index = sibling / subtree_layer_len;
+                    siblings.push(
+                        digests[subtree_index * subtree_digests_len
+                            + node_digest_index(layer, sibling % subtree_layer_len)],
+                    );
+                }
+            }
+            nodes = nodes.iter().map(|&i| i >> 1).collect();
+            nodes.dedup();
+        }
+
+        BatchMerkleProof { siblings }
+    }
+
+    /// Replaces the leaf at `leaf_index`, and recomputes the digests on its path to the cap.
+    pub fn update_leaf(&mut self, leaf_index: usize, leaf: Vec<F>) {
+        self.update_leaves(vec![(leaf_index, leaf)]);
+    }
+
+    /// Replaces several leaves, given as `(leaf_index, leaf)` pairs, and recomputes the digests on
+    /// their paths to the cap. Each affected digest is only computed once, even if it lies on the
+    /// path of several updated leaves. If an index appears several times, its last leaf is kept.
+    /// The tree must be stored in memory.
+    pub fn update_leaves(&mut self, updates: Vec<(usize, Vec<F>)>) {
+        assert!(
+            !self.is_mapped(),
+            "Memory-mapped Merkle trees are read-only"
+        );
+        let cap_height = log2_strict(self.cap.len());
+        let num_layers = log2_strict(self.leaves.len()) - cap_height;
+        let subtree_digests_len = self.digests.len() >> cap_height;
+
+        let mut indices = Vec::with_capacity(updates.len());
+        for (leaf_index, leaf) in updates {
+            self.leaves[leaf_index] = leaf;
+            indices.push(leaf_index);
+        }
+        indices.sort_unstable();
+        indices.dedup();
+
+        let mut digests: Vec<H::Hash> = indices
+            .par_iter()
+            .map(|&i| H::hash_or_noop(&self.leaves[i]))
+            .collect();
+
+        // `indices` holds the indices, within their layer, of the nodes whose digests are in
+        // `digests`. Store these digests, then move up to their parents.
+        for layer in 0..num_layers {
+            let subtree_layer_len = 1 << (num_layers - layer);
+            let digest_index = |i: usize| {
+                let subtree_index = i / subtree_layer_len;
+                subtree_index * subtree_digests_len
+                    + node_digest_index(layer, i % subtree_layer_len)
+            };
+            for (&i, &digest) in indices.iter().zip(&digests) {
+                self.digests[digest_index(i)] = digest;
+            }
+
+            indices = indices.iter().map(|&i| i >> 1).collect();
+            indices.dedup();
+            digests = indices
+                .par_iter()
+                .map(|&i| {
+                    let left = self.digests[digest_index(2 * i)];
+                    let right = self.digests[digest_index(2 * i + 1)];
+                    H::two_to_one(left, right)
+                })
+                .collect();
+        }
+
+        for (&i, &digest) in indices.iter().zip(&digests) {
+            self.cap.0[i] = digest;
+        }
+    }
+
+    /// Appends `leaves` to the tree, keeping its cap height. The new number of leaves must be a
+    /// power of two. The tree is doubled as many times as needed; at each step only the digests of
+    /// the new half are computed, while those of the existing half are moved into place. The tree
+    /// must be stored in memory.
+    pub fn append(&mut self, mut leaves: Vec<Vec<F>>) {
+        assert!(
+            !self.is_mapped(),
+            "Memory-mapped Merkle trees are read-only"
+        );
+        let cap_height = log2_strict(self.cap.len());
+        let new_len = self.leaves.len() + leaves.len();
+        assert!(
+            new_len.is_power_of_two(),
+            "the number of leaves after appending should be a power of two, got {}",
+            new_len
+        );
+
+        while !leaves.is_empty() {
+            let right_leaves = leaves.drain(..self.leaves.len()).collect();
+            let right = Self::new(right_leaves, cap_height);
+            let left = core::mem::take(self);
+
+            // Subtree `j` of the doubled tree is made of subtrees `2j` and `2j + 1` of the
+            // concatenation of both halves, which become its left and right children.
+            let half_subtrees_digests_len = left.digests.len() >> cap_height;
+            let subtrees_digests: Vec<&[H::Hash]> = left
+                .digests
+                .chunks(half_subtrees_digests_len.max(1))
+                .chain(right.digests.chunks(half_subtrees_digests_len.max(1)))
+                .collect();
+            let half_cap: Vec<H::Hash> = left.cap.0.iter().chain(&right.cap.0).copied().collect();
+
+            let mut digests = Vec::with_capacity(2 * (left.digests.len() + left.cap.len()));
+            let mut cap = Vec::with_capacity(left.cap.len());
+            for j in 0..left.cap.len() {
+                let (left_digest, right_digest) = (half_cap[2 * j], half_cap[2 * j + 1]);
+                if half_subtrees_digests_len > 0 {
+                    digests.extend_from_slice(subtrees_digests[2 * j]);
+                }
+                digests.push(left_digest);
+                digests.push(right_digest);
+                if half_subtrees_digests_len > 0 {
+                    digests.extend_from_slice(subtrees_digests[2 * j + 1]);
+                }
+                cap.push(H::two_to_one(left_digest, right_digest));
+            }
+
+            let mut all_leaves = left.leaves;
+            all_leaves.extend(right.leaves);
+            *self = Self {
+                leaves: all_leaves,
+                digests,
+                cap: MerkleCap(cap),
+                #[cfg(feature = "mmap")]
+                mapped: None,
+            };
+        }
+    }
+}
+
+/// Returns the index, within the digests of a subtree, of the digest of node `node_index` in layer
+/// `layer`, where layer `0` holds the leaves. See `MerkleTree::all_digests` for the layout.
+fn node_digest_index(layer: usize, node_index: usize) -> usize {
+    let pair_index = node_index >> 1;
+    let siblings_index = (pair_index << (layer + 1)) + (1 << layer) - 1;
+    2 * siblings_index + (node_index & 1)
 }
 
 #[cfg(test)]
@@ -230,7 +611,10 @@ mod tests {
 
     use super::*;
     use crate::field::extension::Extendable;
-    use crate::hash::merkle_proofs::verify_merkle_proof_to_cap;
+    use crate::field::types::{Field, Sample};
+    use crate::hash::merkle_proofs::{
+        batch_merkle_proof_len, verify_batch_merkle_proof_to_cap, verify_merkle_proof_to_cap,
+    };
     use crate::plonk::config::{GenericConfig, PoseidonGoldilocksConfig};
 
     fn random_data<F: RichField>(n: usize, k: usize) -> Vec<Vec<F>> {
@@ -296,4 +680,156 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_prove_batch() -> Result<()> {
+        const D: usize = 2;
+        type C = PoseidonGoldilocksConfig;
+        type F = <C as GenericConfig<D>>::F;
+        type H = <C as GenericConfig<D>>::Hasher;
+
+        let log_n = 8;
+        let n = 1 << log_n;
+        let leaves = random_data::<F>(n, 7);
+        for cap_height in [0, 3, log_n] {
+            let tree = MerkleTree::<F, H>::new(leaves.clone(), cap_height);
+
+            // Unsorted, with a repeated index and a pair of siblings.
+            let indices = [77, 3, 200, 2, 77, 255];
+            let leaf_data: Vec<_> = indices.iter().map(|&i| leaves[i].clone()).collect();
+            let proof = tree.prove_batch(&indices);
+            assert_eq!(
+                proof.len(),
+                batch_merkle_proof_len(&indices, log_n - cap_height)
+            );
+            if cap_height < log_n {
+                assert!(proof.len() < 5 * (log_n - cap_height));
+            }
+            verify_batch_merkle_proof_to_cap(
+                leaf_data.clone(),
+                &indices,
+                log_n,
+                &tree.cap,
+                &proof,
+            )?;
+
+            let mut bad_leaf_data = leaf_data;
+            bad_leaf_data[2][0] += F::ONE;
+            assert!(verify_batch_merkle_proof_to_cap(
+                bad_leaf_data,
+                &indices,
+                log_n,
+                &tree.cap,
+                &proof
+            )
+            .is_err());
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_update_leaves() -> Result<()> {
+        const D: usize = 2;
+        type C = PoseidonGoldilocksConfig;
+        type F = <C as GenericConfig<D>>::F;
+        type H = <C as GenericConfig<D>>::Hasher;
+
+        let log_n = 8;
+        let n = 1 << log_n;
+        for cap_height in [0, 2, log_n] {
+            let mut leaves = random_data::<F>(n, 7);
+            let mut tree = MerkleTree::<F, H>::new(leaves.clone(), cap_height);
+
+            leaves[5] = F::rand_vec(7);
+            tree.update_leaf(5, leaves[5].clone());
+            assert_eq!(tree, MerkleTree::new(leaves.clone(), cap_height));
+
+            // Neighbouring leaves share most of their paths, and index 3 is updated twice.
+            let updates: Vec<_> = [3, 4, 200, 3, 255]
+                .into_iter()
+                .map(|i| (i, F::rand_vec(7)))
+                .collect();
+            for (i, leaf) in &updates {
+                leaves[*i] = leaf.clone();
+            }
+            tree.update_leaves(updates);
+            assert_eq!(tree, MerkleTree::new(leaves.clone(), cap_height));
+
+            for i in [3, 4, 5, 200, 255] {
+                verify_merkle_proof_to_cap(leaves[i].clone(), i, &tree.cap, &tree.prove(i))?;
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_append() -> Result<()> {
+        const D: usize = 2;
+        type C = PoseidonGoldilocksConfig;
+        type F = <C as GenericConfig<D>>::F;
+        type H = <C as GenericConfig<D>>::Hasher;
+
+        for cap_height in [0, 2] {
+            let leaves = random_data::<F>(1 << 6, 7);
+            let mut tree = MerkleTree::<F, H>::new(leaves[..4].to_vec(), cap_height);
+
+            tree.append(leaves[4..8].to_vec());
+            assert_eq!(tree, MerkleTree::new(leaves[..8].to_vec(), cap_height));
+
+            // Doubles the tree three times.
+            tree.append(leaves[8..].to_vec());
+            assert_eq!(tree, MerkleTree::new(leaves.clone(), cap_height));
+
+            for (i, leaf) in leaves.into_iter().enumerate() {
+                verify_merkle_proof_to_cap(leaf, i, &tree.cap, &tree.prove(i))?;
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_append_not_power_of_two() {
+        const D: usize = 2;
+        type C = PoseidonGoldilocksConfig;
+        type F = <C as GenericConfig<D>>::F;
+
+        let mut tree =
+            MerkleTree::<F, <C as GenericConfig<D>>::Hasher>::new(random_data::<F>(4, 7), 0);
+        tree.append(random_data::<F>(3, 7));
+    }
+
+    #[cfg(feature = "mmap")]
+    #[test]
+    fn test_mapped_merkle_tree() -> Result<()> {
+        const D: usize = 2;
+        type C = PoseidonGoldilocksConfig;
+        type F = <C as GenericConfig<D>>::F;
+        type H = <C as GenericConfig<D>>::Hasher;
+
+        let leaf_len = 7;
+        for cap_height in [0, 2, 6] {
+            let leaves = random_data::<F>(1 << 6, leaf_len);
+            let mut mapped_leaves = MmapSlice::new(leaves.len() * leaf_len, F::ZERO, None)?;
+            mapped_leaves.copy_from_slice(&leaves.concat());
+
+            let tree = MerkleTree::<F, H>::new(leaves.clone(), cap_height);
+            let mapped_tree =
+                MerkleTree::<F, H>::new_mapped(mapped_leaves, leaf_len, cap_height, None)?;
+            assert!(mapped_tree.is_mapped());
+            assert!(mapped_tree.leaves.is_empty() && mapped_tree.digests.is_empty());
+            assert_eq!(mapped_tree, tree);
+
+            for (i, leaf) in leaves.into_iter().enumerate() {
+                assert_eq!(mapped_tree.get(i), leaf);
+                assert_eq!(mapped_tree.prove(i), tree.prove(i));
+                verify_merkle_proof_to_cap(leaf, i, &mapped_tree.cap, &mapped_tree.prove(i))?;
+            }
+            assert_eq!(
+                mapped_tree.prove_batch(&[3, 17, 18]),
+                tree.prove_batch(&[3, 17, 18])
+            );
+        }
+        Ok(())
+    }
 }
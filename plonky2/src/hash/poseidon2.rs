@@ -0,0 +1,526 @@
+//! Implementation of the Poseidon2 permutation, as described in
+//! <https://eprint.iacr.org/2023/323.pdf>
+
+#[cfg(not(feature = "std"))]
+use alloc::vec;
+use core::fmt::Debug;
+
+use crate::field::extension::{Extendable, FieldExtension};
+use crate::field::types::{Field, PrimeField64};
+use crate::gates::poseidon2::Poseidon2Gate;
+use crate::hash::hash_types::{HashOut, RichField};
+use crate::hash::hashing::{compress, hash_n_to_hash_no_pad, PlonkyPermutation};
+use crate::iop::ext_target::ExtensionTarget;
+use crate::iop::target::{BoolTarget, Target};
+use crate::plonk::circuit_builder::CircuitBuilder;
+use crate::plonk::config::{AlgebraicHasher, Hasher};
+
+/// The number of state elements which are never absorbed into or squeezed from.
+pub const SPONGE_CAPACITY: usize = 4;
+
+// As for Poseidon, these are given by the round number calculation of the paper for s-box x^7,
+// and are the same for all of the supported widths.
+//
+// NB: Changing any of these values will require regenerating all of the round constants.
+pub const HALF_N_FULL_ROUNDS: usize = 4;
+pub(crate) const N_FULL_ROUNDS_TOTAL: usize = 2 * HALF_N_FULL_ROUNDS;
+pub const N_PARTIAL_ROUNDS: usize = 22;
+pub const N_ROUNDS: usize = N_FULL_ROUNDS_TOTAL + N_PARTIAL_ROUNDS;
+
+/// The 4x4 MDS matrix from which the external linear layer is built.
+const M4: [[u64; 4]; 4] = [[5, 7, 1, 3], [4, 6, 1, 1], [1, 3, 5, 7], [1, 1, 4, 6]];
+
+/// Multiplies `x` by `M4`, using the addition chain given in the paper.
+#[inline(always)]
+fn apply_m4<F: Field>(x: [F; 4]) -> [F; 4] {
+    let t0 = x[0] + x[1];
+    let t1 = x[2] + x[3];
+    let t2 = x[1].double() + t1;
+    let t3 = x[3].double() + t0;
+    let t4 = t1.double().double() + t3;
+    let t5 = t0.double().double() + t2;
+    let t6 = t3 + t5;
+    let t7 = t2 + t4;
+    [t6, t5, t7, t4]
+}
+
+/// Recursive version of `apply_m4`.
+fn apply_m4_circuit<F: RichField + Extendable<D>, const D: usize>(
+    builder: &mut CircuitBuilder<F, D>,
+    x: [ExtensionTarget<D>; 4],
+) -> [ExtensionTarget<D>; 4] {
+    let two = F::TWO;
+    let four = F::from_canonical_u64(4);
+    let t0 = builder.add_extension(x[0], x[1]);
+    let t1 = builder.add_extension(x[2], x[3]);
+    let t2 = builder.mul_const_add_extension(two, x[1], t1);
+    let t3 = builder.mul_const_add_extension(two, x[3], t0);
+    let t4 = builder.mul_const_add_extension(four, t1, t3);
+    let t5 = builder.mul_const_add_extension(four, t0, t2);
+    let t6 = builder.add_extension(t3, t5);
+    let t7 = builder.add_extension(t2, t4);
+    [t6, t5, t7, t4]
+}
+
+/// The Poseidon2 permutation over a state of `WIDTH` elements, which must be a multiple of 4.
+///
+/// The external (full round) linear layer is the matrix `circ(2 * M4, M4, ..., M4)`, and the
+/// internal (partial round) linear layer is `J + diag(INTERNAL_MATRIX_DIAG_M_1)`, where `J` is the
+/// all-ones matrix. The permutation starts with an external linear layer, followed by
+/// `HALF_N_FULL_ROUNDS` full rounds, `N_PARTIAL_ROUNDS` partial rounds and `HALF_N_FULL_ROUNDS`
+/// full rounds again.
+pub trait Poseidon2<const WIDTH: usize>: PrimeField64 {
+    /// The round constants of the full rounds, the first `HALF_N_FULL_ROUNDS` of which are used
+    /// before the partial rounds.
+    const EXTERNAL_ROUND_CONSTANTS: [[u64; WIDTH]; N_FULL_ROUNDS_TOTAL];
+
+    /// The round constants of the partial rounds, which are only added to the first element.
+    const INTERNAL_ROUND_CONSTANTS: [u64; N_PARTIAL_ROUNDS];
+
+    /// The diagonal of the internal matrix, minus one.
+    const INTERNAL_MATRIX_DIAG_M_1: [u64; WIDTH];
+
+    #[inline(always)]
+    fn external_linear_layer(state: &mut [Self; WIDTH]) {
+        // The entries of `M4` are small, so we can accumulate both matrix multiplications in `u128`
+        // and reduce once at the end.
+        let mut chunk_products = [0u128; WIDTH];
+        for c in (0..WIDTH).step_by(4) {
+            for i in 0..4 {
+                for j in 0..4 {
+                    chunk_products[c + i] +=
+                        M4[i][j] as u128 * state[c + j].to_noncanonical_u64() as u128;
+                }
+            }
+        }
+
+        let mut sums = [0u128; 4];
+        for c in (0..WIDTH).step_by(4) {
+            for i in 0..4 {
+                sums[i] += chunk_products[c + i];
+            }
+        }
+
+        for i in 0..WIDTH {
+            state[i] = Self::from_noncanonical_u128(chunk_products[i] + sums[i % 4]);
+        }
+    }
+
+    /// Same as `external_linear_layer` for field extensions of `Self`.
+    fn external_linear_layer_field<F: FieldExtension<D, BaseField = Self>, const D: usize>(
+        state: &mut [F; WIDTH],
+    ) {
+        for c in (0..WIDTH).step_by(4) {
+            let chunk = apply_m4([state[c], state[c + 1], state[c + 2], state[c + 3]]);
+            state[c..c + 4].copy_from_slice(&chunk);
+        }
+
+        let mut sums = [F::ZERO; 4];
+        for c in (0..WIDTH).step_by(4) {
+            for i in 0..4 {
+                sums[i] += state[c + i];
+            }
+        }
+
+        for i in 0..WIDTH {
+            state[i] += sums[i % 4];
+        }
+    }
+
+    /// Recursive version of `external_linear_layer`.
+    fn external_linear_layer_circuit<const D: usize>(
+        builder: &mut CircuitBuilder<Self, D>,
+        state: &mut [ExtensionTarget<D>; WIDTH],
+    ) where
+        Self: RichField + Extendable<D>,
+    {
+        for c in (0..WIDTH).step_by(4) {
+            let chunk = apply_m4_circuit(
+                builder,
+                [state[c], state[c + 1], state[c + 2], state[c + 3]],
+            );
+            state[c..c + 4].copy_from_slice(&chunk);
+        }
+
+        let sums: [ExtensionTarget<D>; 4] = core::array::from_fn(|i| {
+            builder.add_many_extension((0..WIDTH).step_by(4).map(|c| state[c + i]))
+        });
+
+        for i in 0..WIDTH {
+            state[i] = builder.add_extension(state[i], sums[i % 4]);
+        }
+    }
+
+    #[inline(always)]
+    fn internal_linear_layer(state: &mut [Self; WIDTH]) {
+        // The diagonal entries are arbitrary field elements, so each product is reduced before the
+        // sum is added.
+        let sum = Self::from_noncanonical_u128(
+            state.iter().map(|x| x.to_noncanonical_u64() as u128).sum(),
+        );
+        for i in 0..WIDTH {
+            let product = <Self as Poseidon2<WIDTH>>::INTERNAL_MATRIX_DIAG_M_1[i] as u128
+                * state[i].to_noncanonical_u64() as u128;
+            state[i] = Self::from_noncanonical_u128(product) + sum;
+        }
+    }
+
+    /// Same as `internal_linear_layer` for field extensions of `Self`.
+    fn internal_linear_layer_field<F: FieldExtension<D, BaseField = Self>, const D: usize>(
+        state: &mut [F; WIDTH],
+    ) {
+        let sum: F = state.iter().copied().sum();
+        for i in 0..WIDTH {
+            state[i] = state[i]
+                * F::from_canonical_u64(<Self as Poseidon2<WIDTH>>::INTERNAL_MATRIX_DIAG_M_1[i])
+                + sum;
+        }
+    }
+
+    /// Recursive version of `internal_linear_layer`.
+    fn internal_linear_layer_circuit<const D: usize>(
+        builder: &mut CircuitBuilder<Self, D>,
+        state: &mut [ExtensionTarget<D>; WIDTH],
+    ) where
+        Self: RichField + Extendable<D>,
+    {
+        let sum = builder.add_many_extension(state.iter());
+        for i in 0..WIDTH {
+            let c =
+                Self::from_canonical_u64(<Self as Poseidon2<WIDTH>>::INTERNAL_MATRIX_DIAG_M_1[i]);
+            state[i] = builder.mul_const_add_extension(c, state[i], sum);
+        }
+    }
+
+    #[inline(always)]
+    fn external_constant_layer(state: &mut [Self; WIDTH], round: usize) {
+        for i in 0..WIDTH {
+            state[i] += Self::from_canonical_u64(
+                <Self as Poseidon2<WIDTH>>::EXTERNAL_ROUND_CONSTANTS[round][i],
+            );
+        }
+    }
+
+    /// Same as `external_constant_layer` for field extensions of `Self`.
+    fn external_constant_layer_field<F: FieldExtension<D, BaseField = Self>, const D: usize>(
+        state: &mut [F; WIDTH],
+        round: usize,
+    ) {
+        for i in 0..WIDTH {
+            state[i] += F::from_canonical_u64(
+                <Self as Poseidon2<WIDTH>>::EXTERNAL_ROUND_CONSTANTS[round][i],
+            );
+        }
+    }
+
+    /// Recursive version of `external_constant_layer`.
+    fn external_constant_layer_circuit<const D: usize>(
+        builder: &mut CircuitBuilder<Self, D>,
+        state: &mut [ExtensionTarget<D>; WIDTH],
+        round: usize,
+    ) where
+        Self: RichField + Extendable<D>,
+    {
+        for i in 0..WIDTH {
+            let c = Self::from_canonical_u64(
+                <Self as Poseidon2<WIDTH>>::EXTERNAL_ROUND_CONSTANTS[round][i],
+            );
+            state[i] = builder.add_const_extension(state[i], c);
+        }
+    }
+
+    #[inline(always)]
+    fn sbox_monomial<F: FieldExtension<D, BaseField = Self>, const D: usize>(x: F) -> F {
+        // x |--> x^7
+        let x2 = x.square();
+        let x4 = x2.square();
+        let x3 = x * x2;
+        x3 * x4
+    }
+
+    /// Recursive version of `sbox_monomial`.
+    fn sbox_monomial_circuit<const D: usize>(
+        builder: &mut CircuitBuilder<Self, D>,
+        x: ExtensionTarget<D>,
+    ) -> ExtensionTarget<D>
+    where
+        Self: RichField + Extendable<D>,
+    {
+        // x |--> x^7
+        builder.exp_u64_extension(x, 7)
+    }
+
+    /// Same as `sbox_layer` for field extensions of `Self`.
+    fn sbox_layer_field<F: FieldExtension<D, BaseField = Self>, const D: usize>(
+        state: &mut [F; WIDTH],
+    ) {
+        for x in state.iter_mut() {
+            *x = Self::sbox_monomial(*x);
+        }
+    }
+
+    /// Recursive version of `sbox_layer`.
+    fn sbox_layer_circuit<const D: usize>(
+        builder: &mut CircuitBuilder<Self, D>,
+        state: &mut [ExtensionTarget<D>; WIDTH],
+    ) where
+        Self: RichField + Extendable<D>,
+    {
+        for x in state.iter_mut() {
+            *x = <Self as Poseidon2<WIDTH>>::sbox_monomial_circuit(builder, *x);
+        }
+    }
+
+    /// Applies `HALF_N_FULL_ROUNDS` full rounds, starting with the full round `*round_ctr`.
+    #[inline]
+    fn full_rounds(state: &mut [Self; WIDTH], round_ctr: &mut usize) {
+        for _ in 0..HALF_N_FULL_ROUNDS {
+            Self::external_constant_layer(state, *round_ctr);
+            Self::sbox_layer_field(state);
+            Self::external_linear_layer(state);
+            *round_ctr += 1;
+        }
+    }
+
+    #[inline]
+    fn partial_rounds(state: &mut [Self; WIDTH]) {
+        for r in 0..N_PARTIAL_ROUNDS {
+            state[0] +=
+                Self::from_canonical_u64(<Self as Poseidon2<WIDTH>>::INTERNAL_ROUND_CONSTANTS[r]);
+            state[0] = Self::sbox_monomial(state[0]);
+            Self::internal_linear_layer(state);
+        }
+    }
+
+    #[inline]
+    fn poseidon2(input: [Self; WIDTH]) -> [Self; WIDTH] {
+        let mut state = input;
+        let mut round_ctr = 0;
+
+        Self::external_linear_layer(&mut state);
+        Self::full_rounds(&mut state, &mut round_ctr);
+        Self::partial_rounds(&mut state);
+        Self::full_rounds(&mut state, &mut round_ctr);
+        debug_assert_eq!(round_ctr, N_FULL_ROUNDS_TOTAL);
+
+        state
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Poseidon2Permutation<T, const WIDTH: usize> {
+    state: [T; WIDTH],
+}
+
+impl<T: Copy + Default, const WIDTH: usize> Default for Poseidon2Permutation<T, WIDTH> {
+    fn default() -> Self {
+        Self {
+            state: [T::default(); WIDTH],
+        }
+    }
+}
+
+impl<T: Eq, const WIDTH: usize> Eq for Poseidon2Permutation<T, WIDTH> {}
+
+impl<T, const WIDTH: usize> AsRef<[T]> for Poseidon2Permutation<T, WIDTH> {
+    fn as_ref(&self) -> &[T] {
+        &self.state
+    }
+}
+
+trait Permuter2<const WIDTH: usize>: Sized {
+    fn permute(input: [Self; WIDTH]) -> [Self; WIDTH];
+}
+
+impl<F: Poseidon2<WIDTH>, const WIDTH: usize> Permuter2<WIDTH> for F {
+    fn permute(input: [Self; WIDTH]) -> [Self; WIDTH] {
+        <F as Poseidon2<WIDTH>>::poseidon2(input)
+    }
+}
+
+impl<const WIDTH: usize> Permuter2<WIDTH> for Target {
+    fn permute(_input: [Self; WIDTH]) -> [Self; WIDTH] {
+        panic!("Call `permute_swapped()` instead of `permute()`");
+    }
+}
+
+impl<T: Copy + Debug + Default + Eq + Permuter2<WIDTH> + Send + Sync, const WIDTH: usize>
+    PlonkyPermutation<T> for Poseidon2Permutation<T, WIDTH>
+{
+    const RATE: usize = WIDTH - SPONGE_CAPACITY;
+    const WIDTH: usize = WIDTH;
+
+    fn new<I: IntoIterator<Item = T>>(elts: I) -> Self {
+        let mut perm = Self::default();
+        perm.set_from_iter(elts, 0);
+        perm
+    }
+
+    fn set_elt(&mut self, elt: T, idx: usize) {
+        self.state[idx] = elt;
+    }
+
+    fn set_from_slice(&mut self, elts: &[T], start_idx: usize) {
+        let begin = start_idx;
+        let end = start_idx + elts.len();
+        self.state[begin..end].copy_from_slice(elts);
+    }
+
+    fn set_from_iter<I: IntoIterator<Item = T>>(&mut self, elts: I, start_idx: usize) {
+        for (s, e) in self.state[start_idx..].iter_mut().zip(elts) {
+            *s = e;
+        }
+    }
+
+    fn permute(&mut self) {
+        self.state = T::permute(self.state);
+    }
+
+    fn squeeze(&self) -> &[T] {
+        &self.state[..Self::RATE]
+    }
+}
+
+/// Poseidon2 hash function, with a sponge of `WIDTH` elements. In-circuit hashing uses a
+/// `Poseidon2Gate` of the same width, so the default width of 12 is the one which fits in
+/// `CircuitConfig::standard_recursion_config`.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct Poseidon2Hash<const WIDTH: usize = 12>;
+
+impl<F: RichField + Poseidon2<WIDTH>, const WIDTH: usize> Hasher<F> for Poseidon2Hash<WIDTH> {
+    const HASH_SIZE: usize = 4 * 8;
+    type Hash = HashOut<F>;
+    type Permutation = Poseidon2Permutation<F, WIDTH>;
+
+    fn hash_no_pad(input: &[F]) -> Self::Hash {
+        hash_n_to_hash_no_pad::<F, Self::Permutation>(input)
+    }
+
+    fn two_to_one(left: Self::Hash, right: Self::Hash) -> Self::Hash {
+        compress::<F, Self::Permutation>(left, right)
+    }
+}
+
+impl<F: RichField + Poseidon2<WIDTH>, const WIDTH: usize> AlgebraicHasher<F>
+    for Poseidon2Hash<WIDTH>
+{
+    type AlgebraicPermutation = Poseidon2Permutation<Target, WIDTH>;
+
+    fn permute_swapped<const D: usize>(
+        inputs: Self::AlgebraicPermutation,
+        swap: BoolTarget,
+        builder: &mut CircuitBuilder<F, D>,
+    ) -> Self::AlgebraicPermutation
+    where
+        F: RichField + Extendable<D>,
+    {
+        let gate_type = Poseidon2Gate::<F, D, WIDTH>::new();
+        let gate = builder.add_gate(gate_type, vec![]);
+
+        let swap_wire = Poseidon2Gate::<F, D, WIDTH>::WIRE_SWAP;
+        let swap_wire = Target::wire(gate, swap_wire);
+        builder.connect(swap.target, swap_wire);
+
+        // Route input wires.
+        let inputs = inputs.as_ref();
+        for i in 0..WIDTH {
+            let in_wire = Poseidon2Gate::<F, D, WIDTH>::wire_input(i);
+            let in_wire = Target::wire(gate, in_wire);
+            builder.connect(inputs[i], in_wire);
+        }
+
+        // Collect output wires.
+        Self::AlgebraicPermutation::new(
+            (0..WIDTH).map(|i| Target::wire(gate, Poseidon2Gate::<F, D, WIDTH>::wire_output(i))),
+        )
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod test_helpers {
+    #[cfg(not(feature = "std"))]
+    use alloc::vec::Vec;
+
+    use super::*;
+
+    pub(crate) fn check_test_vectors<F, const WIDTH: usize>(
+        test_vectors: Vec<([u64; WIDTH], [u64; WIDTH])>,
+    ) where
+        F: Poseidon2<WIDTH>,
+    {
+        for (input_, expected_output_) in test_vectors.into_iter() {
+            let input = input_.map(F::from_canonical_u64);
+            let output = F::poseidon2(input);
+            for i in 0..WIDTH {
+                let ex_output = F::from_canonical_u64(expected_output_[i]);
+                assert_eq!(output[i], ex_output);
+            }
+        }
+    }
+
+    /// Checks that the permutation agrees with the same rounds computed over field extensions,
+    /// which is how they are evaluated by `Poseidon2Gate`.
+    pub(crate) fn check_consistency<F, const WIDTH: usize>()
+    where
+        F: Poseidon2<WIDTH>,
+    {
+        let input: [F; WIDTH] = core::array::from_fn(|i| F::from_canonical_u64(i as u64));
+        let output = F::poseidon2(input);
+
+        let mut state = input;
+        F::external_linear_layer_field(&mut state);
+        for r in 0..N_FULL_ROUNDS_TOTAL {
+            if r == HALF_N_FULL_ROUNDS {
+                for i in 0..N_PARTIAL_ROUNDS {
+                    state[0] += F::from_canonical_u64(F::INTERNAL_ROUND_CONSTANTS[i]);
+                    state[0] = F::sbox_monomial(state[0]);
+                    F::internal_linear_layer_field(&mut state);
+                }
+            }
+            F::external_constant_layer_field(&mut state, r);
+            F::sbox_layer_field(&mut state);
+            F::external_linear_layer_field(&mut state);
+        }
+
+        assert_eq!(output, state);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+
+    use crate::field::types::Sample;
+    use crate::hash::poseidon2::Poseidon2Hash;
+    use crate::iop::witness::{PartialWitness, WitnessWrite};
+    use crate::plonk::circuit_builder::CircuitBuilder;
+    use crate::plonk::circuit_data::CircuitConfig;
+    use crate::plonk::config::{GenericConfig, Hasher, Poseidon2GoldilocksConfig};
+
+    #[test]
+    fn test_poseidon2_hash_circuit() -> Result<()> {
+        const D: usize = 2;
+        type C = Poseidon2GoldilocksConfig;
+        type F = <C as GenericConfig<D>>::F;
+        type H = Poseidon2Hash;
+
+        let config = CircuitConfig::standard_recursion_config();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+        let mut pw = PartialWitness::new();
+
+        // The second input needs several absorptions.
+        for len in [3, 21] {
+            let inputs = F::rand_vec(len);
+            let input_targets = builder.add_virtual_targets(len);
+            pw.set_target_arr(&input_targets, &inputs);
+            let hash = builder.hash_n_to_hash_no_pad::<H>(input_targets);
+            let expected = builder.constant_hash(H::hash_no_pad(&inputs));
+            builder.connect_hashes(hash, expected);
+        }
+
+        let data = builder.build::<C>();
+        let proof = data.prove(pw)?;
+        data.verify(proof)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,483 @@
+//! Implementations of Poseidon2 over the Goldilocks field of widths 8, 12 and 16.
+//!
+//! These are the reference Goldilocks instances of the Poseidon2 paper, as implemented by
+//! HorizenLabs and Plonky3, and they agree with the test vectors published with those
+//! implementations.
+//!
+//! The round constants are generated with the Grain LFSR used by the Poseidon reference
+//! implementation, initialised with the parameters `(field = 1, sbox = 0, n = 64, t = WIDTH,
+//! R_F = 8, R_P = 22)`. They are consumed in round order: `WIDTH` constants for each of the first
+//! four full rounds, one for each of the partial rounds, then `WIDTH` for each of the last four
+//! full rounds. Rejection sampling discards any 64-bit value which is not below the field order.
+//!
+//! The internal matrix diagonals are random field elements such that, for `M_I` the internal
+//! matrix of width `t`, the characteristic polynomial of `M_I^k` is irreducible for every `k` in
+//! `1..=2t`, which rules out the invariant subspace trails discussed in the Poseidon2 paper. Both
+//! derivations are checked by the tests of this module.
+
+use crate::field::goldilocks_field::GoldilocksField;
+use crate::hash::poseidon2::{Poseidon2, N_FULL_ROUNDS_TOTAL, N_PARTIAL_ROUNDS};
+
+#[rustfmt::skip]
+impl Poseidon2<8> for GoldilocksField {
+    const EXTERNAL_ROUND_CONSTANTS: [[u64; 8]; N_FULL_ROUNDS_TOTAL] = [
+        [0xdd5743e7f2a5a5d9, 0xcb3a864e58ada44b, 0xffa2449ed32f8cdc, 0x42025f65d6bd13ee,
+         0x7889175e25506323, 0x34b98bb03d24b737, 0xbdcc535ecc4faa2a, 0x5b20ad869fc0d033],
+        [0xf1dda5b9259dfcb4, 0x27515210be112d59, 0x4227d1718c766c3f, 0x26d333161a5bd794,
+         0x49b938957bf4b026, 0x4a56b5938b213669, 0x1120426b48c8353d, 0x6b323c3f10a56cad],
+        [0xce57d6245ddca6b2, 0xb1fc8d402bba1eb1, 0xb5c5096ca959bd04, 0x6db55cd306d31f7f,
+         0xc49d293a81cb9641, 0x1ce55a4fe979719f, 0xa92e60a9d178a4d1, 0x002cc64973bcfd8c],
+        [0xcea721cce82fb11b, 0xe5b55eb8098ece81, 0x4e30525c6f1ddd66, 0x43c6702827070987,
+         0xaca68430a7b5762a, 0x3674238634df9c93, 0x88cee1c825e33433, 0xde99ae8d74b57176],
+        [0x014ef1197d341346, 0x9725e20825d07394, 0xfdb25aef2c5bae3b, 0xbe5402dc598c971e,
+         0x93a5711f04cdca3d, 0xc45a9a5b2f8fb97b, 0xfe8946a924933545, 0x2af997a27369091c],
+        [0xaa62c88e0b294011, 0x058eb9d810ce9f74, 0xb3cb23eced349ae4, 0xa3648177a77b4a84,
+         0x43153d905992d95d, 0xf4e2a97cda44aa4b, 0x5baa2702b908682f, 0x082923bdf4f750d1],
+        [0x98ae09a325893803, 0xf8a6475077968838, 0xceb0735bf00b2c5f, 0x0a1a5d953888e072,
+         0x2fcb190489f94475, 0xb5be06270dec69fc, 0x739cb934b09acf8b, 0x537750b75ec7f25b],
+        [0xe9dd318bae1f3961, 0xf7462137299efe1a, 0xb1f6b8eee9adb940, 0xbdebcc8a809dfe6b,
+         0x40fc1f791b178113, 0x3ac1c3362d014864, 0x9a016184bdb8aeba, 0x95f2394459fbc25e],
+    ];
+
+    const INTERNAL_ROUND_CONSTANTS: [u64; N_PARTIAL_ROUNDS] = [
+        0x488897d85ff51f56, 0x1140737ccb162218, 0xa7eeb9215866ed35, 0x9bd2976fee49fcc9,
+        0xc0c8f0de580a3fcc, 0x4fb2dae6ee8fc793, 0x343a89f35f37395b, 0x223b525a77ca72c8,
+        0x56ccb62574aaa918, 0xc4d507d8027af9ed, 0xa080673cf0b7e95c, 0xf0184884eb70dcf8,
+        0x044f10b0cb3d5c69, 0xe9e3f7993938f186, 0x1b761c80e772f459, 0x606cec607a1b5fac,
+        0x14a0c2e1d45f03cd, 0x4eace8855398574f, 0xf905ca7103eff3e6, 0xf8c8f8d20862c059,
+        0xb524fe8bdd678e5a, 0xfbb7865901a1ec41,
+    ];
+
+    const INTERNAL_MATRIX_DIAG_M_1: [u64; 8] = [
+        0xa98811a1fed4e3a5, 0x1cc48b54f377e2a0, 0xe40cd4f6c5609a26, 0x11de79ebca97a4a3,
+        0x9177c73d8b7e929c, 0x2a6fe8085797e791, 0x3de6e93329f8d5ad, 0x3f7af9125da962fe,
+    ];
+
+    #[cfg(all(target_arch = "x86_64", target_feature = "avx2"))]
+    #[inline]
+    fn full_rounds(state: &mut [Self; 8], round_ctr: &mut usize) {
+        unsafe {
+            crate::hash::arch::x86_64::poseidon2_goldilocks_avx2::full_rounds(state, round_ctr);
+        }
+    }
+}
+
+#[rustfmt::skip]
+impl Poseidon2<12> for GoldilocksField {
+    const EXTERNAL_ROUND_CONSTANTS: [[u64; 12]; N_FULL_ROUNDS_TOTAL] = [
+        [0x13dcf33aba214f46, 0x30b3b654a1da6d83, 0x1fc634ada6159b56, 0x937459964dc03466,
+         0xedd2ef2ca7949924, 0xede9affde0e22f68, 0x8515b9d6bac9282d, 0x6b5c07b4e9e900d8,
+         0x1ec66368838c8a08, 0x9042367d80d1fbab, 0x400283564a3c3799, 0x4a00be0466bca75e],
+        [0x7913beee58e3817f, 0xf545e88532237d90, 0x22f8cb8736042005, 0x6f04990e247a2623,
+         0xfe22e87ba37c38cd, 0xd20e32c85ffe2815, 0x117227674048fe73, 0x4e9fb7ea98a6b145,
+         0xe0866c232b8af08b, 0x00bbc77916884964, 0x7031c0fb990d7116, 0x240a9e87cf35108f],
+        [0x2e6363a5a12244b3, 0x5e1c3787d1b5011c, 0x4132660e2a196e8b, 0x3a013b648d3d4327,
+         0xf79839f49888ea43, 0xfe85658ebafe1439, 0xb6889825a14240bd, 0x578453605541382b,
+         0x4508cda8f6b63ce9, 0x9c3ef35848684c91, 0x0812bde23c87178c, 0xfe49638f7f722c14],
+        [0x8e3f688ce885cbf5, 0xb8e110acf746a87d, 0xb4b2e8973a6dabef, 0x9e714c5da3d462ec,
+         0x6438f9033d3d0c15, 0x24312f7cf1a27199, 0x23f843bb47acbf71, 0x9183f11a34be9f01,
+         0x839062fbb9d45dbf, 0x24b56e7e6c2e43fa, 0xe1683da61c962a72, 0xa95c63971a19bfa7],
+        [0xc68be7c94882a24d, 0xaf996d5d5cdaedd9, 0x9717f025e7daf6a5, 0x6436679e6e7216f4,
+         0x8a223d99047af267, 0xbb512e35a133ba9a, 0xfbbf44097671aa03, 0xf04058ebf6811e61,
+         0x5cca84703fac7ffb, 0x9b55c7945de6469f, 0x8e05bf09808e934f, 0x2ea900de876307d7],
+        [0x7748fff2b38dfb89, 0x6b99a676dd3b5d81, 0xac4bb7c627cf7c13, 0xadb6ebe5e9e2f5ba,
+         0x2d33378cafa24ae3, 0x1e5b73807543f8c2, 0x09208814bfebb10f, 0x782e64b6bb5b93dd,
+         0xadd5a48eac90b50f, 0xadd4c54c736ea4b1, 0xd58dbb86ed817fd8, 0x6d5ed1a533f34ddd],
+        [0x28686aa3e36b7cb9, 0x591abd3476689f36, 0x047d766678f13875, 0xa2a11112625f5b49,
+         0x21fd10a3f8304958, 0xf9b40711443b0280, 0xd2697eb8b2bde88e, 0x3493790b51731b3f,
+         0x11caf9dd73764023, 0x7acfb8f72878164e, 0x744ec4db23cefc26, 0x1e00e58f422c6340],
+        [0x21dd28d906a62dda, 0xf32a46ab5f465b5f, 0xbfce13201f3f7e6b, 0xf30d2e7adb5304e2,
+         0xecdf4ee4abad48e9, 0xf94e82182d395019, 0x4ee52e3744d887c5, 0xa1341c7cac0083b2,
+         0x2302fb26c30c834a, 0xaea3c587273bf7d3, 0xf798e24961823ec7, 0x962deba3e9a2cd94],
+    ];
+
+    const INTERNAL_ROUND_CONSTANTS: [u64; N_PARTIAL_ROUNDS] = [
+        0x4adf842aa75d4316, 0xf8fbb871aa4ab4eb, 0x68e85b6eb2dd6aeb, 0x07a0b06b2d270380,
+        0xd94e0228bd282de4, 0x8bdd91d3250c5278, 0x209c68b88bba778f, 0xb5e18cdab77f3877,
+        0xb296a3e808da93fa, 0x8370ecbda11a327e, 0x3f9075283775dad8, 0xb78095bb23c6aa84,
+        0x3f36b9fe72ad4e5f, 0x69bc96780b10b553, 0x3f1d341f2eb7b881, 0x4e939e9815838818,
+        0xda366b3ae2a31604, 0xbc89db1e7287d509, 0x6102f411f9ef5659, 0x58725c5e7ac1f0ab,
+        0x0df5856c798883e7, 0xf7bb62a8da4c961b,
+    ];
+
+    const INTERNAL_MATRIX_DIAG_M_1: [u64; 12] = [
+        0xc3b6c08e23ba9300, 0xd84b5de94a324fb6, 0x0d0c371c5b35b84f, 0x7964f570e7188037,
+        0x5daf18bbd996604b, 0x6743bc47b9595257, 0x5528b9362c59bb70, 0xac45e25b7127b68b,
+        0xa2077d7dfbb606b5, 0xf3faac6faee378ae, 0x0c6388b51545e883, 0xd27dbb6944917b60,
+    ];
+
+    #[cfg(all(target_arch = "x86_64", target_feature = "avx2"))]
+    #[inline]
+    fn full_rounds(state: &mut [Self; 12], round_ctr: &mut usize) {
+        unsafe {
+            crate::hash::arch::x86_64::poseidon2_goldilocks_avx2::full_rounds(state, round_ctr);
+        }
+    }
+}
+
+#[rustfmt::skip]
+impl Poseidon2<16> for GoldilocksField {
+    const EXTERNAL_ROUND_CONSTANTS: [[u64; 16]; N_FULL_ROUNDS_TOTAL] = [
+        [0x15ebea3fc73397c3, 0xd73cd9fbfe8e275c, 0x8c096bfce77f6c26, 0x4e128f68b53d8fea,
+         0x29b779a36b2763f6, 0xfe2adc6fb65acd08, 0x8d2520e725ad0955, 0x1c2392b214624d2a,
+         0x37482118206dcc6e, 0x2f829bed19be019a, 0x2fe298cb6f8159b0, 0x2bbad982deccdbbf,
+         0xbad568b8cc60a81e, 0xb86a814265baad10, 0xbec2005513b3acb3, 0x6bf89b59a07c2a94],
+        [0xa25deeb835e230f5, 0x3c5bad8512b8b12a, 0x7230f73c3cb7a4f2, 0xa70c87f095c74d0f,
+         0x6b7606b830bb2e80, 0x6cd467cfc4f24274, 0xfeed794df42a9b0a, 0x8cf7cf6163b7dbd3,
+         0x9a6e9dda597175a0, 0xaa52295a684faf7b, 0x017b811cc3589d8d, 0x55bfb699b6181648,
+         0xc2ccaf71501c2421, 0x1707950327596402, 0xdd2fcdcd42a8229f, 0x8b9d7d5b27778a21],
+        [0xac9a05525f9cf512, 0x2ba125c58627b5e8, 0xc74e91250a8147a5, 0xa3e64b640d5bb384,
+         0xf53047d18d1f9292, 0xbaaeddacae3a6374, 0xf2d0914a808b3db1, 0x18af1a3742bfa3b0,
+         0x9a621ef50c55bdb8, 0xc615f4d1cc5466f3, 0xb7fbac19a35cf793, 0xd2b1a15ba517e46d,
+         0x4a290c4d7fd26f6f, 0x4f0cf1bb1770c4c4, 0x548345386cd377f5, 0x33978d2789fddd42],
+        [0xab78c59deb77e211, 0xc485b2a933d2be7f, 0xbde3792c00c03c53, 0xab4cefe8f893d247,
+         0xc5c0e752eab7f85f, 0xdbf5a76f893bafea, 0xa91f6003e3d984de, 0x099539077f311e87,
+         0x097ec52232f9559e, 0x53641bdf8991e48c, 0x2afe9711d5ed9d7c, 0xa7b13d3661b5d117,
+         0x5a0e243fe7af6556, 0x1076fae8932d5f00, 0x9b53a83d434934e3, 0xed3fd595a3c0344a],
+        [0xdacf46dc1c31a045, 0x5d2e3c121eb387f2, 0x51f8b0658b124499, 0x1e7dbd1daa72167d,
+         0x8275015a25c55b88, 0xe8521c24ac7a70b3, 0x6521d121c40b3f67, 0xac12de797de135b0,
+         0xafa28ead79f6ed6a, 0x685174a7a8d26f0b, 0xeff92a08d35d9874, 0x3058734b76dd123a,
+         0xfa55dcfba429f79c, 0x559294d4324c7728, 0x7a770f53012dc178, 0xedd8f7c408f3883b],
+        [0x39b533cf8d795fa5, 0x160ef9de243a8c0a, 0x431d52da6215fe3f, 0x54c51a2a2ef6d528,
+         0x9b13892b46ff9d16, 0x263c46fcee210289, 0xb738c96d25aabdc4, 0x5c33a5203996d38f,
+         0x2626496e7c98d8dd, 0xc669e0a52785903a, 0xaecde726c8ae1f47, 0x039343ef3a81e999,
+         0x2615ceaf044a54f9, 0x7e41e834662b66e1, 0x4ca5fd4895335783, 0x64b334d02916f2b0],
+        [0x87268837389a6981, 0x034b75bcb20a6274, 0x58e658296cc2cd6e, 0xe2d0f759acc31df4,
+         0x81a652e435093e20, 0x0b72b6e0172eaf47, 0x4aec43cec577d66d, 0xde78365b028a84e6,
+         0x444e19569adc0ee4, 0x942b2451fa40d1da, 0xe24506623ea5bd6c, 0x082854bf2ef7c743,
+         0x69dbbc566f59d62e, 0x248c38d02a7b5cb2, 0x4f4e8f8c09d15edb, 0xd96682f188d310cf],
+        [0x6f9a25d56818b54c, 0xb6cefed606546cd9, 0x5bc07523da38a67b, 0x7df5a3c35b8111cf,
+         0xaaa2cc5d4db34bb0, 0x9e673ff22a4653f8, 0xbd8b278d60739c62, 0xe10d20f6925b8815,
+         0xf6c87b91dd4da2bf, 0xfed623e2f71b6f1a, 0xa0f02fa52a94d0d3, 0xbb5794711b39fa16,
+         0xd3b94fba9d005c7f, 0x15a26e89fad946c9, 0xf3cb87db8a67cf49, 0x400d2bf56aa2a577],
+    ];
+
+    const INTERNAL_ROUND_CONSTANTS: [u64; N_PARTIAL_ROUNDS] = [
+        0x28eff4b01103d100, 0x60400ca3e2685a45, 0x1c8636beb3389b84, 0xac1332b60e13eff0,
+        0x2adafcc364e20f87, 0x79ffc2b14054ea0b, 0x3f98e4c0908f0a05, 0xcdb230bc4e8a06c4,
+        0x1bcaf7705b152a74, 0xd9bca249a82a7470, 0x91e24af19bf82551, 0xa62b43ba5cb78858,
+        0xb4898117472e797f, 0xb3228bca606cdaa0, 0x844461051bca39c9, 0xf3411581f6617d68,
+        0xf7fd50646782b533, 0x6ca664253c18fb48, 0x2d2fcdec0886a08f, 0x29da00dd799b575e,
+        0x47d966cc3b6e1e93, 0xde884e9a17ced59e,
+    ];
+
+    const INTERNAL_MATRIX_DIAG_M_1: [u64; 16] = [
+        0xde9b91a467d6afc0, 0xc5f16b9c76a9be17, 0x0ab0fef2d540ac55, 0x3001d27009d05773,
+        0xed23b1f906d3d9eb, 0x5ce73743cba97054, 0x1c3bab944af4ba24, 0x2faa105854dbafae,
+        0x53ffb3ae6d421a10, 0xbcda9df8884ba396, 0xfc1273e4a31807bb, 0xc77952573d5142c0,
+        0x56683339a819b85e, 0x328fcbd8f0ddc8eb, 0xb5101e303fce9cb7, 0x774487b8c40089bb,
+    ];
+
+    #[cfg(all(target_arch = "x86_64", target_feature = "avx2"))]
+    #[inline]
+    fn full_rounds(state: &mut [Self; 16], round_ctr: &mut usize) {
+        unsafe {
+            crate::hash::arch::x86_64::poseidon2_goldilocks_avx2::full_rounds(state, round_ctr);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(not(feature = "std"))]
+    use alloc::{collections::VecDeque, vec, vec::Vec};
+    #[cfg(feature = "std")]
+    use std::collections::VecDeque;
+
+    use crate::field::goldilocks_field::GoldilocksField as F;
+    use crate::field::polynomial::PolynomialCoeffs;
+    use crate::field::types::{Field, Field64, PrimeField64};
+    use crate::hash::poseidon2::test_helpers::{check_consistency, check_test_vectors};
+    use crate::hash::poseidon2::{
+        Poseidon2, HALF_N_FULL_ROUNDS, N_FULL_ROUNDS_TOTAL, N_PARTIAL_ROUNDS,
+    };
+
+    // Test inputs are:
+    // 1. all zeros
+    // 2. range 0..WIDTH
+    // 3. all -1's
+    // 4. random elements of GoldilocksField.
+    // For widths 8 and 12, the outputs on the range inputs are the test vectors published with the
+    // Plonky3 and HorizenLabs implementations respectively. The others are known-answer vectors
+    // computed from the same constants.
+
+    #[test]
+    fn test_vectors_8() {
+        let neg_one: u64 = F::NEG_ONE.to_canonical_u64();
+
+        #[rustfmt::skip]
+        let test_vectors8: Vec<([u64; 8], [u64; 8])> = vec![
+            ([0, 0, 0, 0, 0, 0, 0, 0],
+             [0x3a7def562f511210, 0xab0afaf9756476a0, 0x8faf5cc269ff0a14, 0xd6818fc87ccd41ba,
+              0x8baed826fea3ff62, 0xe133a5f5d18335c6, 0x291171699652ccaa, 0xc63ff85a9e199a0d]),
+            ([0, 1, 2, 3, 4, 5, 6, 7],
+             [0xc5fb1cfe0b4697bb, 0x4a4a32ff849af473, 0xd2fd266077f8efba, 0xf4ad9b74e833916d,
+              0xe6648eb0acc11463, 0x8d5529a930d75194, 0xe8c993aa10da6c90, 0xa73104a95b68031c]),
+            ([neg_one, neg_one, neg_one, neg_one,
+              neg_one, neg_one, neg_one, neg_one],
+             [0x5caed410a9fd6849, 0x31151f18cf2b97fb, 0x0edda42264f38cda, 0xff14843277766957,
+              0x6421bfe1b7a30c8d, 0xe64b19fa554471ad, 0x2da3087bf1b4a9ec, 0xbd4766960009480e]),
+            ([0xf65923cd85132c85, 0x71f2223ff559a0f9, 0x14b67b93571d9d6a, 0xc447a6fbb54b9c52,
+              0x1a3a8d85ecdb2606, 0xf34de6440ce23540, 0xb6214042a94cc30e, 0x2444fb01d3bdc021],
+             [0x26b6a02019ef8243, 0x87cb17147d9f7eb2, 0x569a8a192eebd585, 0xff53856f59bb5af5,
+              0x4b15b2792319a848, 0x5c3f9ed61a8d2458, 0xd645cec873fb684a, 0x4915a2998d47c972]),
+        ];
+
+        check_test_vectors::<F, 8>(test_vectors8);
+    }
+
+    #[test]
+    fn test_vectors_12() {
+        let neg_one: u64 = F::NEG_ONE.to_canonical_u64();
+
+        #[rustfmt::skip]
+        let test_vectors12: Vec<([u64; 12], [u64; 12])> = vec![
+            ([0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+             [0xef311849263abcb4, 0x8bf04d36f9a01799, 0x9e570c4df0f2699f, 0x6927c3a96db0b2ad,
+              0x760d22fbb5fc5de0, 0xafd1fedcdef654f4, 0xbb8c81621d5d5aed, 0x298915feb162422c,
+              0x2082259c8351dacb, 0x90e205e0814883e3, 0x2fd0c9106556082d, 0xa08b335154cbefc5]),
+            ([0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11],
+             [0x01eaef96bdf1c0c1, 0x1f0d2cc525b2540c, 0x6282c1dfe1e0358d, 0xe780d721f698e1e6,
+              0x280c0b6f753d833b, 0x1b942dd5023156ab, 0x43f0df3fcccb8398, 0xe8e8190585489025,
+              0x56bdbf72f77ada22, 0x7911c32bf9dcd705, 0xec467926508fbe67, 0x6a50450ddf85a6ed]),
+            ([neg_one, neg_one, neg_one, neg_one,
+              neg_one, neg_one, neg_one, neg_one,
+              neg_one, neg_one, neg_one, neg_one],
+             [0x3f56a9a7aa786049, 0xf320150bc2d01e34, 0x06e3150b85cd1fc6, 0xaf7493cbe0918063,
+              0xe13c55e947c18211, 0x499b83527cb38e47, 0x51e3f3dc2c5b0a2d, 0x7eb3696091d3fb64,
+              0x35ff59edc014bc95, 0xfda3001e8f6852d5, 0x5f67d6471c4391ab, 0x6484973933877089]),
+            ([0x289098e456a65d64, 0x67474bfe2512affe, 0x9b06c5b6354eb312, 0x5828cef3079fb200,
+              0xa699e71c41a2d6db, 0x6e80a310d9b9851f, 0x972fa9f7887f3786, 0xa51c383332c9fc24,
+              0x70b4c97b27229396, 0x01b5bca76a5b23a3, 0x74105f946644dc1c, 0x1adb88b3eb8d2653],
+             [0x8254124571567e7e, 0x3e25d0e6ce7c9a7c, 0x5a045f1a723fd64e, 0xb5ac6dc1e2475f16,
+              0x743d22bc34c194fd, 0xd659229ce89ae78d, 0xbdd4cd3a7042b2a3, 0x745758ac2dd91034,
+              0x7505b10620dbd317, 0xbaef98d92b0fa0db, 0x52df1a0be2b6178d, 0x3a0e497f4ecacdaa]),
+        ];
+
+        check_test_vectors::<F, 12>(test_vectors12);
+    }
+
+    #[test]
+    fn test_vectors_16() {
+        let neg_one: u64 = F::NEG_ONE.to_canonical_u64();
+
+        #[rustfmt::skip]
+        let test_vectors16: Vec<([u64; 16], [u64; 16])> = vec![
+            ([0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+             [0xf2b2442ea4d72b98, 0x08367625af002a12, 0x41d794a3d56b9451, 0x533967a2f0a214c8,
+              0x9b10cb9aecef64c2, 0x3af18efb76e71cc4, 0x20d42b106f3cd4d6, 0x537149275a93e1b9,
+              0xe48c755b2541ac33, 0xd88485c5e6be8ad5, 0xf864699c52b2d651, 0x3bb13e057d4f33c6,
+              0x7530b7e50b638c15, 0x4664c38414614b49, 0x267451ae2a8b9c47, 0x6a683ad447354817]),
+            ([0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15],
+             [0x85c54702470d9756, 0xaa53c7a7d52d9898, 0x285128096efb0dd7, 0xf3fde5edd3050ac8,
+              0xc7b65efd040df908, 0x4be3f6c467f57ae9, 0x274e9a67b41754fb, 0x0f7d39cd5de94dac,
+              0xd0224b9794d0b78c, 0x372f6139570042e1, 0xce6e8a93dc4ec26c, 0xace65e30a4daf7af,
+              0x016f2824cc1ba3db, 0x2e8f3af37c434dec, 0xc80831bb6e09da01, 0x3a7d670bf1a86ee8]),
+            ([neg_one, neg_one, neg_one, neg_one,
+              neg_one, neg_one, neg_one, neg_one,
+              neg_one, neg_one, neg_one, neg_one,
+              neg_one, neg_one, neg_one, neg_one],
+             [0xf96480fc1ecb6b65, 0x46b7b8361ca98933, 0xa36b6082cc382644, 0xfa7bd5c3e5a7b1b8,
+              0x855d3494b8e91ca5, 0x576b57d6bbe27868, 0x9943ad21b10cefb2, 0xaf51a2619f238176,
+              0x356e37226b758386, 0x87fb4a028637da1a, 0xac2faf7393320507, 0x2ab5e67b1ae650ad,
+              0x8def61d7bcb16fd0, 0x054b3c4419420027, 0x29488e0e9c9b47fe, 0xeb87a38326d3e7e8]),
+            ([0xbac3a3265d65b94b, 0x8bb0203c63f4822e, 0xbbf31fc4594437e3, 0x2bc27e528f6dfd28,
+              0xa8d0ad82a4aa10f4, 0x105c864ba75fa96a, 0xe25acf77593a695d, 0x08f43bdf7e2b5b16,
+              0x86a5bda877f3160c, 0xc301696ae878eea2, 0x96c394627a320165, 0x653e09f78221d081,
+              0x2bdf6c649c220aa9, 0x7fd174cb93801745, 0x1a609df92e29017c, 0x448bce8f963095fd],
+             [0xc2ddf6fe3e8d5ee6, 0x6bb2ff86bb556998, 0xc697f9b1be60d521, 0xc5c10324ee51d42e,
+              0x9fc230758c39aa72, 0x7a9be1ad396fe6c9, 0x33abd49d8092dcbe, 0x9556c37e871cf86a,
+              0xa18de0c89eea349e, 0x1a0467f3c946b02b, 0x9d0b22f8b3a4b557, 0x6d9825b3d2dd3952,
+              0xd1cc7832ac0a051c, 0x2a3e16e839c0a27a, 0xdd0d9c50fd60a7cf, 0xa3a3fe9195071262]),
+        ];
+
+        check_test_vectors::<F, 16>(test_vectors16);
+    }
+
+    #[test]
+    fn consistency() {
+        check_consistency::<F, 8>();
+        check_consistency::<F, 12>();
+        check_consistency::<F, 16>();
+    }
+
+    /// Generates `num` field elements with the Grain LFSR of the Poseidon reference
+    /// implementation, for a permutation of width `WIDTH`.
+    fn grain_constants<const WIDTH: usize>(num: usize) -> Vec<u64> {
+        let mut state = VecDeque::with_capacity(80);
+        for (value, num_bits) in [
+            (1, 2),
+            (0, 4),
+            (64, 12),
+            (WIDTH, 12),
+            (N_FULL_ROUNDS_TOTAL, 10),
+            (N_PARTIAL_ROUNDS, 10),
+        ] {
+            state.extend((0..num_bits).rev().map(|i| (value >> i) & 1 == 1));
+        }
+        state.extend([true; 30]);
+
+        let mut next_bit = || {
+            let bit = state[62] ^ state[51] ^ state[38] ^ state[23] ^ state[13] ^ state[0];
+            state.pop_front();
+            state.push_back(bit);
+            bit
+        };
+        for _ in 0..160 {
+            next_bit();
+        }
+
+        let mut constants = Vec::with_capacity(num);
+        while constants.len() < num {
+            let mut value = 0u64;
+            for _ in 0..64 {
+                // Of each pair of bits, the second is output only if the first is set.
+                let bit = loop {
+                    let keep = next_bit();
+                    let bit = next_bit();
+                    if keep {
+                        break bit;
+                    }
+                };
+                value = (value << 1) | bit as u64;
+            }
+            if value < F::ORDER {
+                constants.push(value);
+            }
+        }
+        constants
+    }
+
+    fn check_round_constants<const WIDTH: usize>()
+    where
+        F: Poseidon2<WIDTH>,
+    {
+        let constants = grain_constants::<WIDTH>(N_FULL_ROUNDS_TOTAL * WIDTH + N_PARTIAL_ROUNDS);
+        let (first, rest) = constants.split_at(HALF_N_FULL_ROUNDS * WIDTH);
+        let (partial, last) = rest.split_at(N_PARTIAL_ROUNDS);
+
+        let external = <F as Poseidon2<WIDTH>>::EXTERNAL_ROUND_CONSTANTS;
+        assert_eq!(external[..HALF_N_FULL_ROUNDS].concat(), first);
+        assert_eq!(partial, <F as Poseidon2<WIDTH>>::INTERNAL_ROUND_CONSTANTS);
+        assert_eq!(external[HALF_N_FULL_ROUNDS..].concat(), last);
+    }
+
+    #[test]
+    fn round_constants() {
+        check_round_constants::<8>();
+        check_round_constants::<12>();
+        check_round_constants::<16>();
+    }
+
+    fn mat_mul(a: &[Vec<F>], b: &[Vec<F>]) -> Vec<Vec<F>> {
+        let n = a.len();
+        (0..n)
+            .map(|i| {
+                (0..n)
+                    .map(|j| (0..n).fold(F::ZERO, |acc, k| acc + a[i][k] * b[k][j]))
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// Computes the characteristic polynomial of `a` with the Faddeev-LeVerrier algorithm.
+    fn charpoly(a: &[Vec<F>]) -> PolynomialCoeffs<F> {
+        let n = a.len();
+        let mut coeffs = vec![F::ZERO; n + 1];
+        coeffs[n] = F::ONE;
+        let mut m = vec![vec![F::ZERO; n]; n];
+        for k in 1..=n {
+            m = mat_mul(a, &m);
+            for (i, row) in m.iter_mut().enumerate() {
+                row[i] += coeffs[n - k + 1];
+            }
+            let am = mat_mul(a, &m);
+            let trace = (0..n).fold(F::ZERO, |acc, i| acc + am[i][i]);
+            coeffs[n - k] = -trace / F::from_canonical_usize(k);
+        }
+        PolynomialCoeffs::new(coeffs)
+    }
+
+    fn mul_mod(
+        a: &PolynomialCoeffs<F>,
+        b: &PolynomialCoeffs<F>,
+        f: &PolynomialCoeffs<F>,
+    ) -> PolynomialCoeffs<F> {
+        (a * b).div_rem_long_division(f).1.trimmed()
+    }
+
+    fn exp_mod(
+        a: &PolynomialCoeffs<F>,
+        mut power: u64,
+        f: &PolynomialCoeffs<F>,
+    ) -> PolynomialCoeffs<F> {
+        let mut base = a.clone();
+        let mut acc = PolynomialCoeffs::new(vec![F::ONE]);
+        while power > 0 {
+            if power & 1 == 1 {
+                acc = mul_mod(&acc, &base, f);
+            }
+            base = mul_mod(&base, &base, f);
+            power >>= 1;
+        }
+        acc
+    }
+
+    fn gcd(a: &PolynomialCoeffs<F>, b: &PolynomialCoeffs<F>) -> PolynomialCoeffs<F> {
+        let (mut a, mut b) = (a.trimmed(), b.trimmed());
+        while !b.is_zero() {
+            let r = a.div_rem_long_division(&b).1.trimmed();
+            a = b;
+            b = r;
+        }
+        a
+    }
+
+    /// Rabin's irreducibility test for a monic polynomial `f` of degree at least 2.
+    fn is_irreducible(f: &PolynomialCoeffs<F>) -> bool {
+        let n = f.degree_plus_one() - 1;
+        let x = PolynomialCoeffs::new(vec![F::ZERO, F::ONE]);
+        // `frobenius[i]` is `x^(p^i) mod f`.
+        let mut frobenius = vec![x.clone()];
+        for i in 1..=n {
+            frobenius.push(exp_mod(&frobenius[i - 1], F::ORDER, f));
+        }
+
+        let prime_divisors =
+            (2..=n).filter(|&q| n.is_multiple_of(q) && (2..q).all(|d| !q.is_multiple_of(d)));
+        (&frobenius[n] - &x).trimmed().is_zero()
+            && prime_divisors
+                .into_iter()
+                .all(|q| gcd(&(&frobenius[n / q] - &x), f).degree_plus_one() == 1)
+    }
+
+    fn check_internal_matrix<const WIDTH: usize>()
+    where
+        F: Poseidon2<WIDTH>,
+    {
+        let diag_m_1 = <F as Poseidon2<WIDTH>>::INTERNAL_MATRIX_DIAG_M_1;
+        let m_i: Vec<Vec<F>> = (0..WIDTH)
+            .map(|i| {
+                (0..WIDTH)
+                    .map(|j| F::ONE + F::from_canonical_u64(if i == j { diag_m_1[i] } else { 0 }))
+                    .collect()
+            })
+            .collect();
+
+        let mut power = m_i.clone();
+        for k in 1..=2 * WIDTH {
+            assert!(
+                is_irreducible(&charpoly(&power)),
+                "M_I^{k} has a reducible characteristic polynomial for width {WIDTH}"
+            );
+            power = mat_mul(&power, &m_i);
+        }
+    }
+
+    #[test]
+    fn internal_matrices() {
+        check_internal_matrix::<8>();
+        check_internal_matrix::<12>();
+        check_internal_matrix::<16>();
+    }
+}
@@ -0,0 +1,522 @@
+//! Sparse Merkle trees, i.e. key-value maps committed to by a Merkle tree with one leaf for every
+//! possible key, most of which are empty.
+//!
+//! A tree of depth `d` has `2^d` leaves. Keys are `HashOut`s, read as `4 * 64`-bit little-endian
+//! integers, and must be smaller than `2^d`; the path to a leaf is given by the bits of its key,
+//! starting from the bottom layer. The leaf of a key holding `value` has digest
+//! `H::hash_pad(value)`, and an empty leaf has the all-zero digest.
+
+#[cfg(not(feature = "std"))]
+use alloc::{collections::BTreeMap, vec, vec::Vec};
+#[cfg(feature = "std")]
+use std::collections::BTreeMap;
+
+use anyhow::{ensure, Result};
+
+use crate::field::extension::Extendable;
+use crate::hash::hash_types::{HashOut, HashOutTarget, RichField, NUM_HASH_OUT_ELTS};
+use crate::hash::hashing::PlonkyPermutation;
+use crate::hash::merkle_proofs::{MerkleProof, MerkleProofTarget};
+use crate::iop::target::{BoolTarget, Target};
+use crate::plonk::circuit_builder::CircuitBuilder;
+use crate::plonk::config::{AlgebraicHasher, GenericHashOut, Hasher};
+
+/// The maximum depth of a sparse Merkle tree, i.e. the number of bits of a key.
+pub const SPARSE_MERKLE_TREE_MAX_DEPTH: usize = NUM_HASH_OUT_ELTS * 64;
+
+/// Returns the path to the leaf of `key` in a tree of the given depth, starting from the bottom
+/// layer. Panics if `key` does not fit in `depth` bits.
+fn key_bits<F: RichField>(key: HashOut<F>, depth: usize) -> Vec<bool> {
+    let bits: Vec<bool> = key
+        .elements
+        .iter()
+        .flat_map(|x| {
+            let x = x.to_canonical_u64();
+            (0..64).map(move |i| (x >> i) & 1 == 1)
+        })
+        .collect();
+    assert!(
+        bits[depth..].iter().all(|&b| !b),
+        "key should fit in {} bits",
+        depth
+    );
+    bits[..depth].to_vec()
+}
+
+/// The digest of a leaf holding `value`, or of an empty leaf if `value` is `None`.
+fn leaf_digest<F: RichField, H: Hasher<F>>(value: Option<&[F]>) -> H::Hash {
+    match value {
+        Some(value) => H::hash_pad(value),
+        None => empty_digest::<F, H>(),
+    }
+}
+
+fn empty_digest<F: RichField, H: Hasher<F>>() -> H::Hash {
+    H::Hash::from_bytes(&vec![0; H::HASH_SIZE])
+}
+
+/// Returns the root of the path from a leaf with the given digest, whose siblings are given by
+/// `proof`.
+fn compute_root<F: RichField, H: Hasher<F>>(
+    leaf_digest: H::Hash,
+    key_bits: &[bool],
+    proof: &MerkleProof<F, H>,
+) -> H::Hash {
+    key_bits
+        .iter()
+        .zip(&proof.siblings)
+        .fold(leaf_digest, |digest, (&bit, &sibling)| {
+            if bit {
+                H::two_to_one(sibling, digest)
+            } else {
+                H::two_to_one(digest, sibling)
+            }
+        })
+}
+
+/// A sparse Merkle tree of configurable depth, which only stores its non-empty nodes.
+#[derive(Clone, Debug)]
+pub struct SparseMerkleTree<F: RichField, H: Hasher<F>> {
+    depth: usize,
+
+    /// The values of the non-empty leaves, indexed by their key.
+    values: BTreeMap<Vec<bool>, Vec<F>>,
+
+    /// The digests of the non-empty nodes, indexed by their height and the path from the root to
+    /// them, given from the bottom as in `key_bits`.
+    nodes: BTreeMap<(usize, Vec<bool>), H::Hash>,
+
+    /// The digests of empty subtrees, indexed by their height.
+    empty_digests: Vec<H::Hash>,
+}
+
+impl<F: RichField, H: Hasher<F>> SparseMerkleTree<F, H> {
+    /// Creates an empty tree with `2^depth` leaves.
+    pub fn new(depth: usize) -> Self {
+        assert!(
+            depth <= SPARSE_MERKLE_TREE_MAX_DEPTH,
+            "depth={} should be at most {}",
+            depth,
+            SPARSE_MERKLE_TREE_MAX_DEPTH
+        );
+
+        let mut empty_digests = Vec::with_capacity(depth + 1);
+        empty_digests.push(empty_digest::<F, H>());
+        for i in 0..depth {
+            empty_digests.push(H::two_to_one(empty_digests[i], empty_digests[i]));
+        }
+
+        Self {
+            depth,
+            values: BTreeMap::new(),
+            nodes: BTreeMap::new(),
+            empty_digests,
+        }
+    }
+
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+
+    pub fn root(&self) -> H::Hash {
+        self.node(self.depth, &[])
+    }
+
+    /// Returns the value stored under `key`, if any.
+    pub fn get(&self, key: HashOut<F>) -> Option<&[F]> {
+        self.values
+            .get(&key_bits(key, self.depth))
+            .map(|v| v.as_slice())
+    }
+
+    /// Stores `value` under `key`, replacing any previous value, and returns the previous value.
+    pub fn insert(&mut self, key: HashOut<F>, value: Vec<F>) -> Option<Vec<F>> {
+        self.update(key, Some(value))
+    }
+
+    /// Removes the value stored under `key`, and returns it.
+    pub fn remove(&mut self, key: HashOut<F>) -> Option<Vec<F>> {
+        self.update(key, None)
+    }
+
+    /// Sets the leaf of `key` to `value`, or empties it if `value` is `None`, and updates the
+    /// digests along its path. Returns the previous value of the leaf.
+    pub fn update(&mut self, key: HashOut<F>, value: Option<Vec<F>>) -> Option<Vec<F>> {
+        let bits = key_bits(key, self.depth);
+
+        let mut digest = leaf_digest::<F, H>(value.as_deref());
+        self.set_node(0, &bits, digest);
+        for height in 0..self.depth {
+            let sibling = self.sibling(height, &bits);
+            digest = if bits[height] {
+                H::two_to_one(sibling, digest)
+            } else {
+                H::two_to_one(digest, sibling)
+            };
+            self.set_node(height + 1, &bits[height + 1..], digest);
+        }
+
+        match value {
+            Some(value) => self.values.insert(bits, value),
+            None => self.values.remove(&bits),
+        }
+    }
+
+    /// Creates a Merkle proof for the leaf of `key`, whether it is empty or not.
+    pub fn prove(&self, key: HashOut<F>) -> MerkleProof<F, H> {
+        let bits = key_bits(key, self.depth);
+        let siblings = (0..self.depth)
+            .map(|height| self.sibling(height, &bits))
+            .collect();
+        MerkleProof { siblings }
+    }
+
+    fn node(&self, height: usize, path: &[bool]) -> H::Hash {
+        self.nodes
+            .get(&(height, path.to_vec()))
+            .copied()
+            .unwrap_or(self.empty_digests[height])
+    }
+
+    /// The sibling at the given height of the node on the path `bits` from the root to a leaf.
+    fn sibling(&self, height: usize, bits: &[bool]) -> H::Hash {
+        let mut path = bits[height..].to_vec();
+        path[0] = !path[0];
+        self.node(height, &path)
+    }
+
+    fn set_node(&mut self, height: usize, path: &[bool], digest: H::Hash) {
+        if digest == self.empty_digests[height] {
+            self.nodes.remove(&(height, path.to_vec()));
+        } else {
+            self.nodes.insert((height, path.to_vec()), digest);
+        }
+    }
+}
+
+/// Verifies that `value` is stored under `key` in the sparse Merkle tree with the given root, or,
+/// if `value` is `None`, that nothing is stored under `key`.
+pub fn verify_sparse_merkle_proof<F: RichField, H: Hasher<F>>(
+    key: HashOut<F>,
+    value: Option<&[F]>,
+    root: H::Hash,
+    proof: &MerkleProof<F, H>,
+) -> Result<()> {
+    ensure!(
+        proof.len() <= SPARSE_MERKLE_TREE_MAX_DEPTH,
+        "Invalid sparse Merkle proof length."
+    );
+    let bits = key_bits(key, proof.len());
+    ensure!(
+        compute_root(leaf_digest::<F, H>(value), &bits, proof) == root,
+        "Invalid sparse Merkle proof."
+    );
+
+    Ok(())
+}
+
+/// Verifies that changing the value stored under `key` from `old_value` to `new_value` changes the
+/// root of the sparse Merkle tree from `old_root` to `new_root`. A value of `None` stands for an
+/// empty leaf, so this covers insertions and removals as well as updates. `proof` is the proof of
+/// the leaf of `key`, which is the same before and after the change.
+pub fn verify_sparse_merkle_update<F: RichField, H: Hasher<F>>(
+    key: HashOut<F>,
+    old_value: Option<&[F]>,
+    new_value: Option<&[F]>,
+    old_root: H::Hash,
+    new_root: H::Hash,
+    proof: &MerkleProof<F, H>,
+) -> Result<()> {
+    verify_sparse_merkle_proof(key, old_value, old_root, proof)?;
+    verify_sparse_merkle_proof(key, new_value, new_root, proof)
+}
+
+impl<F: RichField + Extendable<D>, const D: usize> CircuitBuilder<F, D> {
+    /// Returns the path to the leaf of `key` in a sparse Merkle tree of the given depth, starting
+    /// from the bottom layer. Constrains `key` to fit in `depth` bits, and its elements to be given
+    /// in canonical form, so that every key has a single path.
+    pub fn sparse_merkle_key_bits(&mut self, key: HashOutTarget, depth: usize) -> Vec<BoolTarget> {
+        assert!(depth <= SPARSE_MERKLE_TREE_MAX_DEPTH);
+        debug_assert_eq!(F::BITS, 64);
+
+        let mut bits = Vec::with_capacity(depth);
+        for (i, &x) in key.elements.iter().enumerate() {
+            let num_bits = depth.saturating_sub(64 * i).min(64);
+            if num_bits == 0 {
+                self.assert_zero(x);
+                continue;
+            }
+            let x_bits = self.split_le(x, num_bits);
+            if num_bits == 64 {
+                self.assert_canonical_bits(&x_bits);
+            }
+            bits.extend(x_bits);
+        }
+        bits
+    }
+
+    /// Asserts that the 64 little-endian bits of an element encode an integer smaller than the
+    /// field order, so that the decomposition of the element is unique.
+    fn assert_canonical_bits(&mut self, bits: &[BoolTarget]) {
+        let max = F::ORDER - 1;
+        // `prefix_eq` is set iff the bits seen so far, from the top, match those of `max`. Once
+        // they do not, a smaller bit has been seen, since we never allow a larger one.
+        let mut prefix_eq = self._true();
+        for (i, &bit) in bits.iter().enumerate().rev() {
+            if (max >> i) & 1 == 1 {
+                prefix_eq = self.and(prefix_eq, bit);
+            } else {
+                let too_large = self.and(prefix_eq, bit);
+                self.assert_zero(too_large.target);
+            }
+        }
+    }
+
+    /// Returns the digest of a sparse Merkle tree leaf holding `value` if `is_present` is set, or
+    /// of an empty leaf otherwise.
+    pub fn sparse_merkle_leaf_digest<H: AlgebraicHasher<F>>(
+        &mut self,
+        value: &[Target],
+        is_present: BoolTarget,
+    ) -> HashOutTarget {
+        // Apply the `pad10*1` rule of `Hasher::hash_pad`; the length is known, so it is constant.
+        let zero = self.zero();
+        let one = self.one();
+        let rate = H::AlgebraicPermutation::RATE;
+        let mut padded = value.to_vec();
+        padded.push(one);
+        padded.resize((value.len() + 1) / rate * rate + rate - 1, zero);
+        padded.push(one);
+        let digest = self.hash_n_to_hash_no_pad::<H>(padded);
+
+        HashOutTarget::from_vec(
+            digest
+                .elements
+                .iter()
+                .map(|&x| self.mul(is_present.target, x))
+                .collect(),
+        )
+    }
+
+    /// Returns the root of the path from a leaf with the given digest to the root, whose siblings
+    /// are given by `proof`.
+    fn sparse_merkle_root<H: AlgebraicHasher<F>>(
+        &mut self,
+        leaf_digest: HashOutTarget,
+        key_bits: &[BoolTarget],
+        proof: &MerkleProofTarget,
+    ) -> HashOutTarget {
+        debug_assert!(H::AlgebraicPermutation::RATE >= NUM_HASH_OUT_ELTS);
+        assert_eq!(key_bits.len(), proof.siblings.len());
+
+        let zero = self.zero();
+        let mut state = leaf_digest;
+        for (&bit, &sibling) in key_bits.iter().zip(&proof.siblings) {
+            let mut perm_inputs = H::AlgebraicPermutation::default();
+            perm_inputs.set_from_slice(&state.elements, 0);
+            perm_inputs.set_from_slice(&sibling.elements, NUM_HASH_OUT_ELTS);
+            perm_inputs.set_from_iter(core::iter::repeat(zero), 2 * NUM_HASH_OUT_ELTS);
+            let perm_outs = self.permute_swapped::<H>(perm_inputs, bit);
+            state = HashOutTarget::from_vec(perm_outs.squeeze()[..NUM_HASH_OUT_ELTS].to_vec());
+        }
+        state
+    }
+
+    /// Verifies that the leaf of `key` in the sparse Merkle tree with the given root has the given
+    /// digest, as returned by `sparse_merkle_leaf_digest`. The depth of the tree is the length of
+    /// `proof`.
+    pub fn verify_sparse_merkle_proof<H: AlgebraicHasher<F>>(
+        &mut self,
+        key: HashOutTarget,
+        leaf_digest: HashOutTarget,
+        root: HashOutTarget,
+        proof: &MerkleProofTarget,
+    ) {
+        let key_bits = self.sparse_merkle_key_bits(key, proof.siblings.len());
+        let computed_root = self.sparse_merkle_root::<H>(leaf_digest, &key_bits, proof);
+        self.connect_hashes(computed_root, root);
+    }
+
+    /// Verifies that changing the digest of the leaf of `key` from `old_leaf_digest` to
+    /// `new_leaf_digest` changes the root of the sparse Merkle tree from `old_root` to `new_root`.
+    /// The depth of the tree is the length of `proof`.
+    pub fn verify_sparse_merkle_update<H: AlgebraicHasher<F>>(
+        &mut self,
+        key: HashOutTarget,
+        old_leaf_digest: HashOutTarget,
+        new_leaf_digest: HashOutTarget,
+        old_root: HashOutTarget,
+        new_root: HashOutTarget,
+        proof: &MerkleProofTarget,
+    ) {
+        let key_bits = self.sparse_merkle_key_bits(key, proof.siblings.len());
+        let computed_old_root = self.sparse_merkle_root::<H>(old_leaf_digest, &key_bits, proof);
+        self.connect_hashes(computed_old_root, old_root);
+        let computed_new_root = self.sparse_merkle_root::<H>(new_leaf_digest, &key_bits, proof);
+        self.connect_hashes(computed_new_root, new_root);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::rngs::OsRng;
+    use rand::Rng;
+
+    use super::*;
+    use crate::field::types::{Field, Sample};
+    use crate::iop::witness::{PartialWitness, WitnessWrite};
+    use crate::plonk::circuit_data::CircuitConfig;
+    use crate::plonk::config::{GenericConfig, PoseidonGoldilocksConfig};
+
+    const D: usize = 2;
+    type C = PoseidonGoldilocksConfig;
+    type F = <C as GenericConfig<D>>::F;
+    type H = <C as GenericConfig<D>>::Hasher;
+
+    fn random_key(depth: usize) -> HashOut<F> {
+        let mut rng = OsRng;
+        HashOut {
+            elements: core::array::from_fn(|i| {
+                let num_bits = depth.saturating_sub(64 * i).min(64);
+                let x = if num_bits == 0 {
+                    0
+                } else {
+                    rng.gen::<u64>() >> (64 - num_bits)
+                };
+                F::from_noncanonical_u64(x)
+            }),
+        }
+    }
+
+    #[test]
+    fn test_sparse_merkle_tree() -> Result<()> {
+        for depth in [8, 100, SPARSE_MERKLE_TREE_MAX_DEPTH] {
+            let mut tree = SparseMerkleTree::<F, H>::new(depth);
+            let empty_root = tree.root();
+            // The keys must be distinct, which is not a given for small depths.
+            let mut keys = Vec::new();
+            while keys.len() < 8 {
+                let key = random_key(depth);
+                if !keys.contains(&key) {
+                    keys.push(key);
+                }
+            }
+
+            for &key in &keys {
+                let proof = tree.prove(key);
+                verify_sparse_merkle_proof(key, None, tree.root(), &proof)?;
+
+                let old_root = tree.root();
+                let value = F::rand_vec(3);
+                tree.insert(key, value.clone());
+                assert_eq!(tree.get(key), Some(value.as_slice()));
+                verify_sparse_merkle_update(
+                    key,
+                    None,
+                    Some(&value),
+                    old_root,
+                    tree.root(),
+                    &proof,
+                )?;
+                verify_sparse_merkle_proof(key, Some(&value), tree.root(), &tree.prove(key))?;
+                assert!(verify_sparse_merkle_proof(key, None, tree.root(), &proof).is_err());
+            }
+
+            // Updating a key in place.
+            let old_value = tree.get(keys[0]).unwrap().to_vec();
+            let new_value = F::rand_vec(5);
+            let proof = tree.prove(keys[0]);
+            let old_root = tree.root();
+            assert_eq!(
+                tree.insert(keys[0], new_value.clone()),
+                Some(old_value.clone())
+            );
+            verify_sparse_merkle_update(
+                keys[0],
+                Some(&old_value),
+                Some(&new_value),
+                old_root,
+                tree.root(),
+                &proof,
+            )?;
+
+            // Removing every key brings the tree back to its empty state.
+            for &key in &keys {
+                tree.remove(key);
+            }
+            assert_eq!(tree.root(), empty_root);
+            assert!(tree.nodes.is_empty());
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_key_too_large() {
+        let tree = SparseMerkleTree::<F, H>::new(8);
+        tree.prove(HashOut::from_partial(&[F::from_canonical_u64(256)]));
+    }
+
+    #[test]
+    fn test_sparse_merkle_update_circuit() -> Result<()> {
+        let depth = SPARSE_MERKLE_TREE_MAX_DEPTH;
+        let mut tree = SparseMerkleTree::<F, H>::new(depth);
+        for _ in 0..4 {
+            tree.insert(random_key(depth), F::rand_vec(3));
+        }
+
+        // Insert a new key, then update it.
+        let key = random_key(depth);
+        let values = [None, Some(F::rand_vec(3)), Some(F::rand_vec(3))];
+        let mut roots = vec![tree.root()];
+        let mut proofs = vec![];
+        for value in &values[1..] {
+            proofs.push(tree.prove(key));
+            tree.update(key, value.clone());
+            roots.push(tree.root());
+        }
+
+        let config = CircuitConfig::standard_recursion_config();
+        let mut pw = PartialWitness::new();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+
+        let key_t = builder.add_virtual_hash();
+        pw.set_hash_target(key_t, key);
+        let root_ts = builder.add_virtual_hashes(roots.len());
+        for (&t, &root) in root_ts.iter().zip(&roots) {
+            pw.set_hash_target(t, root);
+        }
+        let leaf_ts: Vec<_> = values
+            .iter()
+            .map(|value| {
+                let value_t = builder.add_virtual_targets(3);
+                let is_present = builder.add_virtual_bool_target_safe();
+                pw.set_target_arr(&value_t, value.as_deref().unwrap_or(&[F::ZERO; 3][..]));
+                pw.set_bool_target(is_present, value.is_some());
+                builder.sparse_merkle_leaf_digest::<H>(&value_t, is_present)
+            })
+            .collect();
+        for (i, proof) in proofs.iter().enumerate() {
+            let proof_t = MerkleProofTarget {
+                siblings: builder.add_virtual_hashes(depth),
+            };
+            for (&t, &sibling) in proof_t.siblings.iter().zip(&proof.siblings) {
+                pw.set_hash_target(t, sibling);
+            }
+            builder.verify_sparse_merkle_update::<H>(
+                key_t,
+                leaf_ts[i],
+                leaf_ts[i + 1],
+                root_ts[i],
+                root_ts[i + 1],
+                &proof_t,
+            );
+        }
+
+        let data = builder.build::<C>();
+        let proof = data.prove(pw)?;
+        data.verify(proof)?;
+        Ok(())
+    }
+}
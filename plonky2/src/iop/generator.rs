@@ -8,6 +8,9 @@ use alloc::{
 use core::fmt::Debug;
 use core::marker::PhantomData;
 
+#[cfg(feature = "parallel")]
+use plonky2_maybe_rayon::*;
+
 use crate::field::extension::Extendable;
 use crate::field::types::Field;
 use crate::hash::hash_types::RichField;
@@ -15,12 +18,16 @@ use crate::iop::ext_target::ExtensionTarget;
 use crate::iop::target::Target;
 use crate::iop::wire::Wire;
 use crate::iop::witness::{PartialWitness, PartitionWitness, Witness, WitnessWrite};
+use crate::iop::witness_error::WitnessError;
 use crate::plonk::circuit_data::{CommonCircuitData, ProverOnlyCircuitData};
 use crate::plonk::config::GenericConfig;
 use crate::util::serialization::{Buffer, IoResult, Read, Write};
 
 /// Given a `PartitionWitness` that has only inputs set, populates the rest of the witness using the
 /// given set of generators.
+///
+/// Returns an error if a target is set twice with different values, or if some generators could not
+/// be run.
 pub fn generate_partial_witness<
     'a,
     F: RichField + Extendable<D>,
@@ -30,11 +37,8 @@ pub fn generate_partial_witness<
     inputs: PartialWitness<F>,
     prover_data: &'a ProverOnlyCircuitData<F, C, D>,
     common_data: &'a CommonCircuitData<F, D>,
-) -> PartitionWitness<'a, F> {
+) -> Result<PartitionWitness<'a, F>, WitnessError<F>> {
     let config = &common_data.config;
-    let generators = &prover_data.generators;
-    let generator_indices_by_watches = &prover_data.generator_indices_by_watches;
-
     let mut witness = PartitionWitness::new(
         config.num_wires,
         common_data.degree(),
@@ -42,16 +46,34 @@ pub fn generate_partial_witness<
     );
 
     for (t, v) in inputs.target_values.into_iter() {
-        witness.set_target(t, v);
+        witness
+            .set_target(t, v)
+            .map_err(|e| with_provenance(e, prover_data, None))?;
     }
 
+    #[cfg(feature = "parallel")]
+    run_generators_in_waves(&mut witness, prover_data)?;
+    #[cfg(not(feature = "parallel"))]
+    run_generators(&mut witness, prover_data)?;
+
+    Ok(witness)
+}
+
+/// Runs the generators one at a time, each seeing the values generated by the previous ones.
+#[cfg(not(feature = "parallel"))]
+fn run_generators<F: RichField + Extendable<D>, C: GenericConfig<D, F = F>, const D: usize>(
+    witness: &mut PartitionWitness<F>,
+    prover_data: &ProverOnlyCircuitData<F, C, D>,
+) -> Result<(), WitnessError<F>> {
+    let generators = &prover_data.generators;
+    let generator_indices_by_watches = &prover_data.generator_indices_by_watches;
+
     // Build a list of "pending" generators which are queued to be run. Initially, all generators
     // are queued.
     let mut pending_generator_indices: Vec<_> = (0..generators.len()).collect();
 
     // We also track a list of "expired" generators which have already returned false.
     let mut generator_is_expired = vec![false; generators.len()];
-    let mut remaining_generators = generators.len();
 
     let mut buffer = GeneratedValues::empty();
 
@@ -64,22 +86,19 @@ pub fn generate_partial_witness<
                 continue;
             }
 
-            let finished = generators[generator_idx].0.run(&witness, &mut buffer);
+            let finished = generators[generator_idx].0.run(witness, &mut buffer);
             if finished {
                 generator_is_expired[generator_idx] = true;
-                remaining_generators -= 1;
             }
 
-            // Merge any generated values into our witness, and get a list of newly-populated
-            // targets' representatives.
-            let new_target_reps = buffer
-                .target_values
-                .drain(..)
-                .flat_map(|(t, v)| witness.set_target_returning_rep(t, v));
-
-            // Enqueue unfinished generators that were watching one of the newly populated targets.
-            for watch in new_target_reps {
-                let opt_watchers = generator_indices_by_watches.get(&watch);
+            // Merge any generated values into our witness, and enqueue unfinished generators that
+            // were watching one of the newly populated targets' representatives.
+            for (t, v) in buffer.target_values.drain(..) {
+                let opt_watch = witness.set_target_returning_rep(t, v).map_err(|e| {
+                    with_provenance(e, prover_data, Some(&generators[generator_idx]))
+                })?;
+                let opt_watchers =
+                    opt_watch.and_then(|watch| generator_indices_by_watches.get(&watch));
                 if let Some(watchers) = opt_watchers {
                     for &watching_generator_idx in watchers {
                         if !generator_is_expired[watching_generator_idx] {
@@ -93,13 +112,128 @@ pub fn generate_partial_witness<
         pending_generator_indices = next_pending_generator_indices;
     }
 
-    assert_eq!(
-        remaining_generators, 0,
-        "{} generators weren't run",
-        remaining_generators,
-    );
+    check_generators_run(&generator_is_expired, prover_data)
+}
+
+/// Runs the generators in waves. The generators of a wave are run in parallel against the witness
+/// as it was at the start of the wave, then their values are merged into the witness in the order
+/// in which they were queued, so that the result does not depend on scheduling. As in the
+/// sequential scheduler, a generator which could not finish is queued again once one of the targets
+/// it watches is populated.
+#[cfg(feature = "parallel")]
+fn run_generators_in_waves<
+    F: RichField + Extendable<D>,
+    C: GenericConfig<D, F = F>,
+    const D: usize,
+>(
+    witness: &mut PartitionWitness<F>,
+    prover_data: &ProverOnlyCircuitData<F, C, D>,
+) -> Result<(), WitnessError<F>> {
+    let generators = &prover_data.generators;
+    let generator_indices_by_watches = &prover_data.generator_indices_by_watches;
+
+    let mut wave: Vec<_> = (0..generators.len()).collect();
+    let mut generator_is_expired = vec![false; generators.len()];
+    // Whether each generator is already queued in the next wave, so that it runs at most once per
+    // wave.
+    let mut is_queued = vec![false; generators.len()];
+
+    while !wave.is_empty() {
+        let outputs: Vec<(bool, GeneratedValues<F>)> = {
+            let witness = &*witness;
+            wave.par_iter()
+                .map(|&generator_idx| {
+                    let mut buffer = GeneratedValues::empty();
+                    let finished = generators[generator_idx].0.run(witness, &mut buffer);
+                    (finished, buffer)
+                })
+                .collect()
+        };
+
+        let mut next_wave = Vec::new();
+        for (&generator_idx, (finished, buffer)) in wave.iter().zip(outputs) {
+            if finished {
+                generator_is_expired[generator_idx] = true;
+            }
+
+            for (t, v) in buffer.target_values {
+                let opt_watch = witness.set_target_returning_rep(t, v).map_err(|e| {
+                    with_provenance(e, prover_data, Some(&generators[generator_idx]))
+                })?;
+                let opt_watchers =
+                    opt_watch.and_then(|watch| generator_indices_by_watches.get(&watch));
+                for &watching_generator_idx in opt_watchers.into_iter().flatten() {
+                    if !generator_is_expired[watching_generator_idx]
+                        && !is_queued[watching_generator_idx]
+                    {
+                        is_queued[watching_generator_idx] = true;
+                        next_wave.push(watching_generator_idx);
+                    }
+                }
+            }
+        }
 
-    witness
+        for &generator_idx in &next_wave {
+            is_queued[generator_idx] = false;
+        }
+        // Generators of this wave may have been queued by an earlier generator of the same wave,
+        // before finishing themselves.
+        next_wave.retain(|&generator_idx| !generator_is_expired[generator_idx]);
+        wave = next_wave;
+    }
+
+    check_generators_run(&generator_is_expired, prover_data)
+}
+
+/// Returns an error if some generators never finished.
+fn check_generators_run<
+    F: RichField + Extendable<D>,
+    C: GenericConfig<D, F = F>,
+    const D: usize,
+>(
+    generator_is_expired: &[bool],
+    prover_data: &ProverOnlyCircuitData<F, C, D>,
+) -> Result<(), WitnessError<F>> {
+    match generator_is_expired.iter().position(|&e| !e) {
+        Some(first_generator_idx) => Err(WitnessError::GeneratorsNotRun {
+            num_generators: generator_is_expired.iter().filter(|&&e| !e).count(),
+            first_generator: prover_data.generators[first_generator_idx].0.id(),
+        }),
+        None => Ok(()),
+    }
+}
+
+/// Attaches to a `TargetConflict` error the context in which the conflicting gate was added, and the
+/// generator which caused the conflict.
+pub(crate) fn with_provenance<
+    F: RichField + Extendable<D>,
+    C: GenericConfig<D, F = F>,
+    const D: usize,
+>(
+    error: WitnessError<F>,
+    prover_data: &ProverOnlyCircuitData<F, C, D>,
+    generator: Option<&WitnessGeneratorRef<F, D>>,
+) -> WitnessError<F> {
+    match error {
+        WitnessError::TargetConflict {
+            target,
+            old_value,
+            new_value,
+            gate,
+            context: _,
+            generator: _,
+        } => WitnessError::TargetConflict {
+            target,
+            old_value,
+            new_value,
+            gate,
+            context: gate
+                .and_then(|gate| prover_data.gate_context(gate))
+                .map(ToString::to_string),
+            generator: generator.map(|g| g.0.id()),
+        },
+        error => error,
+    }
 }
 
 /// A generator participates in the generation of the witness.
@@ -418,3 +552,112 @@ impl<F: RichField + Extendable<D>, const D: usize> SimpleGenerator<F, D> for Con
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+
+    use super::*;
+    use crate::field::types::Sample;
+    use crate::hash::poseidon::PoseidonHash;
+    use crate::iop::witness::{PartialWitness, WitnessWrite};
+    use crate::plonk::circuit_builder::CircuitBuilder;
+    use crate::plonk::circuit_data::CircuitConfig;
+    use crate::plonk::config::{Hasher, PoseidonGoldilocksConfig};
+    use crate::with_context;
+
+    #[test]
+    fn test_generate_partial_witness() -> Result<()> {
+        const D: usize = 2;
+        type C = PoseidonGoldilocksConfig;
+        type F = <C as GenericConfig<D>>::F;
+
+        // Independent hashes, whose generators can run at the same time, and a chain of hashes,
+        // whose generators must run one after the other.
+        let config = CircuitConfig::standard_recursion_config();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+        let inputs = builder.add_virtual_targets(4);
+        let independent_hashes = (0..16)
+            .map(|i| {
+                let mut preimage = inputs.clone();
+                preimage.push(builder.constant(F::from_canonical_usize(i)));
+                builder.hash_n_to_hash_no_pad::<PoseidonHash>(preimage)
+            })
+            .collect::<Vec<_>>();
+        let mut chained_hash = builder.hash_n_to_hash_no_pad::<PoseidonHash>(inputs.clone());
+        for _ in 0..8 {
+            chained_hash =
+                builder.hash_n_to_hash_no_pad::<PoseidonHash>(chained_hash.elements.to_vec());
+        }
+        let data = builder.build::<C>();
+
+        let input_values = F::rand_vec(inputs.len());
+        let mut pw = PartialWitness::new();
+        pw.set_target_arr(&inputs, &input_values);
+        let witness = generate_partial_witness(pw, &data.prover_only, &data.common)?;
+
+        for (i, &hash) in independent_hashes.iter().enumerate() {
+            let mut preimage = input_values.clone();
+            preimage.push(F::from_canonical_usize(i));
+            assert_eq!(
+                witness.get_hash_target(hash),
+                PoseidonHash::hash_no_pad(&preimage)
+            );
+        }
+        let mut expected_chained_hash = PoseidonHash::hash_no_pad(&input_values);
+        for _ in 0..8 {
+            expected_chained_hash = PoseidonHash::hash_no_pad(&expected_chained_hash.elements);
+        }
+        assert_eq!(witness.get_hash_target(chained_hash), expected_chained_hash);
+        Ok(())
+    }
+
+    #[test]
+    fn test_witness_errors() -> Result<()> {
+        const D: usize = 2;
+        type C = PoseidonGoldilocksConfig;
+        type F = <C as GenericConfig<D>>::F;
+
+        let config = CircuitConfig::standard_recursion_config();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+        let x = builder.add_virtual_target();
+        let y = with_context!(builder, "double", builder.add(x, x));
+        let data = builder.build::<C>();
+
+        // `y` is set as an input, but the generator of the `ArithmeticGate` then computes a
+        // different value for it.
+        let mut pw = PartialWitness::new();
+        pw.set_target(x, F::ONE);
+        pw.set_target(y, F::from_canonical_u64(3));
+        let error = generate_partial_witness(pw.clone(), &data.prover_only, &data.common)
+            .expect_err("Witness generation should fail.");
+        let Target::Wire(Wire { row, .. }) = y else {
+            panic!("Expected a wire target.");
+        };
+        assert_eq!(
+            error,
+            WitnessError::TargetConflict {
+                target: y,
+                old_value: F::from_canonical_u64(3),
+                new_value: F::TWO,
+                gate: Some(row),
+                context: Some("root > double".to_string()),
+                generator: Some("ArithmeticBaseGenerator".to_string()),
+            }
+        );
+        // The same error is returned when proving.
+        let prove_error = data.prove(pw).expect_err("Proving should fail.");
+        assert_eq!(prove_error.downcast::<WitnessError<F>>()?, error);
+
+        // `x` is never set, so the `ArithmeticGate` generator can't run.
+        let error = data
+            .prove(PartialWitness::new())
+            .expect_err("Proving should fail.");
+        assert!(matches!(
+            error.downcast::<WitnessError<F>>()?,
+            WitnessError::GeneratorsNotRun { .. }
+        ));
+
+        Ok(())
+    }
+}
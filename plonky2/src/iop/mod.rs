@@ -6,3 +6,4 @@ pub mod generator;
 pub mod target;
 pub mod wire;
 pub mod witness;
+pub mod witness_error;
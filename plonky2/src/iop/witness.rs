@@ -3,16 +3,21 @@ use alloc::{vec, vec::Vec};
 
 use hashbrown::HashMap;
 use itertools::{zip_eq, Itertools};
+use num::BigUint;
 
 use crate::field::extension::{Extendable, FieldExtension};
-use crate::field::types::Field;
+use crate::field::types::{Field, PrimeField};
 use crate::fri::structure::{FriOpenings, FriOpeningsTarget};
 use crate::fri::witness_util::set_fri_proof_target;
+use crate::gadgets::arithmetic_u32::U32Target;
+use crate::gadgets::biguint::BigUintTarget;
+use crate::gadgets::nonnative::NonNativeTarget;
 use crate::hash::hash_types::{HashOut, HashOutTarget, MerkleCapTarget, RichField};
 use crate::hash::merkle_tree::MerkleCap;
 use crate::iop::ext_target::ExtensionTarget;
 use crate::iop::target::{BoolTarget, Target};
 use crate::iop::wire::Wire;
+use crate::iop::witness_error::WitnessError;
 use crate::plonk::circuit_data::{VerifierCircuitTarget, VerifierOnlyCircuitData};
 use crate::plonk::config::{AlgebraicHasher, GenericConfig};
 use crate::plonk::proof::{Proof, ProofTarget, ProofWithPublicInputs, ProofWithPublicInputsTarget};
@@ -67,6 +72,27 @@ pub trait WitnessWrite<F: Field> {
         self.set_target(target.target, F::from_bool(value))
     }
 
+    fn set_u32_target(&mut self, target: U32Target, value: u32) {
+        self.set_target(target.0, F::from_canonical_u32(value))
+    }
+
+    fn set_biguint_target(&mut self, target: &BigUintTarget, value: &BigUint) {
+        let mut limbs = value.to_u32_digits();
+        assert!(
+            target.num_limbs() >= limbs.len(),
+            "value does not fit in {} limbs",
+            target.num_limbs()
+        );
+        limbs.resize(target.num_limbs(), 0);
+        for (&t, l) in target.limbs.iter().zip(limbs) {
+            self.set_u32_target(t, l);
+        }
+    }
+
+    fn set_nonnative_target<FF: PrimeField>(&mut self, target: &NonNativeTarget<FF>, value: FF) {
+        self.set_biguint_target(&target.value, &value.to_canonical_biguint())
+    }
+
     /// Set the targets in a `ProofWithPublicInputsTarget` to their corresponding values in a
     /// `ProofWithPublicInputs`.
     fn set_proof_with_pis_target<C: GenericConfig<D, F = F>, const D: usize>(
@@ -216,6 +242,33 @@ pub trait Witness<F: Field>: WitnessWrite<F> {
         panic!("not a bool")
     }
 
+    fn get_u32_target(&self, target: U32Target) -> u32
+    where
+        F: RichField,
+    {
+        let value = self.get_target(target.0).to_canonical_u64();
+        u32::try_from(value).expect("not a u32")
+    }
+
+    fn get_biguint_target(&self, target: &BigUintTarget) -> BigUint
+    where
+        F: RichField,
+    {
+        let limbs = target
+            .limbs
+            .iter()
+            .map(|&l| self.get_u32_target(l))
+            .collect::<Vec<_>>();
+        BigUint::from_slice(&limbs)
+    }
+
+    fn get_nonnative_target<FF: PrimeField>(&self, target: &NonNativeTarget<FF>) -> FF
+    where
+        F: RichField,
+    {
+        FF::from_noncanonical_biguint(self.get_biguint_target(&target.value))
+    }
+
     fn get_hash_target(&self, ht: HashOutTarget) -> HashOut<F> {
         HashOut {
             elements: self.get_targets(&ht.elements).try_into().unwrap(),
@@ -315,21 +368,47 @@ impl<'a, F: Field> PartitionWitness<'a, F> {
         }
     }
 
+    /// Set a `Target`, returning an error if it was already set to a different value.
+    pub fn set_target(&mut self, target: Target, value: F) -> Result<(), WitnessError<F>> {
+        self.set_target_returning_rep(target, value)?;
+        Ok(())
+    }
+
     /// Set a `Target`. On success, returns the representative index of the newly-set target. If the
-    /// target was already set, returns `None`.
-    pub fn set_target_returning_rep(&mut self, target: Target, value: F) -> Option<usize> {
+    /// target was already set to the same value, returns `None`.
+    pub fn set_target_returning_rep(
+        &mut self,
+        target: Target,
+        value: F,
+    ) -> Result<Option<usize>, WitnessError<F>> {
         let rep_index = self.representative_map[self.target_index(target)];
         let rep_value = &mut self.values[rep_index];
-        if let Some(old_value) = *rep_value {
-            assert_eq!(
-                value, old_value,
-                "Partition containing {:?} was set twice with different values: {} != {}",
-                target, old_value, value
-            );
-            None
-        } else {
-            *rep_value = Some(value);
-            Some(rep_index)
+        match *rep_value {
+            Some(old_value) if old_value == value => Ok(None),
+            Some(old_value) => Err(WitnessError::TargetConflict {
+                target,
+                old_value,
+                new_value: value,
+                gate: self.gate(target),
+                context: None,
+                generator: None,
+            }),
+            None => {
+                *rep_value = Some(value);
+                Ok(Some(rep_index))
+            }
+        }
+    }
+
+    /// The gate holding `target`. For a virtual target, this is the gate holding the representative
+    /// of its partition, if that representative is a wire.
+    pub(crate) fn gate(&self, target: Target) -> Option<usize> {
+        match target {
+            Target::Wire(Wire { row, .. }) => Some(row),
+            Target::VirtualTarget { .. } => {
+                let rep_index = self.representative_map[self.target_index(target)];
+                (rep_index < self.degree * self.num_wires).then(|| rep_index / self.num_wires)
+            }
         }
     }
 
@@ -354,7 +433,9 @@ impl<'a, F: Field> PartitionWitness<'a, F> {
 
 impl<'a, F: Field> WitnessWrite<F> for PartitionWitness<'a, F> {
     fn set_target(&mut self, target: Target, value: F) {
-        self.set_target_returning_rep(target, value);
+        if let Err(e) = PartitionWitness::set_target(self, target, value) {
+            panic!("{e}");
+        }
     }
 }
 
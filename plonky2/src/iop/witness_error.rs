@@ -0,0 +1,73 @@
+//! Errors returned when generating a witness.
+
+#[cfg(not(feature = "std"))]
+use alloc::string::String;
+use core::fmt::{self, Display, Formatter};
+
+use crate::field::types::Field;
+use crate::iop::target::Target;
+
+/// The reason why a witness could not be generated.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum WitnessError<F: Field> {
+    /// A target, or another target copy-constrained to it, was set twice with different values.
+    TargetConflict {
+        target: Target,
+        old_value: F,
+        new_value: F,
+        /// The gate holding `target`. For a virtual target, this is the gate holding a wire it is
+        /// copy-constrained to, if one is known.
+        gate: Option<usize>,
+        /// The path of the innermost context, as tracked by the circuit builder, in which `gate`
+        /// was added.
+        context: Option<String>,
+        /// The ID of the generator which attempted to set the conflicting value, or `None` if the
+        /// value was given as an input or set by the prover itself.
+        generator: Option<String>,
+    },
+    /// Some generators were never run, because the targets they watch were never populated.
+    GeneratorsNotRun {
+        num_generators: usize,
+        /// The ID of the first generator which was not run.
+        first_generator: String,
+    },
+}
+
+impl<F: Field> Display for WitnessError<F> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::TargetConflict {
+                target,
+                old_value,
+                new_value,
+                gate,
+                context,
+                generator,
+            } => {
+                write!(
+                    f,
+                    "Partition containing {target:?} was set twice with different values: {old_value} != {new_value}"
+                )?;
+                if let Some(gate) = gate {
+                    write!(f, ", in gate {gate}")?;
+                }
+                if let Some(context) = context {
+                    write!(f, ", in context \"{context}\"")?;
+                }
+                if let Some(generator) = generator {
+                    write!(f, ", by generator {generator}")?;
+                }
+                Ok(())
+            }
+            Self::GeneratorsNotRun {
+                num_generators,
+                first_generator,
+            } => write!(
+                f,
+                "{num_generators} generators weren't run, starting with {first_generator}"
+            ),
+        }
+    }
+}
+
+impl<F: Field> core::error::Error for WitnessError<F> {}
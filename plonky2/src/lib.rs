@@ -11,6 +11,8 @@ pub extern crate alloc;
 #[doc(inline)]
 pub use plonky2_field as field;
 
+pub mod batch_fri;
+pub mod curve;
 pub mod fri;
 pub mod gadgets;
 pub mod gates;
@@ -456,7 +456,8 @@ fn test_big_lut() -> anyhow::Result<()> {
         F::from_canonical_u16(lut_fn(look_val_b))
     );
 
-    data.verify(proof)
+    data.verify(proof)?;
+    Ok(())
 }
 
 #[test]
@@ -507,7 +508,8 @@ fn test_many_lookups_on_big_lut() -> anyhow::Result<()> {
         F::from_canonical_u16(lut_fn(look_val_a) + lut_fn(look_val_b))
     );
 
-    data.verify(proof)
+    data.verify(proof)?;
+    Ok(())
 }
 
 fn init_logger() {
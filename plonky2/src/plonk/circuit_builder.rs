@@ -1049,9 +1049,19 @@ impl<F: RichField + Extendable<D>, const D: usize> CircuitBuilder<F, D> {
     }
 
     pub fn try_build_with_options<C: GenericConfig<D, F = F>>(
-        mut self,
+        self,
         commit_to_sigma: bool,
     ) -> (CircuitData<F, C, D>, bool) {
+        let (circuit_data, success, _) = self.build_with_gate_instances(commit_to_sigma);
+        (circuit_data, success)
+    }
+
+    /// Builds the circuit, also returning the gate instances of every row, which are otherwise only
+    /// committed to.
+    fn build_with_gate_instances<C: GenericConfig<D, F = F>>(
+        mut self,
+        commit_to_sigma: bool,
+    ) -> (CircuitData<F, C, D>, bool, Vec<GateInstance<F, D>>) {
         let mut timing = TimingTree::new("preprocess", Level::Trace);
 
         #[cfg(feature = "std")]
@@ -1116,6 +1126,7 @@ impl<F: RichField + Extendable<D>, const D: usize> CircuitBuilder<F, D> {
         let degree = self.gate_instances.len();
         debug!("Degree after blinding & padding: {}", degree);
         let degree_bits = log2_strict(degree);
+        let gate_contexts = self.context_log.spans(degree);
         let fri_params = self.fri_params(degree_bits);
         assert!(
             fri_params.total_arities() <= degree_bits + rate_bits - cap_height,
@@ -1279,6 +1290,7 @@ impl<F: RichField + Extendable<D>, const D: usize> CircuitBuilder<F, D> {
             circuit_digest,
             lookup_rows: self.lookup_rows.clone(),
             lut_to_lookups: self.lut_to_lookups.clone(),
+            gate_contexts,
         };
 
         let verifier_only = VerifierOnlyCircuitData::<C, D> {
@@ -1296,6 +1308,7 @@ impl<F: RichField + Extendable<D>, const D: usize> CircuitBuilder<F, D> {
                 common,
             },
             success,
+            self.gate_instances,
         )
     }
 
@@ -1305,10 +1318,14 @@ impl<F: RichField + Extendable<D>, const D: usize> CircuitBuilder<F, D> {
     }
 
     pub fn mock_build<C: GenericConfig<D, F = F>>(self) -> MockCircuitData<F, C, D> {
-        let circuit_data = self.build_with_options(false);
+        let (circuit_data, success, gate_instances) = self.build_with_gate_instances(false);
+        if !success {
+            panic!("Failed to build circuit");
+        }
         MockCircuitData {
             prover_only: circuit_data.prover_only,
             common: circuit_data.common,
+            gate_instances,
         }
     }
     /// Builds a "prover circuit", with data needed to generate proofs but not verify them.
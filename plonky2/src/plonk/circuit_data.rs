@@ -13,7 +13,7 @@
 //! This is useful to allow even small devices to verify plonky2 proofs.
 
 #[cfg(not(feature = "std"))]
-use alloc::{collections::BTreeMap, vec, vec::Vec};
+use alloc::{collections::BTreeMap, string::String, vec, vec::Vec};
 use core::ops::{Range, RangeFrom};
 #[cfg(feature = "std")]
 use std::collections::BTreeMap;
@@ -32,22 +32,27 @@ use crate::fri::structure::{
     FriPolynomialInfo,
 };
 use crate::fri::{FriConfig, FriParams};
-use crate::gates::gate::GateRef;
+use crate::gates::gate::{GateInstance, GateRef};
 use crate::gates::lookup::Lookup;
 use crate::gates::lookup_table::LookupTable;
 use crate::gates::selectors::SelectorsInfo;
 use crate::hash::hash_types::{HashOutTarget, MerkleCapTarget, RichField};
 use crate::hash::merkle_tree::MerkleCap;
 use crate::iop::ext_target::ExtensionTarget;
-use crate::iop::generator::{generate_partial_witness, WitnessGeneratorRef};
+use crate::iop::generator::{generate_partial_witness, with_provenance, WitnessGeneratorRef};
 use crate::iop::target::Target;
 use crate::iop::witness::{PartialWitness, PartitionWitness};
+use crate::iop::witness_error::WitnessError;
 use crate::plonk::circuit_builder::CircuitBuilder;
 use crate::plonk::config::{GenericConfig, Hasher};
+use crate::plonk::constraint_check::{check_constraints, check_lookups, ConstraintViolation};
 use crate::plonk::plonk_common::PlonkOracle;
 use crate::plonk::proof::{CompressedProofWithPublicInputs, ProofWithPublicInputs};
-use crate::plonk::prover::prove;
-use crate::plonk::verifier::verify;
+use crate::plonk::prover::{
+    prove, prove_batch, prove_with_context, set_lookup_wires, ProverContext,
+};
+use crate::plonk::verification_error::VerificationError;
+use crate::plonk::verifier::{verify, verify_batch, verify_with_public_inputs_hash};
 use crate::util::serialization::{
     Buffer, GateSerializer, IoResult, Read, WitnessGeneratorSerializer, Write,
 };
@@ -141,18 +146,62 @@ impl CircuitConfig {
 }
 
 /// Mock circuit data to only do witness generation without generating a proof.
-#[derive(Eq, PartialEq, Debug)]
+#[derive(Debug)]
 pub struct MockCircuitData<F: RichField + Extendable<D>, C: GenericConfig<D, F = F>, const D: usize>
 {
     pub prover_only: ProverOnlyCircuitData<F, C, D>,
     pub common: CommonCircuitData<F, D>,
+    /// The gate and its constants for each row of the circuit.
+    pub gate_instances: Vec<GateInstance<F, D>>,
+}
+
+// The gate instances are determined by the rest of the circuit data, so they are left out.
+impl<F: RichField + Extendable<D>, C: GenericConfig<D, F = F>, const D: usize> PartialEq
+    for MockCircuitData<F, C, D>
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.prover_only == other.prover_only && self.common == other.common
+    }
+}
+
+impl<F: RichField + Extendable<D>, C: GenericConfig<D, F = F>, const D: usize> Eq
+    for MockCircuitData<F, C, D>
+{
 }
 
 impl<F: RichField + Extendable<D>, C: GenericConfig<D, F = F>, const D: usize>
     MockCircuitData<F, C, D>
 {
-    pub fn generate_witness(&self, inputs: PartialWitness<F>) -> PartitionWitness<F> {
-        generate_partial_witness::<F, C, D>(inputs, &self.prover_only, &self.common)
+    /// Runs the witness generators, then sets the lookup multiplicities and the padding of the
+    /// lookup gates as the prover does. The latter is skipped if some lookup is unset or is not an
+    /// entry of its table, which `check_constraints` then reports.
+    pub fn generate_witness(
+        &self,
+        inputs: PartialWitness<F>,
+    ) -> Result<PartitionWitness<'_, F>, WitnessError<F>> {
+        let mut witness =
+            generate_partial_witness::<F, C, D>(inputs, &self.prover_only, &self.common)?;
+        if check_lookups(&self.prover_only, &self.common, &witness).is_ok() {
+            set_lookup_wires(&self.prover_only, &self.common, &mut witness)
+                .map_err(|e| with_provenance(e, &self.prover_only, None))?;
+        }
+        Ok(witness)
+    }
+
+    /// Checks a witness, as returned by `generate_witness`, against the lookups, lookup
+    /// multiplicities, gate constraints and copy constraints of the circuit, and reports the first
+    /// violated one along with the context in which it was added. This is much cheaper than
+    /// proving, and pinpoints failures which would otherwise only show up as an invalid proof.
+    pub fn check_constraints(
+        &self,
+        witness: PartitionWitness<F>,
+    ) -> Result<(), ConstraintViolation<F>> {
+        check_constraints::<F, C, D>(
+            &self.prover_only,
+            &self.common,
+            &self.gate_instances,
+            witness,
+        )
     }
 }
 
@@ -195,14 +244,74 @@ impl<F: RichField + Extendable<D>, C: GenericConfig<D, F = F>, const D: usize>
         )
     }
 
-    pub fn verify(&self, proof_with_pis: ProofWithPublicInputs<F, C, D>) -> Result<()> {
+    /// Proves the statements given by `inputs`, generating the witness of each statement while
+    /// the previous one is being proven.
+    pub fn prove_batch(
+        &self,
+        inputs: impl IntoIterator<Item = PartialWitness<F>>,
+    ) -> Result<Vec<ProofWithPublicInputs<F, C, D>>> {
+        prove_batch::<F, C, D>(
+            &self.prover_only,
+            &self.common,
+            inputs,
+            &mut TimingTree::default(),
+        )
+    }
+
+    /// Creates a context for proving many statements of this circuit with `prove_with_context`.
+    pub fn prover_context(&self) -> ProverContext<F> {
+        ProverContext::new(&self.prover_only, &self.common)
+    }
+
+    /// Like `prove`, but reuses the precomputed data and buffers of `context`, see
+    /// `prover_context`.
+    pub fn prove_with_context(
+        &self,
+        inputs: PartialWitness<F>,
+        context: &mut ProverContext<F>,
+    ) -> Result<ProofWithPublicInputs<F, C, D>> {
+        prove_with_context::<F, C, D>(
+            &self.prover_only,
+            &self.common,
+            inputs,
+            context,
+            &mut TimingTree::default(),
+        )
+    }
+
+    pub fn verify(
+        &self,
+        proof_with_pis: ProofWithPublicInputs<F, C, D>,
+    ) -> Result<(), VerificationError> {
         verify::<F, C, D>(proof_with_pis, &self.verifier_only, &self.common)
     }
 
+    /// Verifies a proof, additionally checking that its public inputs hash to the given value.
+    pub fn verify_with_public_inputs_hash(
+        &self,
+        proof_with_pis: ProofWithPublicInputs<F, C, D>,
+        public_inputs_hash: <<C as GenericConfig<D>>::InnerHasher as Hasher<F>>::Hash,
+    ) -> Result<(), VerificationError> {
+        verify_with_public_inputs_hash::<F, C, D>(
+            proof_with_pis,
+            public_inputs_hash,
+            &self.verifier_only,
+            &self.common,
+        )
+    }
+
+    /// Verifies many proofs of this circuit in parallel, returning one result per proof.
+    pub fn verify_batch(
+        &self,
+        proofs_with_pis: &[ProofWithPublicInputs<F, C, D>],
+    ) -> Vec<Result<(), VerificationError>> {
+        verify_batch::<F, C, D>(proofs_with_pis, &self.verifier_only, &self.common)
+    }
+
     pub fn verify_compressed(
         &self,
         compressed_proof_with_pis: CompressedProofWithPublicInputs<F, C, D>,
-    ) -> Result<()> {
+    ) -> Result<(), VerificationError> {
         compressed_proof_with_pis.verify(&self.verifier_only, &self.common)
     }
 
@@ -292,6 +401,41 @@ impl<F: RichField + Extendable<D>, C: GenericConfig<D, F = F>, const D: usize>
             &mut TimingTree::default(),
         )
     }
+
+    /// Proves the statements given by `inputs`, generating the witness of each statement while
+    /// the previous one is being proven.
+    pub fn prove_batch(
+        &self,
+        inputs: impl IntoIterator<Item = PartialWitness<F>>,
+    ) -> Result<Vec<ProofWithPublicInputs<F, C, D>>> {
+        prove_batch::<F, C, D>(
+            &self.prover_only,
+            &self.common,
+            inputs,
+            &mut TimingTree::default(),
+        )
+    }
+
+    /// Creates a context for proving many statements of this circuit with `prove_with_context`.
+    pub fn prover_context(&self) -> ProverContext<F> {
+        ProverContext::new(&self.prover_only, &self.common)
+    }
+
+    /// Like `prove`, but reuses the precomputed data and buffers of `context`, see
+    /// `prover_context`.
+    pub fn prove_with_context(
+        &self,
+        inputs: PartialWitness<F>,
+        context: &mut ProverContext<F>,
+    ) -> Result<ProofWithPublicInputs<F, C, D>> {
+        prove_with_context::<F, C, D>(
+            &self.prover_only,
+            &self.common,
+            inputs,
+            context,
+            &mut TimingTree::default(),
+        )
+    }
 }
 
 /// Circuit data required by the prover.
@@ -322,14 +466,39 @@ impl<F: RichField + Extendable<D>, C: GenericConfig<D, F = F>, const D: usize>
         buffer.read_verifier_circuit_data(gate_serializer)
     }
 
-    pub fn verify(&self, proof_with_pis: ProofWithPublicInputs<F, C, D>) -> Result<()> {
+    pub fn verify(
+        &self,
+        proof_with_pis: ProofWithPublicInputs<F, C, D>,
+    ) -> Result<(), VerificationError> {
         verify::<F, C, D>(proof_with_pis, &self.verifier_only, &self.common)
     }
 
+    /// Verifies a proof, additionally checking that its public inputs hash to the given value.
+    pub fn verify_with_public_inputs_hash(
+        &self,
+        proof_with_pis: ProofWithPublicInputs<F, C, D>,
+        public_inputs_hash: <<C as GenericConfig<D>>::InnerHasher as Hasher<F>>::Hash,
+    ) -> Result<(), VerificationError> {
+        verify_with_public_inputs_hash::<F, C, D>(
+            proof_with_pis,
+            public_inputs_hash,
+            &self.verifier_only,
+            &self.common,
+        )
+    }
+
+    /// Verifies many proofs of this circuit in parallel, returning one result per proof.
+    pub fn verify_batch(
+        &self,
+        proofs_with_pis: &[ProofWithPublicInputs<F, C, D>],
+    ) -> Vec<Result<(), VerificationError>> {
+        verify_batch::<F, C, D>(proofs_with_pis, &self.verifier_only, &self.common)
+    }
+
     pub fn verify_compressed(
         &self,
         compressed_proof_with_pis: CompressedProofWithPublicInputs<F, C, D>,
-    ) -> Result<()> {
+    ) -> Result<(), VerificationError> {
         compressed_proof_with_pis.verify(&self.verifier_only, &self.common)
     }
 }
@@ -365,11 +534,23 @@ pub struct ProverOnlyCircuitData<
     pub lookup_rows: Vec<LookupWire>,
     /// A vector of (looking_in, looking_out) pairs for each lookup table index.
     pub lut_to_lookups: Vec<Lookup>,
+    /// The range of gates added within each context of the circuit builder, along with the path of
+    /// that context, listed in pre-order.
+    pub gate_contexts: Vec<(Range<usize>, String)>,
 }
 
 impl<F: RichField + Extendable<D>, C: GenericConfig<D, F = F>, const D: usize>
     ProverOnlyCircuitData<F, C, D>
 {
+    /// The path of the innermost context in which the given gate was added.
+    pub fn gate_context(&self, gate: usize) -> Option<&str> {
+        self.gate_contexts
+            .iter()
+            .rev()
+            .find(|(gates, _)| gates.contains(&gate))
+            .map(|(_, path)| path.as_str())
+    }
+
     pub fn to_bytes(
         &self,
         generator_serializer: &dyn WitnessGeneratorSerializer<F, D>,
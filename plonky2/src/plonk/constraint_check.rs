@@ -0,0 +1,491 @@
+//! Checks a witness against the constraints of a circuit without generating a proof, to locate the
+//! constraints it violates.
+
+#[cfg(not(feature = "std"))]
+use alloc::{
+    string::{String, ToString},
+    vec,
+    vec::Vec,
+};
+use core::fmt::{self, Display, Formatter};
+
+use hashbrown::HashMap;
+
+use crate::field::extension::Extendable;
+use crate::field::types::Field;
+use crate::gates::gate::GateInstance;
+use crate::gates::lookup::LookupGate;
+use crate::gates::lookup_table::LookupTableGate;
+use crate::gates::util::StridedConstraintConsumer;
+use crate::hash::hash_types::RichField;
+use crate::iop::target::Target;
+use crate::iop::wire::Wire;
+use crate::iop::witness::{PartitionWitness, Witness};
+use crate::plonk::circuit_data::{CommonCircuitData, ProverOnlyCircuitData};
+use crate::plonk::config::{GenericConfig, Hasher};
+use crate::plonk::vars::EvaluationVarsBase;
+use crate::util::strided_view::PackedStridedView;
+
+/// A constraint of a circuit which is not satisfied by a witness.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ConstraintViolation<F: Field> {
+    /// A constraint of the gate in the given row does not evaluate to zero.
+    Gate {
+        row: usize,
+        /// The ID of the gate in `row`.
+        gate: String,
+        /// The index of the constraint within the constraints of the gate.
+        constraint: usize,
+        /// The value of the constraint, which should be zero.
+        value: F,
+        /// The path of the innermost context in which the gate was added.
+        context: Option<String>,
+    },
+    /// Two wires related by a copy constraint hold different values.
+    CopyConstraint {
+        wire: Wire,
+        copied_wire: Wire,
+        value: F,
+        copied_value: F,
+        /// The path of the innermost context in which the gate of `wire` was added.
+        context: Option<String>,
+    },
+    /// A target which a lookup depends on has no value in the witness.
+    UnsetTarget {
+        target: Target,
+        /// The gate holding `target`, if known.
+        gate: Option<usize>,
+        /// The path of the innermost context in which `gate` was added.
+        context: Option<String>,
+    },
+    /// A looked-up `(input, output)` pair is not an entry of its lookup table.
+    Lookup {
+        lut_index: usize,
+        input: F,
+        output: F,
+        /// The gate holding the looked-up input, if known.
+        gate: Option<usize>,
+        /// The path of the innermost context in which `gate` was added.
+        context: Option<String>,
+    },
+    /// The multiplicity of an entry of a lookup table differs from the number of times it is looked
+    /// up, including by the padding of the last lookup gate.
+    LookupMultiplicity {
+        lut_index: usize,
+        /// The index of the entry in its lookup table.
+        entry: usize,
+        expected: usize,
+        actual: F,
+        /// The lookup table gate holding the multiplicity.
+        gate: usize,
+        /// The path of the innermost context in which `gate` was added.
+        context: Option<String>,
+    },
+}
+
+impl<F: Field> Display for ConstraintViolation<F> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Gate {
+                row,
+                gate,
+                constraint,
+                value,
+                context,
+            } => {
+                write!(
+                    f,
+                    "Constraint {constraint} of gate {gate} in row {row} evaluates to {value}"
+                )?;
+                write_context(f, context)
+            }
+            Self::CopyConstraint {
+                wire,
+                copied_wire,
+                value,
+                copied_value,
+                context,
+            } => {
+                write!(
+                    f,
+                    "Copy constraint between {wire:?} and {copied_wire:?} is violated: {value} != {copied_value}"
+                )?;
+                write_context(f, context)
+            }
+            Self::UnsetTarget {
+                target,
+                gate,
+                context,
+            } => {
+                write!(f, "Lookup target {target:?} is not set")?;
+                if let Some(gate) = gate {
+                    write!(f, ", in gate {gate}")?;
+                }
+                write_context(f, context)
+            }
+            Self::Lookup {
+                lut_index,
+                input,
+                output,
+                gate,
+                context,
+            } => {
+                write!(
+                    f,
+                    "Lookup ({input}, {output}) is not an entry of lookup table {lut_index}"
+                )?;
+                if let Some(gate) = gate {
+                    write!(f, ", in gate {gate}")?;
+                }
+                write_context(f, context)
+            }
+            Self::LookupMultiplicity {
+                lut_index,
+                entry,
+                expected,
+                actual,
+                gate,
+                context,
+            } => {
+                write!(
+                    f,
+                    "Entry {entry} of lookup table {lut_index} has multiplicity {actual} but is looked up {expected} times, in gate {gate}"
+                )?;
+                write_context(f, context)
+            }
+        }
+    }
+}
+
+impl<F: Field> core::error::Error for ConstraintViolation<F> {}
+
+fn write_context(f: &mut Formatter<'_>, context: &Option<String>) -> fmt::Result {
+    match context {
+        Some(context) => write!(f, ", in context \"{context}\""),
+        None => Ok(()),
+    }
+}
+
+/// Checks that every lookup of the circuit, other than the padding of the lookup gates, is set in
+/// `witness` and is an entry of its lookup table.
+pub(crate) fn check_lookups<
+    F: RichField + Extendable<D>,
+    C: GenericConfig<D, F = F>,
+    const D: usize,
+>(
+    prover_data: &ProverOnlyCircuitData<F, C, D>,
+    common_data: &CommonCircuitData<F, D>,
+    witness: &PartitionWitness<F>,
+) -> Result<(), ConstraintViolation<F>> {
+    for (lut_index, lookups) in prover_data.lut_to_lookups.iter().enumerate() {
+        for &(input_target, output_target) in lookups {
+            check_lookup(
+                prover_data,
+                common_data,
+                witness,
+                lut_index,
+                input_target,
+                output_target,
+            )?;
+        }
+    }
+    Ok(())
+}
+
+/// Checks that the lookup of `(input_target, output_target)` in lookup table `lut_index` is set in
+/// `witness` and is an entry of the table, and returns the index of that entry.
+fn check_lookup<F: RichField + Extendable<D>, C: GenericConfig<D, F = F>, const D: usize>(
+    prover_data: &ProverOnlyCircuitData<F, C, D>,
+    common_data: &CommonCircuitData<F, D>,
+    witness: &PartitionWitness<F>,
+    lut_index: usize,
+    input_target: Target,
+    output_target: Target,
+) -> Result<usize, ConstraintViolation<F>> {
+    let context = |gate: Option<usize>| {
+        gate.and_then(|gate| prover_data.gate_context(gate))
+            .map(ToString::to_string)
+    };
+    let get = |target: Target| {
+        witness.try_get_target(target).ok_or_else(|| {
+            let gate = witness.gate(target);
+            ConstraintViolation::UnsetTarget {
+                target,
+                gate,
+                context: context(gate),
+            }
+        })
+    };
+
+    let input = get(input_target)?;
+    let output = get(output_target)?;
+    common_data.luts[lut_index]
+        .iter()
+        .position(|&(inp, out)| {
+            F::from_canonical_u16(inp) == input && F::from_canonical_u16(out) == output
+        })
+        .ok_or_else(|| {
+            let gate = witness.gate(input_target);
+            ConstraintViolation::Lookup {
+                lut_index,
+                input,
+                output,
+                gate,
+                context: context(gate),
+            }
+        })
+}
+
+/// Checks a complete witness, as returned by `MockCircuitData::generate_witness`, against every
+/// lookup, lookup multiplicity, gate constraint and copy constraint of the circuit, returning the
+/// first violation found.
+pub(crate) fn check_constraints<
+    F: RichField + Extendable<D>,
+    C: GenericConfig<D, F = F>,
+    const D: usize,
+>(
+    prover_data: &ProverOnlyCircuitData<F, C, D>,
+    common_data: &CommonCircuitData<F, D>,
+    gate_instances: &[GateInstance<F, D>],
+    witness: PartitionWitness<F>,
+) -> Result<(), ConstraintViolation<F>> {
+    let context = |gate: Option<usize>| {
+        gate.and_then(|gate| prover_data.gate_context(gate))
+            .map(ToString::to_string)
+    };
+
+    check_lookups(prover_data, common_data, &witness)?;
+    let num_lookup_slots = LookupGate::num_slots(&common_data.config);
+    let num_lut_slots = LookupTableGate::num_slots(&common_data.config);
+    for (lut_index, lookup_wire) in prover_data.lookup_rows.iter().enumerate() {
+        let lookups = &prover_data.lut_to_lookups[lut_index];
+        let mut multiplicities = vec![0; common_data.luts[lut_index].len()];
+        for &(input_target, output_target) in lookups {
+            let entry = check_lookup(
+                prover_data,
+                common_data,
+                &witness,
+                lut_index,
+                input_target,
+                output_target,
+            )?;
+            multiplicities[entry] += 1;
+        }
+
+        // The unused slots of the last lookup gate are padded with entries of the table, which are
+        // counted in the multiplicities too.
+        let num_padding_slots =
+            (num_lookup_slots - lookups.len() % num_lookup_slots) % num_lookup_slots;
+        let last_lookup_gate = lookup_wire.last_lut_gate - 1;
+        for slot in (num_lookup_slots - num_padding_slots)..num_lookup_slots {
+            let entry = check_lookup(
+                prover_data,
+                common_data,
+                &witness,
+                lut_index,
+                Target::wire(last_lookup_gate, LookupGate::wire_ith_looking_inp(slot)),
+                Target::wire(last_lookup_gate, LookupGate::wire_ith_looking_out(slot)),
+            )?;
+            multiplicities[entry] += 1;
+        }
+
+        for (entry, &expected) in multiplicities.iter().enumerate() {
+            let gate = lookup_wire.first_lut_gate - entry / num_lut_slots;
+            let target = Target::wire(
+                gate,
+                LookupTableGate::wire_ith_multiplicity(entry % num_lut_slots),
+            );
+            let actual =
+                witness
+                    .try_get_target(target)
+                    .ok_or_else(|| ConstraintViolation::UnsetTarget {
+                        target,
+                        gate: Some(gate),
+                        context: context(Some(gate)),
+                    })?;
+            if actual != F::from_canonical_usize(expected) {
+                return Err(ConstraintViolation::LookupMultiplicity {
+                    lut_index,
+                    entry,
+                    expected,
+                    actual,
+                    gate,
+                    context: context(Some(gate)),
+                });
+            }
+        }
+    }
+
+    let public_inputs = witness.get_targets(&prover_data.public_inputs);
+    let public_inputs_hash = C::InnerHasher::hash_no_pad(&public_inputs);
+    let num_wires = common_data.config.num_wires;
+    let witness = witness.full_witness();
+
+    for (row, instance) in gate_instances.iter().enumerate() {
+        let gate = &instance.gate_ref.0;
+        let local_wires = (0..num_wires)
+            .map(|column| witness.get_wire(row, column))
+            .collect::<Vec<_>>();
+        let vars = EvaluationVarsBase {
+            local_constants: PackedStridedView::new(&instance.constants, 1, 0),
+            local_wires: PackedStridedView::new(&local_wires, 1, 0),
+            public_inputs_hash: &public_inputs_hash,
+        };
+        let mut constraints = vec![F::ZERO; gate.num_constraints()];
+        gate.eval_unfiltered_base_one(vars, StridedConstraintConsumer::new(&mut constraints, 1, 0));
+        if let Some((constraint, &value)) = constraints
+            .iter()
+            .enumerate()
+            .find(|(_, value)| !value.is_zero())
+        {
+            return Err(ConstraintViolation::Gate {
+                row,
+                gate: gate.id(),
+                constraint,
+                value,
+                context: context(Some(row)),
+            });
+        }
+    }
+
+    // Each routed wire must hold the same value as the wire it is mapped to by the permutation
+    // encoded in the sigma polynomials.
+    let wire_by_sigma = common_data
+        .k_is
+        .iter()
+        .enumerate()
+        .flat_map(|(column, &k_i)| {
+            prover_data
+                .subgroup
+                .iter()
+                .enumerate()
+                .map(move |(row, &x)| (k_i * x, Wire { row, column }))
+        })
+        .collect::<HashMap<_, _>>();
+    for (row, sigmas) in prover_data.sigmas.iter().enumerate() {
+        for (column, sigma) in sigmas.iter().enumerate() {
+            let copied_wire = wire_by_sigma[sigma];
+            let value = witness.get_wire(row, column);
+            let copied_value = witness.get_wire(copied_wire.row, copied_wire.column);
+            if value != copied_value {
+                return Err(ConstraintViolation::CopyConstraint {
+                    wire: Wire { row, column },
+                    copied_wire,
+                    value,
+                    copied_value,
+                    context: context(Some(row)),
+                });
+            }
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(not(feature = "std"))]
+    use alloc::{sync::Arc, vec};
+    #[cfg(feature = "std")]
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::iop::witness::{PartialWitness, WitnessWrite};
+    use crate::plonk::circuit_builder::CircuitBuilder;
+    use crate::plonk::circuit_data::CircuitConfig;
+    use crate::plonk::config::PoseidonGoldilocksConfig;
+    use crate::with_context;
+
+    const D: usize = 2;
+    type C = PoseidonGoldilocksConfig;
+    type F = <C as GenericConfig<D>>::F;
+
+    /// Overwrites the value of the partition containing `target`, bypassing the generators.
+    fn tamper(witness: &mut PartitionWitness<F>, target: Target, value: Option<F>) {
+        let rep_index = witness.representative_map[witness.target_index(target)];
+        witness.values[rep_index] = value;
+    }
+
+    #[test]
+    fn test_check_constraints() {
+        let config = CircuitConfig::standard_recursion_config();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+        let x = builder.add_virtual_target();
+        let x_squared = with_context!(builder, "square", builder.mul(x, x));
+        let lut_index = builder.add_lookup_table_from_pairs(Arc::new(vec![(0, 1), (1, 2), (2, 4)]));
+        let y = builder.add_lookup_from_index(x, lut_index);
+        builder.register_public_input(x_squared);
+        builder.register_public_input(y);
+        let data = builder.mock_build::<C>();
+
+        let mut pw = PartialWitness::new();
+        pw.set_target(x, F::TWO);
+        let witness = data.generate_witness(pw).unwrap();
+        assert_eq!(data.check_constraints(witness.clone()), Ok(()));
+
+        let mut tampered = witness.clone();
+        tamper(&mut tampered, x_squared, Some(F::ONE));
+        let Target::Wire(Wire { row, .. }) = x_squared else {
+            panic!("Expected a wire target.");
+        };
+        assert_eq!(
+            data.check_constraints(tampered),
+            Err(ConstraintViolation::Gate {
+                row,
+                gate: data.gate_instances[row].gate_ref.0.id(),
+                constraint: 0,
+                value: F::NEG_ONE * F::from_canonical_u64(3),
+                context: Some("root > square".to_string()),
+            })
+        );
+
+        let mut tampered = witness.clone();
+        tamper(&mut tampered, y, Some(F::ONE));
+        let Err(ConstraintViolation::Lookup {
+            lut_index: 0,
+            input,
+            output,
+            gate: Some(gate),
+            context: Some(context),
+        }) = data.check_constraints(tampered)
+        else {
+            panic!("Expected a lookup violation.");
+        };
+        assert_eq!((input, output), (F::TWO, F::ONE));
+        // Lookup gates are only added when the circuit is built, outside of any user context.
+        assert!(data.gate_instances[gate]
+            .gate_ref
+            .0
+            .id()
+            .starts_with("LookupGate"));
+        assert_eq!(context, "root");
+
+        let mut tampered = witness.clone();
+        tamper(&mut tampered, y, None);
+        let Err(ConstraintViolation::UnsetTarget { target, .. }) = data.check_constraints(tampered)
+        else {
+            panic!("Expected an unset target.");
+        };
+        assert_eq!(target, y);
+
+        // `x = 2` looks up the third entry of the table once.
+        let lut_gate = data.prover_only.lookup_rows[0].first_lut_gate;
+        let mut tampered = witness;
+        tamper(
+            &mut tampered,
+            Target::wire(lut_gate, LookupTableGate::wire_ith_multiplicity(2)),
+            Some(F::TWO),
+        );
+        assert_eq!(
+            data.check_constraints(tampered),
+            Err(ConstraintViolation::LookupMultiplicity {
+                lut_index: 0,
+                entry: 2,
+                expected: 1,
+                actual: F::TWO,
+                gate: lut_gate,
+                context: Some("root".to_string()),
+            })
+        );
+    }
+}
@@ -6,6 +6,7 @@
 pub mod circuit_builder;
 pub mod circuit_data;
 pub mod config;
+pub mod constraint_check;
 pub(crate) mod copy_constraint;
 mod get_challenges;
 pub(crate) mod permutation_argument;
@@ -15,4 +16,5 @@ pub mod prover;
 mod validate_shape;
 pub(crate) mod vanishing_poly;
 pub mod vars;
+pub mod verification_error;
 pub mod verifier;
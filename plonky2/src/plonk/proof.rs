@@ -7,7 +7,6 @@
 #[cfg(not(feature = "std"))]
 use alloc::{vec, vec::Vec};
 
-use anyhow::ensure;
 use plonky2_maybe_rayon::*;
 use serde::{Deserialize, Serialize};
 
@@ -26,6 +25,7 @@ use crate::iop::ext_target::ExtensionTarget;
 use crate::iop::target::Target;
 use crate::plonk::circuit_data::{CommonCircuitData, VerifierOnlyCircuitData};
 use crate::plonk::config::{GenericConfig, Hasher};
+use crate::plonk::verification_error::{ensure_shape, VerificationError};
 use crate::plonk::verifier::verify_with_challenges;
 use crate::util::serialization::{Buffer, Read, Write};
 
@@ -191,7 +191,7 @@ impl<F: RichField + Extendable<D>, C: GenericConfig<D, F = F>, const D: usize>
         common_data: &CommonCircuitData<F, D>,
     ) -> anyhow::Result<ProofWithPublicInputs<F, C, D>> {
         let challenges =
-            self.get_challenges(self.get_public_inputs_hash(), circuit_digest, common_data)?;
+            self.get_challenges(self.get_public_inputs_hash(), circuit_digest, common_data);
         let fri_inferred_elements = self.get_inferred_elements(&challenges, common_data);
         let decompressed_proof =
             self.proof
@@ -206,28 +206,31 @@ impl<F: RichField + Extendable<D>, C: GenericConfig<D, F = F>, const D: usize>
         self,
         verifier_data: &VerifierOnlyCircuitData<C, D>,
         common_data: &CommonCircuitData<F, D>,
-    ) -> anyhow::Result<()> {
-        ensure!(
-            self.public_inputs.len() == common_data.num_public_inputs,
-            "Number of public inputs doesn't match circuit data."
-        );
+    ) -> Result<(), VerificationError> {
+        ensure_shape(
+            "public_inputs",
+            common_data.num_public_inputs,
+            self.public_inputs.len(),
+        )?;
         let public_inputs_hash = self.get_public_inputs_hash();
         let challenges = self.get_challenges(
             public_inputs_hash,
             &verifier_data.circuit_digest,
             common_data,
-        )?;
+        );
         let fri_inferred_elements = self.get_inferred_elements(&challenges, common_data);
         let decompressed_proof =
             self.proof
                 .decompress(&challenges, fri_inferred_elements, &common_data.fri_params);
         verify_with_challenges::<F, C, D>(
-            decompressed_proof,
+            &decompressed_proof,
             public_inputs_hash,
             challenges,
             verifier_data,
             common_data,
-        )
+        )?;
+
+        Ok(())
     }
 
     pub(crate) fn get_public_inputs_hash(
@@ -321,9 +324,9 @@ impl<F: RichField + Extendable<D>, const D: usize> OpeningSet<F, D> {
         common_data: &CommonCircuitData<F, D>,
     ) -> Self {
         let eval_commitment = |z: F::Extension, c: &PolynomialBatch<F, C, D>| {
-            c.polynomials
-                .par_iter()
-                .map(|p| p.to_extension().eval(z))
+            (0..c.num_polys())
+                .into_par_iter()
+                .map(|i| c.polynomial(i).to_extension().eval(z))
                 .collect::<Vec<_>>()
         };
         let constants_sigmas_eval = eval_commitment(zeta, constants_sigmas_commitment);
@@ -506,7 +509,8 @@ mod tests {
         assert_eq!(proof, decompressed_compressed_proof);
 
         verify(proof, &data.verifier_only, &data.common)?;
-        data.verify_compressed(compressed_proof)
+        data.verify_compressed(compressed_proof)?;
+        Ok(())
     }
 
     #[test]
@@ -563,6 +567,7 @@ mod tests {
         assert_eq!(proof, decompressed_compressed_proof);
 
         verify(proof, &data.verifier_only, &data.common)?;
-        data.verify_compressed(compressed_proof)
+        data.verify_compressed(compressed_proof)?;
+        Ok(())
     }
 }
@@ -3,7 +3,7 @@
 #[cfg(not(feature = "std"))]
 use alloc::{format, vec, vec::Vec};
 use core::cmp::min;
-use core::mem::swap;
+use core::mem::{size_of, swap, take};
 
 use anyhow::{ensure, Result};
 use hashbrown::HashMap;
@@ -11,18 +11,21 @@ use plonky2_maybe_rayon::*;
 
 use super::circuit_builder::{LookupChallenges, LookupWire};
 use crate::field::extension::Extendable;
+use crate::field::fft::{fft_root_table, ifft_with_options, FftRootTable};
 use crate::field::polynomial::{PolynomialCoeffs, PolynomialValues};
 use crate::field::types::Field;
 use crate::field::zero_poly_coset::ZeroPolyOnCoset;
-use crate::fri::oracle::PolynomialBatch;
+use crate::fri::oracle::{CommitmentContext, PolynomialBatch};
 use crate::gates::lookup::LookupGate;
 use crate::gates::lookup_table::LookupTableGate;
 use crate::gates::selectors::LookupSelectors;
 use crate::hash::hash_types::RichField;
+use crate::hash::merkle_tree::MerkleStorage;
 use crate::iop::challenger::Challenger;
 use crate::iop::generator::generate_partial_witness;
 use crate::iop::target::Target;
-use crate::iop::witness::{MatrixWitness, PartialWitness, PartitionWitness, Witness, WitnessWrite};
+use crate::iop::witness::{MatrixWitness, PartialWitness, PartitionWitness, Witness};
+use crate::iop::witness_error::WitnessError;
 use crate::plonk::circuit_builder::NUM_COINS_LOOKUP;
 use crate::plonk::circuit_data::{CommonCircuitData, ProverOnlyCircuitData};
 use crate::plonk::config::{GenericConfig, Hasher};
@@ -46,7 +49,7 @@ pub fn set_lookup_wires<
     prover_data: &ProverOnlyCircuitData<F, C, D>,
     common_data: &CommonCircuitData<F, D>,
     pw: &mut PartitionWitness<F>,
-) {
+) -> Result<(), WitnessError<F>> {
     for (
         lut_index,
         &LookupWire {
@@ -88,8 +91,8 @@ pub fn set_lookup_wires<
                 Target::wire(last_lut_gate - 1, LookupGate::wire_ith_looking_inp(slot));
             let out_target =
                 Target::wire(last_lut_gate - 1, LookupGate::wire_ith_looking_out(slot));
-            pw.set_target(inp_target, F::from_canonical_u16(first_inp_value));
-            pw.set_target(out_target, F::from_canonical_u16(first_out_value));
+            pw.set_target(inp_target, F::from_canonical_u16(first_inp_value))?;
+            pw.set_target(out_target, F::from_canonical_u16(first_out_value))?;
 
             multiplicities[0] += 1;
         }
@@ -104,9 +107,160 @@ pub fn set_lookup_wires<
             pw.set_target(
                 mul_target,
                 F::from_canonical_usize(multiplicities[lut_entry]),
-            );
+            )?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Data derived from a circuit which the prover would otherwise recompute for each proof, along
+/// with buffers recycled from previous proofs. Proving many statements of the same circuit with
+/// `prove_with_context` and a single context avoids these recomputations and most of the
+/// allocations of the commitments.
+#[derive(Debug)]
+pub struct ProverContext<F: Field> {
+    /// The tables reused across proofs, which are not computed for a single proof.
+    tables: Option<ProverTables<F>>,
+    /// The subgroup over whose coset the quotient polynomials are evaluated.
+    quotient_points: Vec<F>,
+    /// The evaluations of `Z_H` over that coset.
+    z_h_on_coset: ZeroPolyOnCoset<F>,
+    /// An optional bound, in bytes, on the transient buffers used by the prover, see
+    /// `with_memory_budget`.
+    memory_budget: Option<usize>,
+    /// Where the Merkle trees are stored when proving within a memory budget.
+    low_memory_storage: MerkleStorage,
+}
+
+/// The FFT tables and buffers of a `ProverContext` which only pay off over several proofs.
+#[derive(Debug)]
+struct ProverTables<F: Field> {
+    /// The FFT tables of the wire and quotient polynomials, and their recycled Merkle leaves.
+    commitment: CommitmentContext<F>,
+    /// The FFT root table of the LDEs, if the prover data does not include it.
+    lde_root_table: Option<FftRootTable<F>>,
+    /// The FFT root table for interpolating the quotient polynomials, if their LDE is smaller than
+    /// the other LDEs.
+    quotient_root_table: Option<FftRootTable<F>>,
+    /// The powers of `F::coset_shift().inverse()` needed to interpolate the quotient polynomials.
+    coset_shift_inverse_powers: Vec<F>,
+}
+
+impl<F: RichField> ProverContext<F> {
+    /// Precomputes the data used by the prover of the given circuit.
+    pub fn new<C: GenericConfig<D, F = F>, const D: usize>(
+        prover_data: &ProverOnlyCircuitData<F, C, D>,
+        common_data: &CommonCircuitData<F, D>,
+    ) -> Self
+    where
+        F: Extendable<D>,
+    {
+        let lde_size = common_data.lde_size();
+        let quotient_degree_bits = log2_ceil(common_data.quotient_degree_factor);
+        let quotient_lde_size = 1 << (common_data.degree_bits() + quotient_degree_bits);
+
+        let has_lde_root_table = prover_data
+            .fft_root_table
+            .as_ref()
+            .is_some_and(|table| table.len() == common_data.fri_params.lde_bits());
+        Self {
+            tables: Some(ProverTables {
+                commitment: CommitmentContext::new(common_data.degree()),
+                lde_root_table: (!has_lde_root_table).then(|| fft_root_table(lde_size)),
+                quotient_root_table: (quotient_lde_size != lde_size)
+                    .then(|| fft_root_table(quotient_lde_size)),
+                coset_shift_inverse_powers: F::coset_shift()
+                    .inverse()
+                    .powers()
+                    .take(quotient_lde_size)
+                    .collect(),
+            }),
+            ..Self::for_single_proof(common_data)
         }
     }
+
+    /// A context holding only the data that a single proof needs anyway, for `prove` and
+    /// `prove_with_partition_witness`.
+    fn for_single_proof<const D: usize>(common_data: &CommonCircuitData<F, D>) -> Self
+    where
+        F: Extendable<D>,
+    {
+        let degree_bits = common_data.degree_bits();
+        let quotient_degree_bits = log2_ceil(common_data.quotient_degree_factor);
+        Self {
+            tables: None,
+            quotient_points: F::two_adic_subgroup(degree_bits + quotient_degree_bits),
+            z_h_on_coset: ZeroPolyOnCoset::new(degree_bits, quotient_degree_bits),
+            memory_budget: None,
+            // With the `mmap` feature, Merkle trees default to files in the temporary directory.
+            #[cfg(feature = "mmap")]
+            low_memory_storage: MerkleStorage::Mmap(None),
+            #[cfg(not(feature = "mmap"))]
+            low_memory_storage: MerkleStorage::Memory,
+        }
+    }
+
+    /// Bounds the transient buffers used by the prover to about `memory_budget` bytes. The prover
+    /// then commits to polynomials in column chunks, discards their coefficients once hashed
+    /// (recomputing them from the Merkle leaves when opening), and computes the quotient
+    /// polynomials in row tiles. The Merkle trees themselves are not bounded by this budget, but
+    /// with the `mmap` feature they are stored in memory-mapped temporary files by default, see
+    /// `with_low_memory_storage`.
+    ///
+    /// The budget is set here rather than on `CircuitConfig` because it only changes how a proof
+    /// is computed, not the proof itself. `CircuitConfig` is part of the `CommonCircuitData` that
+    /// verifiers hold: it is serialized with it, and circuits verifying each other, such as cyclic
+    /// recursion and dummy circuits, require their common data to be equal. A budget there would
+    /// make the same circuit incompatible with itself depending on the machine proving it.
+    pub fn with_memory_budget(mut self, memory_budget: usize) -> Self {
+        self.memory_budget = Some(memory_budget);
+        self
+    }
+
+    /// Sets where the Merkle trees are stored when proving within a memory budget, e.g. in
+    /// memory-mapped files in a given directory.
+    pub fn with_low_memory_storage(mut self, storage: MerkleStorage) -> Self {
+        self.low_memory_storage = storage;
+        self
+    }
+
+    /// The FFT root table of the LDEs, taken from the context's tables if they have one, and from
+    /// the prover data otherwise.
+    fn lde_root_table<'a, C: GenericConfig<D, F = F>, const D: usize>(
+        lde_root_table: Option<&'a FftRootTable<F>>,
+        prover_data: &'a ProverOnlyCircuitData<F, C, D>,
+    ) -> Option<&'a FftRootTable<F>>
+    where
+        F: Extendable<D>,
+    {
+        lde_root_table.or(prover_data.fft_root_table.as_ref())
+    }
+
+    /// Interpolates quotient polynomials from their values on the quotient coset.
+    fn quotient_coset_ifft<C: GenericConfig<D, F = F>, const D: usize>(
+        &self,
+        values: PolynomialValues<F>,
+        prover_data: &ProverOnlyCircuitData<F, C, D>,
+    ) -> PolynomialCoeffs<F>
+    where
+        F: Extendable<D>,
+    {
+        let Some(tables) = &self.tables else {
+            return values.coset_ifft(F::coset_shift());
+        };
+        let root_table = tables
+            .quotient_root_table
+            .as_ref()
+            .or_else(|| Self::lde_root_table(tables.lde_root_table.as_ref(), prover_data));
+        let mut coeffs = ifft_with_options(values, None, root_table);
+        coeffs
+            .coeffs
+            .iter_mut()
+            .zip(&tables.coset_shift_inverse_powers)
+            .for_each(|(c, &r)| *c *= r);
+        coeffs
+    }
 }
 
 pub fn prove<F: RichField + Extendable<D>, C: GenericConfig<D, F = F>, const D: usize>(
@@ -123,19 +277,125 @@ where
         timing,
         &format!("run {} generators", prover_data.generators.len()),
         generate_partial_witness(inputs, prover_data, common_data)
-    );
+    )?;
 
     prove_with_partition_witness(prover_data, common_data, partition_witness, timing)
 }
 
+/// Like `prove`, but reuses the precomputed data and buffers of `context`, which must have been
+/// created for the same circuit.
+pub fn prove_with_context<
+    F: RichField + Extendable<D>,
+    C: GenericConfig<D, F = F>,
+    const D: usize,
+>(
+    prover_data: &ProverOnlyCircuitData<F, C, D>,
+    common_data: &CommonCircuitData<F, D>,
+    inputs: PartialWitness<F>,
+    context: &mut ProverContext<F>,
+    timing: &mut TimingTree,
+) -> Result<ProofWithPublicInputs<F, C, D>>
+where
+    C::Hasher: Hasher<F>,
+    C::InnerHasher: Hasher<F>,
+{
+    let partition_witness = timed!(
+        timing,
+        &format!("run {} generators", prover_data.generators.len()),
+        generate_partial_witness(inputs, prover_data, common_data)
+    )?;
+
+    prove_with_partition_witness_and_context(
+        prover_data,
+        common_data,
+        partition_witness,
+        context,
+        timing,
+    )
+}
+
+/// Proves the statements given by `inputs`, in order. The witness of each statement is generated
+/// while the previous statement is being proven, and all proofs share a single `ProverContext`.
+pub fn prove_batch<F: RichField + Extendable<D>, C: GenericConfig<D, F = F>, const D: usize>(
+    prover_data: &ProverOnlyCircuitData<F, C, D>,
+    common_data: &CommonCircuitData<F, D>,
+    inputs: impl IntoIterator<Item = PartialWitness<F>>,
+    timing: &mut TimingTree,
+) -> Result<Vec<ProofWithPublicInputs<F, C, D>>>
+where
+    C::Hasher: Hasher<F>,
+    C::InnerHasher: Hasher<F>,
+{
+    let generate_witness = |inputs: Option<PartialWitness<F>>| {
+        inputs
+            .map(|inputs| generate_partial_witness(inputs, prover_data, common_data))
+            .transpose()
+    };
+
+    let mut context = ProverContext::new(prover_data, common_data);
+    let mut inputs = inputs.into_iter();
+    let mut proofs = Vec::new();
+    let mut next_witness = timed!(
+        timing,
+        &format!("run {} generators", prover_data.generators.len()),
+        generate_witness(inputs.next())
+    )?;
+    while let Some(partition_witness) = next_witness {
+        let next_inputs = inputs.next();
+        let (proof, witness) = join(
+            || {
+                prove_with_partition_witness_and_context(
+                    prover_data,
+                    common_data,
+                    partition_witness,
+                    &mut context,
+                    timing,
+                )
+            },
+            || generate_witness(next_inputs),
+        );
+        proofs.push(proof?);
+        next_witness = witness?;
+    }
+
+    Ok(proofs)
+}
+
 pub fn prove_with_partition_witness<
     F: RichField + Extendable<D>,
     C: GenericConfig<D, F = F>,
     const D: usize,
+>(
+    prover_data: &ProverOnlyCircuitData<F, C, D>,
+    common_data: &CommonCircuitData<F, D>,
+    partition_witness: PartitionWitness<F>,
+    timing: &mut TimingTree,
+) -> Result<ProofWithPublicInputs<F, C, D>>
+where
+    C::Hasher: Hasher<F>,
+    C::InnerHasher: Hasher<F>,
+{
+    let mut context = ProverContext::for_single_proof(common_data);
+    prove_with_partition_witness_and_context(
+        prover_data,
+        common_data,
+        partition_witness,
+        &mut context,
+        timing,
+    )
+}
+
+/// Like `prove_with_partition_witness`, but reuses the precomputed data and buffers of `context`,
+/// which must have been created for the same circuit.
+pub fn prove_with_partition_witness_and_context<
+    F: RichField + Extendable<D>,
+    C: GenericConfig<D, F = F>,
+    const D: usize,
 >(
     prover_data: &ProverOnlyCircuitData<F, C, D>,
     common_data: &CommonCircuitData<F, D>,
     mut partition_witness: PartitionWitness<F>,
+    context: &mut ProverContext<F>,
     timing: &mut TimingTree,
 ) -> Result<ProofWithPublicInputs<F, C, D>>
 where
@@ -148,7 +408,7 @@ where
     let quotient_degree = common_data.quotient_degree();
     let degree = common_data.degree();
 
-    set_lookup_wires(prover_data, common_data, &mut partition_witness);
+    set_lookup_wires(prover_data, common_data, &mut partition_witness)?;
 
     let public_inputs = partition_witness.get_targets(&prover_data.public_inputs);
     let public_inputs_hash = C::InnerHasher::hash_no_pad(&public_inputs);
@@ -169,17 +429,17 @@ where
             .collect()
     );
 
-    let wires_commitment = timed!(
+    let mut wires_commitment = timed!(
         timing,
         "compute wires commitment",
-        PolynomialBatch::<F, C, D>::from_values(
+        commit_values(
             wires_values,
-            config.fri_config.rate_bits,
-            config.zero_knowledge && PlonkOracle::WIRES.blinding,
-            config.fri_config.cap_height,
+            PlonkOracle::WIRES.blinding,
+            prover_data,
+            common_data,
+            context,
             timing,
-            prover_data.fft_root_table.as_ref(),
-        )
+        )?
     );
 
     let mut challenger = Challenger::<F, C::Hasher>::new();
@@ -236,17 +496,17 @@ where
         zs_partial_products
     };
 
-    let partial_products_zs_and_lookup_commitment = timed!(
+    let mut partial_products_zs_and_lookup_commitment = timed!(
         timing,
         "commit to partial products, Z's and, if any, lookup polynomials",
-        PolynomialBatch::from_values(
+        commit_values(
             zs_partial_products_lookups,
-            config.fri_config.rate_bits,
-            config.zero_knowledge && PlonkOracle::ZS_PARTIAL_PRODUCTS.blinding,
-            config.fri_config.cap_height,
+            PlonkOracle::ZS_PARTIAL_PRODUCTS.blinding,
+            prover_data,
+            common_data,
+            context,
             timing,
-            prover_data.fft_root_table.as_ref(),
-        )
+        )?
     );
 
     challenger.observe_cap::<C::Hasher>(&partial_products_zs_and_lookup_commitment.merkle_tree.cap);
@@ -266,6 +526,7 @@ where
             &gammas,
             &deltas,
             &alphas,
+            context,
         )
     );
 
@@ -284,17 +545,17 @@ where
             .collect()
     );
 
-    let quotient_polys_commitment = timed!(
+    let mut quotient_polys_commitment = timed!(
         timing,
         "commit to quotient polys",
-        PolynomialBatch::<F, C, D>::from_coeffs(
+        commit_coeffs(
             all_quotient_poly_chunks,
-            config.fri_config.rate_bits,
-            config.zero_knowledge && PlonkOracle::QUOTIENT.blinding,
-            config.fri_config.cap_height,
+            PlonkOracle::QUOTIENT.blinding,
+            prover_data,
+            common_data,
+            context,
             timing,
-            prover_data.fft_root_table.as_ref(),
-        )
+        )?
     );
 
     challenger.observe_cap::<C::Hasher>(&quotient_polys_commitment.merkle_tree.cap);
@@ -309,6 +570,20 @@ where
         "Opening point is in the subgroup."
     );
 
+    // Batches committed within the memory budget interpolate their polynomials again, once for
+    // the whole opening phase. They are dropped along with the batches.
+    if context.memory_budget.is_some() {
+        timed!(timing, "recompute committed polynomials", {
+            for commitment in [
+                &mut wires_commitment,
+                &mut partial_products_zs_and_lookup_commitment,
+                &mut quotient_polys_commitment,
+            ] {
+                commitment.recompute_polynomials();
+            }
+        });
+    }
+
     let openings = timed!(
         timing,
         "construct the opening set, including lookups",
@@ -342,6 +617,19 @@ where
         )
     );
 
+    // The next proof commits to batches of the same sizes, in the same order.
+    if let Some(tables) = &mut context.tables {
+        for commitment in [
+            &mut quotient_polys_commitment,
+            &mut partial_products_zs_and_lookup_commitment,
+            &mut wires_commitment,
+        ] {
+            tables
+                .commitment
+                .recycle_leaves(take(&mut commitment.merkle_tree.leaves));
+        }
+    }
+
     let proof = Proof::<F, C, D> {
         wires_cap: wires_commitment.merkle_tree.cap,
         plonk_zs_partial_products_cap: partial_products_zs_and_lookup_commitment.merkle_tree.cap,
@@ -355,6 +643,101 @@ where
     })
 }
 
+/// Commits to the polynomials interpolating `values`, within the memory budget of `context` if it
+/// has one.
+fn commit_values<F: RichField + Extendable<D>, C: GenericConfig<D, F = F>, const D: usize>(
+    values: Vec<PolynomialValues<F>>,
+    blinding: bool,
+    prover_data: &ProverOnlyCircuitData<F, C, D>,
+    common_data: &CommonCircuitData<F, D>,
+    context: &mut ProverContext<F>,
+    timing: &mut TimingTree,
+) -> Result<PolynomialBatch<F, C, D>> {
+    let config = &common_data.config;
+    let blinding = config.zero_knowledge && blinding;
+    match (context.memory_budget, &mut context.tables) {
+        (Some(memory_budget), tables) => PolynomialBatch::from_values_low_memory(
+            values,
+            config.fri_config.rate_bits,
+            blinding,
+            config.fri_config.cap_height,
+            timing,
+            ProverContext::lde_root_table(
+                tables
+                    .as_ref()
+                    .and_then(|tables| tables.lde_root_table.as_ref()),
+                prover_data,
+            ),
+            memory_budget,
+            &context.low_memory_storage,
+        ),
+        (None, Some(tables)) => PolynomialBatch::from_values_with_context(
+            values,
+            config.fri_config.rate_bits,
+            blinding,
+            config.fri_config.cap_height,
+            timing,
+            ProverContext::lde_root_table(tables.lde_root_table.as_ref(), prover_data),
+            &mut tables.commitment,
+        ),
+        (None, None) => Ok(PolynomialBatch::from_values(
+            values,
+            config.fri_config.rate_bits,
+            blinding,
+            config.fri_config.cap_height,
+            timing,
+            prover_data.fft_root_table.as_ref(),
+        )),
+    }
+}
+
+/// Commits to `polynomials`, within the memory budget of `context` if it has one.
+fn commit_coeffs<F: RichField + Extendable<D>, C: GenericConfig<D, F = F>, const D: usize>(
+    polynomials: Vec<PolynomialCoeffs<F>>,
+    blinding: bool,
+    prover_data: &ProverOnlyCircuitData<F, C, D>,
+    common_data: &CommonCircuitData<F, D>,
+    context: &mut ProverContext<F>,
+    timing: &mut TimingTree,
+) -> Result<PolynomialBatch<F, C, D>> {
+    let config = &common_data.config;
+    let blinding = config.zero_knowledge && blinding;
+    match (context.memory_budget, &mut context.tables) {
+        (Some(memory_budget), tables) => PolynomialBatch::from_coeffs_low_memory(
+            polynomials,
+            config.fri_config.rate_bits,
+            blinding,
+            config.fri_config.cap_height,
+            timing,
+            ProverContext::lde_root_table(
+                tables
+                    .as_ref()
+                    .and_then(|tables| tables.lde_root_table.as_ref()),
+                prover_data,
+            ),
+            memory_budget,
+            &context.low_memory_storage,
+        ),
+        (None, Some(tables)) => PolynomialBatch::from_coeffs_with_context(
+            polynomials,
+            config.fri_config.rate_bits,
+            blinding,
+            config.fri_config.cap_height,
+            timing,
+            ProverContext::lde_root_table(tables.lde_root_table.as_ref(), prover_data),
+            &mut tables.commitment,
+        ),
+        (None, None) => Ok(PolynomialBatch::from_coeffs(
+            polynomials,
+            config.fri_config.rate_bits,
+            blinding,
+            config.fri_config.cap_height,
+            timing,
+            prover_data.fft_root_table.as_ref(),
+        )),
+    }
+}
+
 /// Compute the partial products used in the `Z` polynomials.
 fn all_wires_permutation_partial_products<
     F: RichField + Extendable<D>,
@@ -615,6 +998,7 @@ fn compute_quotient_polys<
     gammas: &[F],
     deltas: &[F],
     alphas: &[F],
+    context: &ProverContext<F>,
 ) -> Vec<PolynomialCoeffs<F>> {
     let num_challenges = common_data.config.num_challenges;
 
@@ -634,10 +1018,10 @@ fn compute_quotient_polys<
     // steps away since we work on an LDE of degree `max_filtered_constraint_degree`.
     let next_step = 1 << quotient_degree_bits;
 
-    let points = F::two_adic_subgroup(common_data.degree_bits() + quotient_degree_bits);
+    let points = &context.quotient_points;
     let lde_size = points.len();
 
-    let z_h_on_coset = ZeroPolyOnCoset::new(common_data.degree_bits(), quotient_degree_bits);
+    let z_h_on_coset = &context.z_h_on_coset;
 
     // Precompute the lookup table evals on the challenges in delta
     // These values are used to produce the final RE constraints for each lut,
@@ -675,135 +1059,329 @@ fn compute_quotient_polys<
     let lut_re_poly_evals_refs: Vec<&[F]> =
         lut_re_poly_evals.iter().map(|v| v.as_slice()).collect();
 
-    let points_batches = points.par_chunks(BATCH_SIZE);
     let num_batches = points.len().div_ceil(BATCH_SIZE);
 
-    let quotient_values: Vec<Vec<F>> = points_batches
-        .enumerate()
-        .flat_map(|(batch_i, xs_batch)| {
-            // Each batch must be the same size, except the last one, which may be smaller.
-            debug_assert!(
-                xs_batch.len() == BATCH_SIZE
-                    || (batch_i == num_batches - 1 && xs_batch.len() <= BATCH_SIZE)
-            );
+    // The points are processed in tiles of whole batches, whose quotient values are written
+    // directly into their columns, so that only one tile of row-wise values is alive at a time.
+    let tile_size = match context.memory_budget {
+        Some(memory_budget) => {
+            let row_size = size_of::<Vec<F>>() + num_challenges * size_of::<F>();
+            (memory_budget / row_size / BATCH_SIZE).max(1) * BATCH_SIZE
+        }
+        None => lde_size,
+    };
+    let mut quotient_values = vec![vec![F::ZERO; lde_size]; num_challenges];
 
-            let indices_batch: Vec<usize> =
-                (BATCH_SIZE * batch_i..BATCH_SIZE * batch_i + xs_batch.len()).collect();
+    for (tile_i, xs_tile) in points.chunks(tile_size).enumerate() {
+        let tile_start = tile_i * tile_size;
+        let tile_values: Vec<Vec<F>> = xs_tile
+            .par_chunks(BATCH_SIZE)
+            .enumerate()
+            .flat_map(|(tile_batch_i, xs_batch)| {
+                let batch_i = tile_start / BATCH_SIZE + tile_batch_i;
+                // Each batch must be the same size, except the last one, which may be smaller.
+                debug_assert!(
+                    xs_batch.len() == BATCH_SIZE
+                        || (batch_i == num_batches - 1 && xs_batch.len() <= BATCH_SIZE)
+                );
 
-            let mut shifted_xs_batch = Vec::with_capacity(xs_batch.len());
-            let mut local_zs_batch = Vec::with_capacity(xs_batch.len());
-            let mut next_zs_batch = Vec::with_capacity(xs_batch.len());
+                let indices_batch: Vec<usize> =
+                    (BATCH_SIZE * batch_i..BATCH_SIZE * batch_i + xs_batch.len()).collect();
 
-            let mut local_lookup_batch = Vec::with_capacity(xs_batch.len());
-            let mut next_lookup_batch = Vec::with_capacity(xs_batch.len());
+                let mut shifted_xs_batch = Vec::with_capacity(xs_batch.len());
+                let mut local_zs_batch = Vec::with_capacity(xs_batch.len());
+                let mut next_zs_batch = Vec::with_capacity(xs_batch.len());
 
-            let mut partial_products_batch = Vec::with_capacity(xs_batch.len());
-            let mut s_sigmas_batch = Vec::with_capacity(xs_batch.len());
+                let mut local_lookup_batch = Vec::with_capacity(xs_batch.len());
+                let mut next_lookup_batch = Vec::with_capacity(xs_batch.len());
 
-            let mut local_constants_batch_refs = Vec::with_capacity(xs_batch.len());
-            let mut local_wires_batch_refs = Vec::with_capacity(xs_batch.len());
+                let mut partial_products_batch = Vec::with_capacity(xs_batch.len());
+                let mut s_sigmas_batch = Vec::with_capacity(xs_batch.len());
 
-            for (&i, &x) in indices_batch.iter().zip(xs_batch) {
-                let shifted_x = F::coset_shift() * x;
-                let i_next = (i + next_step) % lde_size;
-                let local_constants_sigmas = prover_data
-                    .constants_sigmas_commitment
-                    .get_lde_values(i, step);
-                let local_constants = &local_constants_sigmas[common_data.constants_range()];
-                let s_sigmas = &local_constants_sigmas[common_data.sigmas_range()];
-                let local_wires = wires_commitment.get_lde_values(i, step);
-                let local_zs_partial_and_lookup =
-                    zs_partial_products_and_lookup_commitment.get_lde_values(i, step);
-                let next_zs_partial_and_lookup =
-                    zs_partial_products_and_lookup_commitment.get_lde_values(i_next, step);
+                let mut local_constants_batch_refs = Vec::with_capacity(xs_batch.len());
+                let mut local_wires_batch_refs = Vec::with_capacity(xs_batch.len());
 
-                let local_zs = &local_zs_partial_and_lookup[common_data.zs_range()];
+                for (&i, &x) in indices_batch.iter().zip(xs_batch) {
+                    let shifted_x = F::coset_shift() * x;
+                    let i_next = (i + next_step) % lde_size;
+                    let local_constants_sigmas = prover_data
+                        .constants_sigmas_commitment
+                        .get_lde_values(i, step);
+                    let local_constants = &local_constants_sigmas[common_data.constants_range()];
+                    let s_sigmas = &local_constants_sigmas[common_data.sigmas_range()];
+                    let local_wires = wires_commitment.get_lde_values(i, step);
+                    let local_zs_partial_and_lookup =
+                        zs_partial_products_and_lookup_commitment.get_lde_values(i, step);
+                    let next_zs_partial_and_lookup =
+                        zs_partial_products_and_lookup_commitment.get_lde_values(i_next, step);
 
-                let next_zs = &next_zs_partial_and_lookup[common_data.zs_range()];
+                    let local_zs = &local_zs_partial_and_lookup[common_data.zs_range()];
 
-                let partial_products =
-                    &local_zs_partial_and_lookup[common_data.partial_products_range()];
+                    let next_zs = &next_zs_partial_and_lookup[common_data.zs_range()];
 
-                if has_lookup {
-                    let local_lookup_zs = &local_zs_partial_and_lookup[common_data.lookup_range()];
+                    let partial_products =
+                        &local_zs_partial_and_lookup[common_data.partial_products_range()];
 
-                    let next_lookup_zs = &next_zs_partial_and_lookup[common_data.lookup_range()];
-                    debug_assert_eq!(local_lookup_zs.len(), common_data.num_all_lookup_polys());
+                    if has_lookup {
+                        let local_lookup_zs =
+                            &local_zs_partial_and_lookup[common_data.lookup_range()];
 
-                    local_lookup_batch.push(local_lookup_zs);
-                    next_lookup_batch.push(next_lookup_zs);
-                }
+                        let next_lookup_zs =
+                            &next_zs_partial_and_lookup[common_data.lookup_range()];
+                        debug_assert_eq!(local_lookup_zs.len(), common_data.num_all_lookup_polys());
 
-                debug_assert_eq!(local_wires.len(), common_data.config.num_wires);
-                debug_assert_eq!(local_zs.len(), num_challenges);
+                        local_lookup_batch.push(local_lookup_zs);
+                        next_lookup_batch.push(next_lookup_zs);
+                    }
 
-                local_constants_batch_refs.push(local_constants);
-                local_wires_batch_refs.push(local_wires);
+                    debug_assert_eq!(local_wires.len(), common_data.config.num_wires);
+                    debug_assert_eq!(local_zs.len(), num_challenges);
 
-                shifted_xs_batch.push(shifted_x);
-                local_zs_batch.push(local_zs);
-                next_zs_batch.push(next_zs);
-                partial_products_batch.push(partial_products);
-                s_sigmas_batch.push(s_sigmas);
-            }
+                    local_constants_batch_refs.push(local_constants);
+                    local_wires_batch_refs.push(local_wires);
 
-            // NB (JN): I'm not sure how (in)efficient the below is. It needs measuring.
-            let mut local_constants_batch =
-                vec![F::ZERO; xs_batch.len() * local_constants_batch_refs[0].len()];
-            for i in 0..local_constants_batch_refs[0].len() {
-                for (j, constants) in local_constants_batch_refs.iter().enumerate() {
-                    local_constants_batch[i * xs_batch.len() + j] = constants[i];
+                    shifted_xs_batch.push(shifted_x);
+                    local_zs_batch.push(local_zs);
+                    next_zs_batch.push(next_zs);
+                    partial_products_batch.push(partial_products);
+                    s_sigmas_batch.push(s_sigmas);
                 }
-            }
 
-            let mut local_wires_batch =
-                vec![F::ZERO; xs_batch.len() * local_wires_batch_refs[0].len()];
-            for i in 0..local_wires_batch_refs[0].len() {
-                for (j, wires) in local_wires_batch_refs.iter().enumerate() {
-                    local_wires_batch[i * xs_batch.len() + j] = wires[i];
+                // NB (JN): I'm not sure how (in)efficient the below is. It needs measuring.
+                let mut local_constants_batch =
+                    vec![F::ZERO; xs_batch.len() * local_constants_batch_refs[0].len()];
+                for i in 0..local_constants_batch_refs[0].len() {
+                    for (j, constants) in local_constants_batch_refs.iter().enumerate() {
+                        local_constants_batch[i * xs_batch.len() + j] = constants[i];
+                    }
                 }
-            }
 
-            let vars_batch = EvaluationVarsBaseBatch::new(
-                xs_batch.len(),
-                &local_constants_batch,
-                &local_wires_batch,
-                public_inputs_hash,
-            );
+                let mut local_wires_batch =
+                    vec![F::ZERO; xs_batch.len() * local_wires_batch_refs[0].len()];
+                for i in 0..local_wires_batch_refs[0].len() {
+                    for (j, wires) in local_wires_batch_refs.iter().enumerate() {
+                        local_wires_batch[i * xs_batch.len() + j] = wires[i];
+                    }
+                }
 
-            let mut quotient_values_batch = eval_vanishing_poly_base_batch::<F, D>(
-                common_data,
-                &indices_batch,
-                &shifted_xs_batch,
-                vars_batch,
-                &local_zs_batch,
-                &next_zs_batch,
-                &local_lookup_batch,
-                &next_lookup_batch,
-                &partial_products_batch,
-                &s_sigmas_batch,
-                betas,
-                gammas,
-                deltas,
-                alphas,
-                &z_h_on_coset,
-                &lut_re_poly_evals_refs,
-            );
+                let vars_batch = EvaluationVarsBaseBatch::new(
+                    xs_batch.len(),
+                    &local_constants_batch,
+                    &local_wires_batch,
+                    public_inputs_hash,
+                );
 
-            for (&i, quotient_values) in indices_batch.iter().zip(quotient_values_batch.iter_mut())
-            {
-                let denominator_inv = z_h_on_coset.eval_inverse(i);
-                quotient_values
-                    .iter_mut()
-                    .for_each(|v| *v *= denominator_inv);
+                let mut quotient_values_batch = eval_vanishing_poly_base_batch::<F, D>(
+                    common_data,
+                    &indices_batch,
+                    &shifted_xs_batch,
+                    vars_batch,
+                    &local_zs_batch,
+                    &next_zs_batch,
+                    &local_lookup_batch,
+                    &next_lookup_batch,
+                    &partial_products_batch,
+                    &s_sigmas_batch,
+                    betas,
+                    gammas,
+                    deltas,
+                    alphas,
+                    z_h_on_coset,
+                    &lut_re_poly_evals_refs,
+                );
+
+                for (&i, quotient_values) in
+                    indices_batch.iter().zip(quotient_values_batch.iter_mut())
+                {
+                    let denominator_inv = z_h_on_coset.eval_inverse(i);
+                    quotient_values
+                        .iter_mut()
+                        .for_each(|v| *v *= denominator_inv);
+                }
+                quotient_values_batch
+            })
+            .collect();
+
+        for (column, values) in quotient_values.iter_mut().enumerate() {
+            for (value, row) in values[tile_start..].iter_mut().zip(&tile_values) {
+                *value = row[column];
             }
-            quotient_values_batch
-        })
-        .collect();
+        }
+    }
 
-    transpose(&quotient_values)
+    quotient_values
         .into_par_iter()
         .map(PolynomialValues::new)
-        .map(|values| values.coset_ifft(F::coset_shift()))
+        .map(|values| context.quotient_coset_ifft(values, prover_data))
         .collect()
 }
+
+#[cfg(test)]
+mod tests {
+    #[cfg(not(feature = "std"))]
+    use alloc::{sync::Arc, vec};
+    #[cfg(feature = "std")]
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::fri::FriConfig;
+    use crate::iop::witness::WitnessWrite;
+    use crate::plonk::circuit_builder::CircuitBuilder;
+    use crate::plonk::circuit_data::{CircuitConfig, CircuitData};
+    use crate::plonk::config::PoseidonGoldilocksConfig;
+
+    const D: usize = 2;
+    type C = PoseidonGoldilocksConfig;
+    type F = <C as GenericConfig<D>>::F;
+
+    /// Builds a circuit proving knowledge of `x` in the lookup table `{(0, 1), (1, 2), (2, 4)}`,
+    /// with public inputs `x^2` and the looked-up value, and returns the target of `x`.
+    fn build_circuit_with_input(config: CircuitConfig) -> (CircuitData<F, C, D>, Target) {
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+        let x = builder.add_virtual_target();
+        let x_squared = builder.mul(x, x);
+        let lut_index = builder.add_lookup_table_from_pairs(Arc::new(vec![(0, 1), (1, 2), (2, 4)]));
+        let y = builder.add_lookup_from_index(x, lut_index);
+        builder.register_public_input(x_squared);
+        builder.register_public_input(y);
+        (builder.build::<C>(), x)
+    }
+
+    fn build_circuit(config: CircuitConfig) -> (CircuitData<F, C, D>, PartialWitness<F>) {
+        let (data, x) = build_circuit_with_input(config);
+        let mut pw = PartialWitness::new();
+        pw.set_target(x, F::TWO);
+        (data, pw)
+    }
+
+    #[test]
+    fn test_low_memory_prover() -> Result<()> {
+        let (data, pw) = build_circuit(CircuitConfig::standard_recursion_config());
+        // Some wires are randomized, so both proofs must share the same witness to be equal.
+        let witness = generate_partial_witness(pw, &data.prover_only, &data.common)?;
+        let proof = prove_with_partition_witness(
+            &data.prover_only,
+            &data.common,
+            witness.clone(),
+            &mut TimingTree::default(),
+        )?;
+
+        // A budget small enough to process a single column, and a single batch of rows, at a time.
+        let mut context = data.prover_context().with_memory_budget(1);
+        let low_memory_proof = prove_with_partition_witness_and_context(
+            &data.prover_only,
+            &data.common,
+            witness,
+            &mut context,
+            &mut TimingTree::default(),
+        )?;
+        assert_eq!(low_memory_proof, proof);
+        data.verify(low_memory_proof)?;
+
+        let (zk_data, pw) = build_circuit(CircuitConfig {
+            zero_knowledge: true,
+            ..CircuitConfig::standard_recursion_config()
+        });
+        let mut context = zk_data.prover_context().with_memory_budget(1);
+        let zk_proof = zk_data.prove_with_context(pw, &mut context)?;
+        zk_data.verify(zk_proof)?;
+        Ok(())
+    }
+
+    #[cfg(feature = "mmap")]
+    #[test]
+    fn test_low_memory_storage() -> Result<()> {
+        let (data, pw) = build_circuit(CircuitConfig::standard_recursion_config());
+        let dir = tempfile::tempdir()?;
+        let mut context = data
+            .prover_context()
+            .with_memory_budget(1)
+            .with_low_memory_storage(MerkleStorage::Mmap(Some(dir.path().to_path_buf())));
+        data.verify(data.prove_with_context(pw.clone(), &mut context)?)?;
+
+        // The prover fails, rather than panics, if it cannot create the memory-mapped files.
+        let mut context = data
+            .prover_context()
+            .with_memory_budget(1)
+            .with_low_memory_storage(MerkleStorage::Mmap(Some(dir.path().join("missing"))));
+        assert!(data.prove_with_context(pw, &mut context).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_prover_context() -> Result<()> {
+        // A higher rate than the quotient degree factor, so that the quotient LDE is smaller than
+        // the other LDEs.
+        let standard_config = CircuitConfig::standard_recursion_config();
+        let (data, pw) = build_circuit(CircuitConfig {
+            fri_config: FriConfig {
+                rate_bits: 4,
+                ..standard_config.fri_config.clone()
+            },
+            ..standard_config
+        });
+        let witness = generate_partial_witness(pw.clone(), &data.prover_only, &data.common)?;
+        let proof = prove_with_partition_witness(
+            &data.prover_only,
+            &data.common,
+            witness.clone(),
+            &mut TimingTree::default(),
+        )?;
+
+        // The second proof reuses the buffers recycled from the first one.
+        let mut context = data.prover_context();
+        for _ in 0..2 {
+            let context_proof = prove_with_partition_witness_and_context(
+                &data.prover_only,
+                &data.common,
+                witness.clone(),
+                &mut context,
+                &mut TimingTree::default(),
+            )?;
+            assert_eq!(context_proof, proof);
+        }
+        data.verify(data.prove_with_context(pw, &mut context)?)?;
+
+        // As when the prover data has no LDE root table.
+        let mut context = data.prover_context();
+        context.tables.as_mut().unwrap().lde_root_table =
+            Some(fft_root_table(data.common.lde_size()));
+        let context_proof = prove_with_partition_witness_and_context(
+            &data.prover_only,
+            &data.common,
+            witness,
+            &mut context,
+            &mut TimingTree::default(),
+        )?;
+        assert_eq!(context_proof, proof);
+        Ok(())
+    }
+
+    #[test]
+    fn test_prove_batch() -> Result<()> {
+        let (data, x) = build_circuit_with_input(CircuitConfig::standard_recursion_config());
+        assert!(data.prove_batch([])?.is_empty());
+
+        let inputs = (0..3).map(|i| {
+            let mut pw = PartialWitness::new();
+            pw.set_target(x, F::from_canonical_usize(i));
+            pw
+        });
+        let proofs = data.prove_batch(inputs)?;
+        assert_eq!(proofs.len(), 3);
+        for (i, proof) in proofs.into_iter().enumerate() {
+            let x = F::from_canonical_usize(i);
+            assert_eq!(
+                proof.public_inputs,
+                [x * x, F::from_canonical_usize(1 << i)]
+            );
+            data.verify(proof)?;
+        }
+
+        // Failing to generate a witness, here for lack of `x`, fails the whole batch.
+        let mut valid = PartialWitness::new();
+        valid.set_target(x, F::ONE);
+        assert!(data.prove_batch([valid, PartialWitness::new()]).is_err());
+        Ok(())
+    }
+}
@@ -1,72 +1,129 @@
-use anyhow::ensure;
-
 use crate::field::extension::Extendable;
 use crate::hash::hash_types::RichField;
 use crate::plonk::circuit_data::CommonCircuitData;
 use crate::plonk::config::GenericConfig;
 use crate::plonk::proof::{OpeningSet, Proof, ProofWithPublicInputs};
+use crate::plonk::verification_error::{ensure_shape, VerificationError};
 
 pub(crate) fn validate_proof_with_pis_shape<F, C, const D: usize>(
     proof_with_pis: &ProofWithPublicInputs<F, C, D>,
     common_data: &CommonCircuitData<F, D>,
-) -> anyhow::Result<()>
+) -> Result<(), VerificationError>
 where
     F: RichField + Extendable<D>,
     C: GenericConfig<D, F = F>,
 {
-    let ProofWithPublicInputs {
-        proof,
-        public_inputs,
-    } = proof_with_pis;
-    validate_proof_shape(proof, common_data)?;
-    ensure!(
-        public_inputs.len() == common_data.num_public_inputs,
-        "Number of public inputs doesn't match circuit data."
-    );
-    Ok(())
+    ProofShape::new(common_data).validate(proof_with_pis)
 }
 
-fn validate_proof_shape<F, C, const D: usize>(
-    proof: &Proof<F, C, D>,
-    common_data: &CommonCircuitData<F, D>,
-) -> anyhow::Result<()>
-where
-    F: RichField + Extendable<D>,
-    C: GenericConfig<D, F = F>,
-{
-    let config = &common_data.config;
-    let Proof {
-        wires_cap,
-        plonk_zs_partial_products_cap,
-        quotient_polys_cap,
-        openings,
-        // The shape of the opening proof will be checked in the FRI verifier (see
-        // validate_fri_proof_shape), so we ignore it here.
-        opening_proof: _,
-    } = proof;
-    let OpeningSet {
-        constants,
-        plonk_sigmas,
-        wires,
-        plonk_zs,
-        plonk_zs_next,
-        partial_products,
-        quotient_polys,
-        lookup_zs,
-        lookup_zs_next,
-    } = openings;
-    let cap_height = common_data.fri_params.config.cap_height;
-    ensure!(wires_cap.height() == cap_height);
-    ensure!(plonk_zs_partial_products_cap.height() == cap_height);
-    ensure!(quotient_polys_cap.height() == cap_height);
-    ensure!(constants.len() == common_data.num_constants);
-    ensure!(plonk_sigmas.len() == config.num_routed_wires);
-    ensure!(wires.len() == config.num_wires);
-    ensure!(plonk_zs.len() == config.num_challenges);
-    ensure!(plonk_zs_next.len() == config.num_challenges);
-    ensure!(partial_products.len() == config.num_challenges * common_data.num_partial_products);
-    ensure!(quotient_polys.len() == common_data.num_quotient_polys());
-    ensure!(lookup_zs.len() == common_data.num_all_lookup_polys());
-    ensure!(lookup_zs_next.len() == common_data.num_all_lookup_polys());
-    Ok(())
+/// The expected shape of the proofs of a circuit, which can be computed once and then checked
+/// against many proofs.
+#[derive(Clone, Debug)]
+pub(crate) struct ProofShape {
+    cap_height: usize,
+    num_constants: usize,
+    num_routed_wires: usize,
+    num_wires: usize,
+    num_challenges: usize,
+    num_partial_products: usize,
+    num_quotient_polys: usize,
+    num_lookup_polys: usize,
+    num_public_inputs: usize,
+}
+
+impl ProofShape {
+    pub(crate) fn new<F: RichField + Extendable<D>, const D: usize>(
+        common_data: &CommonCircuitData<F, D>,
+    ) -> Self {
+        let config = &common_data.config;
+        Self {
+            cap_height: common_data.fri_params.config.cap_height,
+            num_constants: common_data.num_constants,
+            num_routed_wires: config.num_routed_wires,
+            num_wires: config.num_wires,
+            num_challenges: config.num_challenges,
+            num_partial_products: config.num_challenges * common_data.num_partial_products,
+            num_quotient_polys: common_data.num_quotient_polys(),
+            num_lookup_polys: common_data.num_all_lookup_polys(),
+            num_public_inputs: common_data.num_public_inputs,
+        }
+    }
+
+    pub(crate) fn validate<F, C, const D: usize>(
+        &self,
+        proof_with_pis: &ProofWithPublicInputs<F, C, D>,
+    ) -> Result<(), VerificationError>
+    where
+        F: RichField + Extendable<D>,
+        C: GenericConfig<D, F = F>,
+    {
+        let ProofWithPublicInputs {
+            proof,
+            public_inputs,
+        } = proof_with_pis;
+        self.validate_proof(proof)?;
+        ensure_shape("public_inputs", self.num_public_inputs, public_inputs.len())
+    }
+
+    fn validate_proof<F, C, const D: usize>(
+        &self,
+        proof: &Proof<F, C, D>,
+    ) -> Result<(), VerificationError>
+    where
+        F: RichField + Extendable<D>,
+        C: GenericConfig<D, F = F>,
+    {
+        let Proof {
+            wires_cap,
+            plonk_zs_partial_products_cap,
+            quotient_polys_cap,
+            openings,
+            // The shape of the opening proof will be checked in the FRI verifier (see
+            // validate_fri_proof_shape), so we ignore it here.
+            opening_proof: _,
+        } = proof;
+        let OpeningSet {
+            constants,
+            plonk_sigmas,
+            wires,
+            plonk_zs,
+            plonk_zs_next,
+            partial_products,
+            quotient_polys,
+            lookup_zs,
+            lookup_zs_next,
+        } = openings;
+        ensure_shape("wires_cap height", self.cap_height, wires_cap.height())?;
+        ensure_shape(
+            "plonk_zs_partial_products_cap height",
+            self.cap_height,
+            plonk_zs_partial_products_cap.height(),
+        )?;
+        ensure_shape(
+            "quotient_polys_cap height",
+            self.cap_height,
+            quotient_polys_cap.height(),
+        )?;
+        ensure_shape("constants", self.num_constants, constants.len())?;
+        ensure_shape("plonk_sigmas", self.num_routed_wires, plonk_sigmas.len())?;
+        ensure_shape("wires", self.num_wires, wires.len())?;
+        ensure_shape("plonk_zs", self.num_challenges, plonk_zs.len())?;
+        ensure_shape("plonk_zs_next", self.num_challenges, plonk_zs_next.len())?;
+        ensure_shape(
+            "partial_products",
+            self.num_partial_products,
+            partial_products.len(),
+        )?;
+        ensure_shape(
+            "quotient_polys",
+            self.num_quotient_polys,
+            quotient_polys.len(),
+        )?;
+        ensure_shape("lookup_zs", self.num_lookup_polys, lookup_zs.len())?;
+        ensure_shape(
+            "lookup_zs_next",
+            self.num_lookup_polys,
+            lookup_zs_next.len(),
+        )
+    }
 }
@@ -46,6 +46,16 @@ pub struct EvaluationVarsBasePacked<'a, P: PackedField> {
     pub public_inputs_hash: &'a HashOut<P::Scalar>,
 }
 
+impl<'a, F: Field> From<EvaluationVarsBase<'a, F>> for EvaluationVarsBasePacked<'a, F> {
+    fn from(vars: EvaluationVarsBase<'a, F>) -> Self {
+        Self {
+            local_constants: vars.local_constants,
+            local_wires: vars.local_wires,
+            public_inputs_hash: vars.public_inputs_hash,
+        }
+    }
+}
+
 impl<'a, F: RichField + Extendable<D>, const D: usize> EvaluationVars<'a, F, D> {
     pub fn get_local_ext_algebra(
         &self,
@@ -0,0 +1,111 @@
+//! Errors returned when verifying a proof.
+
+use core::fmt::{self, Display, Formatter};
+
+/// The reason why a proof was rejected by a verifier.
+///
+/// The plonky2 and starky verifiers return these errors directly. Functions returning
+/// `anyhow::Result` which run the same checks, such as the starky provers, wrap them, so they can
+/// be recovered with `anyhow::Error::downcast_ref`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum VerificationError {
+    /// Some part of the proof does not have the size expected by the verifier. For optional parts
+    /// of a proof, the sizes are `0` or `1` depending on whether they are present.
+    ShapeMismatch {
+        item: &'static str,
+        expected: usize,
+        actual: usize,
+    },
+    /// The public inputs do not hash to the value expected by the verifier.
+    PublicInputsHashMismatch,
+    /// The vanishing polynomial does not match the quotient polynomial at the out-of-domain point,
+    /// for the given challenge. This is also how public inputs which differ from the ones the proof
+    /// was generated with are rejected, since their hash is bound by the public input gate.
+    VanishingPolyMismatch { challenge: usize },
+    /// A Merkle proof of an initial FRI oracle is invalid.
+    InitialMerkleProofFailure { query_round: usize, oracle: usize },
+    /// A Merkle proof of a FRI commit-phase layer is invalid.
+    CommitPhaseMerkleProofFailure { query_round: usize, layer: usize },
+    /// An opened evaluation of a FRI commit-phase layer does not match the evaluation derived from
+    /// the previous layer.
+    FriLayerMismatch { query_round: usize, layer: usize },
+    /// The FRI proof-of-work witness is invalid.
+    ProofOfWorkFailure,
+    /// The final FRI polynomial does not match the evaluation derived from the last layer.
+    FinalPolyMismatch { query_round: usize },
+    /// A STARK verifying key was not computed from the STARK and configuration it is used with.
+    VerifyingKeyMismatch,
+    /// The cross-table lookups of a multi-STARK system are not valid for the given table, for the
+    /// given reason.
+    InvalidCrossTableLookups { table: usize, reason: &'static str },
+    /// The openings of the `Z` polynomials of the looking tables of a cross-table lookup do not
+    /// sum to the opening of the looked table, for the given challenge.
+    CrossTableLookupMismatch { ctl: usize, challenge: usize },
+}
+
+impl Display for VerificationError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::ShapeMismatch {
+                item,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "Invalid proof shape: expected {expected} for {item}, got {actual}."
+            ),
+            Self::PublicInputsHashMismatch => write!(f, "Public inputs hash mismatch."),
+            Self::VanishingPolyMismatch { challenge } => write!(
+                f,
+                "Mismatch between the vanishing polynomial and the quotient polynomial for challenge {challenge}."
+            ),
+            Self::InitialMerkleProofFailure {
+                query_round,
+                oracle,
+            } => write!(
+                f,
+                "Invalid Merkle proof for oracle {oracle} in FRI query round {query_round}."
+            ),
+            Self::CommitPhaseMerkleProofFailure { query_round, layer } => write!(
+                f,
+                "Invalid Merkle proof for FRI layer {layer} in query round {query_round}."
+            ),
+            Self::FriLayerMismatch { query_round, layer } => write!(
+                f,
+                "Inconsistent evaluation in FRI layer {layer} in query round {query_round}."
+            ),
+            Self::ProofOfWorkFailure => write!(f, "Invalid proof of work witness."),
+            Self::FinalPolyMismatch { query_round } => write!(
+                f,
+                "Final polynomial evaluation is invalid in FRI query round {query_round}."
+            ),
+            Self::VerifyingKeyMismatch => write!(f, "The verifying key does not match the STARK."),
+            Self::InvalidCrossTableLookups { table, reason } => {
+                write!(f, "Invalid cross-table lookups for table {table}: {reason}.")
+            }
+            Self::CrossTableLookupMismatch { ctl, challenge } => write!(
+                f,
+                "Cross-table lookup {ctl} verification failed for challenge {challenge}."
+            ),
+        }
+    }
+}
+
+impl core::error::Error for VerificationError {}
+
+/// Returns a `ShapeMismatch` error for `item` if `actual` differs from `expected`.
+pub fn ensure_shape(
+    item: &'static str,
+    expected: usize,
+    actual: usize,
+) -> Result<(), VerificationError> {
+    if expected == actual {
+        Ok(())
+    } else {
+        Err(VerificationError::ShapeMismatch {
+            item,
+            expected,
+            actual,
+        })
+    }
+}
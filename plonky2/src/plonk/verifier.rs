@@ -1,6 +1,10 @@
 //! plonky2 verifier implementation.
 
-use anyhow::{ensure, Result};
+#[cfg(not(feature = "std"))]
+use alloc::vec::Vec;
+
+use anyhow::Result;
+use plonky2_maybe_rayon::*;
 
 use crate::field::extension::Extendable;
 use crate::field::types::Field;
@@ -10,26 +14,61 @@ use crate::plonk::circuit_data::{CommonCircuitData, VerifierOnlyCircuitData};
 use crate::plonk::config::{GenericConfig, Hasher};
 use crate::plonk::plonk_common::reduce_with_powers;
 use crate::plonk::proof::{Proof, ProofChallenges, ProofWithPublicInputs};
-use crate::plonk::validate_shape::validate_proof_with_pis_shape;
+use crate::plonk::validate_shape::{validate_proof_with_pis_shape, ProofShape};
 use crate::plonk::vanishing_poly::eval_vanishing_poly;
 use crate::plonk::vars::EvaluationVars;
+use crate::plonk::verification_error::VerificationError;
 
 pub(crate) fn verify<F: RichField + Extendable<D>, C: GenericConfig<D, F = F>, const D: usize>(
     proof_with_pis: ProofWithPublicInputs<F, C, D>,
     verifier_data: &VerifierOnlyCircuitData<C, D>,
     common_data: &CommonCircuitData<F, D>,
-) -> Result<()> {
+) -> Result<(), VerificationError> {
+    validate_proof_with_pis_shape(&proof_with_pis, common_data)?;
+
+    let public_inputs_hash = proof_with_pis.get_public_inputs_hash();
+    let challenges = proof_with_pis.challenges(
+        public_inputs_hash,
+        &verifier_data.circuit_digest,
+        common_data,
+    );
+
+    verify_with_challenges::<F, C, D>(
+        &proof_with_pis.proof,
+        public_inputs_hash,
+        challenges,
+        verifier_data,
+        common_data,
+    )
+}
+
+/// Like `verify`, but additionally checks that the public inputs of the proof hash to
+/// `expected_public_inputs_hash`, which is useful when the verifier only knows a commitment to the
+/// public inputs.
+pub(crate) fn verify_with_public_inputs_hash<
+    F: RichField + Extendable<D>,
+    C: GenericConfig<D, F = F>,
+    const D: usize,
+>(
+    proof_with_pis: ProofWithPublicInputs<F, C, D>,
+    expected_public_inputs_hash: <<C as GenericConfig<D>>::InnerHasher as Hasher<F>>::Hash,
+    verifier_data: &VerifierOnlyCircuitData<C, D>,
+    common_data: &CommonCircuitData<F, D>,
+) -> Result<(), VerificationError> {
     validate_proof_with_pis_shape(&proof_with_pis, common_data)?;
 
     let public_inputs_hash = proof_with_pis.get_public_inputs_hash();
-    let challenges = proof_with_pis.get_challenges(
+    if public_inputs_hash != expected_public_inputs_hash {
+        return Err(VerificationError::PublicInputsHashMismatch);
+    }
+    let challenges = proof_with_pis.challenges(
         public_inputs_hash,
         &verifier_data.circuit_digest,
         common_data,
-    )?;
+    );
 
     verify_with_challenges::<F, C, D>(
-        proof_with_pis.proof,
+        &proof_with_pis.proof,
         public_inputs_hash,
         challenges,
         verifier_data,
@@ -37,17 +76,55 @@ pub(crate) fn verify<F: RichField + Extendable<D>, C: GenericConfig<D, F = F>, c
     )
 }
 
+/// Verifies many proofs of the same circuit, returning one result per proof rather than stopping at
+/// the first invalid one. Only the expected proof shape is computed once and shared; everything
+/// else, including the challenges and the FRI instance at `zeta`, is derived per proof, and the
+/// proofs are checked in parallel.
+pub(crate) fn verify_batch<
+    F: RichField + Extendable<D>,
+    C: GenericConfig<D, F = F>,
+    const D: usize,
+>(
+    proofs_with_pis: &[ProofWithPublicInputs<F, C, D>],
+    verifier_data: &VerifierOnlyCircuitData<C, D>,
+    common_data: &CommonCircuitData<F, D>,
+) -> Vec<Result<(), VerificationError>> {
+    let shape = ProofShape::new(common_data);
+
+    proofs_with_pis
+        .par_iter()
+        .map(|proof_with_pis| {
+            shape.validate(proof_with_pis)?;
+
+            let public_inputs_hash = proof_with_pis.get_public_inputs_hash();
+            let challenges = proof_with_pis.challenges(
+                public_inputs_hash,
+                &verifier_data.circuit_digest,
+                common_data,
+            );
+
+            verify_with_challenges::<F, C, D>(
+                &proof_with_pis.proof,
+                public_inputs_hash,
+                challenges,
+                verifier_data,
+                common_data,
+            )
+        })
+        .collect()
+}
+
 pub(crate) fn verify_with_challenges<
     F: RichField + Extendable<D>,
     C: GenericConfig<D, F = F>,
     const D: usize,
 >(
-    proof: Proof<F, C, D>,
+    proof: &Proof<F, C, D>,
     public_inputs_hash: <<C as GenericConfig<D>>::InnerHasher as Hasher<F>>::Hash,
     challenges: ProofChallenges<F, D>,
     verifier_data: &VerifierOnlyCircuitData<C, D>,
     common_data: &CommonCircuitData<F, D>,
-) -> Result<()> {
+) -> Result<(), VerificationError> {
     let local_constants = &proof.openings.constants;
     let local_wires = &proof.openings.wires;
     let vars = EvaluationVars {
@@ -94,15 +171,17 @@ pub(crate) fn verify_with_challenges<
         .chunks(common_data.quotient_degree_factor)
         .enumerate()
     {
-        ensure!(vanishing_polys_zeta[i] == z_h_zeta * reduce_with_powers(chunk, zeta_pow_deg));
+        if vanishing_polys_zeta[i] != z_h_zeta * reduce_with_powers(chunk, zeta_pow_deg) {
+            return Err(VerificationError::VanishingPolyMismatch { challenge: i });
+        }
     }
 
     let merkle_caps = &[
         verifier_data.constants_sigmas_cap.clone(),
-        proof.wires_cap,
+        proof.wires_cap.clone(),
         // In the lookup case, `plonk_zs_partial_products_cap` should also include the lookup commitment.
-        proof.plonk_zs_partial_products_cap,
-        proof.quotient_polys_cap,
+        proof.plonk_zs_partial_products_cap.clone(),
+        proof.quotient_polys_cap.clone(),
     ];
 
     verify_fri_proof::<F, C, D>(
@@ -112,7 +191,153 @@ pub(crate) fn verify_with_challenges<
         merkle_caps,
         &proof.opening_proof,
         &common_data.fri_params,
-    )?;
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(not(feature = "std"))]
+    use alloc::vec;
+
+    use anyhow::Result;
+
+    use super::*;
+    use crate::field::types::Sample;
+    use crate::gates::noop::NoopGate;
+    use crate::hash::hash_types::HashOut;
+    use crate::hash::poseidon::PoseidonHash;
+    use crate::iop::witness::{PartialWitness, WitnessWrite};
+    use crate::plonk::circuit_builder::CircuitBuilder;
+    use crate::plonk::circuit_data::CircuitConfig;
+    use crate::plonk::config::PoseidonGoldilocksConfig;
+
+    #[test]
+    fn test_verify_batch() -> Result<()> {
+        const D: usize = 2;
+        type C = PoseidonGoldilocksConfig;
+        type F = <C as GenericConfig<D>>::F;
+
+        let config = CircuitConfig::standard_recursion_config();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+        let x = builder.add_virtual_target();
+        let x_cubed = builder.exp_u64(x, 3);
+        builder.register_public_input(x);
+        builder.register_public_input(x_cubed);
+        let data = builder.build::<C>();
+
+        let mut proofs = (0..4)
+            .map(|_| {
+                let mut pw = PartialWitness::new();
+                pw.set_target(x, F::rand());
+                data.prove(pw)
+            })
+            .collect::<Result<Vec<_>>>()?;
+        // An invalid proof, and a proof with the wrong shape.
+        proofs[1].public_inputs[1] += F::ONE;
+        proofs[2].public_inputs.push(F::ZERO);
 
-    Ok(())
+        let results = data.verify_batch(&proofs);
+        assert_eq!(results.len(), proofs.len());
+        assert!(results[0].is_ok());
+        assert!(results[1].is_err());
+        assert!(results[2].is_err());
+        assert!(results[3].is_ok());
+
+        let verifier_data = data.verifier_data();
+        assert!(verifier_data
+            .verify_batch(&proofs[3..])
+            .into_iter()
+            .all(|r| r.is_ok()));
+        Ok(())
+    }
+
+    #[test]
+    fn test_verification_errors() -> Result<()> {
+        const D: usize = 2;
+        type C = PoseidonGoldilocksConfig;
+        type F = <C as GenericConfig<D>>::F;
+
+        let config = CircuitConfig::standard_recursion_config();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+        let x = builder.add_virtual_target();
+        let x_cubed = builder.exp_u64(x, 3);
+        builder.register_public_input(x);
+        builder.register_public_input(x_cubed);
+        for _ in 0..1000 {
+            builder.add_gate(NoopGate, vec![]);
+        }
+        let data = builder.build::<C>();
+
+        let mut pw = PartialWitness::new();
+        pw.set_target(x, F::rand());
+        let proof = data.prove(pw)?;
+        data.verify(proof.clone())?;
+
+        let verification_error =
+            |result: Result<(), VerificationError>| result.expect_err("Verification should fail.");
+
+        let mut tampered = proof.clone();
+        tampered.public_inputs.push(F::ZERO);
+        assert_eq!(
+            verification_error(data.verify(tampered)),
+            VerificationError::ShapeMismatch {
+                item: "public_inputs",
+                expected: 2,
+                actual: 3,
+            }
+        );
+
+        let mut tampered = proof.clone();
+        tampered.proof.openings.wires[0] += <F as Extendable<D>>::Extension::ONE;
+        assert_eq!(
+            verification_error(data.verify(tampered)),
+            VerificationError::VanishingPolyMismatch { challenge: 0 }
+        );
+
+        let mut tampered = proof.clone();
+        tampered.proof.opening_proof.pow_witness += F::ONE;
+        assert_eq!(
+            verification_error(data.verify(tampered)),
+            VerificationError::ProofOfWorkFailure
+        );
+
+        let mut tampered = proof.clone();
+        tampered.proof.opening_proof.query_round_proofs[0]
+            .initial_trees_proof
+            .evals_proofs[0]
+            .1
+            .siblings[0]
+            .elements[0] += F::ONE;
+        assert_eq!(
+            verification_error(data.verify(tampered)),
+            VerificationError::InitialMerkleProofFailure {
+                query_round: 0,
+                oracle: 0,
+            }
+        );
+
+        let mut tampered = proof.clone();
+        tampered.proof.opening_proof.query_round_proofs[0].steps[0]
+            .merkle_proof
+            .siblings[0]
+            .elements[0] += F::ONE;
+        assert_eq!(
+            verification_error(data.verify(tampered)),
+            VerificationError::CommitPhaseMerkleProofFailure {
+                query_round: 0,
+                layer: 0,
+            }
+        );
+
+        let public_inputs_hash = PoseidonHash::hash_no_pad(&proof.public_inputs);
+        data.verify_with_public_inputs_hash(proof.clone(), public_inputs_hash)?;
+        assert_eq!(
+            verification_error(
+                data.verify_with_public_inputs_hash(proof, HashOut::from_partial(&[F::ONE]))
+            ),
+            VerificationError::PublicInputsHashMismatch
+        );
+
+        Ok(())
+    }
 }
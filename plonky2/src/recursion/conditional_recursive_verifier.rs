@@ -410,7 +410,8 @@ mod tests {
         builder.print_gate_counts(100);
         let data = builder.build::<C>();
         let proof = data.prove(pw)?;
-        data.verify(proof)
+        data.verify(proof)?;
+        Ok(())
     }
 
     fn init_logger() {
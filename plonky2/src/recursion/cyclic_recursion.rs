@@ -365,7 +365,8 @@ mod tests {
         );
         assert_eq!(hash, expected_hash);
 
-        cyclic_circuit_data.verify(proof)
+        cyclic_circuit_data.verify(proof)?;
+        Ok(())
     }
 
     fn iterate_poseidon<F: RichField>(initial_state: [F; 4], n: usize) -> [F; 4] {
@@ -4,6 +4,7 @@ use alloc::{
     vec,
     vec::Vec,
 };
+use core::ops::Range;
 
 use log::{log, Level};
 
@@ -110,6 +111,29 @@ impl ContextTree {
         }
     }
 
+    /// The range of gates added within each context, along with the path of that context, listed
+    /// in pre-order. The innermost context of a gate is thus the last one whose range contains it.
+    pub fn spans(&self, current_gate_count: usize) -> Vec<(Range<usize>, String)> {
+        let mut spans = Vec::new();
+        self.spans_helper(current_gate_count, &mut Vec::new(), &mut spans);
+        spans
+    }
+
+    fn spans_helper<'a>(
+        &'a self,
+        current_gate_count: usize,
+        path: &mut Vec<&'a str>,
+        spans: &mut Vec<(Range<usize>, String)>,
+    ) {
+        path.push(&self.name);
+        let exit_gate_count = self.exit_gate_count.unwrap_or(current_gate_count);
+        spans.push((self.enter_gate_count..exit_gate_count, path.join(" > ")));
+        for child in &self.children {
+            child.spans_helper(current_gate_count, path, spans);
+        }
+        path.pop();
+    }
+
     pub fn print(&self, current_gate_count: usize) {
         self.print_helper(current_gate_count, 0);
     }
@@ -0,0 +1,92 @@
+//! Fixed-length arrays stored in memory-mapped temporary files, so that large prover data can be
+//! paged out to disk by the OS rather than held in memory.
+
+use core::fmt::{self, Debug, Formatter};
+use core::marker::PhantomData;
+use core::mem::{size_of, MaybeUninit};
+use core::ops::{Deref, DerefMut};
+use core::slice;
+use std::io;
+use std::path::Path;
+
+use memmap2::MmapMut;
+use plonky2_maybe_rayon::*;
+
+/// A fixed-length array of values, backed by a temporary file mapped in memory. The file has no
+/// name in the file system, and is deleted once the array is dropped.
+pub struct MmapSlice<T: Copy> {
+    mmap: MmapMut,
+    len: usize,
+    _marker: PhantomData<T>,
+}
+
+impl<T: Copy + Send + Sync> MmapSlice<T> {
+    /// Creates an array of `len` copies of `value`, in a temporary file created in `dir`, or in
+    /// the default temporary directory if `dir` is `None`.
+    pub fn new(len: usize, value: T, dir: Option<&Path>) -> io::Result<Self> {
+        let file = match dir {
+            Some(dir) => tempfile::tempfile_in(dir)?,
+            None => tempfile::tempfile()?,
+        };
+        // Mapping an empty file fails on some platforms.
+        file.set_len((len * size_of::<T>()).max(1) as u64)?;
+        // SAFETY: the file is private to this process, as it is unnamed, so it cannot be modified
+        // behind our back.
+        let mut mmap = unsafe { MmapMut::map_mut(&file)? };
+        let ptr = mmap.as_mut_ptr().cast::<MaybeUninit<T>>();
+        // Mappings are page-aligned, which suffices for any plain data type.
+        assert!(ptr.is_aligned());
+        // SAFETY: the mapping holds `len` properly aligned values of type `T`, which may be
+        // uninitialized, but the `MaybeUninit` makes it safe.
+        let uninit = unsafe { slice::from_raw_parts_mut(ptr, len) };
+        uninit.par_iter_mut().for_each(|x| {
+            x.write(value);
+        });
+
+        Ok(Self {
+            mmap,
+            len,
+            _marker: PhantomData,
+        })
+    }
+}
+
+impl<T: Copy> Deref for MmapSlice<T> {
+    type Target = [T];
+
+    fn deref(&self) -> &[T] {
+        // SAFETY: all `len` values were initialized in `new`, and the mapping is only modified
+        // through `deref_mut`.
+        unsafe { slice::from_raw_parts(self.mmap.as_ptr().cast::<T>(), self.len) }
+    }
+}
+
+impl<T: Copy> DerefMut for MmapSlice<T> {
+    fn deref_mut(&mut self) -> &mut [T] {
+        // SAFETY: see `deref`.
+        unsafe { slice::from_raw_parts_mut(self.mmap.as_mut_ptr().cast::<T>(), self.len) }
+    }
+}
+
+impl<T: Copy + Debug> Debug for MmapSlice<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        Debug::fmt(&**self, f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mmap_slice() -> io::Result<()> {
+        let mut values = MmapSlice::new(1000, 7u64, None)?;
+        assert!(values.iter().all(|&x| x == 7));
+        values[999] = 42;
+        assert_eq!(values[998..], [7, 42]);
+
+        let empty = MmapSlice::new(0, 0u64, Some(&std::env::temp_dir()))?;
+        assert!(empty.is_empty());
+        Ok(())
+    }
+}
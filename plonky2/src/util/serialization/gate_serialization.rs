@@ -97,7 +97,9 @@ pub mod default {
 
     use crate::gates::arithmetic_base::ArithmeticGate;
     use crate::gates::arithmetic_extension::ArithmeticExtensionGate;
+    use crate::gates::arithmetic_u32::U32ArithmeticGate;
     use crate::gates::base_sum::BaseSumGate;
+    use crate::gates::comparison::ComparisonGate;
     use crate::gates::constant::ConstantGate;
     use crate::gates::coset_interpolation::CosetInterpolationGate;
     use crate::gates::exponentiation::ExponentiationGate;
@@ -106,11 +108,13 @@ pub mod default {
     use crate::gates::multiplication_extension::MulExtensionGate;
     use crate::gates::noop::NoopGate;
     use crate::gates::poseidon::PoseidonGate;
+    use crate::gates::poseidon2::Poseidon2Gate;
     use crate::gates::poseidon_mds::PoseidonMdsGate;
     use crate::gates::public_input::PublicInputGate;
     use crate::gates::random_access::RandomAccessGate;
     use crate::gates::reducing::ReducingGate;
     use crate::gates::reducing_extension::ReducingExtensionGate;
+    use crate::gates::subtraction_u32::U32SubtractionGate;
     use crate::hash::hash_types::RichField;
     use crate::util::serialization::GateSerializer;
     /// A gate serializer that can be used to serialize all default gates supported
@@ -127,6 +131,8 @@ pub mod default {
     impl<F: RichField + Extendable<D>, const D: usize> GateSerializer<F, D> for DefaultGateSerializer {
         impl_gate_serializer! {
             DefaultGateSerializer,
+            // Tags are given by positions in this list, so new gates must be appended at the end
+            // for previously serialized circuits to remain readable.
             ArithmeticGate,
             ArithmeticExtensionGate<D>,
             BaseSumGate<2>,
@@ -142,7 +148,13 @@ pub mod default {
             PublicInputGate,
             RandomAccessGate<F, D>,
             ReducingExtensionGate<D>,
-            ReducingGate<D>
+            ReducingGate<D>,
+            ComparisonGate<F, D>,
+            U32ArithmeticGate<F, D>,
+            U32SubtractionGate<F, D>,
+            Poseidon2Gate<F, D, 8>,
+            Poseidon2Gate<F, D, 12>,
+            Poseidon2Gate<F, D, 16>
         }
     }
 }
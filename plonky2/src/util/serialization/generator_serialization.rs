@@ -103,22 +103,32 @@ pub mod default {
 
     use crate::gadgets::arithmetic::EqualityGenerator;
     use crate::gadgets::arithmetic_extension::QuotientGeneratorExtension;
+    use crate::gadgets::arithmetic_u32::SplitToU32Generator;
+    use crate::gadgets::biguint::BigUintDivRemGenerator;
+    use crate::gadgets::glv::GLVDecompositionGenerator;
+    use crate::gadgets::nonnative::{
+        NonNativeAdditionGenerator, NonNativeInverseGenerator, NonNativeSubtractionGenerator,
+    };
     use crate::gadgets::range_check::LowHighGenerator;
     use crate::gadgets::split_base::BaseSumGenerator;
     use crate::gadgets::split_join::{SplitGenerator, WireSplitGenerator};
     use crate::gates::arithmetic_base::ArithmeticBaseGenerator;
     use crate::gates::arithmetic_extension::ArithmeticExtensionGenerator;
+    use crate::gates::arithmetic_u32::U32ArithmeticGenerator;
     use crate::gates::base_sum::BaseSplitGenerator;
+    use crate::gates::comparison::ComparisonGenerator;
     use crate::gates::coset_interpolation::InterpolationGenerator;
     use crate::gates::exponentiation::ExponentiationGenerator;
     use crate::gates::lookup::LookupGenerator;
     use crate::gates::lookup_table::LookupTableGenerator;
     use crate::gates::multiplication_extension::MulExtensionGenerator;
     use crate::gates::poseidon::PoseidonGenerator;
+    use crate::gates::poseidon2::Poseidon2Generator;
     use crate::gates::poseidon_mds::PoseidonMdsGenerator;
     use crate::gates::random_access::RandomAccessGenerator;
     use crate::gates::reducing::ReducingGenerator;
     use crate::gates::reducing_extension::ReducingGenerator as ReducingExtensionGenerator;
+    use crate::gates::subtraction_u32::U32SubtractionGenerator;
     use crate::hash::hash_types::RichField;
     use crate::iop::generator::{
         ConstantGenerator, CopyGenerator, NonzeroTestGenerator, RandomValueGenerator,
@@ -153,6 +163,8 @@ pub mod default {
     {
         impl_generator_serializer! {
             DefaultGeneratorSerializer,
+            // Tags are given by positions in this list, so new generators must be appended at the
+            // end for previously serialized circuits to remain readable.
             ArithmeticBaseGenerator<F, D>,
             ArithmeticExtensionGenerator<F, D>,
             BaseSplitGenerator<2>,
@@ -176,7 +188,19 @@ pub mod default {
             ReducingGenerator<D>,
             ReducingExtensionGenerator<D>,
             SplitGenerator,
-            WireSplitGenerator
+            WireSplitGenerator,
+            ComparisonGenerator<F, D>,
+            SplitToU32Generator<F, D>,
+            U32ArithmeticGenerator<F, D>,
+            U32SubtractionGenerator<F, D>,
+            BigUintDivRemGenerator<F, D>,
+            NonNativeAdditionGenerator<F, D>,
+            NonNativeInverseGenerator<F, D>,
+            NonNativeSubtractionGenerator<F, D>,
+            GLVDecompositionGenerator<F, D>,
+            Poseidon2Generator<F, D, 8>,
+            Poseidon2Generator<F, D, 12>,
+            Poseidon2Generator<F, D, 16>
         }
     }
 }
@@ -5,7 +5,7 @@ pub mod generator_serialization;
 pub mod gate_serialization;
 
 #[cfg(not(feature = "std"))]
-use alloc::{collections::BTreeMap, sync::Arc, vec, vec::Vec};
+use alloc::{collections::BTreeMap, string::String, sync::Arc, vec, vec::Vec};
 use core::convert::Infallible;
 use core::fmt::{Debug, Display, Formatter};
 use core::mem::size_of;
@@ -18,6 +18,7 @@ pub use gate_serialization::GateSerializer;
 pub use generator_serialization::default::DefaultGeneratorSerializer;
 pub use generator_serialization::WitnessGeneratorSerializer;
 use hashbrown::HashMap;
+use num::BigUint;
 
 use crate::field::extension::{Extendable, FieldExtension};
 use crate::field::polynomial::PolynomialCoeffs;
@@ -29,6 +30,8 @@ use crate::fri::proof::{
 };
 use crate::fri::reduction_strategies::FriReductionStrategy;
 use crate::fri::{FriConfig, FriParams};
+use crate::gadgets::arithmetic_u32::U32Target;
+use crate::gadgets::biguint::BigUintTarget;
 use crate::gadgets::polynomial::PolynomialCoeffsExtTarget;
 use crate::gates::gate::GateRef;
 use crate::gates::lookup::Lookup;
@@ -107,6 +110,39 @@ pub trait Read {
             .collect::<Result<Vec<_>, _>>()
     }
 
+    /// Reads a `U32Target` value from `self`.
+    #[inline]
+    fn read_target_u32(&mut self) -> IoResult<U32Target> {
+        Ok(U32Target(self.read_target()?))
+    }
+
+    /// Reads a vector of `U32Target` from `self`.
+    #[inline]
+    fn read_target_u32_vec(&mut self) -> IoResult<Vec<U32Target>> {
+        let length = self.read_usize()?;
+        (0..length)
+            .map(|_| self.read_target_u32())
+            .collect::<Result<Vec<_>, _>>()
+    }
+
+    /// Reads a `BigUintTarget` value from `self`.
+    #[inline]
+    fn read_target_biguint(&mut self) -> IoResult<BigUintTarget> {
+        Ok(BigUintTarget {
+            limbs: self.read_target_u32_vec()?,
+        })
+    }
+
+    /// Reads a `BigUint` value from `self`.
+    #[inline]
+    fn read_biguint(&mut self) -> IoResult<BigUint> {
+        let length = self.read_usize()?;
+        let limbs = (0..length)
+            .map(|_| self.read_u32())
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(BigUint::from_slice(&limbs))
+    }
+
     /// Reads a `u8` value from `self`.
     #[inline]
     fn read_u8(&mut self) -> IoResult<u8> {
@@ -151,6 +187,15 @@ pub trait Read {
         Ok(res)
     }
 
+    /// Reads a UTF-8 encoded `String` from `self`.
+    #[inline]
+    fn read_string(&mut self) -> IoResult<String> {
+        let len = self.read_usize()?;
+        let mut bytes = vec![0; len];
+        self.read_exact(&mut bytes)?;
+        String::from_utf8(bytes).map_err(|_| IoError)
+    }
+
     /// Reads a element from the field `F` with size less than `2^64` from `self.`
     #[inline]
     fn read_field<F>(&mut self) -> IoResult<F>
@@ -335,6 +380,8 @@ pub trait Read {
             leaves,
             digests,
             cap,
+            #[cfg(feature = "mmap")]
+            mapped: None,
         })
     }
 
@@ -891,6 +938,13 @@ pub trait Read {
             lut_to_lookups.push(self.read_target_lut()?);
         }
 
+        let length = self.read_usize()?;
+        let mut gate_contexts = Vec::with_capacity(length);
+        for _ in 0..length {
+            let gates = self.read_usize()?..self.read_usize()?;
+            gate_contexts.push((gates, self.read_string()?));
+        }
+
         Ok(ProverOnlyCircuitData {
             generators,
             generator_indices_by_watches,
@@ -903,6 +957,7 @@ pub trait Read {
             circuit_digest,
             lookup_rows,
             lut_to_lookups,
+            gate_contexts,
         })
     }
 
@@ -1214,6 +1269,41 @@ pub trait Write {
         Ok(())
     }
 
+    /// Writes a target u32 `x` to `self`.
+    #[inline]
+    fn write_target_u32(&mut self, x: U32Target) -> IoResult<()> {
+        self.write_target(x.0)
+    }
+
+    /// Writes a vector of U32Target `v` to `self.`
+    #[inline]
+    fn write_target_u32_vec(&mut self, v: &[U32Target]) -> IoResult<()> {
+        self.write_usize(v.len())?;
+        for &elem in v.iter() {
+            self.write_target_u32(elem)?;
+        }
+
+        Ok(())
+    }
+
+    /// Writes a target BigUint `x` to `self`.
+    #[inline]
+    fn write_target_biguint(&mut self, x: &BigUintTarget) -> IoResult<()> {
+        self.write_target_u32_vec(&x.limbs)
+    }
+
+    /// Writes a BigUint `x` to `self`.
+    #[inline]
+    fn write_biguint(&mut self, x: &BigUint) -> IoResult<()> {
+        let limbs = x.to_u32_digits();
+        self.write_usize(limbs.len())?;
+        for limb in limbs {
+            self.write_u32(limb)?;
+        }
+
+        Ok(())
+    }
+
     /// Writes a byte `x` to `self`.
     #[inline]
     fn write_u8(&mut self, x: u8) -> IoResult<()> {
@@ -1249,6 +1339,13 @@ pub trait Write {
         Ok(())
     }
 
+    /// Writes a string `s` to `self`, UTF-8 encoded.
+    #[inline]
+    fn write_string(&mut self, s: &str) -> IoResult<()> {
+        self.write_usize(s.len())?;
+        self.write_all(s.as_bytes())
+    }
+
     /// Writes an element `x` from the field `F` to `self`.
     #[inline]
     fn write_field<F>(&mut self, x: F) -> IoResult<()>
@@ -1419,12 +1516,12 @@ pub trait Write {
         F: RichField,
         H: Hasher<F>,
     {
-        self.write_usize(tree.leaves.len())?;
-        for i in 0..tree.leaves.len() {
-            self.write_usize(tree.leaves[i].len())?;
-            self.write_field_vec(&tree.leaves[i])?;
+        self.write_usize(tree.num_leaves())?;
+        for i in 0..tree.num_leaves() {
+            self.write_usize(tree.get(i).len())?;
+            self.write_field_vec(tree.get(i))?;
         }
-        self.write_hash_vec::<F, H>(&tree.digests)?;
+        self.write_hash_vec::<F, H>(tree.all_digests())?;
         self.write_usize(tree.cap.height())?;
         self.write_merkle_cap(&tree.cap)?;
 
@@ -1853,6 +1950,7 @@ pub trait Write {
             circuit_digest,
             lookup_rows,
             lut_to_lookups,
+            gate_contexts,
         } = prover_only_circuit_data;
 
         self.write_usize(generators.len())?;
@@ -1903,6 +2001,13 @@ pub trait Write {
             self.write_target_lut(tlut)?;
         }
 
+        self.write_usize(gate_contexts.len())?;
+        for (gates, path) in gate_contexts.iter() {
+            self.write_usize(gates.start)?;
+            self.write_usize(gates.end)?;
+            self.write_string(path)?;
+        }
+
         Ok(())
     }
 
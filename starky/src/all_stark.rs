@@ -0,0 +1,235 @@
+//! Definition of multi-STARK systems, i.e. of several STARK tables whose traces are connected by
+//! cross-table lookups, and which are proven together with
+//! [`prove_all`](crate::prover::prove_all).
+//!
+//! As the tables of a system are generally defined by different types implementing [`Stark`],
+//! operations on a given table go through a [`StarkVisitor`], which is generic over the type of
+//! the table's [`Stark`].
+//!
+//! The openings of all the tables are proven by a single batched FRI proof, in which the tables
+//! whose traces have the same length form a single FRI instance.
+
+#[cfg(not(feature = "std"))]
+use alloc::{vec, vec::Vec};
+use core::cmp::Reverse;
+use core::iter::once;
+
+use itertools::Itertools;
+use plonky2::field::extension::Extendable;
+use plonky2::fri::structure::{FriInstanceInfo, FriInstanceInfoTarget};
+use plonky2::hash::hash_types::RichField;
+use plonky2::plonk::verification_error::VerificationError;
+
+use crate::config::StarkConfig;
+use crate::cross_table_lookup::{num_ctl_helper_columns_by_table, CrossTableLookup, TableIdx};
+use crate::stark::Stark;
+
+/// A system of `N` STARK tables, whose traces are connected by cross-table lookups.
+///
+/// A table must be part of some cross-table lookup if and only if its [`Stark`] requires CTLs,
+/// as given by [`Stark::requires_ctls`].
+pub trait AllStark<F: RichField + Extendable<D>, const D: usize, const N: usize> {
+    /// Outputs the cross-table lookups connecting the tables of this system.
+    fn cross_table_lookups(&self) -> Vec<CrossTableLookup<F>>;
+
+    /// Runs `visitor` on the [`Stark`] defining the `table`-th table of this system.
+    fn visit<V: StarkVisitor<F, D>>(&self, table: TableIdx, visitor: V) -> V::Output;
+}
+
+/// An operation on a table of an [`AllStark`], generic over the type of the table's [`Stark`].
+pub trait StarkVisitor<F: RichField + Extendable<D>, const D: usize> {
+    /// The result of this operation.
+    type Output;
+
+    /// Runs this operation on `stark`.
+    fn visit<S: Stark<F, D>>(self, stark: &S) -> Self::Output;
+}
+
+/// The properties of a table of an [`AllStark`] needed to prove and verify the whole system.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct TableInfo {
+    pub(crate) constraint_degree: usize,
+    pub(crate) requires_ctls: bool,
+    pub(crate) num_lookup_columns: usize,
+    /// The number of rows viewed by the evaluation frame of the table.
+    pub(crate) num_row_offsets: usize,
+}
+
+struct GetTableInfo<'a>(&'a StarkConfig);
+
+impl<F: RichField + Extendable<D>, const D: usize> StarkVisitor<F, D> for GetTableInfo<'_> {
+    type Output = TableInfo;
+
+    fn visit<S: Stark<F, D>>(self, stark: &S) -> TableInfo {
+        TableInfo {
+            constraint_degree: stark.constraint_degree(),
+            requires_ctls: stark.requires_ctls(),
+            num_lookup_columns: stark.num_lookup_helper_columns(self.0),
+            num_row_offsets: S::ROW_OFFSETS.len(),
+        }
+    }
+}
+
+/// Returns the properties of each table of `all_stark`.
+pub(crate) fn table_infos<F, A, const D: usize, const N: usize>(
+    all_stark: &A,
+    config: &StarkConfig,
+) -> [TableInfo; N]
+where
+    F: RichField + Extendable<D>,
+    A: AllStark<F, D, N>,
+{
+    core::array::from_fn(|table| all_stark.visit(table, GetTableInfo(config)))
+}
+
+/// Checks that the cross-table lookups of a system are consistent with its tables, and returns the
+/// constraint degree to use for the CTL helper columns, i.e. the maximal degree of all tables.
+pub(crate) fn check_cross_table_lookups<F: RichField, const N: usize>(
+    cross_table_lookups: &[CrossTableLookup<F>],
+    tables: &[TableInfo; N],
+) -> Result<usize, VerificationError> {
+    let mut in_ctls = [false; N];
+    for ctl in cross_table_lookups {
+        for twc in once(&ctl.looked_table).chain(&ctl.looking_tables) {
+            if twc.table >= N {
+                return Err(VerificationError::InvalidCrossTableLookups {
+                    table: twc.table,
+                    reason: "the table does not exist",
+                });
+            }
+            in_ctls[twc.table] = true;
+        }
+    }
+    for (table, (info, in_ctls)) in tables.iter().zip(in_ctls).enumerate() {
+        if info.requires_ctls != in_ctls {
+            return Err(VerificationError::InvalidCrossTableLookups {
+                table,
+                reason: "it must be in some cross-table lookup if and only if it requires CTLs",
+            });
+        }
+        // The transition constraint on `Z` multiplies it by the combined columns.
+        if in_ctls && info.constraint_degree < 3 {
+            return Err(VerificationError::InvalidCrossTableLookups {
+                table,
+                reason: "its constraint degree must be at least 3 to be in a cross-table lookup",
+            });
+        }
+    }
+
+    let max_constraint_degree = tables
+        .iter()
+        .map(|info| info.constraint_degree)
+        .max()
+        .unwrap_or_default();
+    // Helper columns batch the looking columns of a table according to the maximal degree, which
+    // must then be the degree of the table for its CTL constraints to match.
+    for num_helpers in
+        num_ctl_helper_columns_by_table::<F, N>(cross_table_lookups, max_constraint_degree)
+    {
+        for (table, info) in tables.iter().enumerate() {
+            if num_helpers[table] != 0 && info.constraint_degree != max_constraint_degree {
+                return Err(VerificationError::InvalidCrossTableLookups {
+                    table,
+                    reason: "it needs CTL helper columns, so its constraint degree must be maximal",
+                });
+            }
+        }
+    }
+
+    Ok(max_constraint_degree)
+}
+
+/// Groups the tables of a system by the length of their traces, given by `degree_bits`, in
+/// decreasing order of length. Each group is opened as a single instance of the batched FRI proof
+/// of the system, whose instances must have strictly decreasing degrees.
+pub(crate) fn batch_fri_groups<const N: usize>(degree_bits: &[usize; N]) -> Vec<Vec<TableIdx>> {
+    let mut tables = (0..N).collect::<Vec<_>>();
+    tables.sort_by_key(|&table| Reverse(degree_bits[table]));
+    tables
+        .into_iter()
+        .group_by(|&table| degree_bits[table])
+        .into_iter()
+        .map(|(_, group)| group.collect())
+        .collect()
+}
+
+/// Returns the degrees of the instances of the batched FRI proof of a multi-STARK system, given the
+/// groups of tables of [`batch_fri_groups`], i.e. the degree bounds of the committed polynomials.
+pub(crate) fn batch_fri_degree_bits<const N: usize>(
+    groups: &[Vec<TableIdx>],
+    degree_bits: &[usize; N],
+    config: &StarkConfig,
+) -> Vec<usize> {
+    groups
+        .iter()
+        .map(|group| degree_bits[group[0]] + config.blinding_degree_bits())
+        .collect()
+}
+
+/// Merges the FRI instances of the tables of each group given by [`batch_fri_groups`] into the
+/// instances of the batched FRI proof of the system. The oracles of a merged instance are those of
+/// each table of its group in turn.
+pub(crate) fn batch_fri_instances<F: RichField + Extendable<D>, const D: usize>(
+    groups: &[Vec<TableIdx>],
+    instances: Vec<FriInstanceInfo<F, D>>,
+) -> Vec<FriInstanceInfo<F, D>> {
+    let mut instances = instances.into_iter().map(Some).collect_vec();
+    groups
+        .iter()
+        .map(|group| {
+            let mut merged = FriInstanceInfo {
+                oracles: vec![],
+                batches: vec![],
+            };
+            for &table in group {
+                let instance = instances[table]
+                    .take()
+                    .expect("Each table is in a single group.");
+                let first_oracle = merged.oracles.len();
+                merged.oracles.extend(instance.oracles);
+                merged
+                    .batches
+                    .extend(instance.batches.into_iter().map(|mut batch| {
+                        for polynomial in &mut batch.polynomials {
+                            polynomial.oracle_index += first_oracle;
+                        }
+                        batch
+                    }));
+            }
+            merged
+        })
+        .collect()
+}
+
+/// Circuit version of [`batch_fri_instances`].
+pub(crate) fn batch_fri_instances_target<const D: usize>(
+    groups: &[Vec<TableIdx>],
+    instances: Vec<FriInstanceInfoTarget<D>>,
+) -> Vec<FriInstanceInfoTarget<D>> {
+    let mut instances = instances.into_iter().map(Some).collect_vec();
+    groups
+        .iter()
+        .map(|group| {
+            let mut merged = FriInstanceInfoTarget {
+                oracles: vec![],
+                batches: vec![],
+            };
+            for &table in group {
+                let instance = instances[table]
+                    .take()
+                    .expect("Each table is in a single group.");
+                let first_oracle = merged.oracles.len();
+                merged.oracles.extend(instance.oracles);
+                merged
+                    .batches
+                    .extend(instance.batches.into_iter().map(|mut batch| {
+                        for polynomial in &mut batch.polynomials {
+                            polynomial.oracle_index += first_oracle;
+                        }
+                        batch
+                    }));
+            }
+            merged
+        })
+        .collect()
+}
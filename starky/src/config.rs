@@ -26,6 +26,13 @@ pub struct StarkConfig {
 
     /// The configuration of the FRI sub-protocol.
     pub fri_config: FriConfig,
+
+    /// Whether to hide the trace. If set, each trace and auxiliary polynomial `p` is replaced by
+    /// `p + Z_H * r` for a random `r`, which keeps its values on the trace domain `H` but doubles
+    /// its degree bound, the Merkle leaves of all committed polynomials are salted, and a random
+    /// masking polynomial is committed along with the quotient polynomials, which masks the
+    /// polynomial whose low degree is proven by FRI.
+    pub zero_knowledge: bool,
 }
 
 impl Default for StarkConfig {
@@ -41,6 +48,7 @@ impl StarkConfig {
             security_bits,
             num_challenges,
             fri_config,
+            zero_knowledge: false,
         }
     }
 
@@ -57,12 +65,47 @@ impl StarkConfig {
                 reduction_strategy: FriReductionStrategy::ConstantArityBits(4, 5),
                 num_query_rounds: 84,
             },
+            zero_knowledge: false,
         }
     }
 
-    /// Outputs the [`FriParams`] used during the FRI sub-protocol by this [`StarkConfig`].
+    /// Outputs the [`FriParams`] used during the FRI sub-protocol by this [`StarkConfig`], for a
+    /// trace of `2^degree_bits` rows.
     pub fn fri_params(&self, degree_bits: usize) -> FriParams {
-        self.fri_config.fri_params(degree_bits, false)
+        self.fri_config.fri_params(
+            degree_bits + self.blinding_degree_bits(),
+            self.zero_knowledge,
+        )
+    }
+
+    /// The number of bits by which the degree bound of the committed polynomials exceeds the
+    /// length of the trace, i.e. one in zero-knowledge mode, where they are blinded, and zero
+    /// otherwise.
+    pub const fn blinding_degree_bits(&self) -> usize {
+        self.zero_knowledge as usize
+    }
+
+    /// The number of random coefficients of the multiple of `Z_H` blinding each trace and
+    /// auxiliary polynomial in zero-knowledge mode, for a STARK whose evaluation frame views
+    /// `num_rows` rows. A proof reveals one evaluation of the polynomial per viewed row and per
+    /// FRI query, so this many random coefficients make the revealed values independent of the
+    /// trace.
+    pub const fn num_blinding_coeffs(&self, num_rows: usize) -> usize {
+        if self.zero_knowledge {
+            self.fri_config.num_query_rounds + num_rows
+        } else {
+            0
+        }
+    }
+
+    /// The number of random polynomials committed along with the quotient polynomials, i.e. one
+    /// in zero-knowledge mode and none otherwise.
+    pub const fn num_masking_polys(&self) -> usize {
+        if self.zero_knowledge {
+            1
+        } else {
+            0
+        }
     }
 
     /// Checks that this STARK configuration is consistent, i.e. that the different
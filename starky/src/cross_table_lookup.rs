@@ -34,7 +34,6 @@ use core::cmp::min;
 use core::fmt::Debug;
 use core::iter::once;
 
-use anyhow::{ensure, Result};
 use hashbrown::HashMap;
 use itertools::Itertools;
 use plonky2::field::extension::{Extendable, FieldExtension};
@@ -47,6 +46,7 @@ use plonky2::iop::ext_target::ExtensionTarget;
 use plonky2::iop::target::Target;
 use plonky2::plonk::circuit_builder::CircuitBuilder;
 use plonky2::plonk::config::GenericConfig;
+use plonky2::plonk::verification_error::VerificationError;
 
 use crate::config::StarkConfig;
 use crate::constraint_consumer::{ConstraintConsumer, RecursiveConstraintConsumer};
@@ -55,7 +55,7 @@ use crate::lookup::{
     eval_helper_columns, eval_helper_columns_circuit, get_grand_product_challenge_set,
     get_helper_cols, Column, ColumnFilter, Filter, GrandProductChallenge, GrandProductChallengeSet,
 };
-use crate::proof::{MultiProof, StarkProofTarget, StarkProofWithMetadata};
+use crate::proof::{MultiProof, StarkProofWithMetadata, StarkTableProofTarget};
 use crate::stark::Stark;
 
 /// An alias for `usize`, to represent the index of a STARK table in a multi-STARK setting.
@@ -66,7 +66,7 @@ pub type TableIdx = usize;
 /// `columns` represents linear combinations of the columns of `table`.
 #[derive(Clone, Debug)]
 pub struct TableWithColumns<F: Field> {
-    table: TableIdx,
+    pub(crate) table: TableIdx,
     columns: Vec<Column<F>>,
     filter: Filter<F>,
 }
@@ -302,6 +302,36 @@ pub(crate) fn num_ctl_helper_columns_by_table<F: Field, const N: usize>(
     res
 }
 
+/// Returns, for each table, the total number of CTL helper columns and the total number of CTL `Z`
+/// polynomials in its auxiliary polynomials.
+pub(crate) fn num_ctl_helpers_zs_by_table<F: Field, const N: usize>(
+    ctls: &[CrossTableLookup<F>],
+    num_challenges: usize,
+    constraint_degree: usize,
+) -> [(usize, usize); N] {
+    let mut res = [(0, 0); N];
+    for (ctl, num_helpers) in ctls.iter().zip(num_ctl_helper_columns_by_table::<F, N>(
+        ctls,
+        constraint_degree,
+    )) {
+        for (table, num_helpers) in num_helpers.into_iter().enumerate() {
+            res[table].0 += num_helpers * num_challenges;
+        }
+        // One `Z` polynomial for each looking table, and one for the looked table.
+        let mut looking_tables = [false; N];
+        for twc in &ctl.looking_tables {
+            looking_tables[twc.table] = true;
+        }
+        for (table, is_looking) in looking_tables.into_iter().enumerate() {
+            if is_looking {
+                res[table].1 += num_challenges;
+            }
+        }
+        res[ctl.looked_table.table].1 += num_challenges;
+    }
+    res
+}
+
 /// Gets the auxiliary polynomials associated to these CTL data.
 pub(crate) fn get_ctl_auxiliary_polys<F: Field>(
     ctl_data: Option<&CtlData<F>>,
@@ -501,12 +531,20 @@ impl<'a, F: RichField + Extendable<D>, const D: usize>
         num_helper_ctl_columns: &Vec<[usize; N]>,
     ) -> [Vec<Self>; N] {
         let mut ctl_vars_per_table = [0; N].map(|_| vec![]);
-        // If there are no auxiliary polys in the proofs `openings`,
-        // return early. The verifier will reject the proofs when
-        // calling `validate_proof_shape`.
+        // If there are no auxiliary polys in the `openings` of a table involved
+        // in some cross-table lookup, return early. The verifier will reject
+        // the proofs when calling `validate_proof_shape`.
+        let in_ctls = |table| {
+            cross_table_lookups.iter().any(|ctl| {
+                once(&ctl.looked_table)
+                    .chain(&ctl.looking_tables)
+                    .any(|twc| twc.table == table)
+            })
+        };
         if proofs
             .iter()
-            .any(|p| p.proof.openings.auxiliary_polys.is_none())
+            .enumerate()
+            .any(|(table, p)| in_ctls(table) && p.proof.openings.auxiliary_polys.is_none())
         {
             return ctl_vars_per_table;
         }
@@ -524,16 +562,16 @@ impl<'a, F: RichField + Extendable<D>, const D: usize>
             .zip(num_lookup_columns)
             .map(|(p, &num_lookup)| {
                 let openings = &p.proof.openings;
-
-                let ctl_zs = &openings
-                    .auxiliary_polys
-                    .as_ref()
-                    .expect("We cannot have CTls without auxiliary polynomials.")[num_lookup..];
-                let ctl_zs_next = &openings
-                    .auxiliary_polys_next
-                    .as_ref()
-                    .expect("We cannot have CTls without auxiliary polynomials.")[num_lookup..];
-                ctl_zs.iter().zip(ctl_zs_next).collect::<Vec<_>>()
+                // Tables without auxiliary polynomials are not involved in any cross-table lookup.
+                let (Some(ctl_zs), Some(ctl_zs_next)) =
+                    (&openings.auxiliary_polys, &openings.auxiliary_polys_next)
+                else {
+                    return vec![];
+                };
+                ctl_zs[num_lookup..]
+                    .iter()
+                    .zip(&ctl_zs_next[num_lookup..])
+                    .collect::<Vec<_>>()
             })
             .collect::<Vec<_>>();
 
@@ -724,7 +762,7 @@ impl<'a, F: Field, const D: usize> CtlCheckVarsTarget<F, D> {
     /// Circuit version of `from_proofs`, for a single STARK.
     pub fn from_proof(
         table: TableIdx,
-        proof: &StarkProofTarget<D>,
+        proof: &StarkTableProofTarget<D>,
         cross_table_lookups: &'a [CrossTableLookup<F>],
         ctl_challenges: &'a GrandProductChallengeSet<Target>,
         num_lookup_columns: usize,
@@ -907,7 +945,7 @@ pub(crate) fn eval_cross_table_lookup_checks_circuit<
             let combined = builder.mul_sub_extension(combin1, z_diff, f1);
             let combined = builder.mul_extension(combined, combin0);
             let constr = builder.arithmetic_extension(F::NEG_ONE, F::ONE, f0, combin1, combined);
-            consumer.constraint_last_row(builder, constr);
+            consumer.constraint_transition(builder, constr);
         } else {
             let combin0 = challenges.combine_circuit(builder, &evals[0]);
             let f0 = filter[0].eval_filter_circuit(builder, local_values, next_values);
@@ -927,7 +965,7 @@ pub fn verify_cross_table_lookups<F: RichField + Extendable<D>, const D: usize,
     ctl_zs_first: [Vec<F>; N],
     ctl_extra_looking_sums: &HashMap<usize, Vec<F>>,
     config: &StarkConfig,
-) -> Result<()> {
+) -> Result<(), VerificationError> {
     let mut ctl_zs_openings = ctl_zs_first.iter().map(|v| v.iter()).collect::<Vec<_>>();
     for (
         index,
@@ -958,11 +996,12 @@ pub fn verify_cross_table_lookups<F: RichField + Extendable<D>, const D: usize,
             // Get the looked table CTL polynomial opening.
             let looked_z = *ctl_zs_openings[looked_table.table].next().unwrap();
             // Ensure that the combination of looking table openings is equal to the looked table opening.
-            ensure!(
-                looking_zs_sum == looked_z,
-                "Cross-table lookup {:?} verification failed.",
-                index
-            );
+            if looking_zs_sum != looked_z {
+                return Err(VerificationError::CrossTableLookupMismatch {
+                    ctl: index,
+                    challenge: c,
+                });
+            }
         }
     }
     debug_assert!(ctl_zs_openings.iter_mut().all(|iter| iter.next().is_none()));
@@ -1009,8 +1048,9 @@ pub fn verify_cross_table_lookups_circuit<
             );
 
             // Get elements looking into `looked_table` that are not associated to any STARK.
-            let extra_sum = ctl_extra_looking_sum.map(|v| v[c]).unwrap_or_default();
-            looking_zs_sum = builder.add(looking_zs_sum, extra_sum);
+            if let Some(v) = ctl_extra_looking_sum {
+                looking_zs_sum = builder.add(looking_zs_sum, v[c]);
+            }
 
             // Get the looked table CTL polynomial opening.
             let looked_z = *ctl_zs_openings[looked_table.table].next().unwrap();
@@ -1134,3 +1174,182 @@ pub mod debug_utils {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+    use hashbrown::HashMap;
+    use plonky2::field::extension::Extendable;
+    use plonky2::field::types::Sample;
+    use plonky2::iop::witness::{PartialWitness, WitnessWrite};
+    use plonky2::plonk::circuit_builder::CircuitBuilder;
+    use plonky2::plonk::circuit_data::CircuitConfig;
+    use plonky2::plonk::config::{GenericConfig, PoseidonGoldilocksConfig};
+
+    use super::*;
+    use crate::config::StarkConfig;
+    use crate::constraint_consumer::{ConstraintConsumer, RecursiveConstraintConsumer};
+    use crate::evaluation_frame::StarkEvaluationFrame;
+    use crate::fibonacci_stark::FibonacciStark;
+    use crate::lookup::{Column, Filter};
+    use crate::stark::Stark;
+
+    const D: usize = 2;
+    type C = PoseidonGoldilocksConfig;
+    type F = <C as GenericConfig<D>>::F;
+    type FE = <F as Extendable<D>>::Extension;
+    type S = FibonacciStark<F, D>;
+
+    /// Checks that the circuit CTL checks match the native ones when a table has two
+    /// column sets and no helper columns.
+    #[test]
+    fn test_ctl_checks_circuit_two_columns_no_helpers() -> Result<()> {
+        let columns = [vec![Column::single(0)], vec![Column::single(1)]];
+        let filter = vec![
+            Filter::new_simple(Column::single(1)),
+            Filter::new_simple(Column::single(0)),
+        ];
+        let challenges = GrandProductChallenge {
+            beta: F::rand(),
+            gamma: F::rand(),
+        };
+
+        let vars = <S as Stark<F, D>>::EvaluationFrame::<FE, FE, D>::from_values(
+            &FE::rand_vec(S::COLUMNS),
+            &FE::rand_vec(S::COLUMNS),
+            &FE::rand_vec(S::PUBLIC_INPUTS),
+        );
+        let local_z = FE::rand();
+        let next_z = FE::rand();
+        let alphas = F::rand_vec(1);
+        let z_last = FE::rand();
+        let lagrange_first = FE::rand();
+        let lagrange_last = FE::rand();
+
+        let ctl_vars = [CtlCheckVars::<F, FE, FE, D> {
+            helper_columns: vec![],
+            local_z,
+            next_z,
+            challenges,
+            columns: columns.iter().map(|c| &c[..]).collect(),
+            filter: filter.clone(),
+        }];
+        let mut consumer = ConstraintConsumer::<FE>::new(
+            alphas
+                .iter()
+                .copied()
+                .map(<FE as FieldExtension<D>>::from_basefield)
+                .collect(),
+            z_last,
+            lagrange_first,
+            lagrange_last,
+        );
+        eval_cross_table_lookup_checks::<F, FE, FE, S, D, D>(&vars, &ctl_vars, &mut consumer, 3);
+        let native_eval = consumer.accumulators()[0];
+
+        let mut builder = CircuitBuilder::<F, D>::new(CircuitConfig::standard_recursion_config());
+        let mut pw = PartialWitness::<F>::new();
+
+        let locals_t = builder.add_virtual_extension_targets(S::COLUMNS);
+        pw.set_extension_targets(&locals_t, vars.get_local_values());
+        let nexts_t = builder.add_virtual_extension_targets(S::COLUMNS);
+        pw.set_extension_targets(&nexts_t, vars.get_next_values());
+        let pis_t = builder.add_virtual_extension_targets(S::PUBLIC_INPUTS);
+        pw.set_extension_targets(&pis_t, vars.get_public_inputs());
+        let local_z_t = builder.add_virtual_extension_target();
+        pw.set_extension_target(local_z_t, local_z);
+        let next_z_t = builder.add_virtual_extension_target();
+        pw.set_extension_target(next_z_t, next_z);
+        let beta_t = builder.add_virtual_target();
+        pw.set_target(beta_t, challenges.beta);
+        let gamma_t = builder.add_virtual_target();
+        pw.set_target(gamma_t, challenges.gamma);
+        let alphas_t = builder.add_virtual_targets(1);
+        pw.set_target(alphas_t[0], alphas[0]);
+        let z_last_t = builder.add_virtual_extension_target();
+        pw.set_extension_target(z_last_t, z_last);
+        let lagrange_first_t = builder.add_virtual_extension_target();
+        pw.set_extension_target(lagrange_first_t, lagrange_first);
+        let lagrange_last_t = builder.add_virtual_extension_target();
+        pw.set_extension_target(lagrange_last_t, lagrange_last);
+
+        let vars_t =
+            <S as Stark<F, D>>::EvaluationFrameTarget::from_values(&locals_t, &nexts_t, &pis_t);
+        let ctl_vars_t = [CtlCheckVarsTarget {
+            helper_columns: vec![],
+            local_z: local_z_t,
+            next_z: next_z_t,
+            challenges: GrandProductChallenge {
+                beta: beta_t,
+                gamma: gamma_t,
+            },
+            columns: columns.to_vec(),
+            filter,
+        }];
+        let mut consumer = RecursiveConstraintConsumer::<F, D>::new(
+            builder.zero_extension(),
+            alphas_t,
+            z_last_t,
+            lagrange_first_t,
+            lagrange_last_t,
+        );
+        eval_cross_table_lookup_checks_circuit::<S, F, D>(
+            &mut builder,
+            &vars_t,
+            &ctl_vars_t,
+            &mut consumer,
+            3,
+        );
+        let circuit_eval = consumer.accumulators()[0];
+        let native_eval_t = builder.constant_extension(native_eval);
+        builder.connect_extension(circuit_eval, native_eval_t);
+
+        let data = builder.build::<C>();
+        let proof = data.prove(pw)?;
+        data.verify(proof)?;
+        Ok(())
+    }
+
+    /// Checks that a CTL without extra looking sums only compares the looking and looked
+    /// openings in the circuit.
+    #[test]
+    fn test_verify_ctls_circuit_without_extra_looking_sums() -> Result<()> {
+        let config = StarkConfig::standard_fast_config();
+        let ctl = CrossTableLookup::new(
+            vec![TableWithColumns::new(
+                0,
+                vec![Column::single(0)],
+                Filter::new_simple(Column::single(1)),
+            )],
+            TableWithColumns::new(
+                1,
+                vec![Column::single(0)],
+                Filter::new_simple(Column::single(1)),
+            ),
+        );
+
+        let mut builder = CircuitBuilder::<F, D>::new(CircuitConfig::standard_recursion_config());
+        let mut pw = PartialWitness::<F>::new();
+        let zs = F::rand_vec(config.num_challenges);
+        let ctl_zs_first: [Vec<Target>; 2] = core::array::from_fn(|_| {
+            let zs_t = builder.add_virtual_targets(config.num_challenges);
+            for (&z_t, &z) in zs_t.iter().zip(&zs) {
+                pw.set_target(z_t, z);
+            }
+            zs_t
+        });
+
+        verify_cross_table_lookups_circuit::<F, D, 2>(
+            &mut builder,
+            vec![ctl],
+            ctl_zs_first,
+            &HashMap::new(),
+            &config,
+        );
+
+        let data = builder.build::<C>();
+        let proof = data.prove(pw)?;
+        data.verify(proof)?;
+        Ok(())
+    }
+}
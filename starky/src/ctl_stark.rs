@@ -0,0 +1,372 @@
+//! An example of generating and verifying the proof of a multi-STARK system to highlight the use
+//! of cross-table lookups.
+
+#[cfg(not(feature = "std"))]
+use alloc::{vec, vec::Vec};
+use core::marker::PhantomData;
+
+use plonky2::field::extension::{Extendable, FieldExtension};
+use plonky2::field::packed::PackedField;
+use plonky2::field::polynomial::PolynomialValues;
+use plonky2::field::types::Field;
+use plonky2::hash::hash_types::RichField;
+use plonky2::iop::ext_target::ExtensionTarget;
+use plonky2::plonk::circuit_builder::CircuitBuilder;
+
+use crate::all_stark::{AllStark, StarkVisitor};
+use crate::constraint_consumer::{ConstraintConsumer, RecursiveConstraintConsumer};
+use crate::cross_table_lookup::{CrossTableLookup, TableIdx, TableWithColumns};
+use crate::evaluation_frame::{StarkEvaluationFrame, StarkFrame};
+use crate::fibonacci_stark::FibonacciStark;
+use crate::lookup::{Column, Filter};
+use crate::stark::Stark;
+use crate::util::trace_rows_to_poly_values;
+
+/// Counts from a public input `x0`, with state `[x, x^2, y, y^2]` where `y = x + num_rows`, using
+/// the state transition `x' <- x + 1`. The squares are looked up in the [`SquareStark`] table.
+#[derive(Copy, Clone)]
+struct CounterStark<F: RichField + Extendable<D>, const D: usize> {
+    num_rows: usize,
+    _phantom: PhantomData<F>,
+}
+
+impl<F: RichField + Extendable<D>, const D: usize> CounterStark<F, D> {
+    const fn new(num_rows: usize) -> Self {
+        Self {
+            num_rows,
+            _phantom: PhantomData,
+        }
+    }
+
+    /// Generate the trace using `x0` as initial counter value.
+    fn generate_trace(&self, x0: F) -> Vec<PolynomialValues<F>> {
+        let offset = F::from_canonical_usize(self.num_rows);
+        let trace_rows = (0..self.num_rows)
+            .map(|i| {
+                let x = x0 + F::from_canonical_usize(i);
+                let y = x + offset;
+                [x, x.square(), y, y.square()]
+            })
+            .collect::<Vec<_>>();
+        trace_rows_to_poly_values(trace_rows)
+    }
+}
+
+const COUNTER_COLUMNS: usize = 4;
+const COUNTER_PUBLIC_INPUTS: usize = 1;
+
+impl<F: RichField + Extendable<D>, const D: usize> Stark<F, D> for CounterStark<F, D> {
+    const AIR_ID: &'static str = "starky/counter";
+
+    type EvaluationFrame<FE, P, const D2: usize>
+        = StarkFrame<P, P::Scalar, COUNTER_COLUMNS, COUNTER_PUBLIC_INPUTS>
+    where
+        FE: FieldExtension<D2, BaseField = F>,
+        P: PackedField<Scalar = FE>;
+
+    type EvaluationFrameTarget =
+        StarkFrame<ExtensionTarget<D>, ExtensionTarget<D>, COUNTER_COLUMNS, COUNTER_PUBLIC_INPUTS>;
+
+    fn eval_packed_generic<FE, P, const D2: usize>(
+        &self,
+        vars: &Self::EvaluationFrame<FE, P, D2>,
+        yield_constr: &mut ConstraintConsumer<P>,
+    ) where
+        FE: FieldExtension<D2, BaseField = F>,
+        P: PackedField<Scalar = FE>,
+    {
+        let local_values = vars.get_local_values();
+        let next_values = vars.get_next_values();
+        let public_inputs = vars.get_public_inputs();
+        let offset = FE::from_canonical_usize(self.num_rows);
+
+        yield_constr.constraint_first_row(local_values[0] - public_inputs[0]);
+        yield_constr.constraint_transition(next_values[0] - local_values[0] - FE::ONE);
+        yield_constr.constraint(local_values[2] - local_values[0] - offset);
+    }
+
+    fn eval_ext_circuit(
+        &self,
+        builder: &mut CircuitBuilder<F, D>,
+        vars: &Self::EvaluationFrameTarget,
+        yield_constr: &mut RecursiveConstraintConsumer<F, D>,
+    ) {
+        let local_values = vars.get_local_values();
+        let next_values = vars.get_next_values();
+        let public_inputs = vars.get_public_inputs();
+        let one = builder.one_extension();
+        let offset = builder.constant_extension(F::Extension::from_canonical_usize(self.num_rows));
+
+        let first_row = builder.sub_extension(local_values[0], public_inputs[0]);
+        yield_constr.constraint_first_row(builder, first_row);
+        let increment = builder.add_extension(local_values[0], one);
+        let transition = builder.sub_extension(next_values[0], increment);
+        yield_constr.constraint_transition(builder, transition);
+        let shifted = builder.add_extension(local_values[0], offset);
+        let constraint = builder.sub_extension(local_values[2], shifted);
+        yield_constr.constraint(builder, constraint);
+    }
+
+    // The CTL helper column checks and the `Z` transition constraint are of degree 3.
+    fn constraint_degree(&self) -> usize {
+        3
+    }
+
+    fn requires_ctls(&self) -> bool {
+        true
+    }
+}
+
+/// A table of pairs `[a, a^2]`, in any order.
+#[derive(Copy, Clone)]
+struct SquareStark<F: RichField + Extendable<D>, const D: usize> {
+    num_rows: usize,
+    _phantom: PhantomData<F>,
+}
+
+impl<F: RichField + Extendable<D>, const D: usize> SquareStark<F, D> {
+    const fn new(num_rows: usize) -> Self {
+        Self {
+            num_rows,
+            _phantom: PhantomData,
+        }
+    }
+
+    /// Generate the trace with the squares of `0..num_rows`, in reverse order.
+    fn generate_trace(&self) -> Vec<PolynomialValues<F>> {
+        let trace_rows = (0..self.num_rows)
+            .rev()
+            .map(|i| {
+                let a = F::from_canonical_usize(i);
+                [a, a.square()]
+            })
+            .collect::<Vec<_>>();
+        trace_rows_to_poly_values(trace_rows)
+    }
+}
+
+const SQUARE_COLUMNS: usize = 2;
+const SQUARE_PUBLIC_INPUTS: usize = 0;
+
+impl<F: RichField + Extendable<D>, const D: usize> Stark<F, D> for SquareStark<F, D> {
+    const AIR_ID: &'static str = "starky/square";
+
+    type EvaluationFrame<FE, P, const D2: usize>
+        = StarkFrame<P, P::Scalar, SQUARE_COLUMNS, SQUARE_PUBLIC_INPUTS>
+    where
+        FE: FieldExtension<D2, BaseField = F>,
+        P: PackedField<Scalar = FE>;
+
+    type EvaluationFrameTarget =
+        StarkFrame<ExtensionTarget<D>, ExtensionTarget<D>, SQUARE_COLUMNS, SQUARE_PUBLIC_INPUTS>;
+
+    fn eval_packed_generic<FE, P, const D2: usize>(
+        &self,
+        vars: &Self::EvaluationFrame<FE, P, D2>,
+        yield_constr: &mut ConstraintConsumer<P>,
+    ) where
+        FE: FieldExtension<D2, BaseField = F>,
+        P: PackedField<Scalar = FE>,
+    {
+        let local_values = vars.get_local_values();
+        yield_constr.constraint(local_values[1] - local_values[0].square());
+    }
+
+    fn eval_ext_circuit(
+        &self,
+        builder: &mut CircuitBuilder<F, D>,
+        vars: &Self::EvaluationFrameTarget,
+        yield_constr: &mut RecursiveConstraintConsumer<F, D>,
+    ) {
+        let local_values = vars.get_local_values();
+        let constraint = builder.arithmetic_extension(
+            F::NEG_ONE,
+            F::ONE,
+            local_values[0],
+            local_values[0],
+            local_values[1],
+        );
+        yield_constr.constraint(builder, constraint);
+    }
+
+    // The CTL `Z` transition constraint is of degree 3.
+    fn constraint_degree(&self) -> usize {
+        3
+    }
+
+    fn requires_ctls(&self) -> bool {
+        true
+    }
+}
+
+/// A system of three tables: a [`CounterStark`] looking up pairs from a [`SquareStark`] twice as
+/// long, and a [`FibonacciStark`] which is not connected to the other tables.
+struct SquaresSystem<F: RichField + Extendable<D>, const D: usize> {
+    counter: CounterStark<F, D>,
+    squares: SquareStark<F, D>,
+    fibonacci: FibonacciStark<F, D>,
+}
+
+impl<F: RichField + Extendable<D>, const D: usize> SquaresSystem<F, D> {
+    const COUNTER: TableIdx = 0;
+    const SQUARES: TableIdx = 1;
+    const FIBONACCI: TableIdx = 2;
+
+    const fn new(num_rows: usize) -> Self {
+        Self {
+            counter: CounterStark::new(num_rows),
+            squares: SquareStark::new(2 * num_rows),
+            fibonacci: FibonacciStark::new(num_rows),
+        }
+    }
+}
+
+impl<F: RichField + Extendable<D>, const D: usize> AllStark<F, D, 3> for SquaresSystem<F, D> {
+    fn cross_table_lookups(&self) -> Vec<CrossTableLookup<F>> {
+        vec![CrossTableLookup::new(
+            vec![
+                TableWithColumns::new(
+                    Self::COUNTER,
+                    Column::singles([0, 1]).collect(),
+                    Filter::default(),
+                ),
+                TableWithColumns::new(
+                    Self::COUNTER,
+                    Column::singles([2, 3]).collect(),
+                    Filter::default(),
+                ),
+            ],
+            TableWithColumns::new(
+                Self::SQUARES,
+                Column::singles([0, 1]).collect(),
+                Filter::default(),
+            ),
+        )]
+    }
+
+    fn visit<V: StarkVisitor<F, D>>(&self, table: TableIdx, visitor: V) -> V::Output {
+        match table {
+            Self::COUNTER => visitor.visit(&self.counter),
+            Self::SQUARES => visitor.visit(&self.squares),
+            Self::FIBONACCI => visitor.visit(&self.fibonacci),
+            _ => unreachable!("There are only three tables."),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(not(feature = "std"))]
+    use alloc::vec;
+
+    use anyhow::Result;
+    use plonky2::field::ops::Square;
+    use plonky2::field::types::Field;
+    use plonky2::iop::witness::PartialWitness;
+    use plonky2::plonk::circuit_builder::CircuitBuilder;
+    use plonky2::plonk::circuit_data::CircuitConfig;
+    use plonky2::plonk::config::{GenericConfig, PoseidonGoldilocksConfig};
+    use plonky2::plonk::verification_error::VerificationError;
+    use plonky2::util::timing::TimingTree;
+
+    use crate::config::StarkConfig;
+    use crate::ctl_stark::SquaresSystem;
+    use crate::prover::{all_proving_keys, prove_all, prove_all_with_keys};
+    use crate::recursive_verifier::{
+        add_virtual_multi_proof_with_pis, set_multi_proof_with_pis_target, verify_all_circuit,
+    };
+    use crate::verifier::verify_all;
+    use crate::verifying_key::all_verifying_keys;
+
+    const D: usize = 2;
+    type C = PoseidonGoldilocksConfig;
+    type F = <C as GenericConfig<D>>::F;
+
+    #[test]
+    fn test_cross_table_lookups() -> Result<()> {
+        let config = StarkConfig::standard_fast_config();
+        let num_rows = 1 << 5;
+        let system = SquaresSystem::<F, D>::new(num_rows);
+
+        let fibonacci_trace = system.fibonacci.generate_trace(F::ZERO, F::ONE);
+        let fibonacci_inputs = vec![F::ZERO, F::ONE, fibonacci_trace[1].values[num_rows - 1]];
+        let traces = [
+            system.counter.generate_trace(F::ZERO),
+            system.squares.generate_trace(),
+            fibonacci_trace,
+        ];
+        let public_inputs = [vec![F::ZERO], vec![], fibonacci_inputs];
+
+        let proof = prove_all::<F, C, _, D, 3>(
+            &system,
+            &config,
+            traces.clone(),
+            public_inputs.clone(),
+            &mut TimingTree::default(),
+        )?;
+        let verifying_keys = all_verifying_keys::<F, C, _, D, 3>(&system, &config);
+        verify_all(&system, &proof, &verifying_keys, &config)?;
+
+        // The squares table is twice as long as the others, so the batched FRI proof has two
+        // instances, and it is checked against the degrees of the tables.
+        assert_eq!(proof.proof.degree_bits, [5, 6, 5]);
+        let mut wrong_degrees = proof.clone();
+        wrong_degrees.proof.degree_bits[SquaresSystem::<F, D>::SQUARES] = 5;
+        assert!(verify_all(&system, &wrong_degrees, &verifying_keys, &config).is_err());
+        let mut wrong_opening_proof = proof.clone();
+        wrong_opening_proof.proof.opening_proof.final_poly.coeffs[0] += F::ONE.into();
+        assert!(verify_all(&system, &wrong_opening_proof, &verifying_keys, &config).is_err());
+
+        // Recursively verify the proof.
+        let circuit_config = CircuitConfig::standard_recursion_config();
+        let mut builder = CircuitBuilder::<F, D>::new(circuit_config);
+        let mut pw = PartialWitness::new();
+        let degree_bits = proof.proof.degree_bits;
+        let pt = add_virtual_multi_proof_with_pis(&mut builder, &system, &config, &degree_bits)?;
+        set_multi_proof_with_pis_target(&mut pw, &pt, &proof, builder.zero());
+        verify_all_circuit::<F, C, _, D, 3>(&mut builder, &system, &pt, &verifying_keys, &config)?;
+        let data = builder.build::<C>();
+        data.verify(data.prove(pw)?)?;
+
+        // Verifying keys of the wrong tables are rejected when building the circuit.
+        let mut builder = CircuitBuilder::<F, D>::new(CircuitConfig::standard_recursion_config());
+        let pt = add_virtual_multi_proof_with_pis(&mut builder, &system, &config, &degree_bits)?;
+        let mut swapped_keys = verifying_keys.clone();
+        swapped_keys.swap(0, 2);
+        assert!(verify_all_circuit::<F, C, _, D, 3>(
+            &mut builder,
+            &system,
+            &pt,
+            &swapped_keys,
+            &config
+        )
+        .is_err());
+        assert_eq!(
+            verify_all(&system, &proof, &swapped_keys, &config),
+            Err(VerificationError::VerifyingKeyMismatch)
+        );
+
+        // Replacing a pair of the looked table makes the cross-table lookup fail, even though each
+        // table is valid on its own.
+        let mut traces = traces;
+        traces[SquaresSystem::<F, D>::SQUARES][0].values[0] = F::from_canonical_usize(3 * num_rows);
+        traces[SquaresSystem::<F, D>::SQUARES][1].values[0] =
+            F::from_canonical_usize(3 * num_rows).square();
+        let proving_keys =
+            all_proving_keys::<F, C, _, D, 3>(&system, &config, &mut TimingTree::default());
+        let proof = prove_all_with_keys(
+            &system,
+            &config,
+            &proving_keys,
+            traces,
+            public_inputs,
+            &mut TimingTree::default(),
+        )?;
+        assert!(matches!(
+            verify_all(&system, &proof, &verifying_keys, &config),
+            Err(VerificationError::CrossTableLookupMismatch { .. })
+        ));
+
+        Ok(())
+    }
+}
@@ -2,9 +2,9 @@
 
 /// A trait for viewing an evaluation frame of a STARK table.
 ///
-/// It allows to access the current and next rows at a given step
-/// and can be used to implement constraint evaluation both natively
-/// and recursively.
+/// It allows to access the current and next rows at a given step, as well
+/// as any other row viewed by the frame, and can be used to implement
+/// constraint evaluation both natively and recursively.
 pub trait StarkEvaluationFrame<T: Copy + Clone + Default, U: Copy + Clone + Default>:
     Sized
 {
@@ -12,12 +12,24 @@ pub trait StarkEvaluationFrame<T: Copy + Clone + Default, U: Copy + Clone + Defa
     const COLUMNS: usize;
     /// The number of public inputs for the STARK.
     const PUBLIC_INPUTS: usize;
+    /// The number of rows this evaluation frame views, the first two being
+    /// the current and next rows.
+    const ROWS: usize = 2;
 
     /// Returns the local values (i.e. current row) for this evaluation frame.
     fn get_local_values(&self) -> &[T];
     /// Returns the next values (i.e. next row) for this evaluation frame.
     fn get_next_values(&self) -> &[T];
 
+    /// Returns the values of the `i`-th row viewed by this evaluation frame.
+    fn get_row_values(&self, i: usize) -> &[T] {
+        match i {
+            0 => self.get_local_values(),
+            1 => self.get_next_values(),
+            _ => panic!("Row {i} is out of the evaluation frame."),
+        }
+    }
+
     /// Returns the public inputs for this evaluation frame.
     fn get_public_inputs(&self) -> &[U];
 
@@ -26,34 +38,56 @@ pub trait StarkEvaluationFrame<T: Copy + Clone + Default, U: Copy + Clone + Defa
     /// **NOTE**: Concrete implementations of this method SHOULD ensure that
     /// the provided slices lengths match the `Self::COLUMNS` value.
     fn from_values(lv: &[T], nv: &[T], pis: &[U]) -> Self;
+
+    /// Outputs a new evaluation frame from the provided values of each row.
+    ///
+    /// **NOTE**: Concrete implementations of this method SHOULD ensure that
+    /// `Self::ROWS` rows are provided.
+    fn from_rows(rows: &[&[T]], pis: &[U]) -> Self {
+        assert_eq!(rows.len(), 2);
+        Self::from_values(rows[0], rows[1], pis)
+    }
 }
 
 /// An evaluation frame to be used when defining constraints of a STARK system, that
 /// implements the [`StarkEvaluationFrame`] trait.
+///
+/// The frame views `R` rows, which default to the current and next ones. The offsets
+/// of the rows beyond these are given by [`Stark::ROW_OFFSETS`](crate::stark::Stark::ROW_OFFSETS).
 #[derive(Debug)]
 pub struct StarkFrame<
     T: Copy + Clone + Default,
     U: Copy + Clone + Default,
     const N: usize,
     const N2: usize,
+    const R: usize = 2,
 > {
-    local_values: [T; N],
-    next_values: [T; N],
+    rows: [[T; N]; R],
     public_inputs: [U; N2],
 }
 
-impl<T: Copy + Clone + Default, U: Copy + Clone + Default, const N: usize, const N2: usize>
-    StarkEvaluationFrame<T, U> for StarkFrame<T, U, N, N2>
+impl<
+        T: Copy + Clone + Default,
+        U: Copy + Clone + Default,
+        const N: usize,
+        const N2: usize,
+        const R: usize,
+    > StarkEvaluationFrame<T, U> for StarkFrame<T, U, N, N2, R>
 {
     const COLUMNS: usize = N;
     const PUBLIC_INPUTS: usize = N2;
+    const ROWS: usize = R;
 
     fn get_local_values(&self) -> &[T] {
-        &self.local_values
+        &self.rows[0]
     }
 
     fn get_next_values(&self) -> &[T] {
-        &self.next_values
+        &self.rows[1]
+    }
+
+    fn get_row_values(&self, i: usize) -> &[T] {
+        &self.rows[i]
     }
 
     fn get_public_inputs(&self) -> &[U] {
@@ -61,13 +95,17 @@ impl<T: Copy + Clone + Default, U: Copy + Clone + Default, const N: usize, const
     }
 
     fn from_values(lv: &[T], nv: &[T], pis: &[U]) -> Self {
-        assert_eq!(lv.len(), Self::COLUMNS);
-        assert_eq!(nv.len(), Self::COLUMNS);
+        Self::from_rows(&[lv, nv], pis)
+    }
+
+    fn from_rows(rows: &[&[T]], pis: &[U]) -> Self {
+        assert!(R >= 2, "An evaluation frame views at least two rows.");
+        assert_eq!(rows.len(), Self::ROWS);
+        assert!(rows.iter().all(|row| row.len() == Self::COLUMNS));
         assert_eq!(pis.len(), Self::PUBLIC_INPUTS);
 
         Self {
-            local_values: lv.try_into().unwrap(),
-            next_values: nv.try_into().unwrap(),
+            rows: core::array::from_fn(|i| rows[i].try_into().unwrap()),
             public_inputs: pis.try_into().unwrap(),
         }
     }
@@ -22,7 +22,7 @@ use crate::util::trace_rows_to_poly_values;
 /// Computes a Fibonacci sequence with state `[x0, x1]` using the state transition
 /// `x0' <- x1, x1' <- x0 + x1.
 #[derive(Copy, Clone)]
-struct FibonacciStark<F: RichField + Extendable<D>, const D: usize> {
+pub(crate) struct FibonacciStark<F: RichField + Extendable<D>, const D: usize> {
     num_rows: usize,
     _phantom: PhantomData<F>,
 }
@@ -36,7 +36,7 @@ impl<F: RichField + Extendable<D>, const D: usize> FibonacciStark<F, D> {
     // `num_rows`-th Fibonacci number.
     const PI_INDEX_RES: usize = 2;
 
-    const fn new(num_rows: usize) -> Self {
+    pub(crate) const fn new(num_rows: usize) -> Self {
         Self {
             num_rows,
             _phantom: PhantomData,
@@ -44,7 +44,7 @@ impl<F: RichField + Extendable<D>, const D: usize> FibonacciStark<F, D> {
     }
 
     /// Generate the trace using `x0, x1` as initial state values.
-    fn generate_trace(&self, x0: F, x1: F) -> Vec<PolynomialValues<F>> {
+    pub(crate) fn generate_trace(&self, x0: F, x1: F) -> Vec<PolynomialValues<F>> {
         let trace_rows = (0..self.num_rows)
             .scan([x0, x1], |acc, _| {
                 let tmp = *acc;
@@ -61,7 +61,10 @@ const FIBONACCI_COLUMNS: usize = 2;
 const FIBONACCI_PUBLIC_INPUTS: usize = 3;
 
 impl<F: RichField + Extendable<D>, const D: usize> Stark<F, D> for FibonacciStark<F, D> {
-    type EvaluationFrame<FE, P, const D2: usize> = StarkFrame<P, P::Scalar, FIBONACCI_COLUMNS, FIBONACCI_PUBLIC_INPUTS>
+    const AIR_ID: &'static str = "starky/fibonacci";
+
+    type EvaluationFrame<FE, P, const D2: usize>
+        = StarkFrame<P, P::Scalar, FIBONACCI_COLUMNS, FIBONACCI_PUBLIC_INPUTS>
     where
         FE: FieldExtension<D2, BaseField = F>,
         P: PackedField<Scalar = FE>;
@@ -133,10 +136,14 @@ impl<F: RichField + Extendable<D>, const D: usize> Stark<F, D> for FibonacciStar
 
 #[cfg(test)]
 mod tests {
+    #[cfg(not(feature = "std"))]
+    use alloc::vec::Vec;
+
     use anyhow::Result;
     use plonky2::field::extension::Extendable;
     use plonky2::field::types::Field;
     use plonky2::hash::hash_types::RichField;
+    use plonky2::iop::challenger::Challenger;
     use plonky2::iop::witness::PartialWitness;
     use plonky2::plonk::circuit_builder::CircuitBuilder;
     use plonky2::plonk::circuit_data::CircuitConfig;
@@ -154,6 +161,7 @@ mod tests {
     use crate::stark::Stark;
     use crate::stark_testing::{test_stark_circuit_constraints, test_stark_low_degree};
     use crate::verifier::verify_stark_proof;
+    use crate::verifying_key::StarkVerifyingKey;
 
     fn fibonacci<F: Field>(n: usize, x0: F, x1: F) -> F {
         (0..n).fold((x0, x1), |x, _| (x.1, x.0 + x.1)).1
@@ -180,7 +188,8 @@ mod tests {
             &mut TimingTree::default(),
         )?;
 
-        verify_stark_proof(stark, proof, &config)
+        verify_stark_proof(stark, proof, &StarkVerifyingKey::new(&stark, &config))?;
+        Ok(())
     }
 
     #[test]
@@ -229,11 +238,100 @@ mod tests {
             &public_inputs,
             &mut TimingTree::default(),
         )?;
-        verify_stark_proof(stark, proof.clone(), &config)?;
+        verify_stark_proof(
+            stark,
+            proof.clone(),
+            &StarkVerifyingKey::new(&stark, &config),
+        )?;
+
+        recursive_proof::<F, C, S, C, D>(stark, proof, &config, true)
+    }
+
+    #[test]
+    fn test_zero_knowledge_stark() -> Result<()> {
+        init_logger();
+        const D: usize = 2;
+        type C = PoseidonGoldilocksConfig;
+        type F = <C as GenericConfig<D>>::F;
+        type S = FibonacciStark<F, D>;
+
+        let config = StarkConfig {
+            zero_knowledge: true,
+            ..StarkConfig::standard_fast_config()
+        };
+        let num_rows = 1 << 8;
+        let public_inputs = [F::ZERO, F::ONE, fibonacci(num_rows - 1, F::ZERO, F::ONE)];
+
+        let stark = S::new(num_rows);
+        let trace = stark.generate_trace(public_inputs[0], public_inputs[1]);
+        let verifying_key = StarkVerifyingKey::<F, C, D>::new(&stark, &config);
+        let proofs = (0..2)
+            .map(|_| {
+                prove::<F, C, S, D>(
+                    stark,
+                    &config,
+                    trace.clone(),
+                    &public_inputs,
+                    &mut TimingTree::default(),
+                )
+            })
+            .collect::<Result<Vec<_>>>()?;
+        // The commitments to the same trace are salted differently.
+        assert_ne!(proofs[0].proof.trace_cap, proofs[1].proof.trace_cap);
+        // The trace openings are those of blinded polynomials, not of the trace interpolants.
+        let trace_coeffs = trace.iter().map(|v| v.clone().ifft()).collect::<Vec<_>>();
+        for proof in &proofs {
+            let mut challenger = Challenger::<F, <C as GenericConfig<D>>::Hasher>::new();
+            challenger
+                .observe_hash::<<C as GenericConfig<D>>::Hasher>(verifying_key.circuit_digest());
+            let zeta = proof
+                .get_challenges(&mut challenger, None, false, &config)
+                .stark_zeta;
+            for (coeffs, &opening) in trace_coeffs.iter().zip(&proof.proof.openings.local_values) {
+                assert_ne!(coeffs.to_extension::<D>().eval(zeta), opening);
+            }
+        }
+
+        let proof = proofs[0].clone();
+        verify_stark_proof(stark, proof.clone(), &verifying_key)?;
+        // A proof must be verified with the configuration it was generated with.
+        assert!(verify_stark_proof(
+            stark,
+            proof.clone(),
+            &StarkVerifyingKey::new(&stark, &StarkConfig::standard_fast_config())
+        )
+        .is_err());
 
         recursive_proof::<F, C, S, C, D>(stark, proof, &config, true)
     }
 
+    #[test]
+    fn test_zero_knowledge_stark_too_few_rows() {
+        const D: usize = 2;
+        type C = PoseidonGoldilocksConfig;
+        type F = <C as GenericConfig<D>>::F;
+        type S = FibonacciStark<F, D>;
+
+        let config = StarkConfig {
+            zero_knowledge: true,
+            ..StarkConfig::standard_fast_config()
+        };
+        // Fewer rows than `2 * config.num_blinding_coeffs(2)`.
+        let num_rows = 1 << 5;
+        let public_inputs = [F::ZERO, F::ONE, fibonacci(num_rows - 1, F::ZERO, F::ONE)];
+
+        let stark = S::new(num_rows);
+        let trace = stark.generate_trace(public_inputs[0], public_inputs[1]);
+        assert!(prove::<F, C, S, D>(
+            stark,
+            &config,
+            trace,
+            &public_inputs,
+            &mut TimingTree::default(),
+        )
+        .is_err());
+    }
+
     fn recursive_proof<
         F: RichField + Extendable<D>,
         C: GenericConfig<D, F = F>,
@@ -257,7 +355,12 @@ mod tests {
             add_virtual_stark_proof_with_pis(&mut builder, &stark, inner_config, degree_bits, 0, 0);
         set_stark_proof_with_pis_target(&mut pw, &pt, &inner_proof, builder.zero());
 
-        verify_stark_proof_circuit::<F, InnerC, S, D>(&mut builder, stark, pt, inner_config);
+        verify_stark_proof_circuit::<F, InnerC, S, D>(
+            &mut builder,
+            stark,
+            pt,
+            &StarkVerifyingKey::new(&stark, inner_config),
+        );
 
         if print_gate_counts {
             builder.print_gate_counts(0);
@@ -265,7 +368,8 @@ mod tests {
 
         let data = builder.build::<C>();
         let proof = data.prove(pw)?;
-        data.verify(proof)
+        data.verify(proof)?;
+        Ok(())
     }
 
     fn init_logger() {
@@ -1,13 +1,11 @@
 use plonky2::field::extension::Extendable;
-use plonky2::field::polynomial::PolynomialCoeffs;
 use plonky2::fri::proof::{FriProof, FriProofTarget};
-use plonky2::gadgets::polynomial::PolynomialCoeffsExtTarget;
-use plonky2::hash::hash_types::{MerkleCapTarget, RichField};
+use plonky2::hash::hash_types::{HashOutTarget, MerkleCapTarget, RichField};
 use plonky2::hash::merkle_tree::MerkleCap;
 use plonky2::iop::challenger::{Challenger, RecursiveChallenger};
 use plonky2::iop::target::Target;
 use plonky2::plonk::circuit_builder::CircuitBuilder;
-use plonky2::plonk::config::{AlgebraicHasher, GenericConfig};
+use plonky2::plonk::config::{AlgebraicHasher, GenericConfig, Hasher};
 
 use crate::config::StarkConfig;
 use crate::lookup::{
@@ -16,26 +14,22 @@ use crate::lookup::{
 };
 use crate::proof::*;
 
-/// Generates challenges for a STARK proof from a challenger and given
+/// Generates the challenges of a STARK proof which come before FRI, from a challenger and given
 /// all the arguments needed to update the challenger state.
 ///
 /// Note: `trace_cap` is passed as `Option` to signify whether to observe it
 /// or not by the challenger. Observing it here could be redundant in a
 /// multi-STARK system where trace caps would have already been observed
 /// before proving individually each STARK.
-fn get_challenges<F, C, const D: usize>(
+fn get_table_challenges<F, C, const D: usize>(
     challenger: &mut Challenger<F, C::Hasher>,
     challenges: Option<&GrandProductChallengeSet<F>>,
     trace_cap: Option<&MerkleCap<F, C::Hasher>>,
     auxiliary_polys_cap: Option<&MerkleCap<F, C::Hasher>>,
     quotient_polys_cap: Option<&MerkleCap<F, C::Hasher>>,
     openings: &StarkOpeningSet<F, D>,
-    commit_phase_merkle_caps: &[MerkleCap<F, C::Hasher>],
-    final_poly: &PolynomialCoeffs<F::Extension>,
-    pow_witness: F,
     config: &StarkConfig,
-    degree_bits: usize,
-) -> StarkProofChallenges<F, D>
+) -> StarkTableChallenges<F, D>
 where
     F: RichField + Extendable<D>,
     C: GenericConfig<D, F = F>,
@@ -67,17 +61,10 @@ where
 
     challenger.observe_openings(&openings.to_fri_openings());
 
-    StarkProofChallenges {
+    StarkTableChallenges {
         lookup_challenge_set,
         stark_alphas,
         stark_zeta,
-        fri_challenges: challenger.fri_challenges::<C, D>(
-            commit_phase_merkle_caps,
-            final_poly,
-            pow_witness,
-            degree_bits,
-            &config.fri_config,
-        ),
     }
 }
 
@@ -122,18 +109,57 @@ where
             Some(trace_cap)
         };
 
-        get_challenges::<F, C, D>(
+        let StarkTableChallenges {
+            lookup_challenge_set,
+            stark_alphas,
+            stark_zeta,
+        } = get_table_challenges::<F, C, D>(
             challenger,
             challenges,
             trace_cap,
             auxiliary_polys_cap.as_ref(),
             quotient_polys_cap.as_ref(),
             openings,
-            commit_phase_merkle_caps,
-            final_poly,
-            *pow_witness,
             config,
-            degree_bits,
+        );
+
+        StarkProofChallenges {
+            lookup_challenge_set,
+            stark_alphas,
+            stark_zeta,
+            fri_challenges: challenger.fri_challenges::<C, D>(
+                commit_phase_merkle_caps,
+                final_poly,
+                *pow_witness,
+                degree_bits + config.blinding_degree_bits(),
+                &config.fri_config,
+            ),
+        }
+    }
+}
+
+impl<F, C, const D: usize> StarkTableProof<F, C, D>
+where
+    F: RichField + Extendable<D>,
+    C: GenericConfig<D, F = F>,
+{
+    /// Computes the Fiat-Shamir challenges of a single STARK within a multi-STARK proof, apart
+    /// from the FRI challenges which are shared by all STARKs. The trace cap is not observed, as
+    /// the trace caps of all STARKs are observed ahead of proving each table.
+    pub fn get_challenges(
+        &self,
+        challenger: &mut Challenger<F, C::Hasher>,
+        challenges: Option<&GrandProductChallengeSet<F>>,
+        config: &StarkConfig,
+    ) -> StarkTableChallenges<F, D> {
+        get_table_challenges::<F, C, D>(
+            challenger,
+            challenges,
+            None,
+            self.auxiliary_polys_cap.as_ref(),
+            self.quotient_polys_cap.as_ref(),
+            &self.openings,
+            config,
         )
     }
 }
@@ -163,9 +189,72 @@ where
     }
 }
 
-/// Circuit version of `get_challenges`, with the same flexibility around
+impl<F, C, const D: usize, const N: usize> MultiProofWithPublicInputs<F, C, D, N>
+where
+    F: RichField + Extendable<D>,
+    C: GenericConfig<D, F = F>,
+{
+    /// Computes all Fiat-Shamir challenges used in the multi-STARK proof.
+    ///
+    /// The challenger first observes the circuit digests, the public inputs and the trace caps of
+    /// all STARKs, from which the cross-table lookup challenges are drawn, then goes through each
+    /// STARK proof in turn, and finally through the batched FRI proof of all the openings.
+    pub fn get_challenges(
+        &self,
+        circuit_digests: &[<C::Hasher as Hasher<F>>::Hash; N],
+        config: &StarkConfig,
+    ) -> MultiProofChallenges<F, D, N> {
+        let mut challenger = Challenger::<F, C::Hasher>::new();
+        for &circuit_digest in circuit_digests {
+            challenger.observe_hash::<C::Hasher>(circuit_digest);
+        }
+        for public_inputs in &self.public_inputs {
+            challenger.observe_elements(public_inputs);
+        }
+        for proof in &self.proof.stark_proofs {
+            challenger.observe_cap(&proof.proof.trace_cap);
+        }
+
+        let ctl_challenges =
+            get_grand_product_challenge_set(&mut challenger, config.num_challenges);
+
+        let stark_challenges = core::array::from_fn(|i| {
+            challenger.compact();
+            let proof = &self.proof.stark_proofs[i].proof;
+            // Lookups within a STARK reuse the cross-table lookup challenges.
+            let lookup_challenges = proof
+                .auxiliary_polys_cap
+                .is_some()
+                .then_some(&ctl_challenges);
+            proof.get_challenges(&mut challenger, lookup_challenges, config)
+        });
+
+        let FriProof {
+            commit_phase_merkle_caps,
+            final_poly,
+            pow_witness,
+            ..
+        } = &self.proof.opening_proof;
+        let max_degree_bits = self.proof.degree_bits.iter().copied().max().unwrap_or(0);
+        let fri_challenges = challenger.fri_challenges::<C, D>(
+            commit_phase_merkle_caps,
+            final_poly,
+            *pow_witness,
+            max_degree_bits + config.blinding_degree_bits(),
+            &config.fri_config,
+        );
+
+        MultiProofChallenges {
+            stark_challenges,
+            ctl_challenges,
+            fri_challenges,
+        }
+    }
+}
+
+/// Circuit version of `get_table_challenges`, with the same flexibility around
 /// `trace_cap` being passed as an `Option`.
-fn get_challenges_target<F, C, const D: usize>(
+fn get_table_challenges_target<F, C, const D: usize>(
     builder: &mut CircuitBuilder<F, D>,
     challenger: &mut RecursiveChallenger<F, C::Hasher, D>,
     challenges: Option<&GrandProductChallengeSet<Target>>,
@@ -173,11 +262,8 @@ fn get_challenges_target<F, C, const D: usize>(
     auxiliary_polys_cap: Option<&MerkleCapTarget>,
     quotient_polys_cap: Option<&MerkleCapTarget>,
     openings: &StarkOpeningSetTarget<D>,
-    commit_phase_merkle_caps: &[MerkleCapTarget],
-    final_poly: &PolynomialCoeffsExtTarget<D>,
-    pow_witness: Target,
     config: &StarkConfig,
-) -> StarkProofChallengesTarget<D>
+) -> StarkTableChallengesTarget<D>
 where
     F: RichField + Extendable<D>,
     C: GenericConfig<D, F = F>,
@@ -211,17 +297,10 @@ where
 
     challenger.observe_openings(&openings.to_fri_openings(builder.zero()));
 
-    StarkProofChallengesTarget {
+    StarkTableChallengesTarget {
         lookup_challenge_set,
         stark_alphas,
         stark_zeta,
-        fri_challenges: challenger.fri_challenges(
-            builder,
-            commit_phase_merkle_caps,
-            final_poly,
-            pow_witness,
-            &config.fri_config,
-        ),
     }
 }
 
@@ -266,7 +345,11 @@ impl<const D: usize> StarkProofTarget<D> {
             Some(trace_cap)
         };
 
-        get_challenges_target::<F, C, D>(
+        let StarkTableChallengesTarget {
+            lookup_challenge_set,
+            stark_alphas,
+            stark_zeta,
+        } = get_table_challenges_target::<F, C, D>(
             builder,
             challenger,
             challenges,
@@ -274,9 +357,48 @@ impl<const D: usize> StarkProofTarget<D> {
             auxiliary_polys_cap.as_ref(),
             quotient_polys_cap.as_ref(),
             openings,
-            commit_phase_merkle_caps,
-            final_poly,
-            *pow_witness,
+            config,
+        );
+
+        StarkProofChallengesTarget {
+            lookup_challenge_set,
+            stark_alphas,
+            stark_zeta,
+            fri_challenges: challenger.fri_challenges(
+                builder,
+                commit_phase_merkle_caps,
+                final_poly,
+                *pow_witness,
+                &config.fri_config,
+            ),
+        }
+    }
+}
+
+impl<const D: usize> StarkTableProofTarget<D> {
+    /// Creates the Fiat-Shamir `Target` challenges of a single STARK within a multi-STARK proof,
+    /// apart from the FRI challenges which are shared by all STARKs. The trace cap is not
+    /// observed, as the trace caps of all STARKs are observed ahead of proving each table.
+    pub fn get_challenges<F, C>(
+        &self,
+        builder: &mut CircuitBuilder<F, D>,
+        challenger: &mut RecursiveChallenger<F, C::Hasher, D>,
+        challenges: Option<&GrandProductChallengeSet<Target>>,
+        config: &StarkConfig,
+    ) -> StarkTableChallengesTarget<D>
+    where
+        F: RichField + Extendable<D>,
+        C: GenericConfig<D, F = F>,
+        C::Hasher: AlgebraicHasher<F>,
+    {
+        get_table_challenges_target::<F, C, D>(
+            builder,
+            challenger,
+            challenges,
+            None,
+            self.auxiliary_polys_cap.as_ref(),
+            self.quotient_polys_cap.as_ref(),
+            &self.openings,
             config,
         )
     }
@@ -309,6 +431,65 @@ impl<const D: usize> StarkProofWithPublicInputsTarget<D> {
     }
 }
 
+impl<const D: usize, const N: usize> MultiProofWithPublicInputsTarget<D, N> {
+    /// Creates all Fiat-Shamir `Target` challenges used in the multi-STARK proof.
+    pub fn get_challenges<F, C>(
+        &self,
+        builder: &mut CircuitBuilder<F, D>,
+        circuit_digests: &[HashOutTarget; N],
+        config: &StarkConfig,
+    ) -> MultiProofChallengesTarget<D, N>
+    where
+        F: RichField + Extendable<D>,
+        C: GenericConfig<D, F = F>,
+        C::Hasher: AlgebraicHasher<F>,
+    {
+        let mut challenger = RecursiveChallenger::<F, C::Hasher, D>::new(builder);
+        for circuit_digest in circuit_digests {
+            challenger.observe_hash(circuit_digest);
+        }
+        for public_inputs in &self.public_inputs {
+            challenger.observe_elements(public_inputs);
+        }
+        for proof in &self.proof.stark_proofs {
+            challenger.observe_cap(&proof.trace_cap);
+        }
+
+        let ctl_challenges =
+            get_grand_product_challenge_set_target(builder, &mut challenger, config.num_challenges);
+
+        let stark_challenges = core::array::from_fn(|i| {
+            challenger.compact(builder);
+            let proof = &self.proof.stark_proofs[i];
+            let lookup_challenges = proof
+                .auxiliary_polys_cap
+                .is_some()
+                .then_some(&ctl_challenges);
+            proof.get_challenges::<F, C>(builder, &mut challenger, lookup_challenges, config)
+        });
+
+        let FriProofTarget {
+            commit_phase_merkle_caps,
+            final_poly,
+            pow_witness,
+            ..
+        } = &self.proof.opening_proof;
+        let fri_challenges = challenger.fri_challenges(
+            builder,
+            commit_phase_merkle_caps,
+            final_poly,
+            *pow_witness,
+            &config.fri_config,
+        );
+
+        MultiProofChallengesTarget {
+            stark_challenges,
+            ctl_challenges,
+            fri_challenges,
+        }
+    }
+}
+
 // TODO: Deal with the compressed stuff.
 // impl<F: RichField + Extendable<D>, C: GenericConfig<D, F = F>, const D: usize>
 //     CompressedProofWithPublicInputs<F, C, D>
@@ -78,6 +78,9 @@
 //! const PUBLIC_INPUTS: usize = 3;
 //!
 //! impl<F: RichField + Extendable<D>, const D: usize> Stark<F, D> for FibonacciStark<F, D> {
+//!     // Identify this STARK, so that its proofs are bound to its constraints.
+//!     const AIR_ID: &'static str = "fibonacci";
+//!
 //!     type EvaluationFrame<FE, P, const D2: usize> = StarkFrame<P, P::Scalar, COLUMNS, PUBLIC_INPUTS>
 //!     where
 //!         FE: FieldExtension<D2, BaseField = F>,
@@ -152,7 +155,9 @@
 //! ```
 //!
 //! One can then instantiate a new `FibonacciStark` instance, generate an associated
-//! STARK trace, and generate a proof for it.
+//! STARK trace, and generate a proof for it. The proof is checked against the
+//! [`StarkVerifyingKey`][crate::verifying_key::StarkVerifyingKey] of the STARK, whose
+//! digest binds the proof to this specific STARK.
 //!
 //! ```rust
 //! # use anyhow::Result;
@@ -175,6 +180,7 @@
 //! # use plonky2::plonk::config::{GenericConfig, PoseidonGoldilocksConfig};
 //! # use starky::prover::prove;
 //! # use starky::verifier::verify_stark_proof;
+//! # use starky::verifying_key::StarkVerifyingKey;
 //! # use starky::config::StarkConfig;
 //! #
 //! # #[derive(Copy, Clone)]
@@ -216,6 +222,7 @@
 //! # const COLUMNS: usize = 3;
 //! # const PUBLIC_INPUTS: usize = 3;
 //! # impl<F: RichField + Extendable<D>, const D: usize> Stark<F, D> for FibonacciStark<F, D> {
+//! #     const AIR_ID: &'static str = "fibonacci";
 //! #     type EvaluationFrame<FE, P, const D2: usize> = StarkFrame<P, P::Scalar, COLUMNS, PUBLIC_INPUTS>
 //! #     where
 //! #         FE: FieldExtension<D2, BaseField = F>,
@@ -305,7 +312,8 @@
 //!         &mut TimingTree::default(),
 //!     ).expect("We should have a valid proof!");
 //!
-//!     verify_stark_proof(stark, proof, &CONFIG)
+//!     let verifying_key = StarkVerifyingKey::new(&stark, &CONFIG);
+//!     verify_stark_proof(stark, proof, &verifying_key)
 //!         .expect("We should be able to verify this proof!")
 //! }
 //! ```
@@ -324,6 +332,7 @@ extern crate alloc;
 
 mod get_challenges;
 
+pub mod all_stark;
 pub mod config;
 pub mod constraint_consumer;
 pub mod cross_table_lookup;
@@ -337,10 +346,17 @@ pub mod stark_testing;
 pub mod util;
 mod vanishing_poly;
 pub mod verifier;
+pub mod verifying_key;
 
+#[cfg(test)]
+pub mod ctl_stark;
 #[cfg(test)]
 pub mod fibonacci_stark;
 #[cfg(test)]
+pub mod multi_row_stark;
+#[cfg(test)]
 pub mod permutation_stark;
 #[cfg(test)]
+pub mod preprocessed_stark;
+#[cfg(test)]
 pub mod unconstrained_stark;
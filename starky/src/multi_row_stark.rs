@@ -0,0 +1,253 @@
+//! An example of generating and verifying STARK proofs for the Fibonacci sequence, with
+//! constraints spanning three consecutive rows of a single sequence column.
+
+#[cfg(not(feature = "std"))]
+use alloc::vec::Vec;
+use core::marker::PhantomData;
+
+use plonky2::field::extension::{Extendable, FieldExtension};
+use plonky2::field::packed::PackedField;
+use plonky2::field::polynomial::PolynomialValues;
+use plonky2::hash::hash_types::RichField;
+use plonky2::iop::ext_target::ExtensionTarget;
+use plonky2::plonk::circuit_builder::CircuitBuilder;
+
+use crate::constraint_consumer::{ConstraintConsumer, RecursiveConstraintConsumer};
+use crate::evaluation_frame::{StarkEvaluationFrame, StarkFrame};
+use crate::stark::Stark;
+use crate::util::trace_rows_to_poly_values;
+
+/// Toy STARK system used for testing.
+/// Computes a Fibonacci sequence `x` using the state transition `x'' <- x + x'`, over
+/// frames of three consecutive rows.
+///
+/// The transition wraps around the trace on the last two rows, so it is filtered by a column
+/// `f` which is `1` on all rows but the last two.
+#[derive(Copy, Clone)]
+pub(crate) struct MultiRowFibonacciStark<F: RichField + Extendable<D>, const D: usize> {
+    num_rows: usize,
+    _phantom: PhantomData<F>,
+}
+
+impl<F: RichField + Extendable<D>, const D: usize> MultiRowFibonacciStark<F, D> {
+    // The first public input is `x0`.
+    const PI_INDEX_X0: usize = 0;
+    // The second public input is `x1`.
+    const PI_INDEX_X1: usize = 1;
+    // The third public input is the value of the last row, which should be equal to the
+    // `num_rows - 1`-th Fibonacci number.
+    const PI_INDEX_RES: usize = 2;
+
+    pub(crate) const fn new(num_rows: usize) -> Self {
+        Self {
+            num_rows,
+            _phantom: PhantomData,
+        }
+    }
+
+    /// Generate the trace using `x0, x1` as initial values.
+    pub(crate) fn generate_trace(&self, x0: F, x1: F) -> Vec<PolynomialValues<F>> {
+        let trace_rows = (0..self.num_rows)
+            .scan([x0, x1], |acc, i| {
+                let x = acc[0];
+                *acc = [acc[1], acc[0] + acc[1]];
+                Some([x, F::from_bool(i + 2 < self.num_rows)])
+            })
+            .collect::<Vec<_>>();
+        trace_rows_to_poly_values(trace_rows)
+    }
+}
+
+const MULTI_ROW_COLUMNS: usize = 2;
+const MULTI_ROW_PUBLIC_INPUTS: usize = 3;
+const MULTI_ROW_ROWS: usize = 3;
+
+impl<F: RichField + Extendable<D>, const D: usize> Stark<F, D> for MultiRowFibonacciStark<F, D> {
+    const AIR_ID: &'static str = "starky/multi-row-fibonacci";
+
+    type EvaluationFrame<FE, P, const D2: usize>
+        = StarkFrame<P, P::Scalar, MULTI_ROW_COLUMNS, MULTI_ROW_PUBLIC_INPUTS, MULTI_ROW_ROWS>
+    where
+        FE: FieldExtension<D2, BaseField = F>,
+        P: PackedField<Scalar = FE>;
+
+    type EvaluationFrameTarget = StarkFrame<
+        ExtensionTarget<D>,
+        ExtensionTarget<D>,
+        MULTI_ROW_COLUMNS,
+        MULTI_ROW_PUBLIC_INPUTS,
+        MULTI_ROW_ROWS,
+    >;
+
+    const ROW_OFFSETS: &'static [usize] = &[0, 1, 2];
+
+    fn eval_packed_generic<FE, P, const D2: usize>(
+        &self,
+        vars: &Self::EvaluationFrame<FE, P, D2>,
+        yield_constr: &mut ConstraintConsumer<P>,
+    ) where
+        FE: FieldExtension<D2, BaseField = F>,
+        P: PackedField<Scalar = FE>,
+    {
+        let [x, x_next, x_next_next] = [0, 1, 2].map(|i| vars.get_row_values(i)[0]);
+        let [f, f_next, f_next_next] = [0, 1, 2].map(|i| vars.get_row_values(i)[1]);
+        let public_inputs = vars.get_public_inputs();
+        let one = P::ONES;
+
+        // Check public inputs.
+        yield_constr.constraint_first_row(x - public_inputs[Self::PI_INDEX_X0]);
+        yield_constr.constraint_first_row(x_next - public_inputs[Self::PI_INDEX_X1]);
+        yield_constr.constraint_last_row(x - public_inputs[Self::PI_INDEX_RES]);
+
+        // `f` is a binary column starting with `1` and ending with `0`, which never goes from
+        // `0` to `1` on a transition.
+        yield_constr.constraint(f * (f - one));
+        yield_constr.constraint_first_row(f - one);
+        yield_constr.constraint_last_row(f);
+        yield_constr.constraint_transition(f_next * (one - f));
+        // `f` cannot have three consecutive zeros, nor a single zero between two ones, so that
+        // it ends with exactly two zeros.
+        yield_constr.constraint((one - f) * (one - f_next) * (one - f_next_next));
+        yield_constr.constraint(f * (one - f_next) * f_next_next);
+
+        // x'' <- x + x'
+        yield_constr.constraint(f * (x_next_next - x - x_next));
+    }
+
+    fn eval_ext_circuit(
+        &self,
+        builder: &mut CircuitBuilder<F, D>,
+        vars: &Self::EvaluationFrameTarget,
+        yield_constr: &mut RecursiveConstraintConsumer<F, D>,
+    ) {
+        let [x, x_next, x_next_next] = [0, 1, 2].map(|i| vars.get_row_values(i)[0]);
+        let [f, f_next, f_next_next] = [0, 1, 2].map(|i| vars.get_row_values(i)[1]);
+        let public_inputs = vars.get_public_inputs();
+        let one = builder.one_extension();
+
+        // Check public inputs.
+        let pis_constraints = [
+            builder.sub_extension(x, public_inputs[Self::PI_INDEX_X0]),
+            builder.sub_extension(x_next, public_inputs[Self::PI_INDEX_X1]),
+            builder.sub_extension(x, public_inputs[Self::PI_INDEX_RES]),
+        ];
+        yield_constr.constraint_first_row(builder, pis_constraints[0]);
+        yield_constr.constraint_first_row(builder, pis_constraints[1]);
+        yield_constr.constraint_last_row(builder, pis_constraints[2]);
+
+        // `f` is a binary column starting with `1` and ending with `0`, which never goes from
+        // `0` to `1` on a transition.
+        let binary_constraint = builder.mul_sub_extension(f, f, f);
+        yield_constr.constraint(builder, binary_constraint);
+        let first_row_constraint = builder.sub_extension(f, one);
+        yield_constr.constraint_first_row(builder, first_row_constraint);
+        yield_constr.constraint_last_row(builder, f);
+        let not_f = builder.sub_extension(one, f);
+        let transition_constraint = builder.mul_extension(f_next, not_f);
+        yield_constr.constraint_transition(builder, transition_constraint);
+        // `f` cannot have three consecutive zeros, nor a single zero between two ones, so that
+        // it ends with exactly two zeros.
+        let not_f_next = builder.sub_extension(one, f_next);
+        let not_f_next_next = builder.sub_extension(one, f_next_next);
+        let zeros_constraint = builder.mul_many_extension([not_f, not_f_next, not_f_next_next]);
+        yield_constr.constraint(builder, zeros_constraint);
+        let gap_constraint = builder.mul_many_extension([f, not_f_next, f_next_next]);
+        yield_constr.constraint(builder, gap_constraint);
+
+        // x'' <- x + x'
+        let transition = {
+            let tmp = builder.sub_extension(x_next_next, x);
+            builder.sub_extension(tmp, x_next)
+        };
+        let filtered_transition = builder.mul_extension(f, transition);
+        yield_constr.constraint(builder, filtered_transition);
+    }
+
+    fn constraint_degree(&self) -> usize {
+        3
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+    use plonky2::field::types::Field;
+    use plonky2::iop::witness::PartialWitness;
+    use plonky2::plonk::circuit_builder::CircuitBuilder;
+    use plonky2::plonk::circuit_data::CircuitConfig;
+    use plonky2::plonk::config::{GenericConfig, PoseidonGoldilocksConfig};
+    use plonky2::util::timing::TimingTree;
+
+    use crate::config::StarkConfig;
+    use crate::multi_row_stark::MultiRowFibonacciStark;
+    use crate::prover::prove;
+    use crate::recursive_verifier::{
+        add_virtual_stark_proof_with_pis, set_stark_proof_with_pis_target,
+        verify_stark_proof_circuit,
+    };
+    use crate::stark_testing::{test_stark_circuit_constraints, test_stark_low_degree};
+    use crate::verifier::verify_stark_proof;
+    use crate::verifying_key::StarkVerifyingKey;
+
+    const D: usize = 2;
+    type C = PoseidonGoldilocksConfig;
+    type F = <C as GenericConfig<D>>::F;
+    type S = MultiRowFibonacciStark<F, D>;
+
+    fn fibonacci<F: Field>(n: usize, x0: F, x1: F) -> F {
+        (0..n).fold((x0, x1), |x, _| (x.1, x.0 + x.1)).0
+    }
+
+    #[test]
+    fn test_multi_row_stark() -> Result<()> {
+        let config = StarkConfig::standard_fast_config();
+        let num_rows = 1 << 5;
+        let public_inputs = [F::ZERO, F::ONE, fibonacci(num_rows - 1, F::ZERO, F::ONE)];
+
+        let stark = S::new(num_rows);
+        let trace = stark.generate_trace(public_inputs[0], public_inputs[1]);
+        let proof = prove::<F, C, S, D>(
+            stark,
+            &config,
+            trace,
+            &public_inputs,
+            &mut TimingTree::default(),
+        )?;
+        // The trace is opened at `g^2 * zeta` for the third row of the frames.
+        assert_eq!(proof.proof.openings.additional_values.len(), 1);
+        verify_stark_proof(
+            stark,
+            proof.clone(),
+            &StarkVerifyingKey::new(&stark, &config),
+        )?;
+
+        // Recursively verify the proof.
+        let mut builder = CircuitBuilder::<F, D>::new(CircuitConfig::standard_recursion_config());
+        let mut pw = PartialWitness::new();
+        let degree_bits = proof.proof.recover_degree_bits(&config);
+        let pt = add_virtual_stark_proof_with_pis(&mut builder, &stark, &config, degree_bits, 0, 0);
+        set_stark_proof_with_pis_target(&mut pw, &pt, &proof, builder.zero());
+        verify_stark_proof_circuit::<F, C, S, D>(
+            &mut builder,
+            stark,
+            pt,
+            &StarkVerifyingKey::new(&stark, &config),
+        );
+        let data = builder.build::<C>();
+        let recursive_proof = data.prove(pw)?;
+        data.verify(recursive_proof)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_multi_row_stark_degree() -> Result<()> {
+        let stark = S::new(1 << 5);
+        test_stark_low_degree(stark)
+    }
+
+    #[test]
+    fn test_multi_row_stark_circuit() -> Result<()> {
+        let stark = S::new(1 << 5);
+        test_stark_circuit_constraints::<F, C, S, D>(stark)
+    }
+}
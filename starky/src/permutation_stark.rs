@@ -55,7 +55,10 @@ const PERM_COLUMNS: usize = 3;
 const PERM_PUBLIC_INPUTS: usize = 1;
 
 impl<F: RichField + Extendable<D>, const D: usize> Stark<F, D> for PermutationStark<F, D> {
-    type EvaluationFrame<FE, P, const D2: usize> = StarkFrame<P, P::Scalar, PERM_COLUMNS, PERM_PUBLIC_INPUTS>
+    const AIR_ID: &'static str = "starky/permutation";
+
+    type EvaluationFrame<FE, P, const D2: usize>
+        = StarkFrame<P, P::Scalar, PERM_COLUMNS, PERM_PUBLIC_INPUTS>
     where
         FE: FieldExtension<D2, BaseField = F>,
         P: PackedField<Scalar = FE>;
@@ -120,6 +123,7 @@ mod tests {
     use crate::stark::Stark;
     use crate::stark_testing::{test_stark_circuit_constraints, test_stark_low_degree};
     use crate::verifier::verify_stark_proof;
+    use crate::verifying_key::StarkVerifyingKey;
 
     #[test]
     fn test_pemutations_stark() -> Result<()> {
@@ -143,7 +147,8 @@ mod tests {
             &mut TimingTree::default(),
         )?;
 
-        verify_stark_proof(stark, proof, &config)
+        verify_stark_proof(stark, proof, &StarkVerifyingKey::new(&stark, &config))?;
+        Ok(())
     }
 
     #[test]
@@ -191,7 +196,11 @@ mod tests {
             &[public_input],
             &mut TimingTree::default(),
         )?;
-        verify_stark_proof(stark, proof.clone(), &config)?;
+        verify_stark_proof(
+            stark,
+            proof.clone(),
+            &StarkVerifyingKey::new(&stark, &config),
+        )?;
 
         recursive_proof::<F, C, S, C, D>(stark, proof, &config, true)
     }
@@ -219,7 +228,12 @@ mod tests {
             add_virtual_stark_proof_with_pis(&mut builder, &stark, inner_config, degree_bits, 0, 0);
         set_stark_proof_with_pis_target(&mut pw, &pt, &inner_proof, builder.zero());
 
-        verify_stark_proof_circuit::<F, InnerC, S, D>(&mut builder, stark, pt, inner_config);
+        verify_stark_proof_circuit::<F, InnerC, S, D>(
+            &mut builder,
+            stark,
+            pt,
+            &StarkVerifyingKey::new(&stark, inner_config),
+        );
 
         if print_gate_counts {
             builder.print_gate_counts(0);
@@ -227,7 +241,8 @@ mod tests {
 
         let data = builder.build::<C>();
         let proof = data.prove(pw)?;
-        data.verify(proof)
+        data.verify(proof)?;
+        Ok(())
     }
 
     fn init_logger() {
@@ -0,0 +1,243 @@
+//! An example of generating and verifying a STARK with preprocessed columns, which are
+//! fixed by the STARK itself rather than by the prover's witness.
+
+#[cfg(not(feature = "std"))]
+use alloc::{vec, vec::Vec};
+use core::marker::PhantomData;
+
+use plonky2::field::extension::{Extendable, FieldExtension};
+use plonky2::field::packed::PackedField;
+use plonky2::field::polynomial::PolynomialValues;
+use plonky2::hash::hash_types::RichField;
+use plonky2::iop::ext_target::ExtensionTarget;
+use plonky2::plonk::circuit_builder::CircuitBuilder;
+
+use crate::constraint_consumer::{ConstraintConsumer, RecursiveConstraintConsumer};
+use crate::evaluation_frame::{StarkEvaluationFrame, StarkFrame};
+use crate::lookup::{Column, Filter, Lookup};
+use crate::stark::Stark;
+use crate::util::trace_rows_to_poly_values;
+
+/// Toy STARK system used for testing.
+/// Range-checks a witness column `x` against a preprocessed column `range` containing
+/// `0..num_rows`, only on the rows selected by a preprocessed column `s`, which is `1` on even
+/// rows and `0` on odd rows. `x` must be `0` on the rows which are not selected.
+///
+/// The frame columns are `[x, frequencies, range, s]`, the last two being preprocessed.
+#[derive(Copy, Clone)]
+pub(crate) struct PreprocessedStark<F: RichField + Extendable<D>, const D: usize> {
+    num_rows: usize,
+    _phantom: PhantomData<F>,
+}
+
+impl<F: RichField + Extendable<D>, const D: usize> PreprocessedStark<F, D> {
+    const X: usize = 0;
+    const FREQUENCIES: usize = 1;
+    const RANGE: usize = 2;
+    const SELECTOR: usize = 3;
+
+    pub(crate) const fn new(num_rows: usize) -> Self {
+        Self {
+            num_rows,
+            _phantom: PhantomData,
+        }
+    }
+
+    /// Generate the trace, with `x` taking the values `values` on the selected rows.
+    pub(crate) fn generate_trace(&self, values: &[usize]) -> Vec<PolynomialValues<F>> {
+        assert_eq!(values.len(), self.num_rows / 2);
+        let mut frequencies = vec![0u64; self.num_rows];
+        for &v in values {
+            frequencies[v] += 1;
+        }
+        let trace_rows = (0..self.num_rows)
+            .map(|i| {
+                let x = if i % 2 == 0 { values[i / 2] } else { 0 };
+                [
+                    F::from_canonical_usize(x),
+                    F::from_canonical_u64(frequencies[i]),
+                ]
+            })
+            .collect::<Vec<_>>();
+        trace_rows_to_poly_values(trace_rows)
+    }
+}
+
+const PREPROCESSED_STARK_COLUMNS: usize = 4;
+const PREPROCESSED_STARK_PUBLIC_INPUTS: usize = 0;
+
+impl<F: RichField + Extendable<D>, const D: usize> Stark<F, D> for PreprocessedStark<F, D> {
+    const AIR_ID: &'static str = "starky/preprocessed";
+
+    type EvaluationFrame<FE, P, const D2: usize>
+        = StarkFrame<P, P::Scalar, PREPROCESSED_STARK_COLUMNS, PREPROCESSED_STARK_PUBLIC_INPUTS>
+    where
+        FE: FieldExtension<D2, BaseField = F>,
+        P: PackedField<Scalar = FE>;
+
+    type EvaluationFrameTarget = StarkFrame<
+        ExtensionTarget<D>,
+        ExtensionTarget<D>,
+        PREPROCESSED_STARK_COLUMNS,
+        PREPROCESSED_STARK_PUBLIC_INPUTS,
+    >;
+
+    const PREPROCESSED_COLUMNS: usize = 2;
+
+    fn eval_packed_generic<FE, P, const D2: usize>(
+        &self,
+        vars: &Self::EvaluationFrame<FE, P, D2>,
+        yield_constr: &mut ConstraintConsumer<P>,
+    ) where
+        FE: FieldExtension<D2, BaseField = F>,
+        P: PackedField<Scalar = FE>,
+    {
+        let local_values = vars.get_local_values();
+        let x = local_values[Self::X];
+        let s = local_values[Self::SELECTOR];
+
+        // `x` is zero on the rows which are not selected.
+        yield_constr.constraint((P::ONES - s) * x);
+    }
+
+    fn eval_ext_circuit(
+        &self,
+        builder: &mut CircuitBuilder<F, D>,
+        vars: &Self::EvaluationFrameTarget,
+        yield_constr: &mut RecursiveConstraintConsumer<F, D>,
+    ) {
+        let local_values = vars.get_local_values();
+        let x = local_values[Self::X];
+        let s = local_values[Self::SELECTOR];
+
+        // `x` is zero on the rows which are not selected.
+        let one = builder.one_extension();
+        let not_s = builder.sub_extension(one, s);
+        let constraint = builder.mul_extension(not_s, x);
+        yield_constr.constraint(builder, constraint);
+    }
+
+    fn constraint_degree(&self) -> usize {
+        3
+    }
+
+    fn preprocessed_columns(&self) -> Vec<PolynomialValues<F>> {
+        let range = (0..self.num_rows).map(F::from_canonical_usize).collect();
+        let selector = (0..self.num_rows)
+            .map(|i| F::from_bool(i % 2 == 0))
+            .collect();
+        vec![
+            PolynomialValues::new(range),
+            PolynomialValues::new(selector),
+        ]
+    }
+
+    fn lookups(&self) -> Vec<Lookup<F>> {
+        vec![Lookup {
+            columns: vec![Column::single(Self::X)],
+            table_column: Column::single(Self::RANGE),
+            frequencies_column: Column::single(Self::FREQUENCIES),
+            filter_columns: vec![Filter::new_simple(Column::single(Self::SELECTOR))],
+        }]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(not(feature = "std"))]
+    use alloc::vec::Vec;
+
+    use anyhow::Result;
+    use plonky2::iop::witness::PartialWitness;
+    use plonky2::plonk::circuit_builder::CircuitBuilder;
+    use plonky2::plonk::circuit_data::CircuitConfig;
+    use plonky2::plonk::config::{GenericConfig, PoseidonGoldilocksConfig};
+    use plonky2::util::timing::TimingTree;
+
+    use crate::config::StarkConfig;
+    use crate::preprocessed_stark::PreprocessedStark;
+    use crate::prover::{prove, prove_with_key, StarkProvingKey};
+    use crate::recursive_verifier::{
+        add_virtual_stark_proof_with_pis, set_stark_proof_with_pis_target,
+        verify_stark_proof_circuit,
+    };
+    use crate::stark_testing::{test_stark_circuit_constraints, test_stark_low_degree};
+    use crate::verifier::verify_stark_proof;
+    use crate::verifying_key::StarkVerifyingKey;
+
+    const D: usize = 2;
+    type C = PoseidonGoldilocksConfig;
+    type F = <C as GenericConfig<D>>::F;
+    type S = PreprocessedStark<F, D>;
+
+    #[test]
+    fn test_preprocessed_stark() -> Result<()> {
+        let config = StarkConfig::standard_fast_config();
+        let num_rows = 1 << 5;
+
+        let stark = S::new(num_rows);
+        let values = (0..num_rows / 2)
+            .map(|i| (7 * i) % num_rows)
+            .collect::<Vec<_>>();
+        let trace = stark.generate_trace(&values);
+        let proof = prove::<F, C, S, D>(
+            stark,
+            &config,
+            trace.clone(),
+            &[],
+            &mut TimingTree::default(),
+        )?;
+
+        // The verifier knows the preprocessed columns, and commits to them on its own.
+        let verifying_key = StarkVerifyingKey::new(&stark, &config);
+        assert!(verifying_key.preprocessed_cap.is_some());
+        verify_stark_proof(stark, proof.clone(), &verifying_key)?;
+
+        // The prover can commit to the preprocessed columns once for several proofs.
+        let proving_key =
+            StarkProvingKey::<F, C, D>::new(&stark, &config, &mut TimingTree::default());
+        assert_eq!(
+            proving_key.verifying_key().circuit_digest(),
+            verifying_key.circuit_digest()
+        );
+        let other_values = (0..num_rows / 2).collect::<Vec<_>>();
+        for trace in [trace, stark.generate_trace(&other_values)] {
+            let proof =
+                prove_with_key(stark, &proving_key, trace, &[], &mut TimingTree::default())?;
+            verify_stark_proof(stark, proof, &verifying_key)?;
+        }
+
+        // The proof does not verify against other preprocessed columns.
+        for preprocessed_cap in [None, Some(proof.proof.trace_cap.clone())] {
+            let wrong_key = StarkVerifyingKey {
+                preprocessed_cap,
+                ..verifying_key.clone()
+            };
+            assert!(verify_stark_proof(stark, proof.clone(), &wrong_key).is_err());
+        }
+
+        // Recursively verify the proof.
+        let mut builder = CircuitBuilder::<F, D>::new(CircuitConfig::standard_recursion_config());
+        let mut pw = PartialWitness::new();
+        let degree_bits = proof.proof.recover_degree_bits(&config);
+        let pt = add_virtual_stark_proof_with_pis(&mut builder, &stark, &config, degree_bits, 0, 0);
+        set_stark_proof_with_pis_target(&mut pw, &pt, &proof, builder.zero());
+        verify_stark_proof_circuit::<F, C, S, D>(&mut builder, stark, pt, &verifying_key);
+        let data = builder.build::<C>();
+        let recursive_proof = data.prove(pw)?;
+        data.verify(recursive_proof)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_preprocessed_stark_degree() -> Result<()> {
+        let stark = S::new(1 << 5);
+        test_stark_low_degree(stark)
+    }
+
+    #[test]
+    fn test_preprocessed_stark_circuit() -> Result<()> {
+        let stark = S::new(1 << 5);
+        test_stark_circuit_constraints::<F, C, S, D>(stark)
+    }
+}
@@ -48,7 +48,7 @@ impl<F: RichField + Extendable<D>, C: GenericConfig<D, F = F>, const D: usize> S
             .evals_proofs[0]
             .1;
         let lde_bits = config.fri_config.cap_height + initial_merkle_proof.siblings.len();
-        lde_bits - config.fri_config.rate_bits
+        lde_bits - config.fri_config.rate_bits - config.blinding_degree_bits()
     }
 }
 
@@ -117,7 +117,7 @@ impl<const D: usize> StarkProofTarget<D> {
             .evals_proofs[0]
             .1;
         let lde_bits = config.fri_config.cap_height + initial_merkle_proof.siblings.len();
-        lde_bits - config.fri_config.rate_bits
+        lde_bits - config.fri_config.rate_bits - config.blinding_degree_bits()
     }
 }
 
@@ -172,8 +172,37 @@ pub struct CompressedStarkProofWithPublicInputs<
     pub public_inputs: Vec<F>,
 }
 
-/// A [`StarkProof`] along with metadata about the initial Fiat-Shamir state, which is used when
-/// creating a recursive wrapper proof around a STARK proof.
+/// Merkle caps and openings of a single STARK within a [`MultiProof`]. Unlike a [`StarkProof`], it
+/// has no FRI proof of its own: the openings of all the STARKs are proven together by the batched
+/// FRI proof of the [`MultiProof`].
+#[derive(Debug, Clone)]
+pub struct StarkTableProof<F: RichField + Extendable<D>, C: GenericConfig<D, F = F>, const D: usize>
+{
+    /// Merkle cap of LDEs of trace values.
+    pub trace_cap: MerkleCap<F, C::Hasher>,
+    /// Optional merkle cap of LDEs of lookup helper and CTL columns, if any.
+    pub auxiliary_polys_cap: Option<MerkleCap<F, C::Hasher>>,
+    /// Merkle cap of LDEs of quotient polynomial evaluations.
+    pub quotient_polys_cap: Option<MerkleCap<F, C::Hasher>>,
+    /// Purported values of each polynomial at the challenge point.
+    pub openings: StarkOpeningSet<F, D>,
+}
+
+/// Circuit version of [`StarkTableProof`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct StarkTableProofTarget<const D: usize> {
+    /// `Target` for the Merkle cap trace values LDEs.
+    pub trace_cap: MerkleCapTarget,
+    /// Optional `Target` for the Merkle cap of lookup helper and CTL columns LDEs, if any.
+    pub auxiliary_polys_cap: Option<MerkleCapTarget>,
+    /// `Target` for the Merkle cap of quotient polynomial evaluations LDEs.
+    pub quotient_polys_cap: Option<MerkleCapTarget>,
+    /// `Target`s for the purported values of each polynomial at the challenge point.
+    pub openings: StarkOpeningSetTarget<D>,
+}
+
+/// A [`StarkTableProof`] along with metadata about the initial Fiat-Shamir state, which is used
+/// when creating a recursive wrapper proof around a STARK proof.
 #[derive(Debug, Clone)]
 pub struct StarkProofWithMetadata<F, C, const D: usize>
 where
@@ -182,12 +211,13 @@ where
 {
     /// Initial Fiat-Shamir state.
     pub init_challenger_state: <C::Hasher as Hasher<F>>::Permutation,
-    /// Proof for a single STARK.
-    pub proof: StarkProof<F, C, D>,
+    /// Merkle caps and openings of a single STARK.
+    pub proof: StarkTableProof<F, C, D>,
 }
 
 /// A combination of STARK proofs for independent statements operating on possibly shared variables,
 /// along with Cross-Table Lookup (CTL) challenges to assert consistency of common variables across tables.
+/// The openings of all the STARKs are proven by a single batched FRI proof.
 #[derive(Debug, Clone)]
 pub struct MultiProof<
     F: RichField + Extendable<D>,
@@ -195,20 +225,50 @@ pub struct MultiProof<
     const D: usize,
     const N: usize,
 > {
-    /// Proofs for all the different STARK modules.
+    /// Merkle caps and openings of all the different STARK modules.
     pub stark_proofs: [StarkProofWithMetadata<F, C, D>; N],
+    /// Logarithm of the trace length of each STARK module.
+    pub degree_bits: [usize; N],
     /// Cross-table lookup challenges.
     pub ctl_challenges: GrandProductChallengeSet<F>,
+    /// A batched FRI argument for the openings of all the STARK modules.
+    pub opening_proof: FriProof<F, C::Hasher, D>,
 }
 
-impl<F: RichField + Extendable<D>, C: GenericConfig<D, F = F>, const D: usize, const N: usize>
-    MultiProof<F, C, D, N>
-{
-    /// Returns the degree (i.e. the trace length) of each STARK proof,
-    /// from their common [`StarkConfig`].
-    pub fn recover_degree_bits(&self, config: &StarkConfig) -> [usize; N] {
-        core::array::from_fn(|i| self.stark_proofs[i].proof.recover_degree_bits(config))
-    }
+/// A [`MultiProof`] along with the public inputs of each of its STARK proofs.
+#[derive(Debug, Clone)]
+pub struct MultiProofWithPublicInputs<
+    F: RichField + Extendable<D>,
+    C: GenericConfig<D, F = F>,
+    const D: usize,
+    const N: usize,
+> {
+    /// Proofs for all the different STARK modules.
+    pub proof: MultiProof<F, C, D, N>,
+    /// Public inputs of each STARK proof.
+    pub public_inputs: [Vec<F>; N],
+}
+
+/// Circuit version of [`MultiProof`].
+/// Proofs for all the different STARK modules of a multi-STARK system. The cross-table lookup
+/// challenges are not part of it, as they are computed in the circuit.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MultiProofTarget<const D: usize, const N: usize> {
+    /// `Target` Merkle caps and openings for all the different STARK modules.
+    pub stark_proofs: [StarkTableProofTarget<D>; N],
+    /// Logarithm of the trace length of each STARK module, which is fixed by the circuit.
+    pub degree_bits: [usize; N],
+    /// `Target`s for the batched FRI argument for the openings of all the STARK modules.
+    pub opening_proof: FriProofTarget<D>,
+}
+
+/// Circuit version of [`MultiProofWithPublicInputs`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MultiProofWithPublicInputsTarget<const D: usize, const N: usize> {
+    /// `Target` proofs for all the different STARK modules.
+    pub proof: MultiProofTarget<D, N>,
+    /// `Target` public inputs of each STARK proof.
+    pub public_inputs: [Vec<Target>; N],
 }
 
 /// Randomness used for a STARK proof.
@@ -237,29 +297,68 @@ pub struct StarkProofChallengesTarget<const D: usize> {
     pub fri_challenges: FriChallengesTarget<D>,
 }
 
+/// Randomness used for a single STARK within a [`MultiProof`], apart from FRI.
+#[derive(Debug, Clone)]
+pub struct StarkTableChallenges<F: RichField + Extendable<D>, const D: usize> {
+    /// Optional randomness used in any permutation argument.
+    pub lookup_challenge_set: Option<GrandProductChallengeSet<F>>,
+    /// Random values used to combine STARK constraints.
+    pub stark_alphas: Vec<F>,
+    /// Point at which the STARK polynomials are opened.
+    pub stark_zeta: F::Extension,
+}
+
+/// Circuit version of [`StarkTableChallenges`].
+#[derive(Debug, Clone)]
+pub struct StarkTableChallengesTarget<const D: usize> {
+    /// Optional `Target`'s randomness used in any permutation argument.
+    pub lookup_challenge_set: Option<GrandProductChallengeSet<Target>>,
+    /// `Target`s for the random values used to combine STARK constraints.
+    pub stark_alphas: Vec<Target>,
+    /// `ExtensionTarget` for the point at which the STARK polynomials are opened.
+    pub stark_zeta: ExtensionTarget<D>,
+}
+
 /// Randomness for all STARK proofs contained in a [`MultiProof`]`.
 #[derive(Debug)]
 pub struct MultiProofChallenges<F: RichField + Extendable<D>, const D: usize, const N: usize> {
     /// Randomness used in each STARK proof.
-    pub stark_challenges: [StarkProofChallenges<F, D>; N],
+    pub stark_challenges: [StarkTableChallenges<F, D>; N],
     /// Randomness used for cross-table lookups. It is shared by all STARKs.
     pub ctl_challenges: GrandProductChallengeSet<F>,
+    /// Randomness used in the batched FRI proof.
+    pub fri_challenges: FriChallenges<F, D>,
+}
+
+/// Circuit version of [`MultiProofChallenges`].
+#[derive(Debug)]
+pub struct MultiProofChallengesTarget<const D: usize, const N: usize> {
+    /// `Target`s for the randomness used in each STARK proof.
+    pub stark_challenges: [StarkTableChallengesTarget<D>; N],
+    /// `Target`s for the randomness used for cross-table lookups. It is shared by all STARKs.
+    pub ctl_challenges: GrandProductChallengeSet<Target>,
+    /// `Target`s for the randomness used in the batched FRI proof.
+    pub fri_challenges: FriChallengesTarget<D>,
 }
 
 /// Purported values of each polynomial at the challenge point.
 #[derive(Debug, Clone)]
 pub struct StarkOpeningSet<F: RichField + Extendable<D>, const D: usize> {
-    /// Openings of trace polynomials at `zeta`.
+    /// Openings of trace polynomials at `zeta`, followed by those of preprocessed polynomials.
     pub local_values: Vec<F::Extension>,
-    /// Openings of trace polynomials at `g * zeta`.
+    /// Openings of trace polynomials at `g * zeta`, followed by those of preprocessed polynomials.
     pub next_values: Vec<F::Extension>,
+    /// Openings of trace and preprocessed polynomials at `g^offset * zeta`, for each row offset
+    /// of the STARK beyond the current and next rows.
+    pub additional_values: Vec<Vec<F::Extension>>,
     /// Openings of lookups and cross-table lookups `Z` polynomials at `zeta`.
     pub auxiliary_polys: Option<Vec<F::Extension>>,
     /// Openings of lookups and cross-table lookups `Z` polynomials at `g * zeta`.
     pub auxiliary_polys_next: Option<Vec<F::Extension>>,
     /// Openings of cross-table lookups `Z` polynomials at `1`.
     pub ctl_zs_first: Option<Vec<F>>,
-    /// Openings of quotient polynomials at `zeta`.
+    /// Openings of quotient polynomials at `zeta`, followed by the openings of the masking
+    /// polynomials in zero-knowledge mode.
     pub quotient_polys: Option<Vec<F::Extension>>,
 }
 
@@ -267,11 +366,15 @@ impl<F: RichField + Extendable<D>, const D: usize> StarkOpeningSet<F, D> {
     /// Returns a `StarkOpeningSet` given all the polynomial commitments, the number
     /// of permutation `Z`polynomials, the evaluation point and a generator `g`.
     ///
-    /// Polynomials are evaluated at point `zeta` and, if necessary, at `g * zeta`.
+    /// Polynomials are evaluated at point `zeta` and, if necessary, at `g * zeta`. The trace
+    /// polynomials are also evaluated at `g^offset * zeta` for each of the `row_offsets` beyond
+    /// the first two.
     pub fn new<C: GenericConfig<D, F = F>>(
         zeta: F::Extension,
         g: F,
+        row_offsets: &[usize],
         trace_commitment: &PolynomialBatch<F, C, D>,
+        preprocessed_commitment: Option<&PolynomialBatch<F, C, D>>,
         auxiliary_polys_commitment: Option<&PolynomialBatch<F, C, D>>,
         quotient_commitment: Option<&PolynomialBatch<F, C, D>>,
         num_lookup_columns: usize,
@@ -285,6 +388,14 @@ impl<F: RichField + Extendable<D>, const D: usize> StarkOpeningSet<F, D> {
                 .map(|p| p.to_extension().eval(z))
                 .collect::<Vec<_>>()
         };
+        // Evaluates the trace polynomials, followed by the preprocessed ones, at a point `z`.
+        let eval_columns = |z: F::Extension| {
+            let mut values = eval_commitment(z, trace_commitment);
+            if let Some(c) = preprocessed_commitment {
+                values.extend(eval_commitment(z, c));
+            }
+            values
+        };
         // Batch evaluates polynomials at a base field point `z`.
         let eval_commitment_base = |z: F, c: &PolynomialBatch<F, C, D>| {
             c.polynomials
@@ -297,8 +408,13 @@ impl<F: RichField + Extendable<D>, const D: usize> StarkOpeningSet<F, D> {
         // `g * zeta`.
         let zeta_next = zeta.scalar_mul(g);
         Self {
-            local_values: eval_commitment(zeta, trace_commitment),
-            next_values: eval_commitment(zeta_next, trace_commitment),
+            local_values: eval_columns(zeta),
+            next_values: eval_columns(zeta_next),
+            additional_values: row_offsets
+                .iter()
+                .skip(2)
+                .map(|&offset| eval_columns(zeta.scalar_mul(g.exp_u64(offset as u64))))
+                .collect(),
             auxiliary_polys: auxiliary_polys_commitment.map(|c| eval_commitment(zeta, c)),
             auxiliary_polys_next: auxiliary_polys_commitment.map(|c| eval_commitment(zeta_next, c)),
             ctl_zs_first: requires_ctl.then(|| {
@@ -331,6 +447,9 @@ impl<F: RichField + Extendable<D>, const D: usize> StarkOpeningSet<F, D> {
         };
 
         let mut batches = vec![zeta_batch, zeta_next_batch];
+        batches.extend(self.additional_values.iter().map(|values| FriOpeningBatch {
+            values: values.clone(),
+        }));
 
         if let Some(ctl_zs_first) = self.ctl_zs_first.as_ref() {
             debug_assert!(!ctl_zs_first.is_empty());
@@ -356,17 +475,23 @@ impl<F: RichField + Extendable<D>, const D: usize> StarkOpeningSet<F, D> {
 /// `Target`s for the purported values of each polynomial at the challenge point.
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct StarkOpeningSetTarget<const D: usize> {
-    /// `ExtensionTarget`s for the openings of trace polynomials at `zeta`.
+    /// `ExtensionTarget`s for the openings of trace polynomials at `zeta`, followed by those of
+    /// preprocessed polynomials.
     pub local_values: Vec<ExtensionTarget<D>>,
-    /// `ExtensionTarget`s for the opening of trace polynomials at `g * zeta`.
+    /// `ExtensionTarget`s for the opening of trace polynomials at `g * zeta`, followed by those
+    /// of preprocessed polynomials.
     pub next_values: Vec<ExtensionTarget<D>>,
+    /// `ExtensionTarget`s for the openings of trace and preprocessed polynomials at
+    /// `g^offset * zeta`, for each row offset of the STARK beyond the current and next rows.
+    pub additional_values: Vec<Vec<ExtensionTarget<D>>>,
     /// `ExtensionTarget`s for the opening of lookups and cross-table lookups `Z` polynomials at `zeta`.
     pub auxiliary_polys: Option<Vec<ExtensionTarget<D>>>,
     /// `ExtensionTarget`s for the opening of lookups and cross-table lookups `Z` polynomials at `g * zeta`.
     pub auxiliary_polys_next: Option<Vec<ExtensionTarget<D>>>,
     /// `ExtensionTarget`s for the opening of lookups and cross-table lookups `Z` polynomials at 1.
     pub ctl_zs_first: Option<Vec<Target>>,
-    /// `ExtensionTarget`s for the opening of quotient polynomials at `zeta`.
+    /// `ExtensionTarget`s for the opening of quotient polynomials at `zeta`, followed by the
+    /// openings of the masking polynomials in zero-knowledge mode.
     pub quotient_polys: Option<Vec<ExtensionTarget<D>>>,
 }
 
@@ -375,6 +500,10 @@ impl<const D: usize> StarkOpeningSetTarget<D> {
     pub(crate) fn to_buffer(&self, buffer: &mut Vec<u8>) -> IoResult<()> {
         buffer.write_target_ext_vec(&self.local_values)?;
         buffer.write_target_ext_vec(&self.next_values)?;
+        buffer.write_usize(self.additional_values.len())?;
+        for values in &self.additional_values {
+            buffer.write_target_ext_vec(values)?;
+        }
         if let Some(poly) = &self.auxiliary_polys {
             buffer.write_bool(true)?;
             buffer.write_target_ext_vec(poly)?;
@@ -404,6 +533,10 @@ impl<const D: usize> StarkOpeningSetTarget<D> {
     pub(crate) fn from_buffer(buffer: &mut Buffer) -> IoResult<Self> {
         let local_values = buffer.read_target_ext_vec::<D>()?;
         let next_values = buffer.read_target_ext_vec::<D>()?;
+        let num_additional_rows = buffer.read_usize()?;
+        let additional_values = (0..num_additional_rows)
+            .map(|_| buffer.read_target_ext_vec::<D>())
+            .collect::<IoResult<Vec<_>>>()?;
         let auxiliary_polys = if buffer.read_bool()? {
             Some(buffer.read_target_ext_vec::<D>()?)
         } else {
@@ -428,6 +561,7 @@ impl<const D: usize> StarkOpeningSetTarget<D> {
         Ok(Self {
             local_values,
             next_values,
+            additional_values,
             auxiliary_polys,
             auxiliary_polys_next,
             ctl_zs_first,
@@ -456,6 +590,13 @@ impl<const D: usize> StarkOpeningSetTarget<D> {
         };
 
         let mut batches = vec![zeta_batch, zeta_next_batch];
+        batches.extend(
+            self.additional_values
+                .iter()
+                .map(|values| FriOpeningBatchTarget {
+                    values: values.clone(),
+                }),
+        );
 
         if let Some(ctl_zs_first) = self.ctl_zs_first.as_ref() {
             debug_assert!(!ctl_zs_first.is_empty());
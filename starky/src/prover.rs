@@ -3,9 +3,12 @@
 #[cfg(not(feature = "std"))]
 use alloc::vec::Vec;
 use core::iter::once;
+use core::marker::PhantomData;
 
 use anyhow::{ensure, Result};
 use itertools::Itertools;
+use plonky2::batch_fri::batch_fri_params;
+use plonky2::batch_fri::prover::batch_prove_openings;
 use plonky2::field::extension::Extendable;
 use plonky2::field::packable::Packable;
 use plonky2::field::packed::PackedField;
@@ -13,6 +16,7 @@ use plonky2::field::polynomial::{PolynomialCoeffs, PolynomialValues};
 use plonky2::field::types::Field;
 use plonky2::field::zero_poly_coset::ZeroPolyOnCoset;
 use plonky2::fri::oracle::PolynomialBatch;
+use plonky2::fri::structure::FriInstanceInfo;
 use plonky2::hash::hash_types::RichField;
 use plonky2::iop::challenger::Challenger;
 use plonky2::plonk::config::GenericConfig;
@@ -21,19 +25,117 @@ use plonky2::util::timing::TimingTree;
 use plonky2::util::{log2_ceil, log2_strict, transpose};
 use plonky2_maybe_rayon::*;
 
+use crate::all_stark::{
+    batch_fri_degree_bits, batch_fri_groups, batch_fri_instances, check_cross_table_lookups,
+    table_infos, AllStark, StarkVisitor,
+};
 use crate::config::StarkConfig;
 use crate::constraint_consumer::ConstraintConsumer;
-use crate::cross_table_lookup::{get_ctl_auxiliary_polys, CtlCheckVars, CtlData};
+use crate::cross_table_lookup::{get_ctl_auxiliary_polys, get_ctl_data, CtlCheckVars, CtlData};
 use crate::evaluation_frame::StarkEvaluationFrame;
 use crate::lookup::{
     get_grand_product_challenge_set, lookup_helper_columns, GrandProductChallengeSet, Lookup,
     LookupCheckVars,
 };
-use crate::proof::{StarkOpeningSet, StarkProof, StarkProofWithPublicInputs};
+use crate::proof::{
+    MultiProof, MultiProofWithPublicInputs, StarkOpeningSet, StarkProof, StarkProofWithMetadata,
+    StarkProofWithPublicInputs, StarkTableProof,
+};
 use crate::stark::Stark;
 use crate::vanishing_poly::eval_vanishing_poly;
+use crate::verifying_key::{all_verifying_keys_from_caps, StarkVerifyingKey};
+
+/// The data needed to prove a [`Stark`] which does not depend on the trace, i.e. its verifying key
+/// and the commitment to its preprocessed columns. It can be computed once and reused across
+/// proofs with [`prove_with_key`], instead of committing to the preprocessed columns in each proof.
+#[derive(Debug)]
+pub struct StarkProvingKey<F, C, const D: usize>
+where
+    F: RichField + Extendable<D>,
+    C: GenericConfig<D, F = F>,
+{
+    verifying_key: StarkVerifyingKey<F, C, D>,
+    preprocessed_commitment: Option<PolynomialBatch<F, C, D>>,
+}
+
+impl<F, C, const D: usize> StarkProvingKey<F, C, D>
+where
+    F: RichField + Extendable<D>,
+    C: GenericConfig<D, F = F>,
+{
+    /// Computes the proving key of `stark` for the given configuration, which involves committing
+    /// to its preprocessed columns, if any.
+    pub fn new<S: Stark<F, D>>(stark: &S, config: &StarkConfig, timing: &mut TimingTree) -> Self {
+        Self::from_preprocessed_commitment(
+            stark,
+            config,
+            commit_preprocessed_columns(stark, config, timing),
+        )
+    }
+
+    fn from_preprocessed_commitment<S: Stark<F, D>>(
+        stark: &S,
+        config: &StarkConfig,
+        preprocessed_commitment: Option<PolynomialBatch<F, C, D>>,
+    ) -> Self {
+        let verifying_key = StarkVerifyingKey::from_preprocessed_cap(
+            stark,
+            config,
+            preprocessed_commitment
+                .as_ref()
+                .map(|commitment| commitment.merkle_tree.cap.clone()),
+        );
+        Self {
+            verifying_key,
+            preprocessed_commitment,
+        }
+    }
+
+    /// The verifying key of the STARK, against which proofs generated with this key verify.
+    pub const fn verifying_key(&self) -> &StarkVerifyingKey<F, C, D> {
+        &self.verifying_key
+    }
+
+    /// The commitment to the preprocessed columns of the STARK, if it has any.
+    pub const fn preprocessed_commitment(&self) -> Option<&PolynomialBatch<F, C, D>> {
+        self.preprocessed_commitment.as_ref()
+    }
+}
+
+/// Computes the proving keys of all the tables of a multi-STARK system, with
+/// [`StarkProvingKey::new`].
+pub fn all_proving_keys<F, C, A, const D: usize, const N: usize>(
+    all_stark: &A,
+    config: &StarkConfig,
+    timing: &mut TimingTree,
+) -> [StarkProvingKey<F, C, D>; N]
+where
+    F: RichField + Extendable<D>,
+    C: GenericConfig<D, F = F>,
+    A: AllStark<F, D, N>,
+{
+    let preprocessed_commitments =
+        commit_all_preprocessed_columns::<F, C, A, D, N>(all_stark, config, timing);
+    let verifying_keys = all_verifying_keys_from_caps::<F, C, A, D, N>(
+        all_stark,
+        config,
+        preprocessed_commitments.each_ref().map(|commitment| {
+            commitment
+                .as_ref()
+                .map(|commitment| commitment.merkle_tree.cap.clone())
+        }),
+    );
+    let mut preprocessed_commitments = preprocessed_commitments.into_iter();
+    verifying_keys.map(|verifying_key| StarkProvingKey {
+        verifying_key,
+        preprocessed_commitment: preprocessed_commitments.next().flatten(),
+    })
+}
 
 /// From a STARK trace, computes a STARK proof to attest its correctness.
+///
+/// This commits to the preprocessed columns of the STARK, if any. To prove several traces of the
+/// same STARK, use [`prove_with_key`] with a [`StarkProvingKey`] computed once instead.
 pub fn prove<F, C, S, const D: usize>(
     stark: S,
     config: &StarkConfig,
@@ -46,31 +148,53 @@ where
     C: GenericConfig<D, F = F>,
     S: Stark<F, D>,
 {
+    let proving_key = StarkProvingKey::new(&stark, config, timing);
+    prove_with_key(
+        stark,
+        &proving_key,
+        trace_poly_values,
+        public_inputs,
+        timing,
+    )
+}
+
+/// From a STARK trace, computes a STARK proof to attest its correctness, with a proving key of
+/// the STARK computed beforehand. The configuration is the one of the proving key.
+pub fn prove_with_key<F, C, S, const D: usize>(
+    stark: S,
+    proving_key: &StarkProvingKey<F, C, D>,
+    trace_poly_values: Vec<PolynomialValues<F>>,
+    public_inputs: &[F],
+    timing: &mut TimingTree,
+) -> Result<StarkProofWithPublicInputs<F, C, D>>
+where
+    F: RichField + Extendable<D>,
+    C: GenericConfig<D, F = F>,
+    S: Stark<F, D>,
+{
+    let verifying_key = &proving_key.verifying_key;
+    let config = &verifying_key.config;
+    verifying_key.check(&stark, config)?;
+
     let degree = trace_poly_values[0].len();
     let degree_bits = log2_strict(degree);
     let fri_params = config.fri_params(degree_bits);
     let rate_bits = config.fri_config.rate_bits;
     let cap_height = config.fri_config.cap_height;
     assert!(
-        fri_params.total_arities() <= degree_bits + rate_bits - cap_height,
+        fri_params.total_arities() <= fri_params.degree_bits + rate_bits - cap_height,
         "FRI total reduction arity is too large.",
     );
 
     let trace_commitment = timed!(
         timing,
         "compute trace commitment",
-        PolynomialBatch::<F, C, D>::from_values(
-            trace_poly_values.clone(),
-            rate_bits,
-            false,
-            cap_height,
-            timing,
-            None,
-        )
+        commit_trace(&stark, config, trace_poly_values.clone(), timing)?
     );
 
     let trace_cap = trace_commitment.merkle_tree.cap.clone();
     let mut challenger = Challenger::new();
+    challenger.observe_hash::<C::Hasher>(verifying_key.circuit_digest());
     challenger.observe_elements(public_inputs);
     challenger.observe_cap(&trace_cap);
 
@@ -79,6 +203,7 @@ where
         config,
         &trace_poly_values,
         &trace_commitment,
+        proving_key.preprocessed_commitment(),
         None,
         None,
         &mut challenger,
@@ -87,6 +212,409 @@ where
     )
 }
 
+/// Commits to the trace of a STARK, blinding it in zero-knowledge mode, as expected by
+/// [`prove_with_commitment`]. This fails if the trace is too short to be blinded, see
+/// [`StarkConfig::num_blinding_coeffs`].
+pub fn commit_trace<F, C, S, const D: usize>(
+    _stark: &S,
+    config: &StarkConfig,
+    trace_poly_values: Vec<PolynomialValues<F>>,
+    timing: &mut TimingTree,
+) -> Result<PolynomialBatch<F, C, D>>
+where
+    F: RichField + Extendable<D>,
+    C: GenericConfig<D, F = F>,
+    S: Stark<F, D>,
+{
+    commit_witness_polys(
+        trace_poly_values,
+        config,
+        config.num_blinding_coeffs(S::ROW_OFFSETS.len()),
+        timing,
+    )
+}
+
+/// Commits to the trace or auxiliary polynomials interpolating `values`. In zero-knowledge mode,
+/// each of them is blinded with a random multiple of `Z_H` with `num_blinding_coeffs`
+/// coefficients, see [`StarkConfig::zero_knowledge`].
+fn commit_witness_polys<F, C, const D: usize>(
+    values: Vec<PolynomialValues<F>>,
+    config: &StarkConfig,
+    num_blinding_coeffs: usize,
+    timing: &mut TimingTree,
+) -> Result<PolynomialBatch<F, C, D>>
+where
+    F: RichField + Extendable<D>,
+    C: GenericConfig<D, F = F>,
+{
+    let rate_bits = config.fri_config.rate_bits;
+    let cap_height = config.fri_config.cap_height;
+    if !config.zero_knowledge {
+        return Ok(PolynomialBatch::from_values(
+            values, rate_bits, false, cap_height, timing, None,
+        ));
+    }
+
+    // Keeps the degree of the constraints on blinded polynomials within the quotient chunks.
+    let degree = values.first().map_or(0, PolynomialValues::len);
+    ensure!(
+        2 * num_blinding_coeffs <= degree,
+        "In zero-knowledge mode, the trace must have at least {} rows, but it has {}.",
+        2 * num_blinding_coeffs,
+        degree
+    );
+
+    let coeffs = timed!(
+        timing,
+        "IFFT + blinding",
+        values
+            .into_par_iter()
+            .map(|v| blind(v.ifft(), num_blinding_coeffs))
+            .collect()
+    );
+    Ok(PolynomialBatch::from_coeffs(
+        coeffs, rate_bits, true, cap_height, timing, None,
+    ))
+}
+
+/// Returns `poly + Z_H * r`, where `H` is the subgroup of order `poly.len()` and `r` is a random
+/// polynomial with `num_blinding_coeffs` coefficients, at most half as many as `poly` has. The
+/// result has the same values as `poly` on `H`, and twice as many coefficients.
+fn blind<F: Field>(
+    mut poly: PolynomialCoeffs<F>,
+    num_blinding_coeffs: usize,
+) -> PolynomialCoeffs<F> {
+    let degree = poly.len();
+    debug_assert!(2 * num_blinding_coeffs <= degree);
+    poly.coeffs.resize(2 * degree, F::ZERO);
+    for (i, r) in F::rand_vec(num_blinding_coeffs).into_iter().enumerate() {
+        // `Z_H * r = X^degree * r - r`.
+        poly.coeffs[i] -= r;
+        poly.coeffs[degree + i] += r;
+    }
+    poly
+}
+
+/// Returns the values on the subgroup `H` of order `degree` of a committed polynomial, which may
+/// have been blinded or padded beyond `degree` coefficients. These are the values of its
+/// remainder modulo `Z_H = X^degree - 1`.
+fn values_on_subgroup<F: Field>(poly: &PolynomialCoeffs<F>, degree: usize) -> PolynomialValues<F> {
+    let mut coeffs = poly.coeffs[..degree].to_vec();
+    for chunk in poly.coeffs[degree..].chunks(degree) {
+        coeffs.iter_mut().zip(chunk).for_each(|(c, &x)| *c += x);
+    }
+    PolynomialCoeffs::new(coeffs).fft()
+}
+
+/// Commits to the preprocessed columns of a STARK, if it has any.
+///
+/// The commitment only depends on the STARK and on the configuration, so it can be computed once
+/// and reused for all proofs with [`prove_with_commitment`]. Its Merkle cap is part of the
+/// [`StarkVerifyingKey`] of the STARK.
+pub fn commit_preprocessed_columns<F, C, S, const D: usize>(
+    stark: &S,
+    config: &StarkConfig,
+    timing: &mut TimingTree,
+) -> Option<PolynomialBatch<F, C, D>>
+where
+    F: RichField + Extendable<D>,
+    C: GenericConfig<D, F = F>,
+    S: Stark<F, D>,
+{
+    if S::PREPROCESSED_COLUMNS == 0 {
+        return None;
+    }
+
+    let preprocessed_columns = stark.preprocessed_columns();
+    assert_eq!(
+        preprocessed_columns.len(),
+        S::PREPROCESSED_COLUMNS,
+        "Wrong number of preprocessed columns."
+    );
+    // The preprocessed columns are public, so they are never blinded, but they are padded to the
+    // degree bound of the blinded polynomials in zero-knowledge mode.
+    let committed_degree = preprocessed_columns[0].len() << config.blinding_degree_bits();
+    Some(timed!(
+        timing,
+        "compute preprocessed commitment",
+        PolynomialBatch::from_coeffs(
+            preprocessed_columns
+                .into_par_iter()
+                .map(|column| column.ifft().padded(committed_degree))
+                .collect(),
+            config.fri_config.rate_bits,
+            false,
+            config.fri_config.cap_height,
+            timing,
+            None,
+        )
+    ))
+}
+
+/// Commits to the preprocessed columns of each table of a multi-STARK system, with
+/// [`commit_preprocessed_columns`].
+pub fn commit_all_preprocessed_columns<F, C, A, const D: usize, const N: usize>(
+    all_stark: &A,
+    config: &StarkConfig,
+    timing: &mut TimingTree,
+) -> [Option<PolynomialBatch<F, C, D>>; N]
+where
+    F: RichField + Extendable<D>,
+    C: GenericConfig<D, F = F>,
+    A: AllStark<F, D, N>,
+{
+    core::array::from_fn(|table| {
+        all_stark.visit(
+            table,
+            CommitPreprocessed {
+                config,
+                timing: &mut *timing,
+                _phantom: PhantomData::<C>,
+            },
+        )
+    })
+}
+
+/// Commits to the preprocessed columns of one table of a multi-STARK system.
+struct CommitPreprocessed<'a, C> {
+    config: &'a StarkConfig,
+    timing: &'a mut TimingTree,
+    _phantom: PhantomData<C>,
+}
+
+impl<F, C, const D: usize> StarkVisitor<F, D> for CommitPreprocessed<'_, C>
+where
+    F: RichField + Extendable<D>,
+    C: GenericConfig<D, F = F>,
+{
+    type Output = Option<PolynomialBatch<F, C, D>>;
+
+    fn visit<S: Stark<F, D>>(self, stark: &S) -> Self::Output {
+        commit_preprocessed_columns(stark, self.config, self.timing)
+    }
+}
+
+/// From the traces of all the tables of a multi-STARK system, computes a proof of their
+/// correctness and of the consistency of the cross-table lookups between them.
+///
+/// All tables share a single Fiat-Shamir transcript, which observes the circuit digests, public
+/// inputs and trace caps of every table before the cross-table lookup challenges are drawn, and
+/// then goes through each table in turn.
+///
+/// Each table keeps its own height, and the openings of all tables are proven by a single FRI proof
+/// with [`plonky2::batch_fri`], in which tables of the same height form a single FRI instance.
+///
+/// This commits to the preprocessed columns of all tables. To prove several sets of traces of the
+/// same system, use [`prove_all_with_keys`] with proving keys computed once instead.
+pub fn prove_all<F, C, A, const D: usize, const N: usize>(
+    all_stark: &A,
+    config: &StarkConfig,
+    trace_poly_values: [Vec<PolynomialValues<F>>; N],
+    public_inputs: [Vec<F>; N],
+    timing: &mut TimingTree,
+) -> Result<MultiProofWithPublicInputs<F, C, D, N>>
+where
+    F: RichField + Extendable<D>,
+    C: GenericConfig<D, F = F>,
+    A: AllStark<F, D, N>,
+{
+    let proving_keys = all_proving_keys::<F, C, A, D, N>(all_stark, config, timing);
+    prove_all_with_keys(
+        all_stark,
+        config,
+        &proving_keys,
+        trace_poly_values,
+        public_inputs,
+        timing,
+    )
+}
+
+/// Proves a multi-STARK system like [`prove_all`], with proving keys of all its tables computed
+/// beforehand for `config`, e.g. with [`all_proving_keys`].
+pub fn prove_all_with_keys<F, C, A, const D: usize, const N: usize>(
+    all_stark: &A,
+    config: &StarkConfig,
+    proving_keys: &[StarkProvingKey<F, C, D>; N],
+    trace_poly_values: [Vec<PolynomialValues<F>>; N],
+    public_inputs: [Vec<F>; N],
+    timing: &mut TimingTree,
+) -> Result<MultiProofWithPublicInputs<F, C, D, N>>
+where
+    F: RichField + Extendable<D>,
+    C: GenericConfig<D, F = F>,
+    A: AllStark<F, D, N>,
+{
+    let cross_table_lookups = all_stark.cross_table_lookups();
+    let tables = table_infos(all_stark, config);
+    let max_constraint_degree = check_cross_table_lookups(&cross_table_lookups, &tables)?;
+
+    let trace_commitments = timed!(
+        timing,
+        "compute all trace commitments",
+        trace_poly_values
+            .iter()
+            .zip(&tables)
+            .map(|(trace, info)| {
+                commit_witness_polys::<F, C, D>(
+                    trace.clone(),
+                    config,
+                    config.num_blinding_coeffs(info.num_row_offsets),
+                    timing,
+                )
+            })
+            .collect::<Result<Vec<_>>>()?
+    );
+
+    let preprocessed_commitments = proving_keys
+        .each_ref()
+        .map(StarkProvingKey::preprocessed_commitment);
+
+    let mut challenger = Challenger::<F, C::Hasher>::new();
+    for proving_key in proving_keys {
+        challenger.observe_hash::<C::Hasher>(proving_key.verifying_key.circuit_digest());
+    }
+    for public_inputs in &public_inputs {
+        challenger.observe_elements(public_inputs);
+    }
+    for commitment in &trace_commitments {
+        challenger.observe_cap(&commitment.merkle_tree.cap);
+    }
+
+    // Cross-table lookups may involve the preprocessed columns, which follow the trace columns.
+    let ctl_tables = preprocessed_commitments
+        .iter()
+        .any(Option::is_some)
+        .then(|| {
+            core::array::from_fn(|table| {
+                frame_columns(&trace_poly_values[table], preprocessed_commitments[table])
+            })
+        });
+
+    let (ctl_challenges, ctl_data_per_table) = timed!(
+        timing,
+        "compute CTL data",
+        get_ctl_data::<F, C, D, N>(
+            config,
+            ctl_tables.as_ref().unwrap_or(&trace_poly_values),
+            &cross_table_lookups,
+            &mut challenger,
+            max_constraint_degree,
+        )
+    );
+
+    let mut stark_proofs = Vec::with_capacity(N);
+    let mut fri_instances = Vec::with_capacity(N);
+    let mut commitments = Vec::with_capacity(N);
+    for table in 0..N {
+        let init_challenger_state = challenger.compact();
+        let opened_table = all_stark.visit(
+            table,
+            ProveTable {
+                config,
+                trace_poly_values: &trace_poly_values[table],
+                trace_commitment: &trace_commitments[table],
+                proving_key: &proving_keys[table],
+                ctl_data: tables[table]
+                    .requires_ctls
+                    .then_some(&ctl_data_per_table[table]),
+                ctl_challenges: &ctl_challenges,
+                challenger: &mut challenger,
+                public_inputs: &public_inputs[table],
+                timing,
+            },
+        )?;
+        stark_proofs.push(StarkProofWithMetadata {
+            init_challenger_state,
+            proof: opened_table.proof,
+        });
+        fri_instances.push(opened_table.fri_instance);
+        commitments.push(opened_table.commitments);
+    }
+
+    let degree_bits = trace_poly_values
+        .each_ref()
+        .map(|trace| log2_strict(trace[0].len()));
+    let groups = batch_fri_groups(&degree_bits);
+    let fri_degree_bits = batch_fri_degree_bits(&groups, &degree_bits, config);
+    let fri_params = batch_fri_params(&config.fri_config, &fri_degree_bits, config.zero_knowledge);
+
+    let oracles = groups
+        .iter()
+        .map(|group| {
+            group
+                .iter()
+                .flat_map(|&table| commitments[table].oracles())
+                .collect_vec()
+        })
+        .collect_vec();
+    let oracles = oracles.iter().map(Vec::as_slice).collect_vec();
+    let opening_proof = timed!(
+        timing,
+        "compute batched openings proof",
+        batch_prove_openings(
+            &batch_fri_instances(&groups, fri_instances),
+            &oracles,
+            &mut challenger,
+            &fri_params,
+            timing,
+        )
+    );
+
+    Ok(MultiProofWithPublicInputs {
+        proof: MultiProof {
+            stark_proofs: stark_proofs
+                .try_into()
+                .unwrap_or_else(|_| unreachable!("There is one proof per table.")),
+            degree_bits,
+            ctl_challenges,
+            opening_proof,
+        },
+        public_inputs,
+    })
+}
+
+/// Commits to the polynomials of one table of a multi-STARK system and opens them, with
+/// [`open_table`].
+struct ProveTable<'a, 'b, 'c, F, C, const D: usize>
+where
+    F: RichField + Extendable<D>,
+    C: GenericConfig<D, F = F>,
+{
+    config: &'a StarkConfig,
+    trace_poly_values: &'a [PolynomialValues<F>],
+    trace_commitment: &'a PolynomialBatch<F, C, D>,
+    proving_key: &'a StarkProvingKey<F, C, D>,
+    ctl_data: Option<&'a CtlData<'b, F>>,
+    ctl_challenges: &'a GrandProductChallengeSet<F>,
+    challenger: &'c mut Challenger<F, C::Hasher>,
+    public_inputs: &'a [F],
+    timing: &'c mut TimingTree,
+}
+
+impl<'a, F, C, const D: usize> StarkVisitor<F, D> for ProveTable<'a, '_, '_, F, C, D>
+where
+    F: RichField + Extendable<D>,
+    C: GenericConfig<D, F = F>,
+{
+    type Output = Result<OpenedTable<'a, F, C, D>>;
+
+    fn visit<S: Stark<F, D>>(self, stark: &S) -> Self::Output {
+        self.proving_key.verifying_key.check(stark, self.config)?;
+        open_table(
+            stark,
+            self.config,
+            self.trace_poly_values,
+            self.trace_commitment,
+            self.proving_key.preprocessed_commitment(),
+            self.ctl_data,
+            Some(self.ctl_challenges),
+            self.challenger,
+            self.public_inputs,
+            self.timing,
+        )
+    }
+}
+
 /// Generates a proof for a single STARK table, including:
 ///
 /// - the initial state of the challenger,
@@ -94,11 +622,16 @@ where
 /// - all the required polynomial and FRI argument openings.
 /// - individual `ctl_data` and common `ctl_challenges` if the STARK is part
 ///   of a multi-STARK system.
+///
+/// The challenger must already have observed the
+/// [circuit digest](StarkVerifyingKey::circuit_digest) of the STARK, its public inputs and its
+/// trace cap, where the trace is committed to with [`commit_trace`].
 pub fn prove_with_commitment<F, C, S, const D: usize>(
     stark: &S,
     config: &StarkConfig,
     trace_poly_values: &[PolynomialValues<F>],
     trace_commitment: &PolynomialBatch<F, C, D>,
+    preprocessed_commitment: Option<&PolynomialBatch<F, C, D>>,
     ctl_data: Option<&CtlData<F>>,
     ctl_challenges: Option<&GrandProductChallengeSet<F>>,
     challenger: &mut Challenger<F, C::Hasher>,
@@ -110,21 +643,143 @@ where
     C: GenericConfig<D, F = F>,
     S: Stark<F, D>,
 {
-    let degree = trace_poly_values[0].len();
-    let degree_bits = log2_strict(degree);
+    let degree_bits = log2_strict(trace_poly_values[0].len());
     let fri_params = config.fri_params(degree_bits);
     let rate_bits = config.fri_config.rate_bits;
     let cap_height = config.fri_config.cap_height;
     assert!(
-        fri_params.total_arities() <= degree_bits + rate_bits - cap_height,
+        fri_params.total_arities() <= fri_params.degree_bits + rate_bits - cap_height,
         "FRI total reduction arity is too large.",
     );
 
+    let table = open_table(
+        stark,
+        config,
+        trace_poly_values,
+        trace_commitment,
+        preprocessed_commitment,
+        ctl_data,
+        ctl_challenges,
+        challenger,
+        public_inputs,
+        timing,
+    )?;
+
+    let opening_proof = timed!(
+        timing,
+        "compute openings proof",
+        PolynomialBatch::prove_openings(
+            &table.fri_instance,
+            &table.commitments.oracles(),
+            challenger,
+            &fri_params,
+            timing,
+        )
+    );
+
+    let StarkTableProof {
+        trace_cap,
+        auxiliary_polys_cap,
+        quotient_polys_cap,
+        openings,
+    } = table.proof;
+    let proof = StarkProof {
+        trace_cap,
+        auxiliary_polys_cap,
+        quotient_polys_cap,
+        openings,
+        opening_proof,
+    };
+
+    Ok(StarkProofWithPublicInputs {
+        proof,
+        public_inputs: public_inputs.to_vec(),
+    })
+}
+
+/// A table whose polynomials have been committed to and opened, and whose openings remain to be
+/// proven with FRI.
+struct OpenedTable<'a, F, C, const D: usize>
+where
+    F: RichField + Extendable<D>,
+    C: GenericConfig<D, F = F>,
+{
+    /// The Merkle caps and openings of the table.
+    proof: StarkTableProof<F, C, D>,
+    /// The FRI instance describing the openings.
+    fri_instance: FriInstanceInfo<F, D>,
+    /// The commitments to the polynomials of the table.
+    commitments: TableCommitments<'a, F, C, D>,
+}
+
+/// The commitments to the polynomials of a table, which are the oracles of its FRI instance.
+struct TableCommitments<'a, F, C, const D: usize>
+where
+    F: RichField + Extendable<D>,
+    C: GenericConfig<D, F = F>,
+{
+    trace_commitment: &'a PolynomialBatch<F, C, D>,
+    auxiliary_polys_commitment: Option<PolynomialBatch<F, C, D>>,
+    quotient_commitment: Option<PolynomialBatch<F, C, D>>,
+    preprocessed_commitment: Option<&'a PolynomialBatch<F, C, D>>,
+}
+
+impl<F, C, const D: usize> TableCommitments<'_, F, C, D>
+where
+    F: RichField + Extendable<D>,
+    C: GenericConfig<D, F = F>,
+{
+    /// The oracles of the FRI instance, in order.
+    fn oracles(&self) -> Vec<&PolynomialBatch<F, C, D>> {
+        once(self.trace_commitment)
+            .chain(&self.auxiliary_polys_commitment)
+            .chain(&self.quotient_commitment)
+            .chain(self.preprocessed_commitment)
+            .collect()
+    }
+}
+
+/// Commits to the auxiliary and quotient polynomials of a STARK table and opens all its
+/// polynomials, as in [`prove_with_commitment`], but stops before the FRI proof of the openings.
+#[allow(clippy::too_many_arguments)]
+fn open_table<'a, F, C, S, const D: usize>(
+    stark: &S,
+    config: &StarkConfig,
+    trace_poly_values: &[PolynomialValues<F>],
+    trace_commitment: &'a PolynomialBatch<F, C, D>,
+    preprocessed_commitment: Option<&'a PolynomialBatch<F, C, D>>,
+    ctl_data: Option<&CtlData<F>>,
+    ctl_challenges: Option<&GrandProductChallengeSet<F>>,
+    challenger: &mut Challenger<F, C::Hasher>,
+    public_inputs: &[F],
+    timing: &mut TimingTree,
+) -> Result<OpenedTable<'a, F, C, D>>
+where
+    F: RichField + Extendable<D>,
+    C: GenericConfig<D, F = F>,
+    S: Stark<F, D>,
+{
+    let degree_bits = log2_strict(trace_poly_values[0].len());
+    let rate_bits = config.fri_config.rate_bits;
+    // The degree bound of the committed polynomials, which is doubled in zero-knowledge mode.
+    let committed_degree_bits = degree_bits + config.blinding_degree_bits();
+    let committed_degree = 1 << committed_degree_bits;
+    assert_eq!(
+        trace_commitment.degree_log, committed_degree_bits,
+        "The trace must be committed to with `commit_trace`."
+    );
+
     let constraint_degree = stark.constraint_degree();
     assert!(
         constraint_degree <= (1 << rate_bits) + 1,
         "The degree of the Stark constraints must be <= blowup_factor + 1"
     );
+    let () = S::CHECK_ROW_OFFSETS;
+    assert_eq!(
+        preprocessed_commitment.map_or(0, |c| c.polynomials.len()),
+        S::PREPROCESSED_COLUMNS,
+        "The preprocessed columns must be committed to with `commit_preprocessed_columns`."
+    );
 
     // Permutation arguments.
     let lookup_challenges = stark.uses_lookups().then(|| {
@@ -144,12 +799,14 @@ where
         timing,
         "compute lookup helper columns",
         lookup_challenges.as_ref().map(|challenges| {
+            // Lookups may involve the preprocessed columns, which follow the trace columns.
+            let frame_columns = frame_columns(trace_poly_values, preprocessed_commitment);
             let mut columns = Vec::new();
             for lookup in &lookups {
                 for &challenge in challenges {
                     columns.extend(lookup_helper_columns(
                         lookup,
-                        trace_poly_values,
+                        &frame_columns,
                         challenge,
                         constraint_degree,
                     ));
@@ -179,20 +836,20 @@ where
     );
 
     // Get the polynomial commitments for all auxiliary polynomials.
-    let auxiliary_polys_commitment = auxiliary_polys.map(|aux_polys| {
-        timed!(
-            timing,
-            "compute auxiliary polynomials commitment",
-            PolynomialBatch::from_values(
-                aux_polys,
-                rate_bits,
-                false,
-                config.fri_config.cap_height,
+    let auxiliary_polys_commitment = auxiliary_polys
+        .map(|aux_polys| {
+            timed!(
                 timing,
-                None,
+                "compute auxiliary polynomials commitment",
+                commit_witness_polys(
+                    aux_polys,
+                    config,
+                    config.num_blinding_coeffs(S::ROW_OFFSETS.len()),
+                    timing,
+                )
             )
-        )
-    });
+        })
+        .transpose()?;
 
     let auxiliary_polys_cap = auxiliary_polys_commitment
         .as_ref()
@@ -213,6 +870,7 @@ where
         check_constraints(
             stark,
             trace_commitment,
+            preprocessed_commitment,
             public_inputs,
             &auxiliary_polys_commitment,
             lookup_challenges.as_ref(),
@@ -231,6 +889,7 @@ where
         compute_quotient_polys::<F, <F as Packable>::Packing, C, S, D>(
             stark,
             trace_commitment,
+            preprocessed_commitment,
             &auxiliary_polys_commitment,
             lookup_challenges.as_ref(),
             &lookups,
@@ -244,22 +903,29 @@ where
         )
     );
     let (quotient_commitment, quotient_polys_cap) = if let Some(quotient_polys) = quotient_polys {
-        let all_quotient_chunks = timed!(
+        let mut all_quotient_chunks: Vec<_> = timed!(
             timing,
             "split quotient polys",
             quotient_polys
                 .into_par_iter()
                 .flat_map(|mut quotient_poly| {
                     quotient_poly
-                        .trim_to_len(degree * stark.quotient_degree_factor())
+                        .trim_to_len(committed_degree * stark.quotient_degree_factor())
                         .expect(
                             "Quotient has failed, the vanishing polynomial is not divisible by Z_H",
                         );
-                    // Split quotient into degree-n chunks.
-                    quotient_poly.chunks(degree)
+                    // Split quotient into chunks of the degree of the committed polynomials.
+                    quotient_poly.chunks(committed_degree)
                 })
                 .collect()
         );
+        // In zero-knowledge mode, commit to random polynomials along with the quotient chunks.
+        // Their openings are ignored by the verifier, but they mask the combination of all
+        // openings whose low degree is proven by FRI.
+        all_quotient_chunks.extend(
+            (0..config.num_masking_polys())
+                .map(|_| PolynomialCoeffs::new(F::rand_vec(committed_degree))),
+        );
         // Commit to the quotient polynomials.
         let quotient_commitment = timed!(
             timing,
@@ -267,7 +933,7 @@ where
             PolynomialBatch::from_coeffs(
                 all_quotient_chunks,
                 rate_bits,
-                false,
+                config.zero_knowledge,
                 config.fri_config.cap_height,
                 timing,
                 None,
@@ -296,7 +962,9 @@ where
     let openings = StarkOpeningSet::new(
         zeta,
         g,
+        S::ROW_OFFSETS,
         trace_commitment,
+        preprocessed_commitment,
         auxiliary_polys_commitment.as_ref(),
         quotient_commitment.as_ref(),
         stark.num_lookup_helper_columns(config),
@@ -306,35 +974,49 @@ where
     // Get the FRI openings and observe them.
     challenger.observe_openings(&openings.to_fri_openings());
 
-    let initial_merkle_trees = once(trace_commitment)
-        .chain(&auxiliary_polys_commitment)
-        .chain(&quotient_commitment)
-        .collect_vec();
+    Ok(OpenedTable {
+        proof: StarkTableProof {
+            trace_cap: trace_commitment.merkle_tree.cap.clone(),
+            auxiliary_polys_cap,
+            quotient_polys_cap,
+            openings,
+        },
+        fri_instance: stark.fri_instance(
+            zeta,
+            g,
+            num_ctl_polys.iter().sum(),
+            num_ctl_polys,
+            config,
+        ),
+        commitments: TableCommitments {
+            trace_commitment,
+            auxiliary_polys_commitment,
+            quotient_commitment,
+            preprocessed_commitment,
+        },
+    })
+}
 
-    let opening_proof = timed!(
-        timing,
-        "compute openings proof",
-        PolynomialBatch::prove_openings(
-            &stark.fri_instance(zeta, g, num_ctl_polys.iter().sum(), num_ctl_polys, config),
-            &initial_merkle_trees,
-            challenger,
-            &fri_params,
-            timing,
+/// Returns the values of the columns viewed by the evaluation frames of a STARK, i.e. of its
+/// trace columns followed by its preprocessed columns.
+fn frame_columns<F, C, const D: usize>(
+    trace_poly_values: &[PolynomialValues<F>],
+    preprocessed_commitment: Option<&PolynomialBatch<F, C, D>>,
+) -> Vec<PolynomialValues<F>>
+where
+    F: RichField + Extendable<D>,
+    C: GenericConfig<D, F = F>,
+{
+    let degree = trace_poly_values[0].len();
+    trace_poly_values
+        .iter()
+        .cloned()
+        .chain(
+            preprocessed_commitment
+                .into_iter()
+                .flat_map(|c| c.polynomials.iter().map(|p| values_on_subgroup(p, degree))),
         )
-    );
-
-    let proof = StarkProof {
-        trace_cap: trace_commitment.merkle_tree.cap.clone(),
-        auxiliary_polys_cap,
-        quotient_polys_cap,
-        openings,
-        opening_proof,
-    };
-
-    Ok(StarkProofWithPublicInputs {
-        proof,
-        public_inputs: public_inputs.to_vec(),
-    })
+        .collect()
 }
 
 /// Computes the quotient polynomials `(sum alpha^i C_i(x)) / Z_H(x)` for `alpha` in `alphas`,
@@ -342,6 +1024,7 @@ where
 fn compute_quotient_polys<'a, F, P, C, S, const D: usize>(
     stark: &S,
     trace_commitment: &'a PolynomialBatch<F, C, D>,
+    preprocessed_commitment: Option<&'a PolynomialBatch<F, C, D>>,
     auxiliary_polys_commitment: &'a Option<PolynomialBatch<F, C, D>>,
     lookup_challenges: Option<&'a Vec<F>>,
     lookups: &[Lookup<F>],
@@ -373,26 +1056,34 @@ where
         "Having constraints of degree higher than the rate is not supported yet."
     );
     let step = 1 << (rate_bits - quotient_degree_bits);
+    // The quotient is evaluated on a coset of `2^quotient_degree_bits` times the degree bound of
+    // the committed polynomials, which is twice the trace length in zero-knowledge mode.
+    let extension_bits = quotient_degree_bits + config.blinding_degree_bits();
     // When opening the `Z`s polys at the "next" point, need to look at the point `next_step` steps away.
-    let next_step = 1 << quotient_degree_bits;
+    let next_step = 1 << extension_bits;
 
     // Evaluation of the first Lagrange polynomial on the LDE domain.
-    let lagrange_first = PolynomialValues::selector(degree, 0).lde_onto_coset(quotient_degree_bits);
+    let lagrange_first = PolynomialValues::selector(degree, 0).lde_onto_coset(extension_bits);
     // Evaluation of the last Lagrange polynomial on the LDE domain.
     let lagrange_last =
-        PolynomialValues::selector(degree, degree - 1).lde_onto_coset(quotient_degree_bits);
+        PolynomialValues::selector(degree, degree - 1).lde_onto_coset(extension_bits);
 
-    let z_h_on_coset = ZeroPolyOnCoset::<F>::new(degree_bits, quotient_degree_bits);
+    let z_h_on_coset = ZeroPolyOnCoset::<F>::new(degree_bits, extension_bits);
 
-    // Retrieve the LDE values at index `i`.
-    let get_trace_values_packed =
-        |i_start| -> Vec<P> { trace_commitment.get_lde_values_packed(i_start, step) };
+    // Retrieve the LDE values at index `i` of the trace columns, followed by the preprocessed ones.
+    let get_trace_values_packed = |i_start| -> Vec<P> {
+        let mut values = trace_commitment.get_lde_values_packed(i_start, step);
+        if let Some(c) = preprocessed_commitment {
+            values.extend(c.get_lde_values_packed::<P>(i_start, step));
+        }
+        values
+    };
 
     // Last element of the subgroup.
     let last = F::primitive_root_of_unity(degree_bits).inverse();
-    let size = degree << quotient_degree_bits;
+    let size = degree << extension_bits;
     let coset = F::cyclic_subgroup_coset_known_order(
-        F::primitive_root_of_unity(degree_bits + quotient_degree_bits),
+        F::primitive_root_of_unity(degree_bits + extension_bits),
         F::coset_shift(),
         size,
     );
@@ -417,11 +1108,14 @@ where
                 lagrange_basis_first,
                 lagrange_basis_last,
             );
-            // Get the local and next row evaluations for the current STARK,
+            // Get the evaluations of the rows viewed by the current STARK's frame,
             // as well as the public inputs.
-            let vars = S::EvaluationFrame::from_values(
-                &get_trace_values_packed(i_start),
-                &get_trace_values_packed(i_next_start),
+            let rows = S::ROW_OFFSETS
+                .iter()
+                .map(|&offset| get_trace_values_packed((i_start + offset * next_step) % size))
+                .collect::<Vec<_>>();
+            let vars = S::EvaluationFrame::from_rows(
+                &rows.iter().map(Vec::as_slice).collect::<Vec<_>>(),
                 public_inputs,
             );
             // Get the local and next row evaluations for the permutation argument,
@@ -536,6 +1230,7 @@ where
 fn check_constraints<'a, F, C, S, const D: usize>(
     stark: &S,
     trace_commitment: &'a PolynomialBatch<F, C, D>,
+    preprocessed_commitment: Option<&'a PolynomialBatch<F, C, D>>,
     public_inputs: &[F],
     auxiliary_commitment: &'a Option<PolynomialBatch<F, C, D>>,
     lookup_challenges: Option<&'a Vec<F>>,
@@ -571,13 +1266,19 @@ fn check_constraints<'a, F, C, S, const D: usize>(
         let values = comm
             .polynomials
             .par_iter()
-            .map(|coeffs| coeffs.clone().fft().values)
+            .map(|coeffs| values_on_subgroup(coeffs, degree).values)
             .collect::<Vec<_>>();
         transpose(&values)
     };
 
     // Get batch evaluations of the trace and permutation polynomials over our subgroup.
-    let trace_subgroup_evals = get_subgroup_evals(trace_commitment);
+    // The preprocessed columns follow the trace columns in each row.
+    let mut trace_subgroup_evals = get_subgroup_evals(trace_commitment);
+    if let Some(c) = preprocessed_commitment {
+        for (row, preprocessed_row) in trace_subgroup_evals.iter_mut().zip(get_subgroup_evals(c)) {
+            row.extend(preprocessed_row);
+        }
+    }
     let auxiliary_subgroup_evals = auxiliary_commitment.as_ref().map(get_subgroup_evals);
 
     // Last element of the subgroup.
@@ -598,12 +1299,12 @@ fn check_constraints<'a, F, C, S, const D: usize>(
                 lagrange_basis_first,
                 lagrange_basis_last,
             );
-            // Get the local and next row evaluations for the current STARK's trace.
-            let vars = S::EvaluationFrame::from_values(
-                &trace_subgroup_evals[i],
-                &trace_subgroup_evals[i_next],
-                public_inputs,
-            );
+            // Get the evaluations of the rows viewed by the current STARK's frame.
+            let rows = S::ROW_OFFSETS
+                .iter()
+                .map(|&offset| trace_subgroup_evals[(i + offset * step) % size].as_slice())
+                .collect::<Vec<_>>();
+            let vars = S::EvaluationFrame::from_rows(&rows, public_inputs);
             // Get the local and next row evaluations for the current STARK's permutation argument.
             let lookup_vars = lookup_challenges.map(|challenges| LookupCheckVars {
                 local_values: auxiliary_subgroup_evals.as_ref().unwrap()[i][..num_lookup_columns]
@@ -2,38 +2,55 @@
 //! verification if encoded in a plonky2 circuit.
 
 #[cfg(not(feature = "std"))]
-use alloc::vec::Vec;
+use alloc::{format, vec::Vec};
 use core::iter::once;
 
-use anyhow::{ensure, Result};
+use anyhow::{ensure, Context, Result};
+use hashbrown::HashMap;
 use itertools::Itertools;
+use plonky2::batch_fri::batch_fri_params;
 use plonky2::field::extension::Extendable;
 use plonky2::field::types::Field;
+use plonky2::fri::structure::{FriInstanceInfoTarget, FriOpeningsTarget};
 use plonky2::fri::witness_util::set_fri_proof_target;
-use plonky2::hash::hash_types::RichField;
+use plonky2::hash::hash_types::{MerkleCapTarget, RichField};
+use plonky2::hash::merkle_tree::MerkleCap;
 use plonky2::iop::challenger::RecursiveChallenger;
 use plonky2::iop::ext_target::ExtensionTarget;
 use plonky2::iop::target::Target;
 use plonky2::iop::witness::WitnessWrite;
 use plonky2::plonk::circuit_builder::CircuitBuilder;
 use plonky2::plonk::config::{AlgebraicHasher, GenericConfig};
+use plonky2::plonk::plonk_common::salt_size;
 use plonky2::util::reducing::ReducingFactorTarget;
 use plonky2::with_context;
 
+use crate::all_stark::{
+    batch_fri_degree_bits, batch_fri_groups, batch_fri_instances_target, check_cross_table_lookups,
+    table_infos, AllStark, StarkVisitor,
+};
 use crate::config::StarkConfig;
 use crate::constraint_consumer::RecursiveConstraintConsumer;
-use crate::cross_table_lookup::CtlCheckVarsTarget;
+use crate::cross_table_lookup::{
+    num_ctl_helper_columns_by_table, num_ctl_helpers_zs_by_table,
+    verify_cross_table_lookups_circuit, CtlCheckVarsTarget,
+};
 use crate::evaluation_frame::StarkEvaluationFrame;
 use crate::lookup::LookupCheckVarsTarget;
 use crate::proof::{
-    StarkOpeningSetTarget, StarkProof, StarkProofChallengesTarget, StarkProofTarget,
-    StarkProofWithPublicInputs, StarkProofWithPublicInputsTarget,
+    MultiProofChallengesTarget, MultiProofTarget, MultiProofWithPublicInputs,
+    MultiProofWithPublicInputsTarget, StarkOpeningSet, StarkOpeningSetTarget, StarkProof,
+    StarkProofChallengesTarget, StarkProofTarget, StarkProofWithPublicInputs,
+    StarkProofWithPublicInputsTarget, StarkTableChallengesTarget, StarkTableProofTarget,
 };
 use crate::stark::Stark;
 use crate::vanishing_poly::eval_vanishing_poly_circuit;
+use crate::verifying_key::StarkVerifyingKey;
 
 /// Encodes the verification of a [`StarkProofWithPublicInputsTarget`]
 /// for some statement in a circuit.
+///
+/// The [`StarkVerifyingKey`] of the STARK is hardcoded in the circuit.
 pub fn verify_stark_proof_circuit<
     F: RichField + Extendable<D>,
     C: GenericConfig<D, F = F>,
@@ -43,23 +60,32 @@ pub fn verify_stark_proof_circuit<
     builder: &mut CircuitBuilder<F, D>,
     stark: S,
     proof_with_pis: StarkProofWithPublicInputsTarget<D>,
-    inner_config: &StarkConfig,
+    verifying_key: &StarkVerifyingKey<F, C, D>,
 ) where
     C::Hasher: AlgebraicHasher<F>,
 {
+    let inner_config = &verifying_key.config;
+    verifying_key.check(&stark, inner_config).unwrap();
     assert_eq!(proof_with_pis.public_inputs.len(), S::PUBLIC_INPUTS);
 
     let mut challenger = RecursiveChallenger::<F, C::Hasher, D>::new(builder);
+    let circuit_digest = builder.constant_hash(verifying_key.circuit_digest());
+    challenger.observe_hash(&circuit_digest);
     let challenges = with_context!(
         builder,
         "compute challenges",
         proof_with_pis.get_challenges::<F, C>(builder, &mut challenger, None, false, inner_config)
     );
 
+    let preprocessed_cap = verifying_key
+        .preprocessed_cap
+        .as_ref()
+        .map(|cap| builder.constant_merkle_cap(cap));
     verify_stark_proof_with_challenges_circuit::<F, C, S, D>(
         builder,
         &stark,
         &proof_with_pis.proof,
+        preprocessed_cap.as_ref(),
         &proof_with_pis.public_inputs,
         challenges,
         None,
@@ -67,6 +93,199 @@ pub fn verify_stark_proof_circuit<
     );
 }
 
+/// Encodes the verification of a [`MultiProofWithPublicInputsTarget`] for a multi-STARK system in
+/// a circuit, i.e. of each of its STARK proofs, the batched FRI proof of their openings, as well as
+/// the cross-table lookups between them.
+///
+/// `verifying_keys` are the verifying keys of each table, which must all be computed with
+/// `inner_config` and are hardcoded in the circuit. Returns an error if the cross-table lookups
+/// are inconsistent with the tables, or if a verifying key does not match its table.
+pub fn verify_all_circuit<F, C, A, const D: usize, const N: usize>(
+    builder: &mut CircuitBuilder<F, D>,
+    all_stark: &A,
+    proof_with_pis: &MultiProofWithPublicInputsTarget<D, N>,
+    verifying_keys: &[StarkVerifyingKey<F, C, D>; N],
+    inner_config: &StarkConfig,
+) -> Result<()>
+where
+    F: RichField + Extendable<D>,
+    C: GenericConfig<D, F = F>,
+    C::Hasher: AlgebraicHasher<F>,
+    A: AllStark<F, D, N>,
+{
+    let cross_table_lookups = all_stark.cross_table_lookups();
+    let tables = table_infos(all_stark, inner_config);
+    let max_constraint_degree = check_cross_table_lookups(&cross_table_lookups, &tables)?;
+    let MultiProofWithPublicInputsTarget {
+        proof: multi_proof,
+        public_inputs,
+    } = proof_with_pis;
+
+    let circuit_digests = verifying_keys
+        .each_ref()
+        .map(|vk| builder.constant_hash(vk.circuit_digest()));
+    let challenges = with_context!(
+        builder,
+        "compute challenges",
+        proof_with_pis.get_challenges::<F, C>(builder, &circuit_digests, inner_config)
+    );
+
+    let MultiProofChallengesTarget {
+        stark_challenges,
+        ctl_challenges,
+        fri_challenges,
+    } = challenges;
+
+    let num_helper_ctl_columns =
+        num_ctl_helper_columns_by_table::<F, N>(&cross_table_lookups, max_constraint_degree);
+    let mut fri_instances = Vec::with_capacity(N);
+    let mut preprocessed_caps = Vec::with_capacity(N);
+    for (table, stark_challenges) in stark_challenges.into_iter().enumerate() {
+        let proof = &multi_proof.stark_proofs[table];
+        let info = &tables[table];
+        let ctl_vars = info.requires_ctls.then(|| {
+            let num_helper_ctl_columns = num_helper_ctl_columns
+                .iter()
+                .map(|num_by_table| num_by_table[table])
+                .collect::<Vec<_>>();
+            CtlCheckVarsTarget::from_proof(
+                table,
+                proof,
+                &cross_table_lookups,
+                &ctl_challenges,
+                info.num_lookup_columns,
+                num_helper_ctl_columns.iter().sum::<usize>() * inner_config.num_challenges,
+                &num_helper_ctl_columns,
+            )
+        });
+        let verifying_key = &verifying_keys[table];
+        let preprocessed_cap = verifying_key
+            .preprocessed_cap
+            .as_ref()
+            .map(|cap| builder.constant_merkle_cap(cap));
+        let fri_instance = all_stark
+            .visit(
+                table,
+                VerifyTableCircuit::<F, C, D> {
+                    builder,
+                    proof,
+                    degree_bits: multi_proof.degree_bits[table],
+                    verifying_key,
+                    preprocessed_cap: preprocessed_cap.as_ref(),
+                    public_inputs: &public_inputs[table],
+                    challenges: stark_challenges,
+                    ctl_vars: ctl_vars.as_deref(),
+                    inner_config,
+                },
+            )
+            .with_context(|| format!("Invalid verifying key for table {table}."))?;
+        fri_instances.push(fri_instance);
+        preprocessed_caps.push(preprocessed_cap);
+    }
+
+    let zero = builder.zero();
+    let groups = batch_fri_groups(&multi_proof.degree_bits);
+    let fri_openings = groups
+        .iter()
+        .map(|group| FriOpeningsTarget {
+            batches: group
+                .iter()
+                .flat_map(|&table| {
+                    multi_proof.stark_proofs[table]
+                        .openings
+                        .to_fri_openings(zero)
+                        .batches
+                })
+                .collect(),
+        })
+        .collect_vec();
+    let merkle_caps = groups
+        .iter()
+        .flatten()
+        .flat_map(|&table| {
+            let proof = &multi_proof.stark_proofs[table];
+            once(proof.trace_cap.clone())
+                .chain(proof.auxiliary_polys_cap.clone())
+                .chain(proof.quotient_polys_cap.clone())
+                .chain(preprocessed_caps[table].clone())
+        })
+        .collect_vec();
+    let fri_degree_bits = batch_fri_degree_bits(&groups, &multi_proof.degree_bits, inner_config);
+    with_context!(
+        builder,
+        "verify batched FRI proof",
+        builder.verify_batch_fri_proof::<C>(
+            &batch_fri_instances_target(&groups, fri_instances),
+            &fri_openings,
+            &fri_challenges,
+            &merkle_caps,
+            &multi_proof.opening_proof,
+            &batch_fri_params(
+                &inner_config.fri_config,
+                &fri_degree_bits,
+                inner_config.zero_knowledge
+            ),
+            &fri_degree_bits,
+        )
+    );
+
+    let ctl_zs_first = multi_proof
+        .stark_proofs
+        .each_ref()
+        .map(|p| p.openings.ctl_zs_first.clone().unwrap_or_default());
+    verify_cross_table_lookups_circuit::<F, D, N>(
+        builder,
+        cross_table_lookups,
+        ctl_zs_first,
+        &HashMap::new(),
+        inner_config,
+    );
+    Ok(())
+}
+
+/// Recursively verifies the openings of one table of a multi-STARK system, with
+/// [`verify_table_openings_circuit`].
+struct VerifyTableCircuit<'a, F, C, const D: usize>
+where
+    F: RichField + Extendable<D>,
+    C: GenericConfig<D, F = F>,
+{
+    builder: &'a mut CircuitBuilder<F, D>,
+    proof: &'a StarkTableProofTarget<D>,
+    degree_bits: usize,
+    verifying_key: &'a StarkVerifyingKey<F, C, D>,
+    preprocessed_cap: Option<&'a MerkleCapTarget>,
+    public_inputs: &'a [Target],
+    challenges: StarkTableChallengesTarget<D>,
+    ctl_vars: Option<&'a [CtlCheckVarsTarget<F, D>]>,
+    inner_config: &'a StarkConfig,
+}
+
+impl<F, C, const D: usize> StarkVisitor<F, D> for VerifyTableCircuit<'_, F, C, D>
+where
+    F: RichField + Extendable<D>,
+    C: GenericConfig<D, F = F>,
+    C::Hasher: AlgebraicHasher<F>,
+{
+    type Output = Result<FriInstanceInfoTarget<D>>;
+
+    fn visit<S: Stark<F, D>>(self, stark: &S) -> Self::Output {
+        self.verifying_key.check(stark, self.inner_config)?;
+        assert_eq!(self.public_inputs.len(), S::PUBLIC_INPUTS);
+        Ok(verify_table_openings_circuit::<F, S, D>(
+            self.builder,
+            stark,
+            self.proof,
+            self.degree_bits,
+            self.preprocessed_cap,
+            self.public_inputs,
+            self.challenges,
+            self.ctl_vars,
+            self.inner_config,
+        ))
+    }
+}
+
 /// Recursively verifies an inner STARK proof.
 pub fn verify_stark_proof_with_challenges_circuit<
     F: RichField + Extendable<D>,
@@ -77,6 +296,7 @@ pub fn verify_stark_proof_with_challenges_circuit<
     builder: &mut CircuitBuilder<F, D>,
     stark: &S,
     proof: &StarkProofTarget<D>,
+    preprocessed_cap: Option<&MerkleCapTarget>,
     public_inputs: &[Target],
     challenges: StarkProofChallengesTarget<D>,
     ctl_vars: Option<&[CtlCheckVarsTarget<F, D>]>,
@@ -84,9 +304,83 @@ pub fn verify_stark_proof_with_challenges_circuit<
 ) where
     C::Hasher: AlgebraicHasher<F>,
 {
-    check_lookup_options(stark, proof, &challenges).unwrap();
+    let degree_bits = proof.recover_degree_bits(inner_config);
+    let StarkProofTarget {
+        trace_cap,
+        auxiliary_polys_cap,
+        quotient_polys_cap,
+        openings,
+        opening_proof,
+    } = proof;
+    let table_proof = StarkTableProofTarget {
+        trace_cap: trace_cap.clone(),
+        auxiliary_polys_cap: auxiliary_polys_cap.clone(),
+        quotient_polys_cap: quotient_polys_cap.clone(),
+        openings: openings.clone(),
+    };
+    let StarkProofChallengesTarget {
+        lookup_challenge_set,
+        stark_alphas,
+        stark_zeta,
+        fri_challenges,
+    } = challenges;
+    let table_challenges = StarkTableChallengesTarget {
+        lookup_challenge_set,
+        stark_alphas,
+        stark_zeta,
+    };
+
+    let fri_instance = verify_table_openings_circuit::<F, S, D>(
+        builder,
+        stark,
+        &table_proof,
+        degree_bits,
+        preprocessed_cap,
+        public_inputs,
+        table_challenges,
+        ctl_vars,
+        inner_config,
+    );
+
+    let merkle_caps = once(trace_cap.clone())
+        .chain(auxiliary_polys_cap.clone())
+        .chain(quotient_polys_cap.clone())
+        .chain(preprocessed_cap.cloned())
+        .collect_vec();
 
     let zero = builder.zero();
+    builder.verify_fri_proof::<C>(
+        &fri_instance,
+        &openings.to_fri_openings(zero),
+        &fri_challenges,
+        &merkle_caps,
+        opening_proof,
+        &inner_config.fri_params(degree_bits),
+    );
+}
+
+/// Checks in a circuit that the openings of a STARK whose trace has `2^degree_bits` rows satisfy
+/// its constraints at `zeta`. Returns the FRI instance with which the openings remain to be checked
+/// against the Merkle caps.
+#[allow(clippy::too_many_arguments)]
+fn verify_table_openings_circuit<F: RichField + Extendable<D>, S: Stark<F, D>, const D: usize>(
+    builder: &mut CircuitBuilder<F, D>,
+    stark: &S,
+    proof: &StarkTableProofTarget<D>,
+    degree_bits: usize,
+    preprocessed_cap: Option<&MerkleCapTarget>,
+    public_inputs: &[Target],
+    challenges: StarkTableChallengesTarget<D>,
+    ctl_vars: Option<&[CtlCheckVarsTarget<F, D>]>,
+    inner_config: &StarkConfig,
+) -> FriInstanceInfoTarget<D> {
+    check_lookup_options(stark, proof, &challenges).unwrap();
+    assert_eq!(
+        preprocessed_cap.is_some(),
+        S::PREPROCESSED_COLUMNS > 0,
+        "A preprocessed cap is needed if and only if the STARK has preprocessed columns."
+    );
+
     let one = builder.one_extension();
 
     let num_ctl_polys = ctl_vars
@@ -96,22 +390,26 @@ pub fn verify_stark_proof_with_challenges_circuit<
     let StarkOpeningSetTarget {
         local_values,
         next_values,
+        additional_values,
         auxiliary_polys,
         auxiliary_polys_next,
         ctl_zs_first,
         quotient_polys,
     } = &proof.openings;
 
-    let vars = S::EvaluationFrameTarget::from_values(
-        local_values,
-        next_values,
+    let rows = [local_values, next_values]
+        .into_iter()
+        .chain(additional_values)
+        .map(Vec::as_slice)
+        .collect::<Vec<_>>();
+    let vars = S::EvaluationFrameTarget::from_rows(
+        &rows,
         &public_inputs
             .iter()
             .map(|&t| builder.convert_to_ext(t))
             .collect::<Vec<_>>(),
     );
 
-    let degree_bits = proof.recover_degree_bits(inner_config);
     let zeta_pow_deg = builder.exp_power_of_2_extension(challenges.stark_zeta, degree_bits);
     let z_h_zeta = builder.sub_extension(zeta_pow_deg, one);
     let (l_0, l_last) =
@@ -161,9 +459,14 @@ pub fn verify_stark_proof_with_challenges_circuit<
     let vanishing_polys_zeta = consumer.accumulators();
 
     // Check each polynomial identity, of the form `vanishing(x) = Z_H(x) quotient(x)`, at zeta.
-    let mut scale = ReducingFactorTarget::new(zeta_pow_deg);
+    // The quotient chunks have the degree bound of the committed polynomials, which is doubled in
+    // zero-knowledge mode.
+    let zeta_pow_chunk =
+        builder.exp_power_of_2_extension(zeta_pow_deg, inner_config.blinding_degree_bits());
+    let mut scale = ReducingFactorTarget::new(zeta_pow_chunk);
     if let Some(quotient_polys) = quotient_polys {
-        for (i, chunk) in quotient_polys
+        // The openings of the masking polynomials, if any, come last and are ignored.
+        for (i, chunk) in quotient_polys[..stark.num_quotient_polys(inner_config)]
             .chunks(stark.quotient_degree_factor())
             .enumerate()
         {
@@ -173,27 +476,14 @@ pub fn verify_stark_proof_with_challenges_circuit<
         }
     }
 
-    let merkle_caps = once(proof.trace_cap.clone())
-        .chain(proof.auxiliary_polys_cap.clone())
-        .chain(proof.quotient_polys_cap.clone())
-        .collect_vec();
-
-    let fri_instance = stark.fri_instance_target(
+    stark.fri_instance_target(
         builder,
         challenges.stark_zeta,
         F::primitive_root_of_unity(degree_bits),
         num_ctl_polys,
         ctl_zs_first.as_ref().map_or(0, |c| c.len()),
         inner_config,
-    );
-    builder.verify_fri_proof::<C>(
-        &fri_instance,
-        &proof.openings.to_fri_openings(zero),
-        &challenges.fri_challenges,
-        &merkle_caps,
-        &proof.opening_proof,
-        &inner_config.fri_params(degree_bits),
-    );
+    )
 }
 
 fn eval_l_0_and_l_last_circuit<F: RichField + Extendable<D>, const D: usize>(
@@ -243,6 +533,106 @@ pub fn add_virtual_stark_proof_with_pis<
     }
 }
 
+/// Adds a new `MultiProofWithPublicInputsTarget` to this circuit, for a multi-STARK system whose
+/// tables have the given degrees. Returns an error if the cross-table lookups of the system are
+/// inconsistent with its tables.
+pub fn add_virtual_multi_proof_with_pis<F, A, const D: usize, const N: usize>(
+    builder: &mut CircuitBuilder<F, D>,
+    all_stark: &A,
+    config: &StarkConfig,
+    degree_bits: &[usize; N],
+) -> Result<MultiProofWithPublicInputsTarget<D, N>>
+where
+    F: RichField + Extendable<D>,
+    A: AllStark<F, D, N>,
+{
+    let cross_table_lookups = all_stark.cross_table_lookups();
+    let tables = table_infos(all_stark, config);
+    let max_constraint_degree = check_cross_table_lookups(&cross_table_lookups, &tables)?;
+    let num_ctl_helpers_zs = num_ctl_helpers_zs_by_table::<F, N>(
+        &cross_table_lookups,
+        config.num_challenges,
+        max_constraint_degree,
+    );
+
+    let mut stark_proofs = Vec::with_capacity(N);
+    let mut public_inputs = Vec::with_capacity(N);
+    let mut num_leaves_per_oracle = Vec::with_capacity(N);
+    for (table, (num_ctl_helpers, num_ctl_zs)) in num_ctl_helpers_zs.into_iter().enumerate() {
+        let (proof, pis, num_leaves) = all_stark.visit(
+            table,
+            AddVirtualTableProofWithPis {
+                builder: &mut *builder,
+                config,
+                num_ctl_helper_zs: num_ctl_helpers + num_ctl_zs,
+                num_ctl_zs,
+            },
+        );
+        stark_proofs.push(proof);
+        public_inputs.push(pis);
+        num_leaves_per_oracle.push(num_leaves);
+    }
+
+    let groups = batch_fri_groups(degree_bits);
+    let fri_degree_bits = batch_fri_degree_bits(&groups, degree_bits, config);
+    let num_leaves_per_oracle = groups
+        .iter()
+        .map(|group| {
+            group
+                .iter()
+                .flat_map(|&table| num_leaves_per_oracle[table].iter().copied())
+                .collect_vec()
+        })
+        .collect_vec();
+    let opening_proof = builder.add_virtual_batch_fri_proof(
+        &num_leaves_per_oracle,
+        &batch_fri_params(&config.fri_config, &fri_degree_bits, config.zero_knowledge),
+        &fri_degree_bits,
+    );
+
+    Ok(MultiProofWithPublicInputsTarget {
+        proof: MultiProofTarget {
+            stark_proofs: stark_proofs
+                .try_into()
+                .unwrap_or_else(|_| unreachable!("There is one proof per table.")),
+            degree_bits: *degree_bits,
+            opening_proof,
+        },
+        public_inputs: public_inputs
+            .try_into()
+            .unwrap_or_else(|_| unreachable!("There are public inputs for each table.")),
+    })
+}
+
+/// Adds the Merkle caps, openings and public inputs of one table of a multi-STARK system to a
+/// circuit, and returns them along with the number of leaves of each of its oracles.
+struct AddVirtualTableProofWithPis<'a, F: RichField + Extendable<D>, const D: usize> {
+    builder: &'a mut CircuitBuilder<F, D>,
+    config: &'a StarkConfig,
+    num_ctl_helper_zs: usize,
+    num_ctl_zs: usize,
+}
+
+impl<F: RichField + Extendable<D>, const D: usize> StarkVisitor<F, D>
+    for AddVirtualTableProofWithPis<'_, F, D>
+{
+    type Output = (StarkTableProofTarget<D>, Vec<Target>, Vec<usize>);
+
+    fn visit<S: Stark<F, D>>(self, stark: &S) -> Self::Output {
+        let proof = add_virtual_stark_table_proof::<F, S, D>(
+            self.builder,
+            stark,
+            self.config,
+            self.num_ctl_helper_zs,
+            self.num_ctl_zs,
+        );
+        let public_inputs = self.builder.add_virtual_targets(S::PUBLIC_INPUTS);
+        let num_leaves_per_oracle =
+            num_leaves_per_oracle::<F, S, D>(stark, self.config, self.num_ctl_helper_zs);
+        (proof, public_inputs, num_leaves_per_oracle)
+    }
+}
+
 /// Adds a new `StarkProofTarget` to this circuit.
 pub fn add_virtual_stark_proof<F: RichField + Extendable<D>, S: Stark<F, D>, const D: usize>(
     builder: &mut CircuitBuilder<F, D>,
@@ -252,19 +642,61 @@ pub fn add_virtual_stark_proof<F: RichField + Extendable<D>, S: Stark<F, D>, con
     num_ctl_helper_zs: usize,
     num_ctl_zs: usize,
 ) -> StarkProofTarget<D> {
-    let fri_params = config.fri_params(degree_bits);
-    let cap_height = fri_params.config.cap_height;
+    let StarkTableProofTarget {
+        trace_cap,
+        auxiliary_polys_cap,
+        quotient_polys_cap,
+        openings,
+    } = add_virtual_stark_table_proof::<F, S, D>(
+        builder,
+        stark,
+        config,
+        num_ctl_helper_zs,
+        num_ctl_zs,
+    );
+
+    StarkProofTarget {
+        trace_cap,
+        auxiliary_polys_cap,
+        quotient_polys_cap,
+        openings,
+        opening_proof: builder.add_virtual_fri_proof(
+            &num_leaves_per_oracle::<F, S, D>(stark, config, num_ctl_helper_zs),
+            &config.fri_params(degree_bits),
+        ),
+    }
+}
 
-    let num_leaves_per_oracle = once(S::COLUMNS)
+/// Returns the number of leaves of each oracle of the FRI instance of a STARK proof.
+fn num_leaves_per_oracle<F: RichField + Extendable<D>, S: Stark<F, D>, const D: usize>(
+    stark: &S,
+    config: &StarkConfig,
+    num_ctl_helper_zs: usize,
+) -> Vec<usize> {
+    // The preprocessed polynomials are never blinded.
+    once(S::TRACE_COLUMNS)
         .chain(
             (stark.uses_lookups() || stark.requires_ctls())
                 .then(|| stark.num_lookup_helper_columns(config) + num_ctl_helper_zs),
         )
         .chain(
             (stark.quotient_degree_factor() > 0)
-                .then(|| stark.quotient_degree_factor() * config.num_challenges),
+                .then(|| stark.num_quotient_polys(config) + config.num_masking_polys()),
         )
-        .collect_vec();
+        .map(|num_polys| num_polys + salt_size(config.zero_knowledge))
+        .chain((S::PREPROCESSED_COLUMNS > 0).then_some(S::PREPROCESSED_COLUMNS))
+        .collect()
+}
+
+/// Adds the Merkle caps and openings of a STARK proof to a circuit, without its FRI proof.
+fn add_virtual_stark_table_proof<F: RichField + Extendable<D>, S: Stark<F, D>, const D: usize>(
+    builder: &mut CircuitBuilder<F, D>,
+    stark: &S,
+    config: &StarkConfig,
+    num_ctl_helper_zs: usize,
+    num_ctl_zs: usize,
+) -> StarkTableProofTarget<D> {
+    let cap_height = config.fri_config.cap_height;
 
     let auxiliary_polys_cap = (stark.uses_lookups() || stark.requires_ctls())
         .then(|| builder.add_virtual_cap(cap_height));
@@ -272,7 +704,7 @@ pub fn add_virtual_stark_proof<F: RichField + Extendable<D>, S: Stark<F, D>, con
     let quotient_polys_cap =
         (stark.constraint_degree() > 0).then(|| builder.add_virtual_cap(cap_height));
 
-    StarkProofTarget {
+    StarkTableProofTarget {
         trace_cap: builder.add_virtual_cap(cap_height),
         auxiliary_polys_cap,
         quotient_polys_cap,
@@ -283,7 +715,6 @@ pub fn add_virtual_stark_proof<F: RichField + Extendable<D>, S: Stark<F, D>, con
             num_ctl_zs,
             config,
         ),
-        opening_proof: builder.add_virtual_fri_proof(&num_leaves_per_oracle, &fri_params),
     }
 }
 
@@ -294,9 +725,14 @@ fn add_virtual_stark_opening_set<F: RichField + Extendable<D>, S: Stark<F, D>, c
     num_ctl_zs: usize,
     config: &StarkConfig,
 ) -> StarkOpeningSetTarget<D> {
+    let () = S::CHECK_ROW_OFFSETS;
     StarkOpeningSetTarget {
         local_values: builder.add_virtual_extension_targets(S::COLUMNS),
         next_values: builder.add_virtual_extension_targets(S::COLUMNS),
+        additional_values: S::ROW_OFFSETS[2..]
+            .iter()
+            .map(|_| builder.add_virtual_extension_targets(S::COLUMNS))
+            .collect(),
         auxiliary_polys: (stark.uses_lookups() || stark.requires_ctls()).then(|| {
             builder.add_virtual_extension_targets(
                 stark.num_lookup_helper_columns(config) + num_ctl_helper_zs,
@@ -312,7 +748,7 @@ fn add_virtual_stark_opening_set<F: RichField + Extendable<D>, S: Stark<F, D>, c
             .then(|| builder.add_virtual_targets(num_ctl_zs)),
         quotient_polys: (stark.constraint_degree() > 0).then(|| {
             builder.add_virtual_extension_targets(
-                stark.quotient_degree_factor() * config.num_challenges,
+                stark.num_quotient_polys(config) + config.num_masking_polys(),
             )
         }),
     }
@@ -347,6 +783,61 @@ pub fn set_stark_proof_with_pis_target<F, C: GenericConfig<D, F = F>, W, const D
     set_stark_proof_target(witness, pt, proof, zero);
 }
 
+/// Set the targets in a [`MultiProofWithPublicInputsTarget`] to their corresponding values in a
+/// [`MultiProofWithPublicInputs`].
+pub fn set_multi_proof_with_pis_target<
+    F,
+    C: GenericConfig<D, F = F>,
+    W,
+    const D: usize,
+    const N: usize,
+>(
+    witness: &mut W,
+    multi_proof_with_pis_target: &MultiProofWithPublicInputsTarget<D, N>,
+    multi_proof_with_pis: &MultiProofWithPublicInputs<F, C, D, N>,
+    zero: Target,
+) where
+    F: RichField + Extendable<D>,
+    C::Hasher: AlgebraicHasher<F>,
+    W: WitnessWrite<F>,
+{
+    let MultiProofWithPublicInputs {
+        proof,
+        public_inputs,
+    } = multi_proof_with_pis;
+    let MultiProofWithPublicInputsTarget {
+        proof: pt,
+        public_inputs: pi_targets,
+    } = multi_proof_with_pis_target;
+
+    for (pi_targets, public_inputs) in pi_targets.iter().zip(public_inputs) {
+        for (&pi_t, &pi) in pi_targets.iter().zip_eq(public_inputs) {
+            witness.set_target(pi_t, pi);
+        }
+    }
+    for (proof_target, proof) in pt.stark_proofs.iter().zip(&proof.stark_proofs) {
+        let proof = &proof.proof;
+        set_caps_and_openings_target(
+            witness,
+            [
+                (Some(&proof_target.trace_cap), Some(&proof.trace_cap)),
+                (
+                    proof_target.auxiliary_polys_cap.as_ref(),
+                    proof.auxiliary_polys_cap.as_ref(),
+                ),
+                (
+                    proof_target.quotient_polys_cap.as_ref(),
+                    proof.quotient_polys_cap.as_ref(),
+                ),
+            ],
+            &proof_target.openings,
+            &proof.openings,
+            zero,
+        );
+    }
+    set_fri_proof_target(witness, &pt.opening_proof, &proof.opening_proof);
+}
+
 /// Set the targets in a [`StarkProofTarget`] to their corresponding values in a
 /// [`StarkProof`].
 pub fn set_stark_proof_target<F, C: GenericConfig<D, F = F>, W, const D: usize>(
@@ -359,34 +850,57 @@ pub fn set_stark_proof_target<F, C: GenericConfig<D, F = F>, W, const D: usize>(
     C::Hasher: AlgebraicHasher<F>,
     W: WitnessWrite<F>,
 {
-    witness.set_cap_target(&proof_target.trace_cap, &proof.trace_cap);
-    if let (Some(quotient_polys_cap_target), Some(quotient_polys_cap)) =
-        (&proof_target.quotient_polys_cap, &proof.quotient_polys_cap)
-    {
-        witness.set_cap_target(quotient_polys_cap_target, quotient_polys_cap);
-    }
-
-    witness.set_fri_openings(
-        &proof_target.openings.to_fri_openings(zero),
-        &proof.openings.to_fri_openings(),
+    set_caps_and_openings_target(
+        witness,
+        [
+            (Some(&proof_target.trace_cap), Some(&proof.trace_cap)),
+            (
+                proof_target.auxiliary_polys_cap.as_ref(),
+                proof.auxiliary_polys_cap.as_ref(),
+            ),
+            (
+                proof_target.quotient_polys_cap.as_ref(),
+                proof.quotient_polys_cap.as_ref(),
+            ),
+        ],
+        &proof_target.openings,
+        &proof.openings,
+        zero,
     );
 
-    if let (Some(auxiliary_polys_cap_target), Some(auxiliary_polys_cap)) = (
-        &proof_target.auxiliary_polys_cap,
-        &proof.auxiliary_polys_cap,
-    ) {
-        witness.set_cap_target(auxiliary_polys_cap_target, auxiliary_polys_cap);
+    set_fri_proof_target(witness, &proof_target.opening_proof, &proof.opening_proof);
+}
+
+/// Sets the targets of the Merkle caps of a STARK proof which are present, and of its openings.
+fn set_caps_and_openings_target<F, H, W, const D: usize>(
+    witness: &mut W,
+    caps: [(Option<&MerkleCapTarget>, Option<&MerkleCap<F, H>>); 3],
+    openings_target: &StarkOpeningSetTarget<D>,
+    openings: &StarkOpeningSet<F, D>,
+    zero: Target,
+) where
+    F: RichField + Extendable<D>,
+    H: AlgebraicHasher<F>,
+    W: WitnessWrite<F>,
+{
+    for (cap_target, cap) in caps {
+        if let (Some(cap_target), Some(cap)) = (cap_target, cap) {
+            witness.set_cap_target(cap_target, cap);
+        }
     }
 
-    set_fri_proof_target(witness, &proof_target.opening_proof, &proof.opening_proof);
+    witness.set_fri_openings(
+        &openings_target.to_fri_openings(zero),
+        &openings.to_fri_openings(),
+    );
 }
 
 /// Utility function to check that all lookups data wrapped in `Option`s are `Some` iff
 /// the STARK uses a permutation argument.
 fn check_lookup_options<F: RichField + Extendable<D>, S: Stark<F, D>, const D: usize>(
     stark: &S,
-    proof: &StarkProofTarget<D>,
-    challenges: &StarkProofChallengesTarget<D>,
+    proof: &StarkTableProofTarget<D>,
+    challenges: &StarkTableChallengesTarget<D>,
 ) -> Result<()> {
     let options_is_some = [
         proof.auxiliary_polys_cap.is_some(),
@@ -6,6 +6,7 @@ use alloc::{vec, vec::Vec};
 
 use plonky2::field::extension::{Extendable, FieldExtension};
 use plonky2::field::packed::PackedField;
+use plonky2::field::polynomial::PolynomialValues;
 use plonky2::field::types::Field;
 use plonky2::fri::structure::{
     FriBatchInfo, FriBatchInfoTarget, FriInstanceInfo, FriInstanceInfoTarget, FriOracleInfo,
@@ -22,10 +23,39 @@ use crate::lookup::Lookup;
 
 /// Represents a STARK system.
 pub trait Stark<F: RichField + Extendable<D>, const D: usize>: Sync {
-    /// The total number of columns in the trace.
+    /// An identifier of the constraints of this STARK, which goes into the
+    /// [circuit digest](crate::verifying_key::StarkVerifyingKey::circuit_digest) of its verifying
+    /// key. STARKs with different constraints must have different identifiers, as the rest of the
+    /// verifying key only describes their shape and preprocessed columns.
+    ///
+    /// Uniqueness is the responsibility of the implementor: it is not checked, and the empty
+    /// default is shared by every STARK not overriding it, so proofs of such STARKs are only
+    /// bound to their shape.
+    const AIR_ID: &'static str = "";
+    /// The total number of columns in the evaluation frames, i.e. the trace columns followed by
+    /// the preprocessed columns.
     const COLUMNS: usize = Self::EvaluationFrameTarget::COLUMNS;
+    /// The number of preprocessed columns, which are fixed by the STARK rather than by the
+    /// witness, and given by [`Stark::preprocessed_columns`].
+    const PREPROCESSED_COLUMNS: usize = 0;
+    /// The number of columns in the trace, i.e. of witness columns.
+    const TRACE_COLUMNS: usize = Self::COLUMNS - Self::PREPROCESSED_COLUMNS;
     /// The total number of public inputs.
     const PUBLIC_INPUTS: usize = Self::EvaluationFrameTarget::PUBLIC_INPUTS;
+    /// The offsets, relative to the current row, of the rows viewed by the evaluation frames.
+    /// The first two must be `0` and `1`, i.e. the current and next rows, and there must be as
+    /// many offsets as rows in the evaluation frames.
+    ///
+    /// Like the next row, rows at further offsets wrap around the trace, so constraints involving
+    /// them should be disabled by the STARK itself on the last rows.
+    const ROW_OFFSETS: &'static [usize] = &[0, 1];
+    /// Fails to compile if [`Self::ROW_OFFSETS`] does not start with the current and next rows.
+    /// It is evaluated wherever the row offsets are used, and must not be overridden.
+    #[doc(hidden)]
+    const CHECK_ROW_OFFSETS: () = assert!(
+        Self::ROW_OFFSETS.len() >= 2 && Self::ROW_OFFSETS[0] == 0 && Self::ROW_OFFSETS[1] == 1,
+        "The first two row offsets must be those of the current and next rows."
+    );
 
     /// This is used to evaluate constraints natively.
     type EvaluationFrame<FE, P, const D2: usize>: StarkEvaluationFrame<P, FE>
@@ -106,10 +136,10 @@ pub trait Stark<F: RichField + Extendable<D>, const D: usize>: Sync {
         config: &StarkConfig,
     ) -> FriInstanceInfo<F, D> {
         let mut oracles = vec![];
-        let trace_info = FriPolynomialInfo::from_range(oracles.len(), 0..Self::COLUMNS);
+        let trace_info = FriPolynomialInfo::from_range(oracles.len(), 0..Self::TRACE_COLUMNS);
         oracles.push(FriOracleInfo {
-            num_polys: Self::COLUMNS,
-            blinding: false,
+            num_polys: Self::TRACE_COLUMNS,
+            blinding: config.zero_knowledge,
         });
 
         let num_lookup_columns = self.num_lookup_helper_columns(config);
@@ -118,7 +148,7 @@ pub trait Stark<F: RichField + Extendable<D>, const D: usize>: Sync {
             let aux_polys = FriPolynomialInfo::from_range(oracles.len(), 0..num_auxiliary_polys);
             oracles.push(FriOracleInfo {
                 num_polys: num_auxiliary_polys,
-                blinding: false,
+                blinding: config.zero_knowledge,
             });
             aux_polys
         } else {
@@ -127,21 +157,35 @@ pub trait Stark<F: RichField + Extendable<D>, const D: usize>: Sync {
 
         let num_quotient_polys = self.num_quotient_polys(config);
         let quotient_info = if num_quotient_polys > 0 {
-            let quotient_polys =
-                FriPolynomialInfo::from_range(oracles.len(), 0..num_quotient_polys);
+            // The quotient batch also holds the masking polynomials, if any.
+            let num_polys = num_quotient_polys + config.num_masking_polys();
+            let quotient_polys = FriPolynomialInfo::from_range(oracles.len(), 0..num_polys);
             oracles.push(FriOracleInfo {
-                num_polys: num_quotient_polys,
-                blinding: false,
+                num_polys,
+                blinding: config.zero_knowledge,
             });
             quotient_polys
         } else {
             vec![]
         };
 
+        // The preprocessed columns come last in the frames, and their oracle after all others.
+        let columns_info = if Self::PREPROCESSED_COLUMNS > 0 {
+            let preprocessed_info =
+                FriPolynomialInfo::from_range(oracles.len(), 0..Self::PREPROCESSED_COLUMNS);
+            oracles.push(FriOracleInfo {
+                num_polys: Self::PREPROCESSED_COLUMNS,
+                blinding: false,
+            });
+            [trace_info, preprocessed_info].concat()
+        } else {
+            trace_info
+        };
+
         let zeta_batch = FriBatchInfo {
             point: zeta,
             polynomials: [
-                trace_info.clone(),
+                columns_info.clone(),
                 auxiliary_polys_info.clone(),
                 quotient_info,
             ]
@@ -149,11 +193,20 @@ pub trait Stark<F: RichField + Extendable<D>, const D: usize>: Sync {
         };
         let zeta_next_batch = FriBatchInfo {
             point: zeta.scalar_mul(g),
-            polynomials: [trace_info, auxiliary_polys_info].concat(),
+            polynomials: [columns_info.clone(), auxiliary_polys_info].concat(),
         };
 
         let mut batches = vec![zeta_batch, zeta_next_batch];
 
+        // The trace is also opened at `g^offset * zeta` for the additional rows of the frames.
+        let () = Self::CHECK_ROW_OFFSETS;
+        for &offset in &Self::ROW_OFFSETS[2..] {
+            batches.push(FriBatchInfo {
+                point: zeta.scalar_mul(g.exp_u64(offset as u64)),
+                polynomials: columns_info.clone(),
+            });
+        }
+
         if self.requires_ctls() {
             let ctl_zs_info = FriPolynomialInfo::from_range(
                 1, // auxiliary oracle index
@@ -181,10 +234,10 @@ pub trait Stark<F: RichField + Extendable<D>, const D: usize>: Sync {
         config: &StarkConfig,
     ) -> FriInstanceInfoTarget<D> {
         let mut oracles = vec![];
-        let trace_info = FriPolynomialInfo::from_range(oracles.len(), 0..Self::COLUMNS);
+        let trace_info = FriPolynomialInfo::from_range(oracles.len(), 0..Self::TRACE_COLUMNS);
         oracles.push(FriOracleInfo {
-            num_polys: Self::COLUMNS,
-            blinding: false,
+            num_polys: Self::TRACE_COLUMNS,
+            blinding: config.zero_knowledge,
         });
 
         let num_lookup_columns = self.num_lookup_helper_columns(config);
@@ -193,7 +246,7 @@ pub trait Stark<F: RichField + Extendable<D>, const D: usize>: Sync {
             let aux_polys = FriPolynomialInfo::from_range(oracles.len(), 0..num_auxiliary_polys);
             oracles.push(FriOracleInfo {
                 num_polys: num_auxiliary_polys,
-                blinding: false,
+                blinding: config.zero_knowledge,
             });
             aux_polys
         } else {
@@ -202,21 +255,35 @@ pub trait Stark<F: RichField + Extendable<D>, const D: usize>: Sync {
 
         let num_quotient_polys = self.num_quotient_polys(config);
         let quotient_info = if num_quotient_polys > 0 {
-            let quotient_polys =
-                FriPolynomialInfo::from_range(oracles.len(), 0..num_quotient_polys);
+            // The quotient batch also holds the masking polynomials, if any.
+            let num_polys = num_quotient_polys + config.num_masking_polys();
+            let quotient_polys = FriPolynomialInfo::from_range(oracles.len(), 0..num_polys);
             oracles.push(FriOracleInfo {
-                num_polys: num_quotient_polys,
-                blinding: false,
+                num_polys,
+                blinding: config.zero_knowledge,
             });
             quotient_polys
         } else {
             vec![]
         };
 
+        // The preprocessed columns come last in the frames, and their oracle after all others.
+        let columns_info = if Self::PREPROCESSED_COLUMNS > 0 {
+            let preprocessed_info =
+                FriPolynomialInfo::from_range(oracles.len(), 0..Self::PREPROCESSED_COLUMNS);
+            oracles.push(FriOracleInfo {
+                num_polys: Self::PREPROCESSED_COLUMNS,
+                blinding: false,
+            });
+            [trace_info, preprocessed_info].concat()
+        } else {
+            trace_info
+        };
+
         let zeta_batch = FriBatchInfoTarget {
             point: zeta,
             polynomials: [
-                trace_info.clone(),
+                columns_info.clone(),
                 auxiliary_polys_info.clone(),
                 quotient_info,
             ]
@@ -225,11 +292,20 @@ pub trait Stark<F: RichField + Extendable<D>, const D: usize>: Sync {
         let zeta_next = builder.mul_const_extension(g, zeta);
         let zeta_next_batch = FriBatchInfoTarget {
             point: zeta_next,
-            polynomials: [trace_info, auxiliary_polys_info].concat(),
+            polynomials: [columns_info.clone(), auxiliary_polys_info].concat(),
         };
 
         let mut batches = vec![zeta_batch, zeta_next_batch];
 
+        // The trace is also opened at `g^offset * zeta` for the additional rows of the frames.
+        let () = Self::CHECK_ROW_OFFSETS;
+        for &offset in &Self::ROW_OFFSETS[2..] {
+            batches.push(FriBatchInfoTarget {
+                point: builder.mul_const_extension(g.exp_u64(offset as u64), zeta),
+                polynomials: columns_info.clone(),
+            });
+        }
+
         if self.requires_ctls() {
             let ctl_zs_info = FriPolynomialInfo::from_range(
                 1, // auxiliary oracle index
@@ -246,6 +322,13 @@ pub trait Stark<F: RichField + Extendable<D>, const D: usize>: Sync {
         FriInstanceInfoTarget { oracles, batches }
     }
 
+    /// Outputs the values of the preprocessed columns, which must have as many rows as the traces
+    /// of this STARK. These columns are committed once, and their Merkle cap is part of what a
+    /// verifier needs to know about the STARK.
+    fn preprocessed_columns(&self) -> Vec<PolynomialValues<F>> {
+        vec![]
+    }
+
     /// Outputs all the [`Lookup`] this STARK table needs to perform across its columns.
     fn lookups(&self) -> Vec<Lookup<F>> {
         vec![]
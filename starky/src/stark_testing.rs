@@ -40,11 +40,11 @@ pub fn test_stark_low_degree<F: RichField + Extendable<D>, S: Stark<F, D>, const
     let alpha = F::rand();
     let constraint_evals = (0..size)
         .map(|i| {
-            let vars = S::EvaluationFrame::from_values(
-                &trace_ldes[i],
-                &trace_ldes[(i + (1 << rate_bits)) % size],
-                &public_inputs,
-            );
+            let rows = S::ROW_OFFSETS
+                .iter()
+                .map(|&offset| trace_ldes[(i + (offset << rate_bits)) % size].as_slice())
+                .collect::<Vec<_>>();
+            let vars = S::EvaluationFrame::from_rows(&rows, &public_inputs);
 
             let mut consumer = ConstraintConsumer::<F>::new(
                 vec![alpha],
@@ -82,9 +82,12 @@ pub fn test_stark_circuit_constraints<
     stark: S,
 ) -> Result<()> {
     // Compute native constraint evaluation on random values.
-    let vars = S::EvaluationFrame::from_values(
-        &F::Extension::rand_vec(S::COLUMNS),
-        &F::Extension::rand_vec(S::COLUMNS),
+    let rows = S::ROW_OFFSETS
+        .iter()
+        .map(|_| F::Extension::rand_vec(S::COLUMNS))
+        .collect::<Vec<_>>();
+    let vars = S::EvaluationFrame::from_rows(
+        &rows.iter().map(Vec::as_slice).collect::<Vec<_>>(),
         &F::Extension::rand_vec(S::PUBLIC_INPUTS),
     );
     let alphas = F::rand_vec(1);
@@ -108,10 +111,13 @@ pub fn test_stark_circuit_constraints<
     let mut builder = CircuitBuilder::<F, D>::new(circuit_config);
     let mut pw = PartialWitness::<F>::new();
 
-    let locals_t = builder.add_virtual_extension_targets(S::COLUMNS);
-    pw.set_extension_targets(&locals_t, vars.get_local_values());
-    let nexts_t = builder.add_virtual_extension_targets(S::COLUMNS);
-    pw.set_extension_targets(&nexts_t, vars.get_next_values());
+    let rows_t = (0..S::ROW_OFFSETS.len())
+        .map(|i| {
+            let row_t = builder.add_virtual_extension_targets(S::COLUMNS);
+            pw.set_extension_targets(&row_t, vars.get_row_values(i));
+            row_t
+        })
+        .collect::<Vec<_>>();
     let pis_t = builder.add_virtual_extension_targets(S::PUBLIC_INPUTS);
     pw.set_extension_targets(&pis_t, vars.get_public_inputs());
     let alphas_t = builder.add_virtual_targets(1);
@@ -123,7 +129,10 @@ pub fn test_stark_circuit_constraints<
     let lagrange_last_t = builder.add_virtual_extension_target();
     pw.set_extension_target(lagrange_last_t, lagrange_last);
 
-    let vars = S::EvaluationFrameTarget::from_values(&locals_t, &nexts_t, &pis_t);
+    let vars = S::EvaluationFrameTarget::from_rows(
+        &rows_t.iter().map(Vec::as_slice).collect::<Vec<_>>(),
+        &pis_t,
+    );
     let mut consumer = RecursiveConstraintConsumer::<F, D>::new(
         builder.zero_extension(),
         alphas_t,
@@ -138,7 +147,8 @@ pub fn test_stark_circuit_constraints<
 
     let data = builder.build::<C>();
     let proof = data.prove(pw)?;
-    data.verify(proof)
+    data.verify(proof)?;
+    Ok(())
 }
 
 fn random_low_degree_matrix<F: Field>(num_polys: usize, rate_bits: usize) -> Vec<Vec<F>> {
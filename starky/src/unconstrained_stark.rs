@@ -45,7 +45,10 @@ const COLUMNS: usize = 2;
 const PUBLIC_INPUTS: usize = 0;
 
 impl<F: RichField + Extendable<D>, const D: usize> Stark<F, D> for UnconstrainedStark<F, D> {
-    type EvaluationFrame<FE, P, const D2: usize> = StarkFrame<P, P::Scalar, COLUMNS, PUBLIC_INPUTS>
+    const AIR_ID: &'static str = "starky/unconstrained";
+
+    type EvaluationFrame<FE, P, const D2: usize>
+        = StarkFrame<P, P::Scalar, COLUMNS, PUBLIC_INPUTS>
     where
         FE: FieldExtension<D2, BaseField = F>,
         P: PackedField<Scalar = FE>;
@@ -100,6 +103,7 @@ mod tests {
     use crate::stark_testing::{test_stark_circuit_constraints, test_stark_low_degree};
     use crate::unconstrained_stark::UnconstrainedStark;
     use crate::verifier::verify_stark_proof;
+    use crate::verifying_key::StarkVerifyingKey;
 
     #[test]
     fn test_unconstrained_stark() -> Result<()> {
@@ -115,7 +119,8 @@ mod tests {
         let trace = stark.generate_trace();
         let proof = prove::<F, C, S, D>(stark, &config, trace, &[], &mut TimingTree::default())?;
 
-        verify_stark_proof(stark, proof, &config)
+        verify_stark_proof(stark, proof, &StarkVerifyingKey::new(&stark, &config))?;
+        Ok(())
     }
 
     #[test]
@@ -156,7 +161,11 @@ mod tests {
         let stark = S::new(num_rows);
         let trace = stark.generate_trace();
         let proof = prove::<F, C, S, D>(stark, &config, trace, &[], &mut TimingTree::default())?;
-        verify_stark_proof(stark, proof.clone(), &config)?;
+        verify_stark_proof(
+            stark,
+            proof.clone(),
+            &StarkVerifyingKey::new(&stark, &config),
+        )?;
 
         recursive_proof::<F, C, S, C, D>(stark, proof, &config, true)
     }
@@ -184,7 +193,12 @@ mod tests {
             add_virtual_stark_proof_with_pis(&mut builder, &stark, inner_config, degree_bits, 0, 0);
         set_stark_proof_with_pis_target(&mut pw, &pt, &inner_proof, builder.zero());
 
-        verify_stark_proof_circuit::<F, InnerC, S, D>(&mut builder, stark, pt, inner_config);
+        verify_stark_proof_circuit::<F, InnerC, S, D>(
+            &mut builder,
+            stark,
+            pt,
+            &StarkVerifyingKey::new(&stark, inner_config),
+        );
 
         if print_gate_counts {
             builder.print_gate_counts(0);
@@ -192,7 +206,8 @@ mod tests {
 
         let data = builder.build::<C>();
         let proof = data.prove(pw)?;
-        data.verify(proof)
+        data.verify(proof)?;
+        Ok(())
     }
 
     fn init_logger() {
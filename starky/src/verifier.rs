@@ -5,27 +5,43 @@ use alloc::vec::Vec;
 use core::any::type_name;
 use core::iter::once;
 
-use anyhow::{anyhow, ensure, Result};
+use anyhow::Result;
+use hashbrown::HashMap;
 use itertools::Itertools;
+use plonky2::batch_fri::batch_fri_params;
+use plonky2::batch_fri::verifier::verify_batch_fri_proof;
 use plonky2::field::extension::{Extendable, FieldExtension};
 use plonky2::field::types::Field;
+use plonky2::fri::structure::{FriInstanceInfo, FriOpenings};
 use plonky2::fri::verifier::verify_fri_proof;
 use plonky2::hash::hash_types::RichField;
 use plonky2::hash::merkle_tree::MerkleCap;
 use plonky2::iop::challenger::Challenger;
 use plonky2::plonk::config::GenericConfig;
 use plonky2::plonk::plonk_common::reduce_with_powers;
+use plonky2::plonk::verification_error::{ensure_shape, VerificationError};
 
+use crate::all_stark::{
+    batch_fri_degree_bits, batch_fri_groups, batch_fri_instances, check_cross_table_lookups,
+    table_infos, AllStark, StarkVisitor,
+};
 use crate::config::StarkConfig;
 use crate::constraint_consumer::ConstraintConsumer;
-use crate::cross_table_lookup::CtlCheckVars;
+use crate::cross_table_lookup::{
+    get_ctl_vars_from_proofs, num_ctl_helpers_zs_by_table, verify_cross_table_lookups, CtlCheckVars,
+};
 use crate::evaluation_frame::StarkEvaluationFrame;
 use crate::lookup::LookupCheckVars;
-use crate::proof::{StarkOpeningSet, StarkProof, StarkProofChallenges, StarkProofWithPublicInputs};
+use crate::proof::{
+    MultiProofWithPublicInputs, StarkOpeningSet, StarkProof, StarkProofChallenges,
+    StarkProofWithPublicInputs, StarkTableChallenges, StarkTableProof,
+};
 use crate::stark::Stark;
 use crate::vanishing_poly::eval_vanishing_poly;
+use crate::verifying_key::StarkVerifyingKey;
 
-/// Verifies a [`StarkProofWithPublicInputs`] against a STARK statement.
+/// Verifies a [`StarkProofWithPublicInputs`] against a STARK statement, described by its
+/// [`StarkVerifyingKey`].
 pub fn verify_stark_proof<
     F: RichField + Extendable<D>,
     C: GenericConfig<D, F = F>,
@@ -34,16 +50,24 @@ pub fn verify_stark_proof<
 >(
     stark: S,
     proof_with_pis: StarkProofWithPublicInputs<F, C, D>,
-    config: &StarkConfig,
-) -> Result<()> {
-    ensure!(proof_with_pis.public_inputs.len() == S::PUBLIC_INPUTS);
+    verifying_key: &StarkVerifyingKey<F, C, D>,
+) -> Result<(), VerificationError> {
+    let config = &verifying_key.config;
+    verifying_key.check(&stark, config)?;
+    ensure_shape(
+        "public_inputs",
+        S::PUBLIC_INPUTS,
+        proof_with_pis.public_inputs.len(),
+    )?;
     let mut challenger = Challenger::<F, C::Hasher>::new();
+    challenger.observe_hash::<C::Hasher>(verifying_key.circuit_digest());
 
     let challenges = proof_with_pis.get_challenges(&mut challenger, None, false, config);
 
     verify_stark_proof_with_challenges(
         &stark,
         &proof_with_pis.proof,
+        verifying_key.preprocessed_cap.as_ref(),
         &challenges,
         None,
         &proof_with_pis.public_inputs,
@@ -51,6 +75,208 @@ pub fn verify_stark_proof<
     )
 }
 
+/// Verifies a [`MultiProofWithPublicInputs`] against a multi-STARK system, i.e. each of its STARK
+/// proofs, the batched FRI proof of their openings, as well as the cross-table lookups between
+/// them.
+///
+/// `verifying_keys` are the verifying keys of each table, as given by
+/// [`all_verifying_keys`](crate::verifying_key::all_verifying_keys), which must all be computed
+/// with `config`.
+pub fn verify_all<F, C, A, const D: usize, const N: usize>(
+    all_stark: &A,
+    proof_with_pis: &MultiProofWithPublicInputs<F, C, D, N>,
+    verifying_keys: &[StarkVerifyingKey<F, C, D>; N],
+    config: &StarkConfig,
+) -> Result<(), VerificationError>
+where
+    F: RichField + Extendable<D>,
+    C: GenericConfig<D, F = F>,
+    A: AllStark<F, D, N>,
+{
+    let cross_table_lookups = all_stark.cross_table_lookups();
+    let tables = table_infos(all_stark, config);
+    let max_constraint_degree = check_cross_table_lookups(&cross_table_lookups, &tables)?;
+    let MultiProofWithPublicInputs {
+        proof: multi_proof,
+        public_inputs,
+    } = proof_with_pis;
+
+    // The layout of the batched FRI proof depends on the degrees of the tables, so they are only
+    // bounded here. The FRI verifier then checks the proof against them.
+    let rate_bits = config.fri_config.rate_bits;
+    let blinding_degree_bits = config.blinding_degree_bits();
+    let max_degree_bits = F::TWO_ADICITY - rate_bits - blinding_degree_bits;
+    let min_degree_bits = config
+        .fri_config
+        .cap_height
+        .saturating_sub(rate_bits + blinding_degree_bits);
+    for &degree_bits in &multi_proof.degree_bits {
+        if degree_bits > max_degree_bits {
+            return Err(VerificationError::ShapeMismatch {
+                item: "degree_bits",
+                expected: max_degree_bits,
+                actual: degree_bits,
+            });
+        }
+        if degree_bits < min_degree_bits {
+            return Err(VerificationError::ShapeMismatch {
+                item: "degree_bits",
+                expected: min_degree_bits,
+                actual: degree_bits,
+            });
+        }
+    }
+
+    // The CTL openings are extracted before the shape of each proof is validated, so we first
+    // check that there are as many of them as expected.
+    let num_ctl_helpers_zs = num_ctl_helpers_zs_by_table::<F, N>(
+        &cross_table_lookups,
+        config.num_challenges,
+        max_constraint_degree,
+    );
+    for ((info, (num_helpers, num_zs)), proof) in tables
+        .iter()
+        .zip(num_ctl_helpers_zs)
+        .zip(&multi_proof.stark_proofs)
+    {
+        if info.requires_ctls {
+            let num_auxiliary = info.num_lookup_columns + num_helpers + num_zs;
+            let openings = &proof.proof.openings;
+            ensure_shape(
+                "auxiliary_polys",
+                num_auxiliary,
+                openings.auxiliary_polys.as_ref().map_or(0, Vec::len),
+            )?;
+            ensure_shape(
+                "auxiliary_polys_next",
+                num_auxiliary,
+                openings.auxiliary_polys_next.as_ref().map_or(0, Vec::len),
+            )?;
+        }
+    }
+
+    let circuit_digests = verifying_keys
+        .each_ref()
+        .map(StarkVerifyingKey::circuit_digest);
+    let challenges = proof_with_pis.get_challenges(&circuit_digests, config);
+    let num_lookup_columns = tables.map(|info| info.num_lookup_columns);
+    let ctl_vars_per_table = get_ctl_vars_from_proofs(
+        multi_proof,
+        &cross_table_lookups,
+        &challenges.ctl_challenges,
+        &num_lookup_columns,
+        max_constraint_degree,
+    );
+
+    let fri_instances = challenges
+        .stark_challenges
+        .iter()
+        .enumerate()
+        .map(|(table, stark_challenges)| {
+            all_stark.visit(
+                table,
+                VerifyTable {
+                    proof: &multi_proof.stark_proofs[table].proof,
+                    degree_bits: multi_proof.degree_bits[table],
+                    verifying_key: &verifying_keys[table],
+                    challenges: stark_challenges,
+                    ctl_vars: tables[table]
+                        .requires_ctls
+                        .then_some(&ctl_vars_per_table[table][..]),
+                    public_inputs: &public_inputs[table],
+                    config,
+                },
+            )
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let groups = batch_fri_groups(&multi_proof.degree_bits);
+    let fri_openings = groups
+        .iter()
+        .map(|group| FriOpenings {
+            batches: group
+                .iter()
+                .flat_map(|&table| {
+                    multi_proof.stark_proofs[table]
+                        .proof
+                        .openings
+                        .to_fri_openings()
+                        .batches
+                })
+                .collect(),
+        })
+        .collect_vec();
+    let merkle_caps = groups
+        .iter()
+        .flatten()
+        .flat_map(|&table| {
+            let proof = &multi_proof.stark_proofs[table].proof;
+            once(proof.trace_cap.clone())
+                .chain(proof.auxiliary_polys_cap.clone())
+                .chain(proof.quotient_polys_cap.clone())
+                .chain(verifying_keys[table].preprocessed_cap.clone())
+        })
+        .collect_vec();
+    let fri_degree_bits = batch_fri_degree_bits(&groups, &multi_proof.degree_bits, config);
+    verify_batch_fri_proof::<F, C, D>(
+        &batch_fri_instances(&groups, fri_instances),
+        &fri_openings,
+        &challenges.fri_challenges,
+        &merkle_caps,
+        &multi_proof.opening_proof,
+        &batch_fri_params(&config.fri_config, &fri_degree_bits, config.zero_knowledge),
+        &fri_degree_bits,
+    )?;
+
+    let ctl_zs_first = multi_proof
+        .stark_proofs
+        .each_ref()
+        .map(|p| p.proof.openings.ctl_zs_first.clone().unwrap_or_default());
+    verify_cross_table_lookups::<F, D, N>(
+        &cross_table_lookups,
+        ctl_zs_first,
+        &HashMap::new(),
+        config,
+    )
+}
+
+/// Verifies the openings of one table of a multi-STARK system, with [`verify_table_openings`].
+struct VerifyTable<'a, F, C, const D: usize>
+where
+    F: RichField + Extendable<D>,
+    C: GenericConfig<D, F = F>,
+{
+    proof: &'a StarkTableProof<F, C, D>,
+    degree_bits: usize,
+    verifying_key: &'a StarkVerifyingKey<F, C, D>,
+    challenges: &'a StarkTableChallenges<F, D>,
+    ctl_vars: Option<&'a [CtlCheckVars<'a, F, F::Extension, F::Extension, D>]>,
+    public_inputs: &'a [F],
+    config: &'a StarkConfig,
+}
+
+impl<F, C, const D: usize> StarkVisitor<F, D> for VerifyTable<'_, F, C, D>
+where
+    F: RichField + Extendable<D>,
+    C: GenericConfig<D, F = F>,
+{
+    type Output = Result<FriInstanceInfo<F, D>, VerificationError>;
+
+    fn visit<S: Stark<F, D>>(self, stark: &S) -> Self::Output {
+        self.verifying_key.check(stark, self.config)?;
+        verify_table_openings(
+            stark,
+            self.proof,
+            self.degree_bits,
+            self.verifying_key.preprocessed_cap.as_ref(),
+            self.challenges,
+            self.ctl_vars,
+            self.public_inputs,
+            self.config,
+        )
+    }
+}
+
 /// Verifies a [`StarkProofWithPublicInputs`] against a STARK statement,
 /// with the provided [`StarkProofChallenges`].
 /// It also supports optional cross-table lookups data and challenges,
@@ -58,11 +284,84 @@ pub fn verify_stark_proof<
 pub fn verify_stark_proof_with_challenges<F, C, S, const D: usize>(
     stark: &S,
     proof: &StarkProof<F, C, D>,
+    preprocessed_cap: Option<&MerkleCap<F, C::Hasher>>,
     challenges: &StarkProofChallenges<F, D>,
     ctl_vars: Option<&[CtlCheckVars<F, F::Extension, F::Extension, D>]>,
     public_inputs: &[F],
     config: &StarkConfig,
-) -> Result<()>
+) -> Result<(), VerificationError>
+where
+    F: RichField + Extendable<D>,
+    C: GenericConfig<D, F = F>,
+    S: Stark<F, D>,
+{
+    let degree_bits = proof.recover_degree_bits(config);
+    let StarkProof {
+        trace_cap,
+        auxiliary_polys_cap,
+        quotient_polys_cap,
+        openings,
+        opening_proof,
+    } = proof;
+    let table_proof = StarkTableProof::<F, C, D> {
+        trace_cap: trace_cap.clone(),
+        auxiliary_polys_cap: auxiliary_polys_cap.clone(),
+        quotient_polys_cap: quotient_polys_cap.clone(),
+        openings: openings.clone(),
+    };
+    let StarkProofChallenges {
+        lookup_challenge_set,
+        stark_alphas,
+        stark_zeta,
+        fri_challenges,
+    } = challenges;
+    let table_challenges = StarkTableChallenges {
+        lookup_challenge_set: lookup_challenge_set.clone(),
+        stark_alphas: stark_alphas.clone(),
+        stark_zeta: *stark_zeta,
+    };
+
+    let fri_instance = verify_table_openings(
+        stark,
+        &table_proof,
+        degree_bits,
+        preprocessed_cap,
+        &table_challenges,
+        ctl_vars,
+        public_inputs,
+        config,
+    )?;
+
+    let merkle_caps = once(trace_cap.clone())
+        .chain(auxiliary_polys_cap.clone())
+        .chain(quotient_polys_cap.clone())
+        .chain(preprocessed_cap.cloned())
+        .collect_vec();
+
+    verify_fri_proof::<F, C, D>(
+        &fri_instance,
+        &openings.to_fri_openings(),
+        fri_challenges,
+        &merkle_caps,
+        opening_proof,
+        &config.fri_params(degree_bits),
+    )
+}
+
+/// Checks the shape of the Merkle caps and openings of a STARK whose trace has `2^degree_bits`
+/// rows, and that the openings satisfy the constraints of the STARK at `zeta`. Returns the FRI
+/// instance with which the openings remain to be checked against the Merkle caps.
+#[allow(clippy::too_many_arguments)]
+fn verify_table_openings<F, C, S, const D: usize>(
+    stark: &S,
+    proof: &StarkTableProof<F, C, D>,
+    degree_bits: usize,
+    preprocessed_cap: Option<&MerkleCap<F, C::Hasher>>,
+    challenges: &StarkTableChallenges<F, D>,
+    ctl_vars: Option<&[CtlCheckVars<F, F::Extension, F::Extension, D>]>,
+    public_inputs: &[F],
+    config: &StarkConfig,
+) -> Result<FriInstanceInfo<F, D>, VerificationError>
 where
     F: RichField + Extendable<D>,
     C: GenericConfig<D, F = F>,
@@ -82,6 +381,7 @@ where
     validate_proof_shape(
         stark,
         proof,
+        preprocessed_cap,
         public_inputs,
         config,
         num_ctl_polys,
@@ -91,15 +391,20 @@ where
     let StarkOpeningSet {
         local_values,
         next_values,
+        additional_values,
         auxiliary_polys,
         auxiliary_polys_next,
         ctl_zs_first: _,
         quotient_polys,
     } = &proof.openings;
 
-    let vars = S::EvaluationFrame::from_values(
-        local_values,
-        next_values,
+    let rows = [local_values, next_values]
+        .into_iter()
+        .chain(additional_values)
+        .map(Vec::as_slice)
+        .collect::<Vec<_>>();
+    let vars = S::EvaluationFrame::from_rows(
+        &rows,
         &public_inputs
             .iter()
             .copied()
@@ -107,7 +412,6 @@ where
             .collect::<Vec<_>>(),
     );
 
-    let degree_bits = proof.recover_degree_bits(config);
     let (l_0, l_last) = eval_l_0_and_l_last(degree_bits, challenges.stark_zeta);
     let last = F::primitive_root_of_unity(degree_bits).inverse();
     let z_last = challenges.stark_zeta - last.into();
@@ -161,26 +465,24 @@ where
     let z_h_zeta = zeta_pow_deg - F::Extension::ONE;
     // `quotient_polys_zeta` holds `num_challenges * quotient_degree_factor` evaluations.
     // Each chunk of `quotient_degree_factor` holds the evaluations of `t_0(zeta),...,t_{quotient_degree_factor-1}(zeta)`
-    // where the "real" quotient polynomial is `t(X) = t_0(X) + t_1(X)*X^n + t_2(X)*X^{2n} + ...`.
-    // So to reconstruct `t(zeta)` we can compute `reduce_with_powers(chunk, zeta^n)` for each
-    // `quotient_degree_factor`-sized chunk of the original evaluations.
+    // where the "real" quotient polynomial is `t(X) = t_0(X) + t_1(X)*X^m + t_2(X)*X^{2m} + ...`,
+    // `m` being the degree bound of the committed polynomials, i.e. `n`, or `2n` in zero-knowledge
+    // mode. So to reconstruct `t(zeta)` we can compute `reduce_with_powers(chunk, zeta^m)` for each
+    // `quotient_degree_factor`-sized chunk of the original evaluations. The openings of the
+    // masking polynomials, if any, come last and are not part of any identity.
+    let zeta_pow_chunk = zeta_pow_deg.exp_power_of_2(config.blinding_degree_bits());
+    let num_quotient_polys = stark.num_quotient_polys(config);
 
     for (i, chunk) in quotient_polys
         .iter()
-        .flat_map(|x| x.chunks(stark.quotient_degree_factor()))
+        .flat_map(|x| x[..num_quotient_polys].chunks(stark.quotient_degree_factor()))
         .enumerate()
     {
-        ensure!(
-            vanishing_polys_zeta[i] == z_h_zeta * reduce_with_powers(chunk, zeta_pow_deg),
-            "Mismatch between evaluation and opening of quotient polynomial"
-        );
+        if vanishing_polys_zeta[i] != z_h_zeta * reduce_with_powers(chunk, zeta_pow_chunk) {
+            return Err(VerificationError::VanishingPolyMismatch { challenge: i });
+        }
     }
 
-    let merkle_caps = once(proof.trace_cap.clone())
-        .chain(proof.auxiliary_polys_cap.clone())
-        .chain(proof.quotient_polys_cap.clone())
-        .collect_vec();
-
     let num_ctl_zs = ctl_vars
         .map(|vars| {
             vars.iter()
@@ -189,76 +491,94 @@ where
         })
         .unwrap_or_default();
 
-    verify_fri_proof::<F, C, D>(
-        &stark.fri_instance(
-            challenges.stark_zeta,
-            F::primitive_root_of_unity(degree_bits),
-            num_ctl_polys,
-            num_ctl_zs,
-            config,
-        ),
-        &proof.openings.to_fri_openings(),
-        &challenges.fri_challenges,
-        &merkle_caps,
-        &proof.opening_proof,
-        &config.fri_params(degree_bits),
-    )?;
-
-    Ok(())
+    Ok(stark.fri_instance(
+        challenges.stark_zeta,
+        F::primitive_root_of_unity(degree_bits),
+        num_ctl_polys,
+        num_ctl_zs,
+        config,
+    ))
 }
 
+/// Checks the shape of the Merkle caps and openings of a STARK proof. The shape of the FRI proof
+/// of the openings is checked by the FRI verifier (see `validate_fri_proof_shape`).
 fn validate_proof_shape<F, C, S, const D: usize>(
     stark: &S,
-    proof: &StarkProof<F, C, D>,
+    proof: &StarkTableProof<F, C, D>,
+    preprocessed_cap: Option<&MerkleCap<F, C::Hasher>>,
     public_inputs: &[F],
     config: &StarkConfig,
     num_ctl_helpers: usize,
     num_ctl_zs: usize,
-) -> anyhow::Result<()>
+) -> Result<(), VerificationError>
 where
     F: RichField + Extendable<D>,
     C: GenericConfig<D, F = F>,
     S: Stark<F, D>,
 {
-    let degree_bits = proof.recover_degree_bits(config);
-
-    let StarkProof {
+    let StarkTableProof {
         trace_cap,
         auxiliary_polys_cap,
         quotient_polys_cap,
         openings,
-        // The shape of the opening proof will be checked in the FRI verifier (see
-        // validate_fri_proof_shape), so we ignore it here.
-        opening_proof: _,
     } = proof;
 
     let StarkOpeningSet {
         local_values,
         next_values,
+        additional_values,
         auxiliary_polys,
         auxiliary_polys_next,
         ctl_zs_first,
         quotient_polys,
     } = openings;
 
-    ensure!(public_inputs.len() == S::PUBLIC_INPUTS);
+    ensure_shape("public_inputs", S::PUBLIC_INPUTS, public_inputs.len())?;
 
-    let fri_params = config.fri_params(degree_bits);
-    let cap_height = fri_params.config.cap_height;
+    let cap_height = config.fri_config.cap_height;
 
-    ensure!(trace_cap.height() == cap_height);
-    ensure!(
-        quotient_polys_cap.is_none()
-            || quotient_polys_cap.as_ref().map(|q| q.height()) == Some(cap_height)
-    );
+    ensure_shape("trace_cap height", cap_height, trace_cap.height())?;
+    if let Some(quotient_polys_cap) = quotient_polys_cap {
+        ensure_shape(
+            "quotient_polys_cap height",
+            cap_height,
+            quotient_polys_cap.height(),
+        )?;
+    }
+    ensure_shape(
+        "preprocessed_cap",
+        usize::from(S::PREPROCESSED_COLUMNS > 0),
+        usize::from(preprocessed_cap.is_some()),
+    )?;
+    if let Some(preprocessed_cap) = preprocessed_cap {
+        ensure_shape(
+            "preprocessed_cap height",
+            cap_height,
+            preprocessed_cap.height(),
+        )?;
+    }
 
-    ensure!(local_values.len() == S::COLUMNS);
-    ensure!(next_values.len() == S::COLUMNS);
-    ensure!(if let Some(quotient_polys) = quotient_polys {
-        quotient_polys.len() == stark.num_quotient_polys(config)
-    } else {
-        stark.num_quotient_polys(config) == 0
-    });
+    let () = S::CHECK_ROW_OFFSETS;
+    ensure_shape("local_values", S::COLUMNS, local_values.len())?;
+    ensure_shape("next_values", S::COLUMNS, next_values.len())?;
+    ensure_shape(
+        "additional_values",
+        S::ROW_OFFSETS.len() - 2,
+        additional_values.len(),
+    )?;
+    for values in additional_values {
+        ensure_shape("additional_values", S::COLUMNS, values.len())?;
+    }
+    let num_quotient_polys = stark.num_quotient_polys(config);
+    ensure_shape(
+        "quotient_polys",
+        if num_quotient_polys > 0 {
+            num_quotient_polys + config.num_masking_polys()
+        } else {
+            0
+        },
+        quotient_polys.as_ref().map_or(0, Vec::len),
+    )?;
 
     check_lookup_options::<F, C, S, D>(
         stark,
@@ -297,7 +617,7 @@ fn check_lookup_options<F, C, S, const D: usize>(
     num_ctl_zs: usize,
     ctl_zs_first: &Option<Vec<F>>,
     config: &StarkConfig,
-) -> Result<()>
+) -> Result<(), VerificationError>
 where
     F: RichField + Extendable<D>,
     C: GenericConfig<D, F = F>,
@@ -307,32 +627,54 @@ where
         let num_auxiliary = stark.num_lookup_helper_columns(config) + num_ctl_helpers + num_ctl_zs;
         let cap_height = config.fri_config.cap_height;
 
-        let auxiliary_polys_cap = auxiliary_polys_cap
-            .as_ref()
-            .ok_or_else(|| anyhow!("Missing auxiliary_polys_cap"))?;
-        let auxiliary_polys = auxiliary_polys
-            .as_ref()
-            .ok_or_else(|| anyhow!("Missing auxiliary_polys"))?;
-        let auxiliary_polys_next = auxiliary_polys_next
-            .as_ref()
-            .ok_or_else(|| anyhow!("Missing auxiliary_polys_next"))?;
-
-        if let Some(ctl_zs_first) = ctl_zs_first {
-            ensure!(ctl_zs_first.len() == num_ctl_zs);
+        let auxiliary_polys_cap = ensure_present("auxiliary_polys_cap", auxiliary_polys_cap)?;
+        let auxiliary_polys = ensure_present("auxiliary_polys", auxiliary_polys)?;
+        let auxiliary_polys_next = ensure_present("auxiliary_polys_next", auxiliary_polys_next)?;
+
+        if stark.requires_ctls() {
+            let ctl_zs_first = ensure_present("ctl_zs_first", ctl_zs_first)?;
+            ensure_shape("ctl_zs_first", num_ctl_zs, ctl_zs_first.len())?;
+        } else {
+            ensure_shape("ctl_zs_first", 0, ctl_zs_first.iter().count())?;
         }
 
-        ensure!(auxiliary_polys_cap.height() == cap_height);
-        ensure!(auxiliary_polys.len() == num_auxiliary);
-        ensure!(auxiliary_polys_next.len() == num_auxiliary);
+        ensure_shape(
+            "auxiliary_polys_cap height",
+            cap_height,
+            auxiliary_polys_cap.height(),
+        )?;
+        ensure_shape("auxiliary_polys", num_auxiliary, auxiliary_polys.len())?;
+        ensure_shape(
+            "auxiliary_polys_next",
+            num_auxiliary,
+            auxiliary_polys_next.len(),
+        )?;
     } else {
-        ensure!(auxiliary_polys_cap.is_none());
-        ensure!(auxiliary_polys.is_none());
-        ensure!(auxiliary_polys_next.is_none());
+        ensure_shape("auxiliary_polys_cap", 0, auxiliary_polys_cap.iter().count())?;
+        ensure_shape("auxiliary_polys", 0, auxiliary_polys.iter().count())?;
+        ensure_shape(
+            "auxiliary_polys_next",
+            0,
+            auxiliary_polys_next.iter().count(),
+        )?;
+        ensure_shape("ctl_zs_first", 0, ctl_zs_first.iter().count())?;
     }
 
     Ok(())
 }
 
+/// Returns the content of an optional part of a proof, or a `ShapeMismatch` error if it is missing.
+fn ensure_present<'a, T>(
+    item: &'static str,
+    value: &'a Option<T>,
+) -> Result<&'a T, VerificationError> {
+    value.as_ref().ok_or(VerificationError::ShapeMismatch {
+        item,
+        expected: 1,
+        actual: 0,
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use plonky2::field::goldilocks_field::GoldilocksField;
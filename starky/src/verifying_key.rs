@@ -0,0 +1,344 @@
+//! The verifying key of a [`Stark`], i.e. all the data a verifier needs to know about the STARK
+//! statement being proven, besides its constraints, which are only identified by
+//! [`Stark::AIR_ID`].
+//!
+//! The [`circuit_digest`](StarkVerifyingKey::circuit_digest) of a verifying key is observed by the
+//! Fiat-Shamir transcript before anything else, so that a proof is bound to the STARK it was
+//! generated for, and cannot be verified against another STARK with a compatible shape but a
+//! different identifier.
+
+#[cfg(not(feature = "std"))]
+use alloc::{string::String, vec, vec::Vec};
+
+use plonky2::field::extension::Extendable;
+use plonky2::hash::hash_types::RichField;
+use plonky2::hash::merkle_tree::MerkleCap;
+use plonky2::plonk::config::{GenericConfig, Hasher};
+use plonky2::plonk::verification_error::VerificationError;
+use plonky2::util::serialization::{Buffer, IoError, IoResult, Read, Write};
+use plonky2::util::timing::TimingTree;
+
+use crate::all_stark::{AllStark, StarkVisitor};
+use crate::config::StarkConfig;
+use crate::prover::{commit_all_preprocessed_columns, commit_preprocessed_columns};
+use crate::stark::Stark;
+
+/// The verifying key of a [`Stark`], for a given [`StarkConfig`].
+#[derive(Debug, Clone)]
+pub struct StarkVerifyingKey<
+    F: RichField + Extendable<D>,
+    C: GenericConfig<D, F = F>,
+    const D: usize,
+> {
+    /// The configuration used to generate proofs.
+    pub config: StarkConfig,
+    /// The identifier of the constraints of the STARK, given by [`Stark::AIR_ID`].
+    pub air_id: String,
+    /// The number of columns in the evaluation frames, including the preprocessed columns.
+    pub num_columns: usize,
+    /// The number of preprocessed columns.
+    pub num_preprocessed_columns: usize,
+    /// The number of public inputs.
+    pub num_public_inputs: usize,
+    /// The offsets of the rows viewed by the evaluation frames.
+    pub row_offsets: Vec<usize>,
+    /// The maximal degree of the constraints.
+    pub constraint_degree: usize,
+    /// The number of looking columns of each lookup.
+    pub lookup_sizes: Vec<usize>,
+    /// The number of lookup helper columns.
+    pub num_lookup_helper_columns: usize,
+    /// Whether the STARK is part of some cross-table lookup.
+    pub requires_ctls: bool,
+    /// Merkle cap of LDEs of the preprocessed columns, if any.
+    pub preprocessed_cap: Option<MerkleCap<F, C::Hasher>>,
+}
+
+impl<F: RichField + Extendable<D>, C: GenericConfig<D, F = F>, const D: usize>
+    StarkVerifyingKey<F, C, D>
+{
+    /// Computes the verifying key of `stark` for the given configuration, which involves
+    /// committing to its preprocessed columns, if any.
+    pub fn new<S: Stark<F, D>>(stark: &S, config: &StarkConfig) -> Self {
+        let preprocessed_cap =
+            commit_preprocessed_columns::<F, C, S, D>(stark, config, &mut TimingTree::default())
+                .map(|commitment| commitment.merkle_tree.cap);
+        Self::from_preprocessed_cap(stark, config, preprocessed_cap)
+    }
+
+    /// Computes the verifying key of `stark` from an existing commitment to its preprocessed
+    /// columns.
+    pub(crate) fn from_preprocessed_cap<S: Stark<F, D>>(
+        stark: &S,
+        config: &StarkConfig,
+        preprocessed_cap: Option<MerkleCap<F, C::Hasher>>,
+    ) -> Self {
+        let () = S::CHECK_ROW_OFFSETS;
+        Self {
+            config: config.clone(),
+            air_id: S::AIR_ID.into(),
+            num_columns: S::COLUMNS,
+            num_preprocessed_columns: S::PREPROCESSED_COLUMNS,
+            num_public_inputs: S::PUBLIC_INPUTS,
+            row_offsets: S::ROW_OFFSETS.to_vec(),
+            constraint_degree: stark.constraint_degree(),
+            lookup_sizes: stark
+                .lookups()
+                .iter()
+                .map(|lookup| lookup.columns.len())
+                .collect(),
+            num_lookup_helper_columns: stark.num_lookup_helper_columns(config),
+            requires_ctls: stark.requires_ctls(),
+            preprocessed_cap,
+        }
+    }
+
+    /// Checks that this verifying key is the one of `stark` for the given configuration, up to
+    /// the commitment to the preprocessed columns, which is only checked by verifying a proof.
+    pub fn check<S: Stark<F, D>>(
+        &self,
+        stark: &S,
+        config: &StarkConfig,
+    ) -> Result<(), VerificationError> {
+        let expected = Self::from_preprocessed_cap(stark, config, self.preprocessed_cap.clone());
+        if self.circuit_digest() != expected.circuit_digest() {
+            return Err(VerificationError::VerifyingKeyMismatch);
+        }
+        Ok(())
+    }
+
+    /// Returns a digest of this verifying key, to be observed by the Fiat-Shamir transcript.
+    pub fn circuit_digest(&self) -> <C::Hasher as Hasher<F>>::Hash {
+        let mut bytes = Vec::new();
+        self.to_buffer(&mut bytes)
+            .expect("Writing to a byte vector cannot fail.");
+        // Each chunk of 4 bytes fits in a field element. The encoding is self-delimiting, so the
+        // padding of the last chunk is unambiguous.
+        let elements = bytes
+            .chunks(4)
+            .map(|chunk| {
+                let mut word = [0; 4];
+                word[..chunk.len()].copy_from_slice(chunk);
+                F::from_canonical_u32(u32::from_le_bytes(word))
+            })
+            .collect::<Vec<_>>();
+        C::Hasher::hash_no_pad(&elements)
+    }
+
+    /// Serializes a verifying key.
+    pub fn to_buffer(&self, buffer: &mut Vec<u8>) -> IoResult<()> {
+        let StarkConfig {
+            security_bits,
+            num_challenges,
+            fri_config,
+            zero_knowledge,
+        } = &self.config;
+        buffer.write_usize(*security_bits)?;
+        buffer.write_usize(*num_challenges)?;
+        buffer.write_fri_config(fri_config)?;
+        buffer.write_bool(*zero_knowledge)?;
+
+        buffer.write_usize(self.air_id.len())?;
+        buffer.write_all(self.air_id.as_bytes())?;
+        buffer.write_usize(self.num_columns)?;
+        buffer.write_usize(self.num_preprocessed_columns)?;
+        buffer.write_usize(self.num_public_inputs)?;
+        buffer.write_usize_vec(&self.row_offsets)?;
+        buffer.write_usize(self.constraint_degree)?;
+        buffer.write_usize_vec(&self.lookup_sizes)?;
+        buffer.write_usize(self.num_lookup_helper_columns)?;
+        buffer.write_bool(self.requires_ctls)?;
+        buffer.write_bool(self.preprocessed_cap.is_some())?;
+        if let Some(cap) = &self.preprocessed_cap {
+            buffer.write_merkle_cap(cap)?;
+        }
+        Ok(())
+    }
+
+    /// Deserializes a verifying key.
+    pub fn from_buffer(buffer: &mut Buffer) -> IoResult<Self> {
+        let security_bits = buffer.read_usize()?;
+        let num_challenges = buffer.read_usize()?;
+        let fri_config = buffer.read_fri_config()?;
+        let zero_knowledge = buffer.read_bool()?;
+        let cap_height = fri_config.cap_height;
+        let config = StarkConfig {
+            security_bits,
+            num_challenges,
+            fri_config,
+            zero_knowledge,
+        };
+
+        let mut air_id = vec![0; buffer.read_usize()?];
+        buffer.read_exact(&mut air_id)?;
+        let air_id = String::from_utf8(air_id).map_err(|_| IoError)?;
+        let num_columns = buffer.read_usize()?;
+        let num_preprocessed_columns = buffer.read_usize()?;
+        let num_public_inputs = buffer.read_usize()?;
+        let row_offsets = buffer.read_usize_vec()?;
+        let constraint_degree = buffer.read_usize()?;
+        let lookup_sizes = buffer.read_usize_vec()?;
+        let num_lookup_helper_columns = buffer.read_usize()?;
+        let requires_ctls = buffer.read_bool()?;
+        let preprocessed_cap = if buffer.read_bool()? {
+            Some(buffer.read_merkle_cap(cap_height)?)
+        } else {
+            None
+        };
+
+        Ok(Self {
+            config,
+            air_id,
+            num_columns,
+            num_preprocessed_columns,
+            num_public_inputs,
+            row_offsets,
+            constraint_degree,
+            lookup_sizes,
+            num_lookup_helper_columns,
+            requires_ctls,
+            preprocessed_cap,
+        })
+    }
+}
+
+/// Computes the verifying keys of all the tables of a multi-STARK system, with
+/// [`StarkVerifyingKey::new`].
+pub fn all_verifying_keys<F, C, A, const D: usize, const N: usize>(
+    all_stark: &A,
+    config: &StarkConfig,
+) -> [StarkVerifyingKey<F, C, D>; N]
+where
+    F: RichField + Extendable<D>,
+    C: GenericConfig<D, F = F>,
+    A: AllStark<F, D, N>,
+{
+    let preprocessed_commitments = commit_all_preprocessed_columns::<F, C, A, D, N>(
+        all_stark,
+        config,
+        &mut TimingTree::default(),
+    );
+    all_verifying_keys_from_caps(
+        all_stark,
+        config,
+        preprocessed_commitments.map(|commitment| commitment.map(|c| c.merkle_tree.cap)),
+    )
+}
+
+/// Computes the verifying keys of all the tables of a multi-STARK system from existing
+/// commitments to their preprocessed columns.
+pub(crate) fn all_verifying_keys_from_caps<F, C, A, const D: usize, const N: usize>(
+    all_stark: &A,
+    config: &StarkConfig,
+    preprocessed_caps: [Option<MerkleCap<F, C::Hasher>>; N],
+) -> [StarkVerifyingKey<F, C, D>; N]
+where
+    F: RichField + Extendable<D>,
+    C: GenericConfig<D, F = F>,
+    A: AllStark<F, D, N>,
+{
+    let mut preprocessed_caps = preprocessed_caps.into_iter();
+    core::array::from_fn(|table| {
+        all_stark.visit(
+            table,
+            GetVerifyingKey {
+                config,
+                preprocessed_cap: preprocessed_caps.next().flatten(),
+            },
+        )
+    })
+}
+
+/// Computes the verifying key of one table of a multi-STARK system.
+struct GetVerifyingKey<'a, F: RichField, C: GenericConfig<D, F = F>, const D: usize> {
+    config: &'a StarkConfig,
+    preprocessed_cap: Option<MerkleCap<F, C::Hasher>>,
+}
+
+impl<F, C, const D: usize> StarkVisitor<F, D> for GetVerifyingKey<'_, F, C, D>
+where
+    F: RichField + Extendable<D>,
+    C: GenericConfig<D, F = F>,
+{
+    type Output = StarkVerifyingKey<F, C, D>;
+
+    fn visit<S: Stark<F, D>>(self, stark: &S) -> Self::Output {
+        StarkVerifyingKey::from_preprocessed_cap(stark, self.config, self.preprocessed_cap)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(not(feature = "std"))]
+    use alloc::{vec, vec::Vec};
+
+    use anyhow::Result;
+    use plonky2::plonk::config::{GenericConfig, PoseidonGoldilocksConfig};
+    use plonky2::util::serialization::Buffer;
+    use plonky2::util::timing::TimingTree;
+
+    use crate::config::StarkConfig;
+    use crate::fibonacci_stark::FibonacciStark;
+    use crate::preprocessed_stark::PreprocessedStark;
+    use crate::prover::prove;
+    use crate::verifier::verify_stark_proof;
+    use crate::verifying_key::StarkVerifyingKey;
+
+    const D: usize = 2;
+    type C = PoseidonGoldilocksConfig;
+    type F = <C as GenericConfig<D>>::F;
+    type S = PreprocessedStark<F, D>;
+
+    #[test]
+    fn test_verifying_key_serialization() -> Result<()> {
+        let config = StarkConfig::standard_fast_config();
+        let stark = S::new(1 << 5);
+        let verifying_key = StarkVerifyingKey::<F, C, D>::new(&stark, &config);
+
+        let mut bytes = Vec::new();
+        verifying_key.to_buffer(&mut bytes).unwrap();
+        let read_key = StarkVerifyingKey::<F, C, D>::from_buffer(&mut Buffer::new(&bytes)).unwrap();
+        assert_eq!(read_key.circuit_digest(), verifying_key.circuit_digest());
+        assert_eq!(read_key.preprocessed_cap, verifying_key.preprocessed_cap);
+        read_key.check(&stark, &config)?;
+        assert!(read_key
+            .check(&FibonacciStark::<F, D>::new(1 << 5), &config)
+            .is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_proof_bound_to_verifying_key() -> Result<()> {
+        let config = StarkConfig::standard_fast_config();
+        let num_rows = 1 << 5;
+        let stark = S::new(num_rows);
+        let trace = stark.generate_trace(&vec![0; num_rows / 2]);
+        let proof = prove::<F, C, S, D>(stark, &config, trace, &[], &mut TimingTree::default())?;
+        verify_stark_proof(
+            stark,
+            proof.clone(),
+            &StarkVerifyingKey::new(&stark, &config),
+        )?;
+
+        // The security level does not change the shape of the proof, but it changes the circuit
+        // digest, hence the Fiat-Shamir challenges.
+        let other_config = StarkConfig {
+            security_bits: config.security_bits - 1,
+            ..config.clone()
+        };
+        let other_key = StarkVerifyingKey::new(&stark, &other_config);
+        other_key.check(&stark, &other_config)?;
+        assert!(verify_stark_proof(stark, proof.clone(), &other_key).is_err());
+
+        // Likewise, the identifier of the constraints is bound to the proof.
+        let other_key = StarkVerifyingKey {
+            air_id: "other".into(),
+            ..StarkVerifyingKey::new(&stark, &config)
+        };
+        assert!(other_key.check(&stark, &config).is_err());
+        assert!(verify_stark_proof(stark, proof, &other_key).is_err());
+
+        Ok(())
+    }
+}